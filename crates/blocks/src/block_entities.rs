@@ -1,4 +1,6 @@
+use crate::blocks::Block;
 use crate::items::Item;
+use crate::BlockFacing;
 use mchprs_proc_macros::protocol_id;
 use mchprs_utils::{map, nbt_unwrap_val};
 use serde::{Deserialize, Serialize};
@@ -25,6 +27,7 @@ pub enum ContainerType {
     Furnace,
     Barrel,
     Hopper,
+    Crafter,
 }
 
 impl FromStr for ContainerType {
@@ -35,6 +38,7 @@ impl FromStr for ContainerType {
             "barrel" => ContainerType::Barrel,
             "furnace" => ContainerType::Furnace,
             "hopper" => ContainerType::Hopper,
+            "crafter" => ContainerType::Crafter,
             _ => return Err(()),
         })
     }
@@ -46,6 +50,7 @@ impl std::fmt::Display for ContainerType {
             ContainerType::Furnace => "minecraft:furnace",
             ContainerType::Barrel => "minecraft:barrel",
             ContainerType::Hopper => "minecraft:hopper",
+            ContainerType::Crafter => "minecraft:crafter",
         })
     }
 }
@@ -56,6 +61,7 @@ impl ContainerType {
             ContainerType::Furnace => 3,
             ContainerType::Barrel => 27,
             ContainerType::Hopper => 5,
+            ContainerType::Crafter => 9,
         }
     }
 
@@ -65,6 +71,7 @@ impl ContainerType {
             ContainerType::Furnace => protocol_id!("minecraft:menu", "minecraft:furnace"),
             ContainerType::Barrel => protocol_id!("minecraft:menu", "minecraft:generic_9x3"),
             ContainerType::Hopper => protocol_id!("minecraft:menu", "minecraft:hopper"),
+            ContainerType::Crafter => protocol_id!("minecraft:menu", "minecraft:crafter_3x3"),
         }
     }
 }
@@ -80,6 +87,14 @@ pub enum BlockEntity {
         ty: ContainerType,
     },
     Sign(Box<SignBlockEntity>),
+    MovingPiston {
+        /// The state id of the block that is being moved
+        block_state: u32,
+        facing: BlockFacing,
+        extending: bool,
+        /// True if the block being moved is the piston base or head
+        source: bool,
+    },
 }
 
 impl BlockEntity {
@@ -96,8 +111,10 @@ impl BlockEntity {
                 ContainerType::Furnace => block_entity_id!("minecraft:comparator"),
                 ContainerType::Barrel => block_entity_id!("minecraft:barrel"),
                 ContainerType::Hopper => block_entity_id!("minecraft:hopper"),
+                ContainerType::Crafter => block_entity_id!("minecraft:crafter"),
             },
             BlockEntity::Sign(_) => block_entity_id!("minecraft:sign"),
+            BlockEntity::MovingPiston { .. } => block_entity_id!("minecraft:piston"),
         }
     }
 
@@ -143,10 +160,16 @@ impl BlockEntity {
 
             fullness_sum += count as f32 / item_type.map_or(64, Item::max_stack_size) as f32;
         }
-        Some(BlockEntity::Container {
-            comparator_override: (if fullness_sum > 0.0 { 1.0 } else { 0.0 }
+        let comparator_override = match ty {
+            // Crafters output the number of occupied slots instead of how full they are. Vanilla
+            // also counts disabled slots, but those are not kept when loading.
+            ContainerType::Crafter => inventory.len() as u8,
+            _ => (if fullness_sum > 0.0 { 1.0 } else { 0.0 }
                 + (fullness_sum / num_slots as f32) * 14.0)
                 .floor() as u8,
+        };
+        Some(BlockEntity::Container {
+            comparator_override,
             inventory,
             ty,
         })
@@ -170,6 +193,10 @@ impl BlockEntity {
                 nbt_unwrap_val!(&nbt["Items"], Value::List),
                 ContainerType::Hopper,
             ),
+            "crafter" => BlockEntity::load_container(
+                nbt_unwrap_val!(&nbt["Items"], Value::List),
+                ContainerType::Crafter,
+            ),
             "sign" => {
                 let sign = if nbt.contains_key("Text1") {
                     // This is the pre-1.20 encoding
@@ -202,6 +229,34 @@ impl BlockEntity {
                 };
                 Some(BlockEntity::Sign(Box::new(sign)))
             }
+            "piston" => {
+                let state = nbt_unwrap_val!(&nbt["blockState"], Value::Compound);
+                let mut block = Block::from_name(nbt_unwrap_val!(&state["Name"], Value::String))?;
+                if let Some(Value::Compound(properties)) = state.get("Properties") {
+                    let mut props = HashMap::new();
+                    for (name, value) in properties {
+                        props.insert(
+                            name.as_str(),
+                            nbt_unwrap_val!(value, Value::String).as_str(),
+                        );
+                    }
+                    block.set_properties(props);
+                }
+                let facing = match nbt_get_int(nbt, "facing")? {
+                    0 => BlockFacing::Down,
+                    1 => BlockFacing::Up,
+                    2 => BlockFacing::North,
+                    3 => BlockFacing::South,
+                    4 => BlockFacing::West,
+                    _ => BlockFacing::East,
+                };
+                Some(BlockEntity::MovingPiston {
+                    block_state: block.get_id(),
+                    facing,
+                    extending: nbt_get_int(nbt, "extending")? != 0,
+                    source: nbt_get_int(nbt, "source")? != 0,
+                })
+            }
             _ => None,
         }
     }
@@ -237,6 +292,30 @@ impl BlockEntity {
                     "id" => Value::String("minecraft:comparator".to_owned())
                 })
             }),
+            BlockEntity::MovingPiston {
+                block_state,
+                facing,
+                extending,
+                source,
+            } => Some({
+                let block = Block::from_id(*block_state);
+                let properties = block
+                    .properties()
+                    .into_iter()
+                    .map(|(name, value)| (name.to_owned(), Value::String(value)))
+                    .collect();
+                nbt::Blob::with_content(map! {
+                    "blockState" => Value::Compound(map! {
+                        "Name" => Value::String(block.get_name().to_owned()),
+                        "Properties" => Value::Compound(properties)
+                    }),
+                    "facing" => Value::Int(facing.block_face() as i32),
+                    "progress" => Value::Float(0.0),
+                    "extending" => Value::Byte(*extending as i8),
+                    "source" => Value::Byte(*source as i8),
+                    "id" => Value::String("minecraft:piston".to_owned())
+                })
+            }),
             BlockEntity::Container { inventory, ty, .. } => Some({
                 let mut items = Vec::new();
                 for entry in inventory {
@@ -57,6 +57,8 @@ noop_block_transform!(
     TrapdoorHalf,
     LeverFace,
     ComparatorMode,
+    PistonType,
+    CrafterOrientation,
     Instrument,
     SlabType,
 );
@@ -131,6 +133,8 @@ impl Block {
                     | Block::Barrel { .. }
                     | Block::Furnace { .. }
                     | Block::Hopper { .. }
+                    | Block::Crafter { .. }
+                    | Block::MovingPiston { .. }
             )
     }
 
@@ -153,6 +157,22 @@ impl Block {
     pub(crate) fn complex_rotate(&mut self, amt: RotateAmt) {
         match self {
             Block::RedstoneWire(wire) => wire.rotate(amt),
+            Block::Tripwire {
+                north,
+                east,
+                south,
+                west,
+                ..
+            } => {
+                let turns = match amt {
+                    RotateAmt::Rotate90 => 1,
+                    RotateAmt::Rotate180 => 2,
+                    RotateAmt::Rotate270 => 3,
+                };
+                for _ in 0..turns {
+                    (*north, *east, *south, *west) = (*west, *north, *east, *south);
+                }
+            }
             _ => unreachable!(),
         }
     }
@@ -160,6 +180,16 @@ impl Block {
     pub(crate) fn complex_flip(&mut self, dir: FlipDirection) {
         match self {
             Block::RedstoneWire(wire) => wire.flip(dir),
+            Block::Tripwire {
+                north,
+                east,
+                south,
+                west,
+                ..
+            } => match dir {
+                FlipDirection::FlipX => std::mem::swap(east, west),
+                FlipDirection::FlipZ => std::mem::swap(north, south),
+            },
             _ => unreachable!(),
         }
     }
@@ -206,6 +236,7 @@ impl Block {
             Block::SmoothStoneSlab { ty, .. } | Block::QuartzSlab { ty, .. } => {
                 ty != SlabType::Double
             }
+            Block::Piston { extended, .. } | Block::StickyPiston { extended, .. } => extended,
             _ => unreachable!(),
         }
     }
@@ -213,6 +244,7 @@ impl Block {
     pub(crate) fn is_cube_dynamic(self) -> bool {
         match self {
             Block::SmoothStoneSlab { ty, .. } | Block::QuartzSlab { ty, .. } => ty == SlabType::Top,
+            Block::Piston { extended, .. } | Block::StickyPiston { extended, .. } => !extended,
             _ => unreachable!(),
         }
     }
@@ -235,6 +267,21 @@ impl Block {
             _ => None,
         }
     }
+
+    /// Returns the `lit` and `powered` properties of any of the copper bulb variants.
+    pub fn get_copper_bulb_state(&mut self) -> Option<(&mut bool, &mut bool)> {
+        match self {
+            Block::CopperBulb { lit, powered }
+            | Block::ExposedCopperBulb { lit, powered }
+            | Block::WeatheredCopperBulb { lit, powered }
+            | Block::OxidizedCopperBulb { lit, powered }
+            | Block::WaxedCopperBulb { lit, powered }
+            | Block::WaxedExposedCopperBulb { lit, powered }
+            | Block::WaxedWeatheredCopperBulb { lit, powered }
+            | Block::WaxedOxidizedCopperBulb { lit, powered } => Some((lit, powered)),
+            _ => None,
+        }
+    }
 }
 
 #[test]
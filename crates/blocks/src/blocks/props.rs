@@ -1,4 +1,5 @@
 use mchprs_proc_macros::protocol_id;
+use serde::{Deserialize, Serialize};
 
 use super::{Block, BlockDirection, BlockProperty, BlockTransform, FlipDirection};
 
@@ -32,7 +33,7 @@ impl Repeater {
     }
 }
 
-#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ComparatorMode {
     #[default]
     Compare,
@@ -165,7 +166,32 @@ pub enum TrapdoorHalf {
     Bottom,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PistonType {
+    #[default]
+    Normal,
+    Sticky,
+}
+
+/// The direction a crafter's front faces, followed by the direction its top faces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CrafterOrientation {
+    DownEast,
+    DownNorth,
+    DownSouth,
+    DownWest,
+    UpEast,
+    UpNorth,
+    UpSouth,
+    UpWest,
+    WestUp,
+    EastUp,
+    #[default]
+    NorthUp,
+    SouthUp,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Instrument {
     Harp,
     Basedrum,
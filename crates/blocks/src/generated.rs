@@ -124,12 +124,38 @@ pub enum Block {
         facing: BlockDirection,
         powered: bool,
     },
+    Tripwire {
+        attached: bool,
+        disarmed: bool,
+        east: bool,
+        north: bool,
+        powered: bool,
+        south: bool,
+        west: bool,
+    },
     Comparator(Comparator),
     RedstoneBlock,
     Observer {
         facing: BlockFacing,
         powered: bool,
     },
+    Piston {
+        extended: bool,
+        facing: BlockFacing,
+    },
+    StickyPiston {
+        extended: bool,
+        facing: BlockFacing,
+    },
+    PistonHead {
+        facing: BlockFacing,
+        short: bool,
+        ty: PistonType,
+    },
+    MovingPiston {
+        facing: BlockFacing,
+        ty: PistonType,
+    },
     SeaPickle {
         pickles: u8,
         waterlogged: bool,
@@ -137,6 +163,47 @@ pub enum Block {
     Target {
         power: u8,
     },
+    DaylightDetector {
+        inverted: bool,
+        power: u8,
+    },
+    CopperBulb {
+        lit: bool,
+        powered: bool,
+    },
+    ExposedCopperBulb {
+        lit: bool,
+        powered: bool,
+    },
+    WeatheredCopperBulb {
+        lit: bool,
+        powered: bool,
+    },
+    OxidizedCopperBulb {
+        lit: bool,
+        powered: bool,
+    },
+    WaxedCopperBulb {
+        lit: bool,
+        powered: bool,
+    },
+    WaxedExposedCopperBulb {
+        lit: bool,
+        powered: bool,
+    },
+    WaxedWeatheredCopperBulb {
+        lit: bool,
+        powered: bool,
+    },
+    WaxedOxidizedCopperBulb {
+        lit: bool,
+        powered: bool,
+    },
+    Crafter {
+        crafting: bool,
+        orientation: CrafterOrientation,
+        triggered: bool,
+    },
     OakPressurePlate {
         powered: bool,
     },
@@ -344,11 +411,26 @@ impl Block {
             Block::Repeater(_) => "minecraft:repeater",
             Block::RedstoneLamp { .. } => "minecraft:redstone_lamp",
             Block::TripwireHook { .. } => "minecraft:tripwire_hook",
+            Block::Tripwire { .. } => "minecraft:tripwire",
             Block::Comparator(_) => "minecraft:comparator",
             Block::RedstoneBlock => "minecraft:redstone_block",
             Block::Observer { .. } => "minecraft:observer",
+            Block::Piston { .. } => "minecraft:piston",
+            Block::StickyPiston { .. } => "minecraft:sticky_piston",
+            Block::PistonHead { .. } => "minecraft:piston_head",
+            Block::MovingPiston { .. } => "minecraft:moving_piston",
             Block::SeaPickle { .. } => "minecraft:sea_pickle",
             Block::Target { .. } => "minecraft:target",
+            Block::DaylightDetector { .. } => "minecraft:daylight_detector",
+            Block::CopperBulb { .. } => "minecraft:copper_bulb",
+            Block::ExposedCopperBulb { .. } => "minecraft:exposed_copper_bulb",
+            Block::WeatheredCopperBulb { .. } => "minecraft:weathered_copper_bulb",
+            Block::OxidizedCopperBulb { .. } => "minecraft:oxidized_copper_bulb",
+            Block::WaxedCopperBulb { .. } => "minecraft:waxed_copper_bulb",
+            Block::WaxedExposedCopperBulb { .. } => "minecraft:waxed_exposed_copper_bulb",
+            Block::WaxedWeatheredCopperBulb { .. } => "minecraft:waxed_weathered_copper_bulb",
+            Block::WaxedOxidizedCopperBulb { .. } => "minecraft:waxed_oxidized_copper_bulb",
+            Block::Crafter { .. } => "minecraft:crafter",
             Block::OakPressurePlate { .. } => "minecraft:oak_pressure_plate",
             Block::SprucePressurePlate { .. } => "minecraft:spruce_pressure_plate",
             Block::BirchPressurePlate { .. } => "minecraft:birch_pressure_plate",
@@ -586,6 +668,15 @@ impl Block {
                 facing: BlockDirection::North,
                 powered: false,
             },
+            "minecraft:tripwire" => Block::Tripwire {
+                attached: false,
+                disarmed: false,
+                east: false,
+                north: false,
+                powered: false,
+                south: false,
+                west: false,
+            },
             "minecraft:comparator" => Block::Comparator(Comparator {
                 facing: BlockDirection::North,
                 mode: ComparatorMode::Compare,
@@ -596,11 +687,69 @@ impl Block {
                 facing: BlockFacing::South,
                 powered: false,
             },
+            "minecraft:piston" => Block::Piston {
+                extended: false,
+                facing: BlockFacing::North,
+            },
+            "minecraft:sticky_piston" => Block::StickyPiston {
+                extended: false,
+                facing: BlockFacing::North,
+            },
+            "minecraft:piston_head" => Block::PistonHead {
+                facing: BlockFacing::North,
+                short: false,
+                ty: PistonType::Normal,
+            },
+            "minecraft:moving_piston" => Block::MovingPiston {
+                facing: BlockFacing::North,
+                ty: PistonType::Normal,
+            },
             "minecraft:sea_pickle" => Block::SeaPickle {
                 pickles: 1,
                 waterlogged: true,
             },
             "minecraft:target" => Block::Target { power: 0 },
+            "minecraft:daylight_detector" => Block::DaylightDetector {
+                inverted: false,
+                power: 0,
+            },
+            "minecraft:copper_bulb" => Block::CopperBulb {
+                lit: false,
+                powered: false,
+            },
+            "minecraft:exposed_copper_bulb" => Block::ExposedCopperBulb {
+                lit: false,
+                powered: false,
+            },
+            "minecraft:weathered_copper_bulb" => Block::WeatheredCopperBulb {
+                lit: false,
+                powered: false,
+            },
+            "minecraft:oxidized_copper_bulb" => Block::OxidizedCopperBulb {
+                lit: false,
+                powered: false,
+            },
+            "minecraft:waxed_copper_bulb" => Block::WaxedCopperBulb {
+                lit: false,
+                powered: false,
+            },
+            "minecraft:waxed_exposed_copper_bulb" => Block::WaxedExposedCopperBulb {
+                lit: false,
+                powered: false,
+            },
+            "minecraft:waxed_weathered_copper_bulb" => Block::WaxedWeatheredCopperBulb {
+                lit: false,
+                powered: false,
+            },
+            "minecraft:waxed_oxidized_copper_bulb" => Block::WaxedOxidizedCopperBulb {
+                lit: false,
+                powered: false,
+            },
+            "minecraft:crafter" => Block::Crafter {
+                crafting: false,
+                orientation: CrafterOrientation::NorthUp,
+                triggered: false,
+            },
             "minecraft:oak_pressure_plate" => Block::OakPressurePlate { powered: false },
             "minecraft:spruce_pressure_plate" => Block::SprucePressurePlate { powered: false },
             "minecraft:birch_pressure_plate" => Block::BirchPressurePlate { powered: false },
@@ -744,6 +893,15 @@ impl Block {
             Block::Stone => true,
             Block::RedstoneLamp { .. } => true,
             Block::Target { .. } => true,
+            Block::CopperBulb { .. } => true,
+            Block::ExposedCopperBulb { .. } => true,
+            Block::WeatheredCopperBulb { .. } => true,
+            Block::OxidizedCopperBulb { .. } => true,
+            Block::WaxedCopperBulb { .. } => true,
+            Block::WaxedExposedCopperBulb { .. } => true,
+            Block::WaxedWeatheredCopperBulb { .. } => true,
+            Block::WaxedOxidizedCopperBulb { .. } => true,
+            Block::Crafter { .. } => true,
             Block::Barrel { .. } => true,
             Block::Sandstone => true,
             Block::CoalBlock => true,
@@ -825,7 +983,18 @@ impl Block {
             Block::Glowstone => true,
             Block::RedstoneLamp { .. } => true,
             Block::RedstoneBlock => true,
+            Block::Piston { .. } => self.is_cube_dynamic(),
+            Block::StickyPiston { .. } => self.is_cube_dynamic(),
             Block::Target { .. } => true,
+            Block::CopperBulb { .. } => true,
+            Block::ExposedCopperBulb { .. } => true,
+            Block::WeatheredCopperBulb { .. } => true,
+            Block::OxidizedCopperBulb { .. } => true,
+            Block::WaxedCopperBulb { .. } => true,
+            Block::WaxedExposedCopperBulb { .. } => true,
+            Block::WaxedWeatheredCopperBulb { .. } => true,
+            Block::WaxedOxidizedCopperBulb { .. } => true,
+            Block::Crafter { .. } => true,
             Block::Barrel { .. } => true,
             Block::Hopper { .. } => true,
             Block::Sandstone => true,
@@ -922,7 +1091,12 @@ impl Block {
         match self {
             Block::Glass => true,
             Block::Glowstone => true,
+            Block::Tripwire { .. } => true,
             Block::RedstoneBlock => true,
+            Block::Piston { .. } => self.is_transparent_dynamic(),
+            Block::StickyPiston { .. } => self.is_transparent_dynamic(),
+            Block::PistonHead { .. } => true,
+            Block::MovingPiston { .. } => true,
             Block::Hopper { .. } => true,
             Block::SmoothStoneSlab { .. } => self.is_transparent_dynamic(),
             Block::QuartzSlab { .. } => self.is_transparent_dynamic(),
@@ -1169,6 +1343,23 @@ impl Block {
                 <BlockDirection as BlockProperty>::decode(facing, &props, "facing");
                 <bool as BlockProperty>::decode(powered, &props, "powered");
             }
+            Block::Tripwire {
+                attached,
+                disarmed,
+                east,
+                north,
+                powered,
+                south,
+                west,
+            } => {
+                <bool as BlockProperty>::decode(attached, &props, "attached");
+                <bool as BlockProperty>::decode(disarmed, &props, "disarmed");
+                <bool as BlockProperty>::decode(east, &props, "east");
+                <bool as BlockProperty>::decode(north, &props, "north");
+                <bool as BlockProperty>::decode(powered, &props, "powered");
+                <bool as BlockProperty>::decode(south, &props, "south");
+                <bool as BlockProperty>::decode(west, &props, "west");
+            }
             Block::Comparator(Comparator {
                 facing,
                 mode,
@@ -1183,6 +1374,23 @@ impl Block {
                 <BlockFacing as BlockProperty>::decode(facing, &props, "facing");
                 <bool as BlockProperty>::decode(powered, &props, "powered");
             }
+            Block::Piston { extended, facing } => {
+                <bool as BlockProperty>::decode(extended, &props, "extended");
+                <BlockFacing as BlockProperty>::decode(facing, &props, "facing");
+            }
+            Block::StickyPiston { extended, facing } => {
+                <bool as BlockProperty>::decode(extended, &props, "extended");
+                <BlockFacing as BlockProperty>::decode(facing, &props, "facing");
+            }
+            Block::PistonHead { facing, short, ty } => {
+                <BlockFacing as BlockProperty>::decode(facing, &props, "facing");
+                <bool as BlockProperty>::decode(short, &props, "short");
+                <PistonType as BlockProperty>::decode(ty, &props, "type");
+            }
+            Block::MovingPiston { facing, ty } => {
+                <BlockFacing as BlockProperty>::decode(facing, &props, "facing");
+                <PistonType as BlockProperty>::decode(ty, &props, "type");
+            }
             Block::SeaPickle {
                 pickles,
                 waterlogged,
@@ -1193,6 +1401,51 @@ impl Block {
             Block::Target { power } => {
                 <u8 as BlockProperty>::decode(power, &props, "power");
             }
+            Block::DaylightDetector { inverted, power } => {
+                <bool as BlockProperty>::decode(inverted, &props, "inverted");
+                <u8 as BlockProperty>::decode(power, &props, "power");
+            }
+            Block::CopperBulb { lit, powered } => {
+                <bool as BlockProperty>::decode(lit, &props, "lit");
+                <bool as BlockProperty>::decode(powered, &props, "powered");
+            }
+            Block::ExposedCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::decode(lit, &props, "lit");
+                <bool as BlockProperty>::decode(powered, &props, "powered");
+            }
+            Block::WeatheredCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::decode(lit, &props, "lit");
+                <bool as BlockProperty>::decode(powered, &props, "powered");
+            }
+            Block::OxidizedCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::decode(lit, &props, "lit");
+                <bool as BlockProperty>::decode(powered, &props, "powered");
+            }
+            Block::WaxedCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::decode(lit, &props, "lit");
+                <bool as BlockProperty>::decode(powered, &props, "powered");
+            }
+            Block::WaxedExposedCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::decode(lit, &props, "lit");
+                <bool as BlockProperty>::decode(powered, &props, "powered");
+            }
+            Block::WaxedWeatheredCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::decode(lit, &props, "lit");
+                <bool as BlockProperty>::decode(powered, &props, "powered");
+            }
+            Block::WaxedOxidizedCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::decode(lit, &props, "lit");
+                <bool as BlockProperty>::decode(powered, &props, "powered");
+            }
+            Block::Crafter {
+                crafting,
+                orientation,
+                triggered,
+            } => {
+                <bool as BlockProperty>::decode(crafting, &props, "crafting");
+                <CrafterOrientation as BlockProperty>::decode(orientation, &props, "orientation");
+                <bool as BlockProperty>::decode(triggered, &props, "triggered");
+            }
             Block::OakPressurePlate { powered } => {
                 <bool as BlockProperty>::decode(powered, &props, "powered");
             }
@@ -1596,6 +1849,23 @@ impl Block {
                 <BlockDirection as BlockProperty>::encode(*facing, &mut props, "facing");
                 <bool as BlockProperty>::encode(*powered, &mut props, "powered");
             }
+            Block::Tripwire {
+                attached,
+                disarmed,
+                east,
+                north,
+                powered,
+                south,
+                west,
+            } => {
+                <bool as BlockProperty>::encode(*attached, &mut props, "attached");
+                <bool as BlockProperty>::encode(*disarmed, &mut props, "disarmed");
+                <bool as BlockProperty>::encode(*east, &mut props, "east");
+                <bool as BlockProperty>::encode(*north, &mut props, "north");
+                <bool as BlockProperty>::encode(*powered, &mut props, "powered");
+                <bool as BlockProperty>::encode(*south, &mut props, "south");
+                <bool as BlockProperty>::encode(*west, &mut props, "west");
+            }
             Block::Comparator(Comparator {
                 facing,
                 mode,
@@ -1610,6 +1880,23 @@ impl Block {
                 <BlockFacing as BlockProperty>::encode(*facing, &mut props, "facing");
                 <bool as BlockProperty>::encode(*powered, &mut props, "powered");
             }
+            Block::Piston { extended, facing } => {
+                <bool as BlockProperty>::encode(*extended, &mut props, "extended");
+                <BlockFacing as BlockProperty>::encode(*facing, &mut props, "facing");
+            }
+            Block::StickyPiston { extended, facing } => {
+                <bool as BlockProperty>::encode(*extended, &mut props, "extended");
+                <BlockFacing as BlockProperty>::encode(*facing, &mut props, "facing");
+            }
+            Block::PistonHead { facing, short, ty } => {
+                <BlockFacing as BlockProperty>::encode(*facing, &mut props, "facing");
+                <bool as BlockProperty>::encode(*short, &mut props, "short");
+                <PistonType as BlockProperty>::encode(*ty, &mut props, "type");
+            }
+            Block::MovingPiston { facing, ty } => {
+                <BlockFacing as BlockProperty>::encode(*facing, &mut props, "facing");
+                <PistonType as BlockProperty>::encode(*ty, &mut props, "type");
+            }
             Block::SeaPickle {
                 pickles,
                 waterlogged,
@@ -1620,6 +1907,55 @@ impl Block {
             Block::Target { power } => {
                 <u8 as BlockProperty>::encode(*power, &mut props, "power");
             }
+            Block::DaylightDetector { inverted, power } => {
+                <bool as BlockProperty>::encode(*inverted, &mut props, "inverted");
+                <u8 as BlockProperty>::encode(*power, &mut props, "power");
+            }
+            Block::CopperBulb { lit, powered } => {
+                <bool as BlockProperty>::encode(*lit, &mut props, "lit");
+                <bool as BlockProperty>::encode(*powered, &mut props, "powered");
+            }
+            Block::ExposedCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::encode(*lit, &mut props, "lit");
+                <bool as BlockProperty>::encode(*powered, &mut props, "powered");
+            }
+            Block::WeatheredCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::encode(*lit, &mut props, "lit");
+                <bool as BlockProperty>::encode(*powered, &mut props, "powered");
+            }
+            Block::OxidizedCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::encode(*lit, &mut props, "lit");
+                <bool as BlockProperty>::encode(*powered, &mut props, "powered");
+            }
+            Block::WaxedCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::encode(*lit, &mut props, "lit");
+                <bool as BlockProperty>::encode(*powered, &mut props, "powered");
+            }
+            Block::WaxedExposedCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::encode(*lit, &mut props, "lit");
+                <bool as BlockProperty>::encode(*powered, &mut props, "powered");
+            }
+            Block::WaxedWeatheredCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::encode(*lit, &mut props, "lit");
+                <bool as BlockProperty>::encode(*powered, &mut props, "powered");
+            }
+            Block::WaxedOxidizedCopperBulb { lit, powered } => {
+                <bool as BlockProperty>::encode(*lit, &mut props, "lit");
+                <bool as BlockProperty>::encode(*powered, &mut props, "powered");
+            }
+            Block::Crafter {
+                crafting,
+                orientation,
+                triggered,
+            } => {
+                <bool as BlockProperty>::encode(*crafting, &mut props, "crafting");
+                <CrafterOrientation as BlockProperty>::encode(
+                    *orientation,
+                    &mut props,
+                    "orientation",
+                );
+                <bool as BlockProperty>::encode(*triggered, &mut props, "triggered");
+            }
             Block::OakPressurePlate { powered } => {
                 <bool as BlockProperty>::encode(*powered, &mut props, "powered");
             }
@@ -2017,6 +2353,15 @@ impl Block {
                 <BlockDirection as BlockTransform>::rotate(facing, amt);
                 <bool as BlockTransform>::rotate(powered, amt);
             }
+            Block::Tripwire {
+                attached,
+                disarmed,
+                east,
+                north,
+                powered,
+                south,
+                west,
+            } => self.complex_rotate(amt),
             Block::Comparator(Comparator {
                 facing,
                 mode,
@@ -2031,6 +2376,23 @@ impl Block {
                 <BlockFacing as BlockTransform>::rotate(facing, amt);
                 <bool as BlockTransform>::rotate(powered, amt);
             }
+            Block::Piston { extended, facing } => {
+                <bool as BlockTransform>::rotate(extended, amt);
+                <BlockFacing as BlockTransform>::rotate(facing, amt);
+            }
+            Block::StickyPiston { extended, facing } => {
+                <bool as BlockTransform>::rotate(extended, amt);
+                <BlockFacing as BlockTransform>::rotate(facing, amt);
+            }
+            Block::PistonHead { facing, short, ty } => {
+                <BlockFacing as BlockTransform>::rotate(facing, amt);
+                <bool as BlockTransform>::rotate(short, amt);
+                <PistonType as BlockTransform>::rotate(ty, amt);
+            }
+            Block::MovingPiston { facing, ty } => {
+                <BlockFacing as BlockTransform>::rotate(facing, amt);
+                <PistonType as BlockTransform>::rotate(ty, amt);
+            }
             Block::SeaPickle {
                 pickles,
                 waterlogged,
@@ -2041,6 +2403,51 @@ impl Block {
             Block::Target { power } => {
                 <u8 as BlockTransform>::rotate(power, amt);
             }
+            Block::DaylightDetector { inverted, power } => {
+                <bool as BlockTransform>::rotate(inverted, amt);
+                <u8 as BlockTransform>::rotate(power, amt);
+            }
+            Block::CopperBulb { lit, powered } => {
+                <bool as BlockTransform>::rotate(lit, amt);
+                <bool as BlockTransform>::rotate(powered, amt);
+            }
+            Block::ExposedCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::rotate(lit, amt);
+                <bool as BlockTransform>::rotate(powered, amt);
+            }
+            Block::WeatheredCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::rotate(lit, amt);
+                <bool as BlockTransform>::rotate(powered, amt);
+            }
+            Block::OxidizedCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::rotate(lit, amt);
+                <bool as BlockTransform>::rotate(powered, amt);
+            }
+            Block::WaxedCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::rotate(lit, amt);
+                <bool as BlockTransform>::rotate(powered, amt);
+            }
+            Block::WaxedExposedCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::rotate(lit, amt);
+                <bool as BlockTransform>::rotate(powered, amt);
+            }
+            Block::WaxedWeatheredCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::rotate(lit, amt);
+                <bool as BlockTransform>::rotate(powered, amt);
+            }
+            Block::WaxedOxidizedCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::rotate(lit, amt);
+                <bool as BlockTransform>::rotate(powered, amt);
+            }
+            Block::Crafter {
+                crafting,
+                orientation,
+                triggered,
+            } => {
+                <bool as BlockTransform>::rotate(crafting, amt);
+                <CrafterOrientation as BlockTransform>::rotate(orientation, amt);
+                <bool as BlockTransform>::rotate(triggered, amt);
+            }
             Block::OakPressurePlate { powered } => {
                 <bool as BlockTransform>::rotate(powered, amt);
             }
@@ -2437,6 +2844,15 @@ impl Block {
                 <BlockDirection as BlockTransform>::flip(facing, dir);
                 <bool as BlockTransform>::flip(powered, dir);
             }
+            Block::Tripwire {
+                attached,
+                disarmed,
+                east,
+                north,
+                powered,
+                south,
+                west,
+            } => self.complex_flip(dir),
             Block::Comparator(Comparator {
                 facing,
                 mode,
@@ -2451,6 +2867,23 @@ impl Block {
                 <BlockFacing as BlockTransform>::flip(facing, dir);
                 <bool as BlockTransform>::flip(powered, dir);
             }
+            Block::Piston { extended, facing } => {
+                <bool as BlockTransform>::flip(extended, dir);
+                <BlockFacing as BlockTransform>::flip(facing, dir);
+            }
+            Block::StickyPiston { extended, facing } => {
+                <bool as BlockTransform>::flip(extended, dir);
+                <BlockFacing as BlockTransform>::flip(facing, dir);
+            }
+            Block::PistonHead { facing, short, ty } => {
+                <BlockFacing as BlockTransform>::flip(facing, dir);
+                <bool as BlockTransform>::flip(short, dir);
+                <PistonType as BlockTransform>::flip(ty, dir);
+            }
+            Block::MovingPiston { facing, ty } => {
+                <BlockFacing as BlockTransform>::flip(facing, dir);
+                <PistonType as BlockTransform>::flip(ty, dir);
+            }
             Block::SeaPickle {
                 pickles,
                 waterlogged,
@@ -2461,6 +2894,51 @@ impl Block {
             Block::Target { power } => {
                 <u8 as BlockTransform>::flip(power, dir);
             }
+            Block::DaylightDetector { inverted, power } => {
+                <bool as BlockTransform>::flip(inverted, dir);
+                <u8 as BlockTransform>::flip(power, dir);
+            }
+            Block::CopperBulb { lit, powered } => {
+                <bool as BlockTransform>::flip(lit, dir);
+                <bool as BlockTransform>::flip(powered, dir);
+            }
+            Block::ExposedCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::flip(lit, dir);
+                <bool as BlockTransform>::flip(powered, dir);
+            }
+            Block::WeatheredCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::flip(lit, dir);
+                <bool as BlockTransform>::flip(powered, dir);
+            }
+            Block::OxidizedCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::flip(lit, dir);
+                <bool as BlockTransform>::flip(powered, dir);
+            }
+            Block::WaxedCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::flip(lit, dir);
+                <bool as BlockTransform>::flip(powered, dir);
+            }
+            Block::WaxedExposedCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::flip(lit, dir);
+                <bool as BlockTransform>::flip(powered, dir);
+            }
+            Block::WaxedWeatheredCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::flip(lit, dir);
+                <bool as BlockTransform>::flip(powered, dir);
+            }
+            Block::WaxedOxidizedCopperBulb { lit, powered } => {
+                <bool as BlockTransform>::flip(lit, dir);
+                <bool as BlockTransform>::flip(powered, dir);
+            }
+            Block::Crafter {
+                crafting,
+                orientation,
+                triggered,
+            } => {
+                <bool as BlockTransform>::flip(crafting, dir);
+                <CrafterOrientation as BlockTransform>::flip(orientation, dir);
+                <bool as BlockTransform>::flip(triggered, dir);
+            }
             Block::OakPressurePlate { powered } => {
                 <bool as BlockTransform>::flip(powered, dir);
             }
@@ -2780,6 +3258,23 @@ impl Block {
                 facing,
                 powered,
             } => 7521 + (!attached as u32) * 8 + (facing.get_id()) * 2 + (!powered as u32),
+            Block::Tripwire {
+                attached,
+                disarmed,
+                east,
+                north,
+                powered,
+                south,
+                west,
+            } => {
+                7537 + (!attached as u32) * 64
+                    + (!disarmed as u32) * 32
+                    + (!east as u32) * 16
+                    + (!north as u32) * 8
+                    + (!powered as u32) * 4
+                    + (!south as u32) * 2
+                    + (!west as u32)
+            }
             Block::Comparator(Comparator {
                 facing,
                 mode,
@@ -2789,11 +3284,49 @@ impl Block {
             Block::Observer { facing, powered } => {
                 12550 + (facing.get_id()) * 2 + (!powered as u32)
             }
+            Block::Piston { extended, facing } => 2011 + (!extended as u32) * 6 + (facing.get_id()),
+            Block::StickyPiston { extended, facing } => {
+                1992 + (!extended as u32) * 6 + (facing.get_id())
+            }
+            Block::PistonHead { facing, short, ty } => {
+                2023 + (facing.get_id()) * 4 + (!short as u32) * 2 + (ty.get_id())
+            }
+            Block::MovingPiston { facing, ty } => 2063 + (facing.get_id()) * 2 + (ty.get_id()),
             Block::SeaPickle {
                 pickles,
                 waterlogged,
             } => 12933 + (pickles as u32 - 1) * 2 + (!waterlogged as u32),
             Block::Target { power } => 19381 + (power as u32 - 0),
+            Block::DaylightDetector { inverted, power } => {
+                9191 + (!inverted as u32) * 16 + (power as u32 - 0)
+            }
+            Block::CopperBulb { lit, powered } => 24692 + (!lit as u32) * 2 + (!powered as u32),
+            Block::ExposedCopperBulb { lit, powered } => {
+                24696 + (!lit as u32) * 2 + (!powered as u32)
+            }
+            Block::WeatheredCopperBulb { lit, powered } => {
+                24700 + (!lit as u32) * 2 + (!powered as u32)
+            }
+            Block::OxidizedCopperBulb { lit, powered } => {
+                24704 + (!lit as u32) * 2 + (!powered as u32)
+            }
+            Block::WaxedCopperBulb { lit, powered } => {
+                24708 + (!lit as u32) * 2 + (!powered as u32)
+            }
+            Block::WaxedExposedCopperBulb { lit, powered } => {
+                24712 + (!lit as u32) * 2 + (!powered as u32)
+            }
+            Block::WaxedWeatheredCopperBulb { lit, powered } => {
+                24716 + (!lit as u32) * 2 + (!powered as u32)
+            }
+            Block::WaxedOxidizedCopperBulb { lit, powered } => {
+                24720 + (!lit as u32) * 2 + (!powered as u32)
+            }
+            Block::Crafter {
+                crafting,
+                orientation,
+                triggered,
+            } => 26590 + (!crafting as u32) * 24 + (orientation.get_id()) * 2 + (!triggered as u32),
             Block::OakPressurePlate { powered } => 5716 + (!powered as u32),
             Block::SprucePressurePlate { powered } => 5718 + (!powered as u32),
             Block::BirchPressurePlate { powered } => 5720 + (!powered as u32),
@@ -3156,6 +3689,18 @@ impl Block {
                     powered: (((id) % 2) & 1) == 0,
                 }
             }
+            7537..7665 => {
+                id -= 7537;
+                Block::Tripwire {
+                    attached: (((id / 64) % 2) & 1) == 0,
+                    disarmed: (((id / 32) % 2) & 1) == 0,
+                    east: (((id / 16) % 2) & 1) == 0,
+                    north: (((id / 8) % 2) & 1) == 0,
+                    powered: (((id / 4) % 2) & 1) == 0,
+                    south: (((id / 2) % 2) & 1) == 0,
+                    west: (((id) % 2) & 1) == 0,
+                }
+            }
             9175..9191 => {
                 id -= 9175;
                 Block::Comparator(Comparator {
@@ -3175,6 +3720,35 @@ impl Block {
                     powered: (((id) % 2) & 1) == 0,
                 }
             }
+            2011..2023 => {
+                id -= 2011;
+                Block::Piston {
+                    extended: (((id / 6) % 2) & 1) == 0,
+                    facing: BlockFacing::from_id(((id) % 6)),
+                }
+            }
+            1992..2004 => {
+                id -= 1992;
+                Block::StickyPiston {
+                    extended: (((id / 6) % 2) & 1) == 0,
+                    facing: BlockFacing::from_id(((id) % 6)),
+                }
+            }
+            2023..2047 => {
+                id -= 2023;
+                Block::PistonHead {
+                    facing: BlockFacing::from_id(((id / 4) % 6)),
+                    short: (((id / 2) % 2) & 1) == 0,
+                    ty: PistonType::from_id(((id) % 2)),
+                }
+            }
+            2063..2075 => {
+                id -= 2063;
+                Block::MovingPiston {
+                    facing: BlockFacing::from_id(((id / 2) % 6)),
+                    ty: PistonType::from_id(((id) % 2)),
+                }
+            }
             12933..12941 => {
                 id -= 12933;
                 Block::SeaPickle {
@@ -3188,6 +3762,77 @@ impl Block {
                     power: ((id) % 16) as u8 + 0,
                 }
             }
+            9191..9223 => {
+                id -= 9191;
+                Block::DaylightDetector {
+                    inverted: (((id / 16) % 2) & 1) == 0,
+                    power: ((id) % 16) as u8 + 0,
+                }
+            }
+            24692..24696 => {
+                id -= 24692;
+                Block::CopperBulb {
+                    lit: (((id / 2) % 2) & 1) == 0,
+                    powered: (((id) % 2) & 1) == 0,
+                }
+            }
+            24696..24700 => {
+                id -= 24696;
+                Block::ExposedCopperBulb {
+                    lit: (((id / 2) % 2) & 1) == 0,
+                    powered: (((id) % 2) & 1) == 0,
+                }
+            }
+            24700..24704 => {
+                id -= 24700;
+                Block::WeatheredCopperBulb {
+                    lit: (((id / 2) % 2) & 1) == 0,
+                    powered: (((id) % 2) & 1) == 0,
+                }
+            }
+            24704..24708 => {
+                id -= 24704;
+                Block::OxidizedCopperBulb {
+                    lit: (((id / 2) % 2) & 1) == 0,
+                    powered: (((id) % 2) & 1) == 0,
+                }
+            }
+            24708..24712 => {
+                id -= 24708;
+                Block::WaxedCopperBulb {
+                    lit: (((id / 2) % 2) & 1) == 0,
+                    powered: (((id) % 2) & 1) == 0,
+                }
+            }
+            24712..24716 => {
+                id -= 24712;
+                Block::WaxedExposedCopperBulb {
+                    lit: (((id / 2) % 2) & 1) == 0,
+                    powered: (((id) % 2) & 1) == 0,
+                }
+            }
+            24716..24720 => {
+                id -= 24716;
+                Block::WaxedWeatheredCopperBulb {
+                    lit: (((id / 2) % 2) & 1) == 0,
+                    powered: (((id) % 2) & 1) == 0,
+                }
+            }
+            24720..24724 => {
+                id -= 24720;
+                Block::WaxedOxidizedCopperBulb {
+                    lit: (((id / 2) % 2) & 1) == 0,
+                    powered: (((id) % 2) & 1) == 0,
+                }
+            }
+            26590..26638 => {
+                id -= 26590;
+                Block::Crafter {
+                    crafting: (((id / 24) % 2) & 1) == 0,
+                    orientation: CrafterOrientation::from_id(((id / 2) % 12)),
+                    triggered: (((id) % 2) & 1) == 0,
+                }
+            }
             5716..5718 => {
                 id -= 5716;
                 Block::OakPressurePlate {
@@ -3935,6 +4580,36 @@ impl FromStr for ComparatorMode {
         })
     }
 }
+impl FromStr for PistonType {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "normal" => PistonType::Normal,
+            "sticky" => PistonType::Sticky,
+            _ => return Err(()),
+        })
+    }
+}
+impl FromStr for CrafterOrientation {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "down_east" => CrafterOrientation::DownEast,
+            "down_north" => CrafterOrientation::DownNorth,
+            "down_south" => CrafterOrientation::DownSouth,
+            "down_west" => CrafterOrientation::DownWest,
+            "up_east" => CrafterOrientation::UpEast,
+            "up_north" => CrafterOrientation::UpNorth,
+            "up_south" => CrafterOrientation::UpSouth,
+            "up_west" => CrafterOrientation::UpWest,
+            "west_up" => CrafterOrientation::WestUp,
+            "east_up" => CrafterOrientation::EastUp,
+            "north_up" => CrafterOrientation::NorthUp,
+            "south_up" => CrafterOrientation::SouthUp,
+            _ => return Err(()),
+        })
+    }
+}
 impl std::fmt::Display for BlockDirection {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(match self {
@@ -4049,6 +4724,32 @@ impl std::fmt::Display for ComparatorMode {
         })
     }
 }
+impl std::fmt::Display for PistonType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PistonType::Normal => "normal",
+            PistonType::Sticky => "sticky",
+        })
+    }
+}
+impl std::fmt::Display for CrafterOrientation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CrafterOrientation::DownEast => "down_east",
+            CrafterOrientation::DownNorth => "down_north",
+            CrafterOrientation::DownSouth => "down_south",
+            CrafterOrientation::DownWest => "down_west",
+            CrafterOrientation::UpEast => "up_east",
+            CrafterOrientation::UpNorth => "up_north",
+            CrafterOrientation::UpSouth => "up_south",
+            CrafterOrientation::UpWest => "up_west",
+            CrafterOrientation::WestUp => "west_up",
+            CrafterOrientation::EastUp => "east_up",
+            CrafterOrientation::NorthUp => "north_up",
+            CrafterOrientation::SouthUp => "south_up",
+        })
+    }
+}
 impl BlockDirection {
     fn get_id(self) -> u32 {
         self as u32
@@ -4203,6 +4904,40 @@ impl ComparatorMode {
         }
     }
 }
+impl PistonType {
+    fn get_id(self) -> u32 {
+        self as u32
+    }
+    fn from_id(id: u32) -> Self {
+        match id {
+            0 => PistonType::Normal,
+            1 => PistonType::Sticky,
+            id => unreachable!(),
+        }
+    }
+}
+impl CrafterOrientation {
+    fn get_id(self) -> u32 {
+        self as u32
+    }
+    fn from_id(id: u32) -> Self {
+        match id {
+            0 => CrafterOrientation::DownEast,
+            1 => CrafterOrientation::DownNorth,
+            2 => CrafterOrientation::DownSouth,
+            3 => CrafterOrientation::DownWest,
+            4 => CrafterOrientation::UpEast,
+            5 => CrafterOrientation::UpNorth,
+            6 => CrafterOrientation::UpSouth,
+            7 => CrafterOrientation::UpWest,
+            8 => CrafterOrientation::WestUp,
+            9 => CrafterOrientation::EastUp,
+            10 => CrafterOrientation::NorthUp,
+            11 => CrafterOrientation::SouthUp,
+            id => unreachable!(),
+        }
+    }
+}
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Item {
     Stone,
@@ -4228,8 +4963,20 @@ pub enum Item {
     Comparator,
     RedstoneBlock,
     Observer,
+    Piston,
+    StickyPiston,
     SeaPickle,
     Target,
+    DaylightDetector,
+    CopperBulb,
+    ExposedCopperBulb,
+    WeatheredCopperBulb,
+    OxidizedCopperBulb,
+    WaxedCopperBulb,
+    WaxedExposedCopperBulb,
+    WaxedWeatheredCopperBulb,
+    WaxedOxidizedCopperBulb,
+    Crafter,
     OakPressurePlate,
     SprucePressurePlate,
     BirchPressurePlate,
@@ -4339,6 +5086,7 @@ pub enum Item {
     TotemOfUndying,
     MilkBucket,
     Redstone,
+    String,
     EnderEye,
     Unknown(u32),
 }
@@ -4368,8 +5116,20 @@ impl Item {
             Item::Comparator => 660,
             Item::RedstoneBlock => 658,
             Item::Observer => 665,
+            Item::Piston => 661,
+            Item::StickyPiston => 662,
             Item::SeaPickle => 200,
             Item::Target => 670,
+            Item::DaylightDetector => 673,
+            Item::CopperBulb => 1302,
+            Item::ExposedCopperBulb => 1303,
+            Item::WeatheredCopperBulb => 1304,
+            Item::OxidizedCopperBulb => 1305,
+            Item::WaxedCopperBulb => 1306,
+            Item::WaxedExposedCopperBulb => 1307,
+            Item::WaxedWeatheredCopperBulb => 1308,
+            Item::WaxedOxidizedCopperBulb => 1309,
+            Item::Crafter => 978,
             Item::OakPressurePlate => 698,
             Item::SprucePressurePlate => 699,
             Item::BirchPressurePlate => 700,
@@ -4479,6 +5239,7 @@ impl Item {
             Item::TotemOfUndying => 1156,
             Item::MilkBucket => 911,
             Item::Redstone => 656,
+            Item::String => 847,
             Item::EnderEye => 1003,
             Item::Unknown(id) => id,
         }
@@ -4508,8 +5269,20 @@ impl Item {
             660 => Item::Comparator,
             658 => Item::RedstoneBlock,
             665 => Item::Observer,
+            661 => Item::Piston,
+            662 => Item::StickyPiston,
             200 => Item::SeaPickle,
             670 => Item::Target,
+            673 => Item::DaylightDetector,
+            1302 => Item::CopperBulb,
+            1303 => Item::ExposedCopperBulb,
+            1304 => Item::WeatheredCopperBulb,
+            1305 => Item::OxidizedCopperBulb,
+            1306 => Item::WaxedCopperBulb,
+            1307 => Item::WaxedExposedCopperBulb,
+            1308 => Item::WaxedWeatheredCopperBulb,
+            1309 => Item::WaxedOxidizedCopperBulb,
+            978 => Item::Crafter,
             698 => Item::OakPressurePlate,
             699 => Item::SprucePressurePlate,
             700 => Item::BirchPressurePlate,
@@ -4619,6 +5392,7 @@ impl Item {
             1156 => Item::TotemOfUndying,
             911 => Item::MilkBucket,
             656 => Item::Redstone,
+            847 => Item::String,
             1003 => Item::EnderEye,
             _ => Item::Unknown(id),
         }
@@ -4648,8 +5422,20 @@ impl Item {
             Item::Comparator => "minecraft:comparator",
             Item::RedstoneBlock => "minecraft:redstone_block",
             Item::Observer => "minecraft:observer",
+            Item::Piston => "minecraft:piston",
+            Item::StickyPiston => "minecraft:sticky_piston",
             Item::SeaPickle => "minecraft:sea_pickle",
             Item::Target => "minecraft:target",
+            Item::DaylightDetector => "minecraft:daylight_detector",
+            Item::CopperBulb => "minecraft:copper_bulb",
+            Item::ExposedCopperBulb => "minecraft:exposed_copper_bulb",
+            Item::WeatheredCopperBulb => "minecraft:weathered_copper_bulb",
+            Item::OxidizedCopperBulb => "minecraft:oxidized_copper_bulb",
+            Item::WaxedCopperBulb => "minecraft:waxed_copper_bulb",
+            Item::WaxedExposedCopperBulb => "minecraft:waxed_exposed_copper_bulb",
+            Item::WaxedWeatheredCopperBulb => "minecraft:waxed_weathered_copper_bulb",
+            Item::WaxedOxidizedCopperBulb => "minecraft:waxed_oxidized_copper_bulb",
+            Item::Crafter => "minecraft:crafter",
             Item::OakPressurePlate => "minecraft:oak_pressure_plate",
             Item::SprucePressurePlate => "minecraft:spruce_pressure_plate",
             Item::BirchPressurePlate => "minecraft:birch_pressure_plate",
@@ -4759,6 +5545,7 @@ impl Item {
             Item::TotemOfUndying => "minecraft:totem_of_undying",
             Item::MilkBucket => "minecraft:milk_bucket",
             Item::Redstone => "minecraft:redstone",
+            Item::String => "minecraft:string",
             Item::EnderEye => "minecraft:ender_eye",
             Item::Unknown(_) => "minecraft:redstone",
         }
@@ -4788,8 +5575,20 @@ impl Item {
             "minecraft:comparator" => Item::Comparator,
             "minecraft:redstone_block" => Item::RedstoneBlock,
             "minecraft:observer" => Item::Observer,
+            "minecraft:piston" => Item::Piston,
+            "minecraft:sticky_piston" => Item::StickyPiston,
             "minecraft:sea_pickle" => Item::SeaPickle,
             "minecraft:target" => Item::Target,
+            "minecraft:daylight_detector" => Item::DaylightDetector,
+            "minecraft:copper_bulb" => Item::CopperBulb,
+            "minecraft:exposed_copper_bulb" => Item::ExposedCopperBulb,
+            "minecraft:weathered_copper_bulb" => Item::WeatheredCopperBulb,
+            "minecraft:oxidized_copper_bulb" => Item::OxidizedCopperBulb,
+            "minecraft:waxed_copper_bulb" => Item::WaxedCopperBulb,
+            "minecraft:waxed_exposed_copper_bulb" => Item::WaxedExposedCopperBulb,
+            "minecraft:waxed_weathered_copper_bulb" => Item::WaxedWeatheredCopperBulb,
+            "minecraft:waxed_oxidized_copper_bulb" => Item::WaxedOxidizedCopperBulb,
+            "minecraft:crafter" => Item::Crafter,
             "minecraft:oak_pressure_plate" => Item::OakPressurePlate,
             "minecraft:spruce_pressure_plate" => Item::SprucePressurePlate,
             "minecraft:birch_pressure_plate" => Item::BirchPressurePlate,
@@ -4899,6 +5698,7 @@ impl Item {
             "minecraft:totem_of_undying" => Item::TotemOfUndying,
             "minecraft:milk_bucket" => Item::MilkBucket,
             "minecraft:redstone" => Item::Redstone,
+            "minecraft:string" => Item::String,
             "minecraft:ender_eye" => Item::EnderEye,
             _ => return None,
         })
@@ -5014,8 +5814,20 @@ impl Item {
             Item::Comparator => true,
             Item::RedstoneBlock => true,
             Item::Observer => true,
+            Item::Piston => true,
+            Item::StickyPiston => true,
             Item::SeaPickle => true,
             Item::Target => true,
+            Item::DaylightDetector => true,
+            Item::CopperBulb => true,
+            Item::ExposedCopperBulb => true,
+            Item::WeatheredCopperBulb => true,
+            Item::OxidizedCopperBulb => true,
+            Item::WaxedCopperBulb => true,
+            Item::WaxedExposedCopperBulb => true,
+            Item::WaxedWeatheredCopperBulb => true,
+            Item::WaxedOxidizedCopperBulb => true,
+            Item::Crafter => true,
             Item::OakPressurePlate => true,
             Item::SprucePressurePlate => true,
             Item::BirchPressurePlate => true,
@@ -5125,6 +5937,7 @@ impl Item {
             Item::TotemOfUndying => false,
             Item::MilkBucket => false,
             Item::Redstone => true,
+            Item::String => true,
             Item::EnderEye => false,
             Item::Unknown(_) => false,
         }
@@ -5154,8 +5967,20 @@ impl Item {
             Item::Comparator => 64,
             Item::RedstoneBlock => 64,
             Item::Observer => 64,
+            Item::Piston => 64,
+            Item::StickyPiston => 64,
             Item::SeaPickle => 64,
             Item::Target => 64,
+            Item::DaylightDetector => 64,
+            Item::CopperBulb => 64,
+            Item::ExposedCopperBulb => 64,
+            Item::WeatheredCopperBulb => 64,
+            Item::OxidizedCopperBulb => 64,
+            Item::WaxedCopperBulb => 64,
+            Item::WaxedExposedCopperBulb => 64,
+            Item::WaxedWeatheredCopperBulb => 64,
+            Item::WaxedOxidizedCopperBulb => 64,
+            Item::Crafter => 64,
             Item::OakPressurePlate => 64,
             Item::SprucePressurePlate => 64,
             Item::BirchPressurePlate => 64,
@@ -5265,6 +6090,7 @@ impl Item {
             Item::TotemOfUndying => 1,
             Item::MilkBucket => 1,
             Item::Redstone => 64,
+            Item::String => 64,
             Item::EnderEye => 64,
             Item::Unknown(_) => 64,
         }
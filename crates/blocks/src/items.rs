@@ -16,12 +16,19 @@ impl ItemStack {
             ContainerType::Barrel => Item::Barrel {},
             ContainerType::Hopper => Item::Hopper {},
             ContainerType::Furnace => Item::Furnace {},
+            ContainerType::Crafter => Item::Crafter {},
         };
         let slots = container_ty.num_slots() as u32;
 
-        let items_needed = match ss {
-            0 => 0,
-            15 => slots * 64,
+        // Crafters output the number of occupied slots, so they only hold one item per slot
+        let stack_size = match container_ty {
+            ContainerType::Crafter => 1,
+            _ => 64,
+        };
+        let items_needed = match (container_ty, ss) {
+            (ContainerType::Crafter, _) => ss.min(9) as u32,
+            (_, 0) => 0,
+            (_, 15) => slots * 64,
             _ => ((32 * slots * ss as u32) as f32 / 7.0 - 1.0).ceil() as u32,
         } as usize;
 
@@ -30,8 +37,8 @@ impl ItemStack {
             _ => Some({
                 let list = nbt::Value::List({
                     let mut items = Vec::new();
-                    for (slot, items_added) in (0..items_needed).step_by(64).enumerate() {
-                        let count = (items_needed - items_added).min(64);
+                    for (slot, items_added) in (0..items_needed).step_by(stack_size).enumerate() {
+                        let count = (items_needed - items_added).min(stack_size);
                         items.push(nbt::Value::Compound(map! {
                             "Count" => nbt::Value::Byte(count as i8),
                             "id" => nbt::Value::String("minecraft:redstone".to_owned()),
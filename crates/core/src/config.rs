@@ -73,7 +73,40 @@ gen_config! {
     luckperms: Option<PermissionsConfig> = None,
     block_in_hitbox: bool = true,
     auto_redpiler: bool = false,
-    velocity: Option<VelocityConfig> = None
+    worldedit_history_entries: i64 = 100,
+    worldedit_history_blocks: i64 = 50_000_000,
+    online_mode: bool = false,
+    velocity: Option<VelocityConfig> = None,
+    bungeecord: Option<BungeeCordConfig> = None
+}
+
+impl ServerConfig {
+    pub fn velocity_enabled(&self) -> bool {
+        self.velocity
+            .as_ref()
+            .is_some_and(|velocity| velocity.enabled)
+    }
+
+    pub fn bungeecord_enabled(&self) -> bool {
+        self.bungeecord
+            .as_ref()
+            .is_some_and(|bungeecord| bungeecord.enabled)
+    }
+
+    /// Whether players are authenticated by a proxy that forwards their info to the server.
+    pub fn forwarding_enabled(&self) -> bool {
+        self.velocity_enabled() || self.bungeecord_enabled()
+    }
+
+    /// Checks for options that cannot be used together.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.velocity_enabled() && self.bungeecord_enabled() {
+            return Err(
+                "Velocity and BungeeCord forwarding cannot be enabled at the same time".to_string(),
+            );
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -81,3 +114,8 @@ pub struct VelocityConfig {
     pub enabled: bool,
     pub secret: String,
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct BungeeCordConfig {
+    pub enabled: bool,
+}
@@ -61,6 +61,30 @@ pub fn on_use(
     }
 }
 
+/// Crafters face towards the player. When placed facing up or down, their top faces along the
+/// player's horizontal direction instead.
+fn get_crafter_orientation(context: &UseOnBlockContext<'_>) -> CrafterOrientation {
+    let direction = context.player.get_direction();
+    match context.player.get_facing().opposite() {
+        BlockFacing::Down => match direction.opposite() {
+            BlockDirection::North => CrafterOrientation::DownNorth,
+            BlockDirection::South => CrafterOrientation::DownSouth,
+            BlockDirection::East => CrafterOrientation::DownEast,
+            BlockDirection::West => CrafterOrientation::DownWest,
+        },
+        BlockFacing::Up => match direction {
+            BlockDirection::North => CrafterOrientation::UpNorth,
+            BlockDirection::South => CrafterOrientation::UpSouth,
+            BlockDirection::East => CrafterOrientation::UpEast,
+            BlockDirection::West => CrafterOrientation::UpWest,
+        },
+        BlockFacing::North => CrafterOrientation::NorthUp,
+        BlockFacing::South => CrafterOrientation::SouthUp,
+        BlockFacing::East => CrafterOrientation::EastUp,
+        BlockFacing::West => CrafterOrientation::WestUp,
+    }
+}
+
 fn get_sign_placement(
     context: &UseOnBlockContext<'_>,
     standard: fn(u8) -> Block,
@@ -164,6 +188,7 @@ pub fn get_state_for_placement(
                 attached: false,
             },
         },
+        Item::String => redstone::tripwire::get_state_for_placement(world, pos),
         Item::StoneButton => {
             let face = match context.block_face {
                 BlockFace::Top => LeverFace::Floor,
@@ -200,12 +225,65 @@ pub fn get_state_for_placement(
             false,
         )),
         Item::Redstone => Block::RedstoneWire(redstone::wire::get_state_for_placement(world, pos)),
+        Item::Observer => Block::Observer {
+            facing: context.player.get_facing(),
+            powered: false,
+        },
+        Item::Piston => Block::Piston {
+            extended: false,
+            facing: context.player.get_facing().opposite(),
+        },
+        Item::StickyPiston => Block::StickyPiston {
+            extended: false,
+            facing: context.player.get_facing().opposite(),
+        },
         // TODO: Barrel facing
         Item::Barrel => Block::Barrel {
             facing: BlockFacing::Up,
             open: false,
         },
         Item::Target => Block::Target { power: 0 },
+        Item::DaylightDetector => Block::DaylightDetector {
+            inverted: false,
+            power: 0,
+        },
+        Item::CopperBulb => Block::CopperBulb {
+            lit: false,
+            powered: false,
+        },
+        Item::ExposedCopperBulb => Block::ExposedCopperBulb {
+            lit: false,
+            powered: false,
+        },
+        Item::WeatheredCopperBulb => Block::WeatheredCopperBulb {
+            lit: false,
+            powered: false,
+        },
+        Item::OxidizedCopperBulb => Block::OxidizedCopperBulb {
+            lit: false,
+            powered: false,
+        },
+        Item::WaxedCopperBulb => Block::WaxedCopperBulb {
+            lit: false,
+            powered: false,
+        },
+        Item::WaxedExposedCopperBulb => Block::WaxedExposedCopperBulb {
+            lit: false,
+            powered: false,
+        },
+        Item::WaxedWeatheredCopperBulb => Block::WaxedWeatheredCopperBulb {
+            lit: false,
+            powered: false,
+        },
+        Item::WaxedOxidizedCopperBulb => Block::WaxedOxidizedCopperBulb {
+            lit: false,
+            powered: false,
+        },
+        Item::Crafter => Block::Crafter {
+            crafting: false,
+            orientation: get_crafter_orientation(context),
+            triggered: false,
+        },
         Item::SmoothStoneSlab => Block::SmoothStoneSlab {
             ty: SlabType::Top,
             waterlogged: false,
@@ -287,18 +365,29 @@ pub fn place_in_world(
         world.set_block_entity(pos, block_entity);
     };
     world.set_block(pos, block);
+    redstone::observer::notify_observers(world, pos);
     change_surrounding_blocks(world, pos);
     if let Block::RedstoneWire { .. } = block {
         redstone::update_wire_neighbors(world, pos);
     } else {
         redstone::update_surrounding_blocks(world, pos);
     }
+    match block {
+        Block::Piston { .. } | Block::StickyPiston { .. } => redstone::update(block, world, pos),
+        Block::TripwireHook { .. } => redstone::tripwire::update_hook(world, pos, false),
+        Block::Tripwire { .. } => redstone::tripwire::update_source(world, pos),
+        block if block.clone().get_copper_bulb_state().is_some() => {
+            redstone::update(block, world, pos)
+        }
+        _ => {}
+    }
 }
 
 pub fn destroy(block: Block, world: &mut impl World, pos: BlockPos) {
     if block.has_block_entity() {
         world.delete_block_entity(pos);
     }
+    redstone::observer::notify_observers(world, pos);
 
     match block {
         Block::RedstoneWire { .. } => {
@@ -328,6 +417,58 @@ pub fn destroy(block: Block, world: &mut impl World, pos: BlockPos) {
                 }
             }
         }
+        Block::PistonHead { facing, .. }
+        | Block::Piston {
+            extended: true,
+            facing,
+        }
+        | Block::StickyPiston {
+            extended: true,
+            facing,
+        } => {
+            // Breaking either part of an extended piston removes the other part as well
+            let other_pos = match block {
+                Block::PistonHead { .. } => pos.offset(facing.opposite().block_face()),
+                _ => pos.offset(facing.block_face()),
+            };
+            let is_other_part = match world.get_block(other_pos) {
+                Block::PistonHead { facing: other, .. } => {
+                    !matches!(block, Block::PistonHead { .. }) && other == facing
+                }
+                Block::Piston {
+                    extended: true,
+                    facing: other,
+                }
+                | Block::StickyPiston {
+                    extended: true,
+                    facing: other,
+                } => matches!(block, Block::PistonHead { .. }) && other == facing,
+                _ => false,
+            };
+            world.set_block(pos, Block::Air);
+            change_surrounding_blocks(world, pos);
+            redstone::update_surrounding_blocks(world, pos);
+            if is_other_part {
+                world.set_block(other_pos, Block::Air);
+                redstone::observer::notify_observers(world, other_pos);
+                change_surrounding_blocks(world, other_pos);
+                redstone::update_surrounding_blocks(world, other_pos);
+            }
+        }
+        Block::TripwireHook { facing, .. } => {
+            // Detach the hook on the other end of the string
+            redstone::tripwire::update_hook(world, pos, true);
+            world.set_block(pos, Block::Air);
+            change_surrounding_blocks(world, pos);
+            redstone::update_surrounding_blocks(world, pos);
+            redstone::update_surrounding_blocks(world, pos.offset(facing.opposite().block_face()));
+        }
+        Block::Tripwire { .. } => {
+            world.set_block(pos, Block::Air);
+            change_surrounding_blocks(world, pos);
+            redstone::update_surrounding_blocks(world, pos);
+            redstone::tripwire::update_source(world, pos);
+        }
         _ => {
             world.set_block(pos, Block::Air);
             change_surrounding_blocks(world, pos);
@@ -405,9 +546,16 @@ pub fn change(block: Block, world: &mut impl World, pos: BlockPos, direction: Bl
     if let Block::RedstoneWire(wire) = block {
         let new_state = redstone::wire::on_neighbor_changed(wire, world, pos, direction);
         if world.set_block(pos, Block::RedstoneWire(new_state)) {
+            redstone::observer::notify_observers(world, pos);
             redstone::update_wire_neighbors(world, pos);
         }
     }
+    if let Block::Tripwire { .. } = block {
+        let new_state = redstone::tripwire::update_connections(block, world, pos);
+        if world.set_block(pos, new_state) {
+            redstone::observer::notify_observers(world, pos);
+        }
+    }
 }
 
 fn change_surrounding_blocks(world: &mut impl World, pos: BlockPos) {
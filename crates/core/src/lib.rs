@@ -9,6 +9,7 @@ mod player;
 pub mod plot;
 mod profile;
 pub mod server;
+pub mod session;
 
 #[macro_use]
 extern crate bitflags;
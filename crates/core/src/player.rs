@@ -1,6 +1,8 @@
 use crate::config::CONFIG;
 use crate::permissions::{self, PlayerPermissionsCache};
-use crate::plot::worldedit::WorldEditUndo;
+use crate::plot::worldedit::mask::WorldEditMask;
+use crate::plot::worldedit::region::{Region, RegionSelector, MAX_HULL_VERTICES};
+use crate::plot::worldedit::tool::WorldEditTool;
 use crate::plot::PLOT_SCALE;
 use crate::utils::{self, HyphenatedUUID};
 use byteorder::{BigEndian, ReadBytesExt};
@@ -161,12 +163,19 @@ pub struct Player {
     pub first_position: Option<BlockPos>,
     /// The worldedit second position.
     pub second_position: Option<BlockPos>,
+    /// The shape of the worldedit selection, chosen with `//sel`.
+    pub worldedit_selector: RegionSelector,
+    /// The vertices of a polygon or convex hull selection, starting at the first position.
+    pub worldedit_vertices: Vec<BlockPos>,
+    /// The mask set with `//gmask`, limiting which blocks worldedit commands change.
+    pub worldedit_global_mask: Option<WorldEditMask>,
+    /// The tools bound to items with `//brush` and `//tool`.
+    pub worldedit_tools: Vec<(Item, WorldEditTool)>,
+    /// When a tool was last used on a clicked block, to ignore the use item packet sent for the
+    /// same click.
+    pub worldedit_tool_used_at: Option<Instant>,
     /// The worldedit current clipboard.
     pub worldedit_clipboard: Option<WorldEditClipboard>,
-    /// The saved sections used for worldedit //undo
-    /// Each entry stores the plot coords and the clipboard
-    pub worldedit_undo: Vec<WorldEditUndo>,
-    pub worldedit_redo: Vec<WorldEditUndo>,
     /// Commands are stored so they can be handled after packets
     pub command_queue: Vec<String>,
     permissions_cache: Option<PlayerPermissionsCache>,
@@ -243,9 +252,12 @@ impl Player {
             last_keep_alive_sent: Instant::now(),
             first_position: None,
             second_position: None,
+            worldedit_selector: RegionSelector::Cuboid,
+            worldedit_vertices: Vec::new(),
+            worldedit_global_mask: None,
+            worldedit_tools: Vec::new(),
+            worldedit_tool_used_at: None,
             worldedit_clipboard: None,
-            worldedit_undo: Vec::new(),
-            worldedit_redo: Vec::new(),
             command_queue: Vec::new(),
             permissions_cache,
         }
@@ -439,21 +451,155 @@ impl Player {
     }
 
     pub fn worldedit_set_first_position(&mut self, pos: BlockPos) {
-        self.send_worldedit_message(&format!(
-            "First position set to ({}, {}, {})",
-            pos.x, pos.y, pos.z
-        ));
         self.first_position = Some(pos);
-        self.worldedit_send_cui(&format!("p|0|{}|{}|{}|0", pos.x, pos.y, pos.z));
+        if self.worldedit_selector == RegionSelector::Cuboid {
+            self.send_worldedit_message(&format!(
+                "First position set to ({}, {}, {})",
+                pos.x, pos.y, pos.z
+            ));
+            self.worldedit_send_cui(&format!("p|0|{}|{}|{}|0", pos.x, pos.y, pos.z));
+            return;
+        }
+
+        // The rest of the selection was built from the old first position
+        self.second_position = None;
+        self.worldedit_vertices.clear();
+        if self.worldedit_selector.uses_vertices() {
+            self.worldedit_vertices.push(pos);
+            self.send_worldedit_message(&format!("Started a new selection at {}", pos));
+        } else {
+            self.send_worldedit_message(&format!("Center set to {}", pos));
+        }
+        self.worldedit_send_selection_cui();
     }
 
     pub fn worldedit_set_second_position(&mut self, pos: BlockPos) {
-        self.send_worldedit_message(&format!(
-            "Second position set to ({}, {}, {})",
-            pos.x, pos.y, pos.z
-        ));
+        match self.worldedit_selector {
+            RegionSelector::Cuboid => {
+                self.send_worldedit_message(&format!(
+                    "Second position set to ({}, {}, {})",
+                    pos.x, pos.y, pos.z
+                ));
+                self.second_position = Some(pos);
+                self.worldedit_send_cui(&format!("p|1|{}|{}|{}|0", pos.x, pos.y, pos.z));
+                return;
+            }
+            selector if selector.uses_vertices() => {
+                if self.worldedit_vertices.is_empty() {
+                    self.worldedit_set_first_position(pos);
+                    return;
+                }
+                if selector == RegionSelector::ConvexHull
+                    && self.worldedit_vertices.len() >= MAX_HULL_VERTICES
+                {
+                    self.send_error_message(&format!(
+                        "A convex selection can't have more than {} vertices.",
+                        MAX_HULL_VERTICES
+                    ));
+                    return;
+                }
+                self.worldedit_vertices.push(pos);
+                self.send_worldedit_message(&format!(
+                    "Added vertex #{} at {}",
+                    self.worldedit_vertices.len(),
+                    pos
+                ));
+            }
+            _ => self.send_worldedit_message(&format!("Radius set to {}", pos)),
+        }
         self.second_position = Some(pos);
-        self.worldedit_send_cui(&format!("p|1|{}|{}|{}|0", pos.x, pos.y, pos.z));
+        self.worldedit_send_selection_cui();
+    }
+
+    /// Changes the shape of the selection. The selection is cleared, since the positions mean
+    /// something different for each shape.
+    pub fn worldedit_set_selector(&mut self, selector: RegionSelector) {
+        self.worldedit_selector = selector;
+        self.first_position = None;
+        self.second_position = None;
+        self.worldedit_vertices.clear();
+        self.worldedit_send_cui(&format!("s|{}", selector.cui_name()));
+    }
+
+    /// Returns the selected region, or `None` if the selection is incomplete.
+    pub fn worldedit_region(&self) -> Option<Region> {
+        Region::new(
+            self.worldedit_selector,
+            self.first_position,
+            self.second_position,
+            &self.worldedit_vertices,
+        )
+    }
+
+    /// Moves the whole selection by `offset`.
+    pub fn worldedit_shift_selection(&mut self, offset: BlockPos) {
+        if self.worldedit_selector == RegionSelector::Cuboid {
+            if let Some(pos) = self.first_position {
+                self.worldedit_set_first_position(pos + offset);
+            }
+            if let Some(pos) = self.second_position {
+                self.worldedit_set_second_position(pos + offset);
+            }
+            return;
+        }
+        self.first_position = self.first_position.map(|pos| pos + offset);
+        self.second_position = self.second_position.map(|pos| pos + offset);
+        for vertex in &mut self.worldedit_vertices {
+            *vertex = *vertex + offset;
+        }
+        self.worldedit_send_selection_cui();
+    }
+
+    /// Sends the whole selection to the CUI mod. A cuboid selection sends each position as it is
+    /// set instead.
+    fn worldedit_send_selection_cui(&self) {
+        self.worldedit_send_cui(&format!("s|{}", self.worldedit_selector.cui_name()));
+        match self.worldedit_selector {
+            RegionSelector::Cuboid => {}
+            RegionSelector::Poly2d => {
+                for (i, pos) in self.worldedit_vertices.iter().enumerate() {
+                    self.worldedit_send_cui(&format!("p2|{}|{}|{}|0", i, pos.x, pos.z));
+                }
+                let heights = self.worldedit_vertices.iter().map(|pos| pos.y);
+                if let (Some(min_y), Some(max_y)) = (heights.clone().min(), heights.max()) {
+                    self.worldedit_send_cui(&format!("mm|{}|{}", min_y, max_y));
+                }
+            }
+            RegionSelector::Cylinder => {
+                if let Some(Region::Cylinder {
+                    center,
+                    radius_x,
+                    radius_z,
+                    min_y,
+                    max_y,
+                }) = self.worldedit_region()
+                {
+                    self.worldedit_send_cui(&format!(
+                        "cyl|{}|{}|{}|{}|{}",
+                        center.x, center.y, center.z, radius_x, radius_z
+                    ));
+                    self.worldedit_send_cui(&format!("mm|{}|{}", min_y, max_y));
+                } else if let Some(center) = self.first_position {
+                    self.worldedit_send_cui(&format!(
+                        "cyl|{}|{}|{}|0|0",
+                        center.x, center.y, center.z
+                    ));
+                }
+            }
+            RegionSelector::Sphere => {
+                if let Some(center) = self.first_position {
+                    self.worldedit_send_cui(&format!("e|0|{}|{}|{}", center.x, center.y, center.z));
+                }
+                if let Some(Region::Sphere { radius, .. }) = self.worldedit_region() {
+                    self.worldedit_send_cui(&format!("e|1|{0}|{0}|{0}", radius));
+                }
+            }
+            RegionSelector::ConvexHull => {
+                for (i, pos) in self.worldedit_vertices.iter().enumerate() {
+                    self.worldedit_send_cui(&format!("p|{}|{}|{}|{}|0", i, pos.x, pos.y, pos.z));
+                }
+            }
+        }
     }
 
     pub fn worldedit_send_cui(&self, message: &str) {
@@ -1,22 +1,35 @@
-use super::{database, worldedit, Plot, PlotWorld};
+use super::scoreboard::RedpilerState;
+use super::worldedit::region::RegionSelector;
+use super::{database, send_diagnostics, worldedit, Plot, PlotWorld};
 use crate::player::{Gamemode, PacketSender, PlayerPos};
 use crate::plot::data::sleep_time_for_tps;
 use crate::profile::PlayerProfile;
 use crate::server::{get_version_string, Message};
+use mchprs_blocks::block_entities::ContainerType;
+use mchprs_blocks::blocks::Block;
 use mchprs_blocks::items::ItemStack;
+use mchprs_blocks::{BlockDirection, BlockPos};
 use mchprs_network::packets::clientbound::{
     CCommands, CCommandsNode as Node, CDeclareCommandsNodeParser as Parser, ClientBoundPacket,
 };
 use mchprs_network::packets::PacketEncoder;
 use mchprs_network::PlayerPacketSender;
+use mchprs_redpiler::backend::NodeInspection;
+use mchprs_redpiler::breakpoint::Condition;
+use mchprs_redpiler::compile_graph::LinkType;
+use mchprs_redpiler::diagnostics::Diagnostics;
 use mchprs_redpiler::CompilerOptions;
+use mchprs_redstone::{target, tripwire};
 use mchprs_save_data::plot_data::{Tps, WorldSendRate};
-use mchprs_text::TextComponent;
+use mchprs_text::{ClickEvent, ColorCode, TextComponent, TextComponentBuilder};
+use mchprs_world::World;
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::ops::Add;
 use std::str::FromStr;
 use std::sync::LazyLock;
 use std::time::Instant;
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
 
 // Parses a relative or absolute coordinate relative to a reference coordinate
 fn parse_relative_coord<F: FromStr + Add + Add<Output = F>>(
@@ -32,6 +45,86 @@ fn parse_relative_coord<F: FromStr + Add + Add<Output = F>>(
     }
 }
 
+fn text(text: String, color: ColorCode) -> TextComponent {
+    TextComponentBuilder::new(text).color_code(color).finish()
+}
+
+/// Renders the result of `/redpiler inspect`. Clicking a link inspects the node on its other end.
+fn inspection_message(pos: BlockPos, inspection: &NodeInspection) -> Vec<TextComponent> {
+    let state = &inspection.state;
+    let mut message = vec![
+        text(
+            format!("Node #{} at {}\n", inspection.id, pos),
+            ColorCode::Gold,
+        ),
+        text("Type: ".to_string(), ColorCode::Gray),
+        text(format!("{}\n", inspection.ty), ColorCode::White),
+        text("State: ".to_string(), ColorCode::Gray),
+        text(
+            format!(
+                "{}, ss {}{}{}\n",
+                if state.powered {
+                    "powered"
+                } else {
+                    "unpowered"
+                },
+                state.output_strength,
+                if state.repeater_locked {
+                    ", locked"
+                } else {
+                    ""
+                },
+                if inspection.is_io { ", io" } else { "" },
+            ),
+            ColorCode::White,
+        ),
+        text("Pending ticks: ".to_string(), ColorCode::Gray),
+    ];
+    let pending_ticks = if inspection.pending_ticks.is_empty() {
+        "none".to_string()
+    } else {
+        let ticks: Vec<String> = inspection
+            .pending_ticks
+            .iter()
+            .map(|(delay, priority)| format!("in {} ({:?})", delay, priority))
+            .collect();
+        ticks.join(", ")
+    };
+    message.push(text(pending_ticks, ColorCode::White));
+
+    for (title, links) in [
+        ("Inputs", &inspection.inputs),
+        ("Outputs", &inspection.outputs),
+    ] {
+        message.push(text(
+            format!("\n{} ({}):", title, links.len()),
+            ColorCode::Gray,
+        ));
+        for link in links {
+            let ty = match link.ty {
+                LinkType::Default => "default",
+                LinkType::Side => "side",
+            };
+            message.push(text(
+                format!("\n  {} -{} ss {} ", ty, link.weight, link.power),
+                ColorCode::White,
+            ));
+            message.push(match link.pos {
+                Some(pos) => TextComponentBuilder::new(pos.to_string())
+                    .color_code(ColorCode::Aqua)
+                    .underlined(true)
+                    .click_event(ClickEvent::run_command(format!(
+                        "/redpiler inspect {} {} {}",
+                        pos.x, pos.y, pos.z
+                    )))
+                    .finish(),
+                None => text("(no block)".to_string(), ColorCode::DarkGray),
+            });
+        }
+    }
+    message
+}
+
 impl Plot {
     /// Handles a command that starts with `/plot` or `/p`
     fn handle_plot_command(&mut self, player: usize, command: &str, args: &[&str]) {
@@ -46,6 +139,7 @@ impl Plot {
             "teleport" | "tp" => "plots.visit",
             "lock" | "unlock" => "plots.lock",
             "sel" | "select" => "plots.select",
+            "merge" => "plots.merge",
             _ => {
                 self.players[player].send_error_message("Invalid argument for /plot");
                 return;
@@ -164,9 +258,31 @@ impl Plot {
             }
             "select" | "sel" => {
                 let corners = self.world.get_corners();
+                if self.players[player].worldedit_selector != RegionSelector::Cuboid {
+                    self.players[player].worldedit_set_selector(RegionSelector::Cuboid);
+                }
                 self.players[player].worldedit_set_first_position(corners.0);
                 self.players[player].worldedit_set_second_position(corners.1);
             }
+            "merge" => {
+                if args.len() > 1 {
+                    self.players[player].send_error_message("Invalid number of arguments!");
+                    return;
+                }
+                let direction = match args.first() {
+                    Some(arg) => match BlockDirection::from_str(arg) {
+                        Ok(direction) => direction,
+                        Err(()) => {
+                            self.players[player].send_error_message(
+                                "Direction must be north, south, east or west.",
+                            );
+                            return;
+                        }
+                    },
+                    None => self.players[player].get_direction(),
+                };
+                self.merge_plots(player, direction);
+            }
             _ => self.players[player].send_error_message("Invalid argument for /plot"),
         }
     }
@@ -177,7 +293,12 @@ impl Plot {
             "compile" | "c" => {
                 let start_time = Instant::now();
                 let args = args.join(" ");
-                let options = CompilerOptions::parse(&args);
+                let mut diagnostics = Diagnostics::default();
+                let options = CompilerOptions::parse(&args, &mut diagnostics);
+                if diagnostics.has_errors() {
+                    send_diagnostics(&self.players[player], &diagnostics);
+                    return;
+                }
 
                 if options.optimize {
                     let msg = "Redpiler optimization is highly unstable and can break builds. Use with caution!";
@@ -185,33 +306,389 @@ impl Plot {
                     self.players[player].send_system_message(msg);
                 }
 
+                let selection = if options.selection {
+                    let p = &self.players[player];
+                    let Some(region) = p.worldedit_region() else {
+                        p.send_error_message("Make a region selection first.");
+                        return;
+                    };
+                    let (plot_min, plot_max) = self.world.get_corners();
+                    let (min, max) = region.bounds();
+                    if min.min(plot_min) != plot_min || max.max(plot_max) != plot_max {
+                        p.send_error_message("Your selection must be inside of the plot.");
+                        return;
+                    }
+                    Some((min, max))
+                } else {
+                    None
+                };
+
                 self.reset_redpiler();
+                self.redpiler_bounds = selection;
                 self.start_redpiler(options);
 
                 debug!("Compile took {:?}", start_time.elapsed());
             }
             "inspect" | "i" => {
                 let player = &self.players[player];
-                let pos = worldedit::ray_trace_block(
-                    &self.world,
-                    player.pos,
-                    player.pitch as f64,
-                    player.yaw as f64,
-                    10.0,
-                );
+                let pos = match args {
+                    [] => worldedit::ray_trace_block(
+                        &self.world,
+                        player.pos,
+                        player.pitch as f64,
+                        player.yaw as f64,
+                        10.0,
+                    ),
+                    [x, y, z] => {
+                        let player_pos = player.pos.block_pos();
+                        let x = parse_relative_coord(x, player_pos.x);
+                        let y = parse_relative_coord(y, player_pos.y);
+                        let z = parse_relative_coord(z, player_pos.z);
+                        let (Ok(x), Ok(y), Ok(z)) = (x, y, z) else {
+                            player.send_error_message("Unable to parse coordinates!");
+                            return;
+                        };
+                        Some(BlockPos::new(x, y, z))
+                    }
+                    _ => {
+                        player.send_error_message("Usage: /redpiler inspect [<x> <y> <z>]");
+                        return;
+                    }
+                };
                 let Some(pos) = pos else {
                     player.send_error_message("Trace failed");
                     return;
                 };
-                self.redpiler.inspect(pos);
+                if !self.redpiler.is_active() {
+                    player.send_error_message("Redpiler is not running.");
+                    return;
+                }
+                match self.redpiler.inspect(pos) {
+                    Some(inspection) => {
+                        player.send_chat_message(&inspection_message(pos, &inspection))
+                    }
+                    None => {
+                        player.send_error_message("That block is not part of the compiled circuit.")
+                    }
+                }
             }
             "reset" | "r" => {
                 self.reset_redpiler();
             }
+            "snapshot" | "s" => self.handle_redpiler_snapshot_command(player, args),
+            "probe" | "p" => self.handle_redpiler_probe_command(player, args),
+            "trace" | "t" => self.handle_redpiler_trace_command(player, args),
+            "break" | "b" => self.handle_redpiler_break_command(player, args),
+            "pause" => {
+                if !self.redpiler.is_active() {
+                    self.players[player].send_error_message("Redpiler is not running.");
+                    return;
+                }
+                self.redpiler_paused = true;
+                self.scoreboard
+                    .set_redpiler_state(&self.players, RedpilerState::Paused);
+            }
+            "continue" => {
+                if !self.redpiler_paused {
+                    self.players[player].send_error_message("Redpiler is not paused.");
+                    return;
+                }
+                self.redpiler_paused = false;
+                self.scoreboard
+                    .set_redpiler_state(&self.players, RedpilerState::Running);
+            }
+            "step" => self.handle_redpiler_step_command(player, args),
             _ => self.players[player].send_error_message("Invalid argument for /redpiler"),
         }
     }
 
+    /// Handles `/redpiler snapshot save|load <name>`
+    fn handle_redpiler_snapshot_command(&mut self, player: usize, args: &[&str]) {
+        let (action, name) = match args {
+            [action, name] => (*action, *name),
+            _ => {
+                self.players[player]
+                    .send_error_message("Usage: /redpiler snapshot save|load <name>");
+                return;
+            }
+        };
+        if !self.redpiler.is_active() {
+            self.players[player].send_error_message("Redpiler is not running.");
+            return;
+        }
+        match action {
+            "save" => {
+                let snapshot = self.redpiler.snapshot();
+                self.redpiler_snapshots.insert(name.to_string(), snapshot);
+                self.players[player]
+                    .send_system_message(&format!("Saved redpiler snapshot {}.", name));
+            }
+            "load" => {
+                let Some(snapshot) = self.redpiler_snapshots.get(name) else {
+                    self.players[player]
+                        .send_error_message(&format!("There is no snapshot named {}.", name));
+                    return;
+                };
+                if !self.redpiler.restore(snapshot) {
+                    self.players[player]
+                        .send_error_message("The snapshot does not match the compiled circuit.");
+                    return;
+                }
+                self.redpiler.flush(&mut self.world);
+                self.players[player]
+                    .send_system_message(&format!("Loaded redpiler snapshot {}.", name));
+            }
+            _ => self.players[player].send_error_message("Invalid argument for /redpiler snapshot"),
+        }
+    }
+
+    /// Handles `/redpiler probe add|sel <name>` and `/redpiler probe list|clear`
+    fn handle_redpiler_probe_command(&mut self, player: usize, args: &[&str]) {
+        match args {
+            ["add", name] => {
+                if !self.redpiler.is_active() {
+                    self.players[player].send_error_message("Redpiler is not running.");
+                    return;
+                }
+                let p = &self.players[player];
+                let pos = worldedit::ray_trace_block(
+                    &self.world,
+                    p.pos,
+                    p.pitch as f64,
+                    p.yaw as f64,
+                    10.0,
+                );
+                let Some(pos) = pos else {
+                    p.send_error_message("Trace failed");
+                    return;
+                };
+                if self.redpiler.add_probe(name.to_string(), pos) {
+                    self.players[player]
+                        .send_system_message(&format!("Added probe {} at {}.", name, pos));
+                } else {
+                    self.players[player]
+                        .send_error_message("That block is not part of the compiled circuit.");
+                }
+            }
+            ["sel", name] => {
+                if !self.redpiler.is_active() {
+                    self.players[player].send_error_message("Redpiler is not running.");
+                    return;
+                }
+                let p = &self.players[player];
+                let Some(region) = p.worldedit_region() else {
+                    p.send_error_message("Make a region selection first.");
+                    return;
+                };
+                let mut count = 0;
+                for pos in region.blocks() {
+                    let probe_name = format!("{}_{}", name, count);
+                    if self.redpiler.add_probe(probe_name, pos) {
+                        count += 1;
+                    }
+                }
+                self.players[player]
+                    .send_system_message(&format!("Added {} probes named {}_<n>.", count, name));
+            }
+            ["list"] => {
+                let probes = self.redpiler.probes();
+                if probes.is_empty() {
+                    self.players[player].send_system_message("There are no probes.");
+                    return;
+                }
+                for probe in probes {
+                    self.players[player]
+                        .send_system_message(&format!("{} at {}", probe.name, probe.pos));
+                }
+            }
+            ["clear"] => {
+                self.redpiler.clear_probes();
+                self.players[player].send_system_message("Removed all probes.");
+            }
+            _ => self.players[player]
+                .send_error_message("Usage: /redpiler probe add|sel <name> or list|clear"),
+        }
+    }
+
+    /// Handles `/redpiler trace export <file>` and `/redpiler trace restart`
+    fn handle_redpiler_trace_command(&mut self, player: usize, args: &[&str]) {
+        if !self.redpiler.is_active() {
+            self.players[player].send_error_message("Redpiler is not running.");
+            return;
+        }
+        match args {
+            ["export", file_name] => {
+                let valid = file_name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+                if file_name.is_empty() || !valid {
+                    self.players[player].send_error_message("Filename is invalid");
+                    return;
+                }
+                let trace = self.redpiler.trace();
+                let path = format!("./traces/{}.vcd", file_name);
+                let result = File::create(&path).and_then(|file| {
+                    let mut writer = BufWriter::new(file);
+                    trace.write_vcd(&mut writer)?;
+                    writer.flush()
+                });
+                match result {
+                    Ok(()) => self.players[player].send_system_message(&format!(
+                        "Exported {} signals over {} ticks to {}.",
+                        trace.signals.len(),
+                        trace.end,
+                        path
+                    )),
+                    Err(err) => {
+                        error!("There was an error exporting a trace: {}", err);
+                        self.players[player]
+                            .send_error_message("There was an error exporting the trace.");
+                    }
+                }
+            }
+            ["restart"] => {
+                self.redpiler.restart_trace();
+                self.players[player].send_system_message("Restarted the trace.");
+            }
+            _ => self.players[player]
+                .send_error_message("Usage: /redpiler trace export <file> or restart"),
+        }
+    }
+
+    /// Handles `/redpiler break <condition>` and `/redpiler break remove <id>|list|clear`
+    fn handle_redpiler_break_command(&mut self, player: usize, args: &[&str]) {
+        if !self.redpiler.is_active() {
+            self.players[player].send_error_message("Redpiler is not running.");
+            return;
+        }
+        match args {
+            ["remove", id] => {
+                let removed = id
+                    .parse()
+                    .is_ok_and(|id| self.redpiler.remove_breakpoint(id));
+                if removed {
+                    self.players[player]
+                        .send_system_message(&format!("Removed breakpoint #{}.", id));
+                } else {
+                    self.players[player]
+                        .send_error_message(&format!("There is no breakpoint #{}.", id));
+                }
+            }
+            ["list"] => {
+                let breakpoints = self.redpiler.breakpoints();
+                if breakpoints.is_empty() {
+                    self.players[player].send_system_message("There are no breakpoints.");
+                    return;
+                }
+                for breakpoint in breakpoints {
+                    self.players[player].send_system_message(&format!(
+                        "#{}: {} at {}",
+                        breakpoint.id, breakpoint.condition, breakpoint.pos
+                    ));
+                }
+            }
+            ["clear"] => {
+                self.redpiler.clear_breakpoints();
+                self.players[player].send_system_message("Removed all breakpoints.");
+            }
+            [condition] => {
+                let condition = match condition.parse::<Condition>() {
+                    Ok(condition) => condition,
+                    Err(err) => {
+                        self.players[player]
+                            .send_error_message(&format!("Invalid condition: {}", err));
+                        return;
+                    }
+                };
+                let p = &self.players[player];
+                let pos = worldedit::ray_trace_block(
+                    &self.world,
+                    p.pos,
+                    p.pitch as f64,
+                    p.yaw as f64,
+                    10.0,
+                );
+                let Some(pos) = pos else {
+                    p.send_error_message("Trace failed");
+                    return;
+                };
+                match self.redpiler.add_breakpoint(pos, condition) {
+                    Some(id) => self.players[player].send_system_message(&format!(
+                        "Added breakpoint #{}: {} at {}",
+                        id, condition, pos
+                    )),
+                    None => self.players[player]
+                        .send_error_message("That block is not part of the compiled circuit."),
+                }
+            }
+            _ => self.players[player]
+                .send_error_message("Usage: /redpiler break <condition> or remove <id>|list|clear"),
+        }
+    }
+
+    /// Handles `/redpiler step [priority]`
+    fn handle_redpiler_step_command(&mut self, player: usize, args: &[&str]) {
+        if !self.redpiler.is_active() {
+            self.players[player].send_error_message("Redpiler is not running.");
+            return;
+        }
+        if !self.redpiler_paused {
+            self.redpiler_paused = true;
+            self.scoreboard
+                .set_redpiler_state(&self.players, RedpilerState::Paused);
+        }
+        let msg = match args {
+            [] => {
+                self.redpiler.tick();
+                "Stepped to the end of the tick.".to_string()
+            }
+            ["priority"] => {
+                let priority = self.redpiler.step_priority();
+                format!("Ran the {:?} priority ticks.", priority)
+            }
+            _ => {
+                self.players[player].send_error_message("Usage: /redpiler step [priority]");
+                return;
+            }
+        };
+        self.redpiler.flush(&mut self.world);
+        self.world.flush_block_changes();
+        self.players[player].send_system_message(&msg);
+
+        // Show the state of everything being watched
+        let watched = self
+            .redpiler
+            .breakpoints()
+            .iter()
+            .map(|breakpoint| (format!("#{}", breakpoint.id), breakpoint.pos))
+            .chain(
+                self.redpiler
+                    .probes()
+                    .iter()
+                    .map(|probe| (probe.name.clone(), probe.pos)),
+            );
+        for (name, pos) in watched {
+            let Some(state) = self.redpiler.node_state(pos) else {
+                continue;
+            };
+            let powered = if state.powered {
+                "powered"
+            } else {
+                "unpowered"
+            };
+            let locked = if state.repeater_locked {
+                ", locked"
+            } else {
+                ""
+            };
+            self.players[player].send_system_message(&format!(
+                "{} at {}: {}, ss {}{}",
+                name, pos, powered, state.output_strength, locked
+            ));
+        }
+        self.check_redpiler_breakpoints();
+    }
+
     // Returns true if packets should stop being handled
     pub(super) fn handle_command(
         &mut self,
@@ -470,7 +947,7 @@ impl Plot {
                     Ok(ty) => ty,
                     Err(()) => {
                         self.players[player].send_error_message(
-                            "Container type must be one of [barrel, furnace, hopper]",
+                            "Container type must be one of [barrel, furnace, hopper, crafter]",
                         );
                         return false;
                     }
@@ -483,6 +960,13 @@ impl Plot {
                     return false;
                 }
 
+                if container_ty == ContainerType::Crafter && power > 9 {
+                    self.players[player].send_error_message(
+                        "Crafter power must be greater than 0 and lower than 9!",
+                    );
+                    return false;
+                }
+
                 let item = ItemStack::container_with_ss(container_ty, power);
                 let slot = 36 + self.players[player].selected_slot;
                 self.players[player].set_inventory_slot(slot, Some(item));
@@ -523,6 +1007,138 @@ impl Plot {
             "version" => {
                 self.players[player].send_system_message(&get_version_string());
             }
+            "target" => {
+                if !(1..=2).contains(&args.len()) {
+                    self.players[player]
+                        .send_error_message("Usage: /target [power] [arrow|projectile]");
+                    return false;
+                }
+
+                let power = match args[0].parse::<u8>() {
+                    Ok(power) if (1..=15).contains(&power) => power,
+                    _ => {
+                        self.players[player]
+                            .send_error_message("Target power must be between 1 and 15!");
+                        return false;
+                    }
+                };
+
+                let ticks = match args.get(1) {
+                    None | Some(&"arrow") => target::ARROW_HIT_TICKS,
+                    Some(&"projectile") => target::PROJECTILE_HIT_TICKS,
+                    Some(_) => {
+                        self.players[player].send_error_message(
+                            "Projectile type must be one of [arrow, projectile]",
+                        );
+                        return false;
+                    }
+                };
+
+                let player_ref = &self.players[player];
+                let pos = worldedit::ray_trace_block(
+                    &self.world,
+                    player_ref.pos,
+                    player_ref.pitch as f64,
+                    player_ref.yaw as f64,
+                    10.0,
+                );
+                let Some(pos) = pos else {
+                    player_ref.send_error_message("Trace failed");
+                    return false;
+                };
+                if !matches!(self.world.get_block(pos), Block::Target { .. }) {
+                    player_ref.send_error_message("You are not looking at a target block!");
+                    return false;
+                }
+
+                if !self.hit_target(pos, power, ticks) {
+                    self.players[player].send_error_message("That target block is still powered!");
+                }
+            }
+            "daylight" => {
+                if !(1..=2).contains(&args.len()) {
+                    self.players[player]
+                        .send_error_message("Usage: /daylight <power> [normal|inverted]");
+                    return false;
+                }
+
+                let power = match args[0].parse::<u8>() {
+                    Ok(power) if power <= 15 => power,
+                    _ => {
+                        self.players[player].send_error_message(
+                            "Daylight detector power must be between 0 and 15!",
+                        );
+                        return false;
+                    }
+                };
+
+                let inverted = match args.get(1) {
+                    None => None,
+                    Some(&"normal") => Some(false),
+                    Some(&"inverted") => Some(true),
+                    Some(_) => {
+                        self.players[player]
+                            .send_error_message("Mode must be one of [normal, inverted]");
+                        return false;
+                    }
+                };
+
+                let player_ref = &self.players[player];
+                let pos = worldedit::ray_trace_block(
+                    &self.world,
+                    player_ref.pos,
+                    player_ref.pitch as f64,
+                    player_ref.yaw as f64,
+                    10.0,
+                );
+                let Some(pos) = pos else {
+                    player_ref.send_error_message("Trace failed");
+                    return false;
+                };
+                if !matches!(self.world.get_block(pos), Block::DaylightDetector { .. }) {
+                    player_ref.send_error_message("You are not looking at a daylight detector!");
+                    return false;
+                }
+
+                self.set_daylight_detector(pos, power, inverted);
+            }
+            "tripwire" => {
+                if args.len() > 1 {
+                    self.players[player].send_error_message("Usage: /tripwire [ticks]");
+                    return false;
+                }
+
+                let ticks = match args.first().map(|arg| arg.parse::<u32>()) {
+                    None => tripwire::ENTITY_CHECK_TICKS,
+                    Some(Ok(ticks)) if (1..=15).contains(&ticks) => ticks,
+                    Some(_) => {
+                        self.players[player]
+                            .send_error_message("Tripwire ticks must be between 1 and 15!");
+                        return false;
+                    }
+                };
+
+                let player_ref = &self.players[player];
+                let pos = worldedit::ray_trace_block(
+                    &self.world,
+                    player_ref.pos,
+                    player_ref.pitch as f64,
+                    player_ref.yaw as f64,
+                    10.0,
+                );
+                let Some(pos) = pos else {
+                    player_ref.send_error_message("Trace failed");
+                    return false;
+                };
+                if !matches!(self.world.get_block(pos), Block::Tripwire { .. }) {
+                    player_ref.send_error_message("You are not looking at tripwire string!");
+                    return false;
+                }
+
+                if !self.trigger_tripwire(pos, ticks) {
+                    self.players[player].send_error_message("That tripwire is still triggered!");
+                }
+            }
             _ => self.players[player].send_error_message("Command not found!"),
         }
         false
@@ -551,7 +1167,7 @@ pub static DECLARE_COMMANDS: LazyLock<PacketEncoder> = LazyLock::new(|| {
             Node {
                 flags: CommandFlags::ROOT.bits() as i8,
                 children: vec![
-                    1, 4, 5, 6, 8, 10, 11, 13, 18, 30, 34, 41, 43, 44, 45, 49, 51, 52,
+                    1, 4, 5, 6, 8, 10, 11, 13, 18, 30, 34, 41, 43, 44, 45, 49, 51, 52, 53, 57, 59,
                 ],
                 redirect_node: None,
                 name: None,
@@ -669,7 +1285,7 @@ pub static DECLARE_COMMANDS: LazyLock<PacketEncoder> = LazyLock::new(|| {
             // 13: /plot
             Node {
                 flags: (CommandFlags::LITERAL).bits() as i8,
-                children: vec![14, 15, 16, 17, 19, 20, 21, 22, 24, 25, 27, 28, 29],
+                children: vec![14, 15, 16, 17, 19, 20, 21, 22, 24, 25, 27, 28, 29, 90],
                 redirect_node: None,
                 name: Some("plot"),
                 parser: None,
@@ -858,7 +1474,7 @@ pub static DECLARE_COMMANDS: LazyLock<PacketEncoder> = LazyLock::new(|| {
             // 34: /container
             Node {
                 flags: (CommandFlags::LITERAL).bits() as i8,
-                children: vec![35, 36, 37],
+                children: vec![35, 36, 37, 63],
                 redirect_node: None,
                 name: Some("container"),
                 parser: None,
@@ -951,7 +1567,8 @@ pub static DECLARE_COMMANDS: LazyLock<PacketEncoder> = LazyLock::new(|| {
             // 44: /redpiler
             Node {
                 flags: CommandFlags::LITERAL.bits() as i8,
-                children: vec![46, 47, 48], // Children are compile, inspect, reset
+                // Children are compile, inspect, reset, snapshot, probe, trace, break, pause, continue, step
+                children: vec![46, 47, 48, 65, 69, 75, 79, 85, 86, 87],
                 redirect_node: None,
                 name: Some("redpiler"),
                 parser: None,
@@ -978,7 +1595,7 @@ pub static DECLARE_COMMANDS: LazyLock<PacketEncoder> = LazyLock::new(|| {
             // 47: /redpiler inspect
             Node {
                 flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
-                children: vec![],
+                children: vec![89],
                 redirect_node: None,
                 name: Some("inspect"),
                 parser: None,
@@ -1029,6 +1646,357 @@ pub static DECLARE_COMMANDS: LazyLock<PacketEncoder> = LazyLock::new(|| {
                 parser: None,
                 suggestions_type: None,
             },
+            // 53: /target
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![54],
+                redirect_node: None,
+                name: Some("target"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 54: /target [power]
+            Node {
+                flags: (CommandFlags::ARGUMENT | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![55, 56],
+                redirect_node: None,
+                name: Some("power"),
+                parser: Some(Parser::Integer(1, 15)),
+                suggestions_type: None,
+            },
+            // 55: /target [power] arrow
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("arrow"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 56: /target [power] projectile
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("projectile"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 57: /tripwire
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![58],
+                redirect_node: None,
+                name: Some("tripwire"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 58: /tripwire [ticks]
+            Node {
+                flags: (CommandFlags::ARGUMENT | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("ticks"),
+                parser: Some(Parser::Integer(1, 15)),
+                suggestions_type: None,
+            },
+            // 59: /daylight
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![60],
+                redirect_node: None,
+                name: Some("daylight"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 60: /daylight [power]
+            Node {
+                flags: (CommandFlags::ARGUMENT | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![61, 62],
+                redirect_node: None,
+                name: Some("power"),
+                parser: Some(Parser::Integer(0, 15)),
+                suggestions_type: None,
+            },
+            // 61: /daylight [power] normal
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("normal"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 62: /daylight [power] inverted
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("inverted"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 63: /container crafter
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![64],
+                redirect_node: None,
+                name: Some("crafter"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 64: /container crafter [power]
+            Node {
+                flags: (CommandFlags::ARGUMENT | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("power"),
+                parser: Some(Parser::Integer(1, 9)),
+                suggestions_type: None,
+            },
+            // 65: /redpiler snapshot
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![66, 67],
+                redirect_node: None,
+                name: Some("snapshot"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 66: /redpiler snapshot save
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![68],
+                redirect_node: None,
+                name: Some("save"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 67: /redpiler snapshot load
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![68],
+                redirect_node: None,
+                name: Some("load"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 68: /redpiler snapshot save|load [name]
+            Node {
+                flags: (CommandFlags::ARGUMENT | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("name"),
+                parser: Some(Parser::String(0)),
+                suggestions_type: None,
+            },
+            // 69: /redpiler probe
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![70, 71, 73, 74],
+                redirect_node: None,
+                name: Some("probe"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 70: /redpiler probe add
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![72],
+                redirect_node: None,
+                name: Some("add"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 71: /redpiler probe sel
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![72],
+                redirect_node: None,
+                name: Some("sel"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 72: /redpiler probe add|sel [name]
+            Node {
+                flags: (CommandFlags::ARGUMENT | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("name"),
+                parser: Some(Parser::String(0)),
+                suggestions_type: None,
+            },
+            // 73: /redpiler probe list
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("list"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 74: /redpiler probe clear
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("clear"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 75: /redpiler trace
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![76, 78],
+                redirect_node: None,
+                name: Some("trace"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 76: /redpiler trace export
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![77],
+                redirect_node: None,
+                name: Some("export"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 77: /redpiler trace export [file]
+            Node {
+                flags: (CommandFlags::ARGUMENT | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("file"),
+                parser: Some(Parser::String(0)),
+                suggestions_type: None,
+            },
+            // 78: /redpiler trace restart
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("restart"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 79: /redpiler break
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![80, 81, 83, 84],
+                redirect_node: None,
+                name: Some("break"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 80: /redpiler break [condition]
+            Node {
+                flags: (CommandFlags::ARGUMENT | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("condition"),
+                parser: Some(Parser::String(0)),
+                suggestions_type: None,
+            },
+            // 81: /redpiler break remove
+            Node {
+                flags: (CommandFlags::LITERAL).bits() as i8,
+                children: vec![82],
+                redirect_node: None,
+                name: Some("remove"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 82: /redpiler break remove [id]
+            Node {
+                flags: (CommandFlags::ARGUMENT | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("id"),
+                parser: Some(Parser::Integer(0, i32::MAX)),
+                suggestions_type: None,
+            },
+            // 83: /redpiler break list
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("list"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 84: /redpiler break clear
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("clear"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 85: /redpiler pause
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("pause"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 86: /redpiler continue
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("continue"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 87: /redpiler step
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![88],
+                redirect_node: None,
+                name: Some("step"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 88: /redpiler step priority
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("priority"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 89: /redpiler inspect [pos]
+            Node {
+                flags: (CommandFlags::ARGUMENT | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("pos"),
+                parser: Some(Parser::BlockPos),
+                suggestions_type: None,
+            },
+            // 90: /p merge
+            Node {
+                flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![91],
+                redirect_node: None,
+                name: Some("merge"),
+                parser: None,
+                suggestions_type: None,
+            },
+            // 91: /p merge [direction]
+            Node {
+                flags: (CommandFlags::ARGUMENT | CommandFlags::EXECUTABLE).bits() as i8,
+                children: vec![],
+                redirect_node: None,
+                name: Some("direction"),
+                parser: Some(Parser::String(0)),
+                suggestions_type: None,
+            },
         ],
         root_index: 0,
     };
@@ -1,5 +1,6 @@
-use super::{Plot, PlotWorld, PLOT_WIDTH};
-use anyhow::{Context, Result};
+use super::database::PlotGroup;
+use super::{Plot, PlotWorld, NUM_CHUNKS, PLOT_SCALE, PLOT_WIDTH};
+use anyhow::{bail, Context, Result};
 use mchprs_save_data::plot_data::{ChunkData, PlotData, Tps, WorldSendRate};
 use std::path::Path;
 use std::sync::LazyLock;
@@ -29,8 +30,114 @@ pub fn load_plot(path: impl AsRef<Path>) -> Result<PlotData> {
     }
 }
 
-pub fn empty_plot() -> PlotData {
-    EMPTY_PLOT.clone()
+pub fn plot_path(plot_x: i32, plot_z: i32) -> String {
+    format!("./world/plots/p{},{}", plot_x, plot_z)
+}
+
+pub fn history_path(plot_x: i32, plot_z: i32) -> String {
+    format!("./world/history/p{},{}", plot_x, plot_z)
+}
+
+/// Loads every plot of the group and joins them into one, with the chunks in the order of
+/// [`PlotWorld`]'s chunk index. The settings and redpiler state are taken from the first plot of
+/// the group.
+pub fn load_group(group: PlotGroup) -> Result<PlotData> {
+    if group.size_x() == 1 && group.size_z() == 1 {
+        return load_plot(plot_path(group.min_x, group.min_z));
+    }
+
+    let plots = group
+        .plots()
+        .map(|(plot_x, plot_z)| {
+            load_plot(plot_path(plot_x, plot_z))
+                .with_context(|| format!("error loading plot {},{}", plot_x, plot_z))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    join_group(group, plots)
+}
+
+/// Splits the data of a group loaded with [`load_group`] back into its plots and saves each of
+/// them.
+pub fn save_group(group: PlotGroup, data: PlotData) -> Result<()> {
+    if group.size_x() == 1 && group.size_z() == 1 {
+        data.save_to_file(plot_path(group.min_x, group.min_z))?;
+        return Ok(());
+    }
+
+    for ((plot_x, plot_z), plot_data) in group.plots().zip(split_group(group, data)) {
+        plot_data.save_to_file(plot_path(plot_x, plot_z))?;
+    }
+    Ok(())
+}
+
+/// Joins the data of every plot of the group, given in the order of [`PlotGroup::plots`].
+pub(super) fn join_group(group: PlotGroup, plots: Vec<PlotData>) -> Result<PlotData> {
+    let row_len = group.size_z() * PLOT_WIDTH;
+    let num_plots = (group.size_x() * group.size_z()) as usize;
+    let mut chunks = vec![None; num_plots * NUM_CHUNKS];
+    let mut group_data: Option<PlotData> = None;
+    for ((plot_x, plot_z), mut data) in group.plots().zip(plots) {
+        if data.chunk_data.len() != NUM_CHUNKS {
+            bail!(
+                "plot {},{} has the wrong number of chunks to be merged",
+                plot_x,
+                plot_z
+            );
+        }
+        let offset_x = (plot_x - group.min_x) * PLOT_WIDTH;
+        let offset_z = (plot_z - group.min_z) * PLOT_WIDTH;
+        for (i, chunk) in data.chunk_data.drain(..).enumerate() {
+            let x = offset_x + i as i32 / PLOT_WIDTH;
+            let z = offset_z + i as i32 % PLOT_WIDTH;
+            chunks[(x * row_len + z) as usize] = Some(chunk);
+        }
+        match &mut group_data {
+            Some(group_data) => group_data.pending_ticks.append(&mut data.pending_ticks),
+            None => group_data = Some(data),
+        }
+    }
+
+    let mut data = group_data.context("plot group has no plots")?;
+    data.chunk_data = chunks
+        .into_iter()
+        .collect::<Option<_>>()
+        .context("plot group is missing plots")?;
+    Ok(data)
+}
+
+/// Splits the data of a group back into its plots, in the order of [`PlotGroup::plots`].
+fn split_group(group: PlotGroup, mut data: PlotData) -> Vec<PlotData> {
+    let row_len = group.size_z() * PLOT_WIDTH;
+    let mut chunks: Vec<Option<ChunkData>> = data.chunk_data.drain(..).map(Some).collect();
+    let mut pending_ticks = std::mem::take(&mut data.pending_ticks);
+    let mut plots = Vec::new();
+    for (plot_x, plot_z) in group.plots() {
+        let offset_x = (plot_x - group.min_x) * PLOT_WIDTH;
+        let offset_z = (plot_z - group.min_z) * PLOT_WIDTH;
+        let chunk_data = (0..NUM_CHUNKS as i32)
+            .map(|i| {
+                let x = offset_x + i / PLOT_WIDTH;
+                let z = offset_z + i % PLOT_WIDTH;
+                chunks[(x * row_len + z) as usize].take().unwrap()
+            })
+            .collect();
+        let (plot_ticks, rest) = pending_ticks.into_iter().partition(|entry| {
+            (
+                entry.pos.x >> 4 >> PLOT_SCALE,
+                entry.pos.z >> 4 >> PLOT_SCALE,
+            ) == (plot_x, plot_z)
+        });
+        pending_ticks = rest;
+        plots.push(PlotData {
+            tps: data.tps,
+            world_send_rate: data.world_send_rate,
+            chunk_data,
+            pending_ticks: plot_ticks,
+            // The whole group's circuit is kept with its first plot
+            redpiler_state: data.redpiler_state.take(),
+        });
+    }
+    plots
 }
 
 static EMPTY_PLOT: LazyLock<PlotData> = LazyLock::new(|| {
@@ -47,9 +154,12 @@ static EMPTY_PLOT: LazyLock<PlotData> = LazyLock::new(|| {
         let mut world = PlotWorld {
             x: 0,
             z: 0,
+            size_x: 1,
+            size_z: 1,
             chunks,
             to_be_ticked: Vec::new(),
             packet_senders: Vec::new(),
+            changed_blocks: None,
         };
         let chunk_data: Vec<ChunkData> = world.chunks.iter_mut().map(ChunkData::new).collect();
         PlotData {
@@ -57,6 +167,98 @@ static EMPTY_PLOT: LazyLock<PlotData> = LazyLock::new(|| {
             world_send_rate: WorldSendRate::default(),
             chunk_data,
             pending_ticks: Vec::new(),
+            redpiler_state: None,
         }
     }
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plot::PLOT_BLOCK_WIDTH;
+    use mchprs_blocks::BlockPos;
+    use mchprs_save_data::plot_data::ChunkSectionData;
+    use mchprs_world::{TickEntry, TickPriority};
+
+    /// A plot whose chunks and tick are tagged with its position, so they can be told apart after
+    /// being joined and split again.
+    fn tagged_plot(plot_x: i32, plot_z: i32) -> PlotData {
+        let chunk_data = (0..NUM_CHUNKS as u64)
+            .map(|i| ChunkData {
+                sections: vec![Some(ChunkSectionData {
+                    data: vec![plot_x as u64, plot_z as u64, i],
+                    palette: vec![0],
+                    bits_per_block: 4,
+                    block_count: 0,
+                })],
+                block_entities: Default::default(),
+            })
+            .collect();
+        PlotData {
+            tps: Tps::Limited(10),
+            world_send_rate: WorldSendRate::default(),
+            chunk_data,
+            pending_ticks: vec![TickEntry {
+                ticks_left: 2,
+                tick_priority: TickPriority::Normal,
+                pos: BlockPos::new(
+                    plot_x * PLOT_BLOCK_WIDTH + 3,
+                    64,
+                    plot_z * PLOT_BLOCK_WIDTH + 5,
+                ),
+            }],
+            redpiler_state: None,
+        }
+    }
+
+    fn sections(data: &PlotData) -> Vec<Vec<Option<ChunkSectionData>>> {
+        data.chunk_data
+            .iter()
+            .map(|chunk| chunk.sections.clone())
+            .collect()
+    }
+
+    #[test]
+    fn group_round_trip() {
+        let group = PlotGroup {
+            min_x: -1,
+            min_z: 2,
+            max_x: 0,
+            max_z: 4,
+        };
+        let plots: Vec<PlotData> = group
+            .plots()
+            .map(|(plot_x, plot_z)| tagged_plot(plot_x, plot_z))
+            .collect();
+
+        let joined = join_group(group, plots.clone()).unwrap();
+        assert_eq!(joined.chunk_data.len(), plots.len() * NUM_CHUNKS);
+        assert_eq!(joined.pending_ticks.len(), plots.len());
+
+        let split = split_group(group, joined);
+        assert_eq!(split.len(), plots.len());
+        for (original, restored) in plots.iter().zip(&split) {
+            assert_eq!(sections(original), sections(restored));
+            assert_eq!(original.pending_ticks, restored.pending_ticks);
+        }
+    }
+
+    #[test]
+    fn joined_chunks_follow_world_index() {
+        let group = PlotGroup {
+            min_x: 0,
+            min_z: 0,
+            max_x: 1,
+            max_z: 0,
+        };
+        let plots = group
+            .plots()
+            .map(|(plot_x, plot_z)| tagged_plot(plot_x, plot_z))
+            .collect();
+        let joined = join_group(group, plots).unwrap();
+        // The second plot along x starts a full plot width of rows later
+        let chunk = &joined.chunk_data[(PLOT_WIDTH * PLOT_WIDTH) as usize];
+        let section = chunk.sections[0].as_ref().unwrap();
+        assert_eq!(section.data, vec![1, 0, 0]);
+    }
+}
@@ -9,6 +9,64 @@ fn lock<'a>() -> MutexGuard<'a, Connection> {
     CONN.lock().unwrap()
 }
 
+/// A rectangle of plots that were merged into one world, which is loaded and ticked as a single
+/// plot. Plots that were never merged are a group of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlotGroup {
+    pub min_x: i32,
+    pub min_z: i32,
+    pub max_x: i32,
+    pub max_z: i32,
+}
+
+impl PlotGroup {
+    pub fn single(plot_x: i32, plot_z: i32) -> PlotGroup {
+        PlotGroup {
+            min_x: plot_x,
+            min_z: plot_z,
+            max_x: plot_x,
+            max_z: plot_z,
+        }
+    }
+
+    pub fn contains(&self, plot_x: i32, plot_z: i32) -> bool {
+        (self.min_x..=self.max_x).contains(&plot_x) && (self.min_z..=self.max_z).contains(&plot_z)
+    }
+
+    pub fn size_x(&self) -> i32 {
+        self.max_x - self.min_x + 1
+    }
+
+    pub fn size_z(&self) -> i32 {
+        self.max_z - self.min_z + 1
+    }
+
+    /// Returns the coordinates of every plot in the group, in the order their chunks are stored.
+    pub fn plots(self) -> impl Iterator<Item = (i32, i32)> {
+        (self.min_x..=self.max_x).flat_map(move |x| (self.min_z..=self.max_z).map(move |z| (x, z)))
+    }
+
+    /// Grows the group until it fully contains every group that one of its plots belongs to, so
+    /// that merging never splits an existing group.
+    pub fn absorb_overlapping(self, group_of: impl Fn(i32, i32) -> PlotGroup) -> PlotGroup {
+        let mut merged = self;
+        loop {
+            let mut grown = merged;
+            for (plot_x, plot_z) in merged.plots() {
+                let group = group_of(plot_x, plot_z);
+                grown.min_x = grown.min_x.min(group.min_x);
+                grown.min_z = grown.min_z.min(group.min_z);
+                grown.max_x = grown.max_x.max(group.max_x);
+                grown.max_z = grown.max_z.max(group.max_z);
+            }
+            if grown == merged {
+                return merged;
+            }
+            merged = grown;
+        }
+    }
+}
+
 pub fn get_plot_owner(plot_x: i32, plot_z: i32) -> Option<String> {
     lock()
         .query_row(
@@ -45,6 +103,22 @@ pub fn get_cached_username(uuid: String) -> Option<String> {
         .ok()
 }
 
+/// Looks up the uuid of a player who has joined before. The name is not case sensitive.
+pub fn get_user_uuid(name: &str) -> Option<String> {
+    lock()
+        .query_row(
+            "SELECT
+                uuid
+            FROM
+                user
+            WHERE
+                name=?1 COLLATE NOCASE",
+            params![name],
+            |row| row.get::<_, String>(0),
+        )
+        .ok()
+}
+
 pub fn get_owned_plots(player: &str) -> Vec<(i32, i32)> {
     let conn = lock();
     let mut stmt = conn
@@ -98,6 +172,49 @@ pub fn claim_plot(plot_x: i32, plot_z: i32, uuid: &str) {
     .unwrap();
 }
 
+/// Returns the group the plot was merged into, or a group of just the plot itself.
+pub fn get_plot_group(plot_x: i32, plot_z: i32) -> PlotGroup {
+    lock()
+        .query_row(
+            "SELECT
+                min_x, min_z, max_x, max_z
+            FROM
+                plotgroup
+            WHERE
+                min_x <= ?1 AND ?1 <= max_x
+                AND min_z <= ?2 AND ?2 <= max_z",
+            params![plot_x, plot_z],
+            |row| {
+                Ok(PlotGroup {
+                    min_x: row.get(0)?,
+                    min_z: row.get(1)?,
+                    max_x: row.get(2)?,
+                    max_z: row.get(3)?,
+                })
+            },
+        )
+        .unwrap_or(PlotGroup::single(plot_x, plot_z))
+}
+
+/// Stores `group` as a merged group, replacing the groups it covers.
+pub fn merge_plots(group: PlotGroup) {
+    let mut conn = lock();
+    let tx = conn.transaction().unwrap();
+    tx.execute(
+        "DELETE FROM plotgroup
+            WHERE ?1 <= min_x AND max_x <= ?3
+            AND ?2 <= min_z AND max_z <= ?4",
+        params![group.min_x, group.min_z, group.max_x, group.max_z],
+    )
+    .unwrap();
+    tx.execute(
+        "INSERT INTO plotgroup(min_x, min_z, max_x, max_z) VALUES(?1, ?2, ?3, ?4)",
+        params![group.min_x, group.min_z, group.max_x, group.max_z],
+    )
+    .unwrap();
+    tx.commit().unwrap();
+}
+
 pub fn ensure_user(uuid: &str, name: &str) {
     lock()
         .execute(
@@ -143,4 +260,58 @@ pub fn init() {
         [],
     )
     .unwrap();
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS plotgroup(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            min_x INTEGER NOT NULL,
+            min_z INTEGER NOT NULL,
+            max_x INTEGER NOT NULL,
+            max_z INTEGER NOT NULL
+        )",
+        [],
+    )
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_absorbs_overlapping_group() {
+        // An existing 2x2 group that the new merge only partly covers
+        let existing = PlotGroup {
+            min_x: 1,
+            min_z: 0,
+            max_x: 2,
+            max_z: 1,
+        };
+        let group_of = |plot_x, plot_z| {
+            if existing.contains(plot_x, plot_z) {
+                existing
+            } else {
+                PlotGroup::single(plot_x, plot_z)
+            }
+        };
+        let merged = PlotGroup {
+            min_x: 0,
+            min_z: 0,
+            max_x: 1,
+            max_z: 0,
+        };
+        assert_eq!(
+            merged.absorb_overlapping(group_of),
+            PlotGroup {
+                min_x: 0,
+                min_z: 0,
+                max_x: 2,
+                max_z: 1,
+            }
+        );
+        assert_eq!(
+            PlotGroup::single(5, 5).absorb_overlapping(group_of),
+            PlotGroup::single(5, 5)
+        );
+    }
 }
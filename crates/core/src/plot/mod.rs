@@ -17,11 +17,14 @@ use bus::BusReader;
 use mchprs_blocks::block_entities::BlockEntity;
 use mchprs_blocks::blocks::Block;
 use mchprs_blocks::items::Item;
-use mchprs_blocks::{BlockFace, BlockPos};
+use mchprs_blocks::{BlockDirection, BlockFace, BlockPos};
 use mchprs_network::packets::clientbound::*;
 use mchprs_network::packets::serverbound::SUseItemOn;
 use mchprs_network::PlayerPacketSender;
-use mchprs_redpiler::{Compiler, CompilerOptions};
+use mchprs_redpiler::backend::BackendSnapshot;
+use mchprs_redpiler::diagnostics::{Diagnostics, Severity};
+use mchprs_redpiler::{CompileError, Compiler, CompilerOptions, SavedCircuit, TaskMonitor};
+use mchprs_redstone::{daylight_detector, target, tripwire};
 use mchprs_save_data::plot_data::{ChunkData, PlotData, Tps, WorldSendRate};
 use mchprs_text::TextComponent;
 use mchprs_world::storage::Chunk;
@@ -29,16 +32,18 @@ use mchprs_world::{TickEntry, TickPriority, World};
 use monitor::TimingsMonitor;
 use scoreboard::RedpilerState;
 use std::cmp::Ordering;
-use std::collections::HashSet;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
 use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 use tracing::{debug, error, warn};
 
 use self::data::sleep_time_for_tps;
+use self::database::PlotGroup;
 use self::scoreboard::Scoreboard;
+use self::worldedit::history::WorldEditHistory;
 
 /// The width of a plot (2^n)
 pub const PLOT_SCALE: u32 = 5;
@@ -55,11 +60,22 @@ pub const PLOT_SECTIONS: usize = 24;
 pub const PLOT_BLOCK_HEIGHT: i32 = PLOT_SECTIONS as i32 * 16;
 
 const ERROR_IO_ONLY: &str = "This plot cannot be interacted with while redpiler is active with `--io-only`. To stop redpiler, run `/redpiler reset`.";
+/// Edits this close to a selection compiled with `--selection` may connect the circuit to blocks
+/// outside of it, so they reset redpiler.
+const SELECTION_EDIT_MARGIN: i32 = 2;
 
 pub struct Plot {
     pub world: PlotWorld,
     pub players: Vec<Player>,
     pub redpiler: Compiler,
+    /// Named redpiler snapshots, which only stay valid until redpiler is reset.
+    redpiler_snapshots: HashMap<String, BackendSnapshot>,
+    /// When true, redpiler is not ticked until it is continued or stepped.
+    redpiler_paused: bool,
+    /// The region redpiler was compiled for with `--selection`, as its minimum and maximum
+    /// corners. The rest of the plot keeps being ticked by `mchprs_redstone`.
+    redpiler_bounds: Option<(BlockPos, BlockPos)>,
+    worldedit_history: WorldEditHistory,
 
     // Thread communication
     message_receiver: BusReader<BroadcastMessage>,
@@ -86,6 +102,9 @@ pub struct Plot {
     running: bool,
     /// If true, the plot will remain running even if no players are on for a long time.
     always_running: bool,
+    /// Set once the plot has saved and unloaded so that it can be merged with other plots. The
+    /// merged group is saved by its own plot from then on, so this one must not save again.
+    unloading_for_merge: bool,
     auto_redpiler: bool,
 
     owner: Option<u128>,
@@ -94,39 +113,100 @@ pub struct Plot {
 }
 
 pub struct PlotWorld {
+    /// The plot with the lowest coordinates of the merged group, or the plot itself if it was
+    /// never merged.
     pub x: i32,
     pub z: i32,
+    /// How many plots the world spans on each axis.
+    pub size_x: i32,
+    pub size_z: i32,
     pub chunks: Vec<Chunk>,
     pub to_be_ticked: Vec<TickEntry>,
     pub packet_senders: Vec<PlayerPacketSender>,
+    /// The blocks changed since redpiler was reset for an edit, see
+    /// [`Plot::reset_redpiler_for_edit`].
+    changed_blocks: Option<Vec<BlockPos>>,
 }
 
 impl PlotWorld {
-    fn get_chunk_index_for_chunk(&self, chunk_x: i32, chunk_z: i32) -> usize {
+    /// Loads the world of a group from the data returned by [`data::load_group`].
+    fn from_data(
+        group: PlotGroup,
+        chunk_data: Vec<ChunkData>,
+        to_be_ticked: Vec<TickEntry>,
+    ) -> PlotWorld {
+        let chunk_x_offset = group.min_x << PLOT_SCALE;
+        let chunk_z_offset = group.min_z << PLOT_SCALE;
+        let row_len = group.size_z() * PLOT_WIDTH;
+        let chunks: Vec<Chunk> = chunk_data
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| {
+                c.load(
+                    chunk_x_offset + i as i32 / row_len,
+                    chunk_z_offset + i as i32 % row_len,
+                )
+            })
+            .collect();
+        let num_plots = (group.size_x() * group.size_z()) as usize;
+        if chunks.len() != num_plots * NUM_CHUNKS {
+            error!("This plot has the wrong number of chunks!");
+            let possible_scale = (chunks.len() as f64).sqrt().log2();
+            error!("Note: it most likely came from a server running plot scale {}, this server is running a plot scale of {}", possible_scale, PLOT_SCALE);
+        }
+        PlotWorld {
+            x: group.min_x,
+            z: group.min_z,
+            size_x: group.size_x(),
+            size_z: group.size_z(),
+            chunks,
+            to_be_ticked,
+            packet_senders: Vec::new(),
+            changed_blocks: None,
+        }
+    }
+
+    fn get_chunk_index_for_chunk(&self, chunk_x: i32, chunk_z: i32) -> Option<usize> {
         let local_x = chunk_x - self.x * PLOT_WIDTH;
         let local_z = chunk_z - self.z * PLOT_WIDTH;
-        (local_x * PLOT_WIDTH + local_z).unsigned_abs() as usize
+        let row_len = self.size_z * PLOT_WIDTH;
+        if !(0..self.size_x * PLOT_WIDTH).contains(&local_x) || !(0..row_len).contains(&local_z) {
+            return None;
+        }
+        Some((local_x * row_len + local_z) as usize)
     }
 
     fn get_chunk_index_for_block(&self, block_x: i32, block_z: i32) -> Option<usize> {
-        let chunk_x = (block_x - (self.x * PLOT_BLOCK_WIDTH)) >> 4;
-        let chunk_z = (block_z - (self.z * PLOT_BLOCK_WIDTH)) >> 4;
-        if !(0..PLOT_WIDTH).contains(&chunk_x) || !(0..PLOT_WIDTH).contains(&chunk_z) {
-            return None;
-        }
-        Some(((chunk_x << PLOT_SCALE) + chunk_z).unsigned_abs() as usize)
+        self.get_chunk_index_for_chunk(block_x >> 4, block_z >> 4)
     }
 
     pub fn get_corners(&self) -> (BlockPos, BlockPos) {
         const W: i32 = PLOT_BLOCK_WIDTH;
         let first_pos = BlockPos::new(self.x * W, 0, self.z * W);
         let second_pos = BlockPos::new(
-            (self.x + 1) * W - 1,
+            (self.x + self.size_x) * W - 1,
             PLOT_BLOCK_HEIGHT - 1,
-            (self.z + 1) * W - 1,
+            (self.z + self.size_z) * W - 1,
         );
         (first_pos, second_pos)
     }
+
+    pub fn group(&self) -> PlotGroup {
+        PlotGroup {
+            min_x: self.x,
+            min_z: self.z,
+            max_x: self.x + self.size_x - 1,
+            max_z: self.z + self.size_z - 1,
+        }
+    }
+
+    pub fn chunk_in_plot_bounds(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        self.get_chunk_index_for_chunk(chunk_x, chunk_z).is_some()
+    }
+
+    pub fn in_plot_bounds(&self, x: i32, z: i32) -> bool {
+        self.chunk_in_plot_bounds(x >> 4, z >> 4)
+    }
 }
 
 impl World for PlotWorld {
@@ -143,12 +223,16 @@ impl World for PlotWorld {
         }
 
         let chunk = &mut self.chunks[chunk_index];
-        chunk.set_block(
+        let changed = chunk.set_block(
             (pos.x & 0xF) as u32,
             pos.y as u32,
             (pos.z & 0xF) as u32,
             block,
-        )
+        );
+        if changed && let Some(changed_blocks) = &mut self.changed_blocks {
+            changed_blocks.push(pos);
+        }
+        changed
     }
 
     /// Returns the block state id of the block at `pos`
@@ -168,6 +252,9 @@ impl World for PlotWorld {
         };
         let chunk = &mut self.chunks[chunk_index];
         chunk.delete_block_entity(BlockPos::new(pos.x & 0xF, pos.y, pos.z & 0xF));
+        if let Some(changed_blocks) = &mut self.changed_blocks {
+            changed_blocks.push(pos);
+        }
     }
 
     fn get_block_entity(&self, pos: BlockPos) -> Option<&BlockEntity> {
@@ -197,14 +284,18 @@ impl World for PlotWorld {
         }
         let chunk = &mut self.chunks[chunk_index];
         chunk.set_block_entity(BlockPos::new(pos.x & 0xF, pos.y, pos.z & 0xF), block_entity);
+        if let Some(changed_blocks) = &mut self.changed_blocks {
+            changed_blocks.push(pos);
+        }
     }
 
     fn get_chunk(&self, x: i32, z: i32) -> Option<&Chunk> {
-        self.chunks.get(self.get_chunk_index_for_chunk(x, z))
+        let chunk_idx = self.get_chunk_index_for_chunk(x, z)?;
+        self.chunks.get(chunk_idx)
     }
 
     fn get_chunk_mut(&mut self, x: i32, z: i32) -> Option<&mut Chunk> {
-        let chunk_idx = self.get_chunk_index_for_chunk(x, z);
+        let chunk_idx = self.get_chunk_index_for_chunk(x, z)?;
         self.chunks.get_mut(chunk_idx)
     }
 
@@ -267,7 +358,7 @@ impl World for PlotWorld {
 
 impl Plot {
     fn tickn(&mut self, ticks: u64) {
-        if self.redpiler.is_active() {
+        if self.redpiler.is_active() && self.redpiler_bounds.is_none() {
             self.timings.tickn(ticks);
             self.redpiler.tickn(ticks);
             return;
@@ -282,7 +373,9 @@ impl Plot {
         self.timings.tick();
         if self.redpiler.is_active() {
             self.redpiler.tick();
-            return;
+            if self.redpiler_bounds.is_none() {
+                return;
+            }
         }
 
         self.world
@@ -352,7 +445,7 @@ impl Plot {
     }
 
     fn set_pressure_plate(&mut self, pos: BlockPos, new_powered: bool) {
-        if self.redpiler.is_active() {
+        if self.redpiler_covers(pos, 0) {
             self.redpiler.set_pressure_plate(pos, new_powered);
             return;
         }
@@ -371,6 +464,65 @@ impl Plot {
         }
     }
 
+    /// Simulates a projectile hitting the target block at `pos`, powering it with `power` for
+    /// `ticks` redstone ticks. Returns false if the target is still powered from a previous hit.
+    fn hit_target(&mut self, pos: BlockPos, power: u8, ticks: u32) -> bool {
+        if self.redpiler_covers(pos, 0) {
+            if !self.redpiler.hit_target(pos, power, ticks) {
+                return false;
+            }
+            self.redpiler.flush(&mut self.world);
+        } else if !target::hit(&mut self.world, pos, power, ticks) {
+            return false;
+        }
+        self.world.flush_block_changes();
+        true
+    }
+
+    /// Simulates something passing through the tripwire string at `pos`, triggering it for
+    /// `ticks` redstone ticks. Returns false if the string is still triggered.
+    fn trigger_tripwire(&mut self, pos: BlockPos, ticks: u32) -> bool {
+        if self.redpiler_covers(pos, 0) {
+            // Redpiler only knows about the hooks, so the hooks are powered directly. A hook is
+            // powered for as long as it is triggered, so both are checked before triggering either
+            // to never leave the line half triggered.
+            let Some(hooks) = tripwire::find_line_hooks(&self.world, pos) else {
+                return false;
+            };
+            let can_trigger = hooks.iter().all(|&hook| {
+                self.redpiler
+                    .node_state(hook)
+                    .is_some_and(|state| !state.powered)
+            });
+            if !can_trigger {
+                return false;
+            }
+            for hook in hooks {
+                self.redpiler.trigger_tripwire_hook(hook, ticks);
+            }
+            self.redpiler.flush(&mut self.world);
+        } else if !tripwire::trigger(&mut self.world, pos, ticks) {
+            return false;
+        }
+        self.world.flush_block_changes();
+        true
+    }
+
+    /// Sets the output strength of the daylight detector at `pos`. If `inverted` is given, the
+    /// detector is also switched to that mode, which requires resetting redpiler.
+    fn set_daylight_detector(&mut self, pos: BlockPos, power: u8, inverted: Option<bool>) {
+        if inverted.is_some() {
+            self.reset_redpiler();
+        }
+        if self.redpiler_covers(pos, 0) {
+            self.redpiler.set_daylight_detector(pos, power);
+            self.redpiler.flush(&mut self.world);
+        } else {
+            daylight_detector::set_power(&mut self.world, pos, power, inverted);
+        }
+        self.world.flush_block_changes();
+    }
+
     fn are_players_on_block(&mut self, pos: BlockPos) -> bool {
         for player in &self.players {
             if player.pos.block_pos() == pos && player.on_ground {
@@ -433,15 +585,14 @@ impl Plot {
             // let unload_chunk = CUnloadChunk { chunk_x, chunk_z }.encode();
             // self.players[player_idx].client.send_packet(&unload_chunk);
         } else if !was_loaded && should_be_loaded {
-            if !Plot::chunk_in_plot_bounds(self.world.x, self.world.z, chunk_x, chunk_z) {
-                self.players[player_idx]
+            match self.world.get_chunk(chunk_x, chunk_z) {
+                Some(chunk) => {
+                    let chunk_data = chunk.encode_packet();
+                    self.players[player_idx].client.send_packet(&chunk_data);
+                }
+                None => self.players[player_idx]
                     .client
-                    .send_packet(&Chunk::encode_empty_packet(chunk_x, chunk_z, PLOT_SECTIONS));
-            } else {
-                let chunk_data = self.world.chunks
-                    [self.world.get_chunk_index_for_chunk(chunk_x, chunk_z)]
-                .encode_packet();
-                self.players[player_idx].client.send_packet(&chunk_data);
+                    .send_packet(&Chunk::encode_empty_packet(chunk_x, chunk_z, PLOT_SECTIONS)),
             }
         }
     }
@@ -508,7 +659,7 @@ impl Plot {
             self.players[player].inventory[45].clone()
         };
 
-        if !Plot::in_plot_bounds(self.world.x, self.world.z, block_pos.x, block_pos.z) {
+        if !self.world.in_plot_bounds(block_pos.x, block_pos.z) {
             self.players[player].send_system_message("Can't interact with blocks outside of plot");
             cancel(self);
             return;
@@ -528,6 +679,11 @@ impl Plot {
             }
         }
 
+        if use_item_on.hand == 0 && worldedit::tool::use_tool(self, player, Some(block_pos)) {
+            cancel(self);
+            return;
+        }
+
         if let Some(owner) = self.owner {
             let player = &mut self.players[player];
             if owner != player.uuid && !player.has_permission("plots.admin.interact.other") {
@@ -541,15 +697,36 @@ impl Plot {
             return;
         }
 
-        if self.redpiler.is_active() {
+        // Right-clicking a target block with an empty hand simulates an arrow hitting it
+        if item_in_hand.is_none() && !self.players[player].crouching {
             let block = self.world.get_block(block_pos);
-            let lever_or_button = matches!(block, Block::Lever { .. } | Block::StoneButton { .. });
-            if lever_or_button && !self.players[player].crouching {
-                self.redpiler.on_use_block(block_pos);
-                self.redpiler.flush(&mut self.world);
-                self.world.flush_block_changes();
+            if let Block::Target { .. } = block {
+                let cursor = (
+                    use_item_on.cursor_x,
+                    use_item_on.cursor_y,
+                    use_item_on.cursor_z,
+                );
+                let power = target::get_hit_strength(block_face, cursor);
+                self.hit_target(block_pos, power, target::ARROW_HIT_TICKS);
                 return;
-            } else {
+            }
+        }
+
+        if self.redpiler.is_active() {
+            let block = self.world.get_block(block_pos);
+            let is_input = matches!(
+                block,
+                Block::Lever { .. } | Block::StoneButton { .. } | Block::DaylightDetector { .. }
+            );
+            if is_input && !self.players[player].crouching {
+                // Inputs outside of a compiled selection are used like without redpiler
+                if self.redpiler_covers(block_pos, 0) {
+                    self.redpiler.on_use_block(block_pos);
+                    self.redpiler.flush(&mut self.world);
+                    self.world.flush_block_changes();
+                    return;
+                }
+            } else if self.redpiler_covers(block_pos, SELECTION_EDIT_MARGIN) {
                 match self.redpiler.current_flags() {
                     Some(flags) if flags.io_only => {
                         self.players[player].send_error_message(ERROR_IO_ONLY);
@@ -558,7 +735,7 @@ impl Plot {
                     }
                     _ => {}
                 }
-                self.reset_redpiler();
+                self.reset_redpiler_for_edit();
             }
         }
 
@@ -596,7 +773,7 @@ impl Plot {
     fn handle_player_digging(&mut self, block_pos: BlockPos, player: usize) {
         let block = self.world.get_block(block_pos);
 
-        if !Plot::in_plot_bounds(self.world.x, self.world.z, block_pos.x, block_pos.z) {
+        if !self.world.in_plot_bounds(block_pos.x, block_pos.z) {
             self.players[player].send_system_message("Can't break blocks outside of plot");
             return;
         }
@@ -633,18 +810,20 @@ impl Plot {
             return;
         }
 
-        match self.redpiler.current_flags() {
-            Some(flags) if flags.io_only => {
-                self.players[player].send_error_message(ERROR_IO_ONLY);
-                self.send_block_change(block_pos, block.get_id());
-                return;
+        if self.redpiler_covers(block_pos, SELECTION_EDIT_MARGIN) {
+            match self.redpiler.current_flags() {
+                Some(flags) if flags.io_only => {
+                    self.players[player].send_error_message(ERROR_IO_ONLY);
+                    self.send_block_change(block_pos, block.get_id());
+                    return;
+                }
+                _ => {}
             }
-            _ => {}
+            self.reset_redpiler_for_edit();
         }
 
-        self.reset_redpiler();
-
         interaction::destroy(block, &mut self.world, block_pos);
+        self.finish_redpiler_edit();
         self.world.flush_block_changes();
 
         let effect = CWorldEvent {
@@ -681,17 +860,33 @@ impl Plot {
         self.scoreboard
             .set_redpiler_options(&self.players, &options);
 
-        let bounds = self.world.get_corners();
+        self.run_redpiler_compile(|redpiler, world, bounds, ticks, monitor| {
+            redpiler.compile(world, bounds, options, ticks, monitor)
+        });
+    }
+
+    /// Runs a redpiler compile on another thread while keeping the players connected, then
+    /// reports the result to them.
+    fn run_redpiler_compile(
+        &mut self,
+        compile: impl FnOnce(
+                &mut Compiler,
+                &PlotWorld,
+                (BlockPos, BlockPos),
+                Vec<TickEntry>,
+                Arc<TaskMonitor>,
+            ) -> Result<Diagnostics, CompileError>
+            + Send,
+    ) {
+        let bounds = self.redpiler_bounds();
         // TODO: use monitor
         let monitor = Default::default();
-        let ticks = self.world.to_be_ticked.drain(..).collect();
+        let ticks = self.world.to_be_ticked.clone();
 
         let mut players_need_updates = HashSet::new();
-        thread::scope(|s| {
-            let handle = s.spawn(|| {
-                self.redpiler
-                    .compile(&self.world, bounds, options, ticks, monitor)
-            });
+        let result = thread::scope(|s| {
+            let handle =
+                s.spawn(|| compile(&mut self.redpiler, &self.world, bounds, ticks, monitor));
             while !handle.is_finished() {
                 // We'll update the players so that they don't time out.
                 for player_idx in 0..self.players.len() {
@@ -704,6 +899,7 @@ impl Plot {
                 }
                 thread::sleep(Duration::from_millis(20));
             }
+            handle.join().unwrap()
         });
 
         // Now that we have ownership of the world again, we can update player view positions
@@ -711,8 +907,41 @@ impl Plot {
             self.update_view_pos_for_player(player_idx, false);
         }
 
-        self.scoreboard
-            .set_redpiler_state(&self.players, RedpilerState::Running);
+        match result {
+            Ok(diagnostics) => {
+                for player in &self.players {
+                    send_diagnostics(player, &diagnostics);
+                }
+                match self.redpiler_bounds {
+                    Some((min, max)) => self
+                        .world
+                        .to_be_ticked
+                        .retain(|entry| !in_region(entry.pos, min, max)),
+                    None => self.world.to_be_ticked.clear(),
+                }
+                self.scoreboard
+                    .set_redpiler_state(&self.players, RedpilerState::Running);
+            }
+            Err(err) => {
+                warn!("Redpiler compile failed: {}", err);
+                self.redpiler_bounds = None;
+                for player in &self.players {
+                    player.send_error_message(&err.to_string());
+                    send_diagnostics(player, &err.diagnostics);
+                }
+                if self.auto_redpiler {
+                    // Otherwise we would try to compile again on the next tick
+                    self.auto_redpiler = false;
+                    for player in &self.players {
+                        player.send_system_message(
+                            "Automatic redpiler compilation has been disabled.",
+                        );
+                    }
+                }
+                self.scoreboard
+                    .set_redpiler_state(&self.players, RedpilerState::Stopped);
+            }
+        }
 
         self.reset_timings();
     }
@@ -722,15 +951,97 @@ impl Plot {
     fn reset_redpiler(&mut self) {
         if self.redpiler.is_active() {
             debug!("Discarding redpiler");
-            let bounds = self.world.get_corners();
+            let bounds = self.redpiler_bounds();
             self.redpiler.reset(&mut self.world, bounds);
-            self.scoreboard
-                .set_redpiler_state(&self.players, RedpilerState::Stopped);
-            self.scoreboard
-                .set_redpiler_options(&self.players, &Default::default());
+            self.on_redpiler_reset();
+        }
+        self.redpiler_bounds = None;
+    }
+
+    fn on_redpiler_reset(&mut self) {
+        self.redpiler_snapshots.clear();
+        self.redpiler_paused = false;
+        self.scoreboard
+            .set_redpiler_state(&self.players, RedpilerState::Stopped);
+        self.scoreboard
+            .set_redpiler_options(&self.players, &Default::default());
+
+        // reseting redpiler could cause a large amount of block updates
+        self.reset_timings();
+    }
+
+    /// Resets redpiler before a player edits a block. With auto redpiler, the compiled circuit is
+    /// kept so that [`Plot::finish_redpiler_edit`] can patch it after the edit instead of
+    /// compiling the whole plot again.
+    fn reset_redpiler_for_edit(&mut self) {
+        if !self.auto_redpiler || !self.redpiler.is_active() {
+            self.reset_redpiler();
+            return;
+        }
+        debug!("Resetting redpiler for edit");
+        // Resetting may already change blocks if the circuit was compiled with `--update`
+        self.world.changed_blocks = Some(Vec::new());
+        let bounds = self.redpiler_bounds();
+        if !self.redpiler.reset_for_recompile(&mut self.world, bounds) {
+            self.world.changed_blocks = None;
+            self.redpiler_bounds = None;
+        }
+        self.on_redpiler_reset();
+    }
 
-            // reseting redpiler could cause a large amount of block updates
-            self.reset_timings();
+    /// Recompiles the circuit kept by [`Plot::reset_redpiler_for_edit`] around the changed blocks.
+    fn finish_redpiler_edit(&mut self) {
+        let Some(changed) = self.world.changed_blocks.take() else {
+            return;
+        };
+        debug!("Recompiling redpiler after edit");
+        self.scoreboard
+            .set_redpiler_state(&self.players, RedpilerState::Compiling);
+        self.run_redpiler_compile(|redpiler, world, bounds, ticks, monitor| {
+            redpiler.recompile(world, bounds, &changed, ticks, monitor)
+        });
+        if let Some(options) = self.redpiler.current_flags() {
+            self.scoreboard.set_redpiler_options(&self.players, options);
+        }
+    }
+
+    /// The region redpiler is compiled for, which is the whole plot unless it was compiled with
+    /// `--selection`.
+    fn redpiler_bounds(&self) -> (BlockPos, BlockPos) {
+        self.redpiler_bounds
+            .unwrap_or_else(|| self.world.get_corners())
+    }
+
+    /// Returns true if redpiler is running and simulates the block at `pos`, or is close enough
+    /// to it that changing the block affects the compiled circuit.
+    fn redpiler_covers(&self, pos: BlockPos, margin: i32) -> bool {
+        if !self.redpiler.is_active() {
+            return false;
+        }
+        let Some((min, max)) = self.redpiler_bounds else {
+            return true;
+        };
+        let margin = BlockPos::new(margin, margin, margin);
+        in_region(pos, min - margin, max + margin)
+    }
+
+    /// Pauses redpiler if any breakpoints triggered and tells the players which ones.
+    fn check_redpiler_breakpoints(&mut self) {
+        let triggered = self.redpiler.take_triggered_breakpoints();
+        if triggered.is_empty() {
+            return;
+        }
+        self.redpiler_paused = true;
+        self.scoreboard
+            .set_redpiler_state(&self.players, RedpilerState::Paused);
+        for breakpoint in triggered {
+            let msg = format!(
+                "Breakpoint #{} hit: {} at {}",
+                breakpoint.id, breakpoint.condition, breakpoint.pos
+            );
+            for player in &self.players {
+                player.send_system_message(&msg);
+            }
         }
     }
 
@@ -755,13 +1066,11 @@ impl Plot {
         .encode();
         player.client.send_packet(&destroy_other_entities);
 
-        let chunk_offset_x = self.world.x << PLOT_SCALE;
-        let chunk_offset_z = self.world.z << PLOT_SCALE;
         for chunk in &self.world.chunks {
             player.client.send_packet(
                 &CUnloadChunk {
-                    chunk_x: chunk_offset_x + chunk.x,
-                    chunk_z: chunk_offset_z + chunk.z,
+                    chunk_x: chunk.x,
+                    chunk_z: chunk.z,
                 }
                 .encode(),
             );
@@ -772,15 +1081,6 @@ impl Plot {
         player
     }
 
-    fn chunk_in_plot_bounds(plot_x: i32, plot_z: i32, chunk_x: i32, chunk_z: i32) -> bool {
-        let (x, z) = (chunk_x >> PLOT_SCALE, chunk_z >> PLOT_SCALE);
-        plot_x == x && plot_z == z
-    }
-
-    fn in_plot_bounds(plot_x: i32, plot_z: i32, x: i32, z: i32) -> bool {
-        Plot::chunk_in_plot_bounds(plot_x, plot_z, x >> 4, z >> 4)
-    }
-
     pub fn claim_plot(&mut self, plot_x: i32, plot_z: i32, player: usize) {
         let player = &mut self.players[player];
         database::claim_plot(plot_x, plot_z, &format!("{:032x}", player.uuid));
@@ -789,6 +1089,50 @@ impl Plot {
         player.send_system_message(&format!("Claimed plot {},{}", plot_x, plot_z));
     }
 
+    /// Merges this plot's group with the plots next to it in `direction`. Groups have to stay
+    /// rectangular, so any group those plots are already part of is merged as a whole.
+    pub fn merge_plots(&mut self, player: usize, direction: BlockDirection) {
+        let player = &mut self.players[player];
+        let mut merged = self.world.group();
+        match direction {
+            BlockDirection::North => merged.min_z -= 1,
+            BlockDirection::South => merged.max_z += 1,
+            BlockDirection::West => merged.min_x -= 1,
+            BlockDirection::East => merged.max_x += 1,
+        }
+        let merged = merged.absorb_overlapping(database::get_plot_group);
+        if merged.size_x() * merged.size_z() > MAX_MERGED_PLOTS {
+            player.send_error_message(&format!(
+                "Merged plots can't be larger than {} plots.",
+                MAX_MERGED_PLOTS
+            ));
+            return;
+        }
+
+        let uuid = format!("{:032x}", player.uuid);
+        let owns_all = merged
+            .plots()
+            .all(|(plot_x, plot_z)| database::get_plot_owner(plot_x, plot_z) == Some(uuid.clone()));
+        if !owns_all {
+            player.send_error_message("You must own every plot you are merging.");
+            return;
+        }
+
+        database::merge_plots(merged);
+        player.send_system_message(&format!(
+            "Merged plots from {},{} to {},{} ({}x{}).",
+            merged.min_x,
+            merged.min_z,
+            merged.max_x,
+            merged.max_z,
+            merged.size_x(),
+            merged.size_z()
+        ));
+        self.message_sender
+            .send(Message::PlotsMerged(merged))
+            .unwrap();
+    }
+
     pub fn get_center(plot_x: i32, plot_z: i32) -> (f64, f64) {
         const WIDTH: f64 = PLOT_BLOCK_WIDTH as f64;
         (
@@ -918,10 +1262,34 @@ impl Plot {
                     }
                     self.enter_plot(player);
                 }
+                PrivMessage::UnloadForMerge => {
+                    self.unload_for_merge();
+                    return;
+                }
             }
         }
     }
 
+    /// Saves the plot and sends its players back to the server, which moves them to the merged
+    /// group once it has loaded.
+    fn unload_for_merge(&mut self) {
+        // The circuit was compiled for this plot alone, so it can't be resumed over the merged
+        // group. Resetting puts its pending ticks back into the world to be saved instead.
+        self.reset_redpiler();
+        self.save_with_redpiler_state(None);
+
+        while !self.players.is_empty() {
+            let uuid = self.players[0].uuid;
+            let player = self.leave_plot(uuid);
+            self.message_sender
+                .send(Message::PlayerLeavePlot(player))
+                .unwrap();
+        }
+        self.always_running = false;
+        self.running = false;
+        self.unloading_for_merge = true;
+    }
+
     /// Remove players outside of the plot
     fn remove_oob_players(&mut self) {
         let mut outside_players = Vec::new();
@@ -931,7 +1299,7 @@ impl Plot {
                 continue;
             }
             let (plot_x, plot_z) = player.pos.plot_pos();
-            if plot_x != self.world.x || plot_z != self.world.z {
+            if !self.world.group().contains(plot_x, plot_z) {
                 outside_players.push(player.uuid);
             }
         }
@@ -1019,8 +1387,11 @@ impl Plot {
                 let batch_size = batch_size.min(50_000) as u32;
                 let mut ticks_completed = batch_size;
                 if self.redpiler.is_active() {
-                    self.tickn(batch_size as u64);
-                    self.redpiler.flush(&mut self.world);
+                    if !self.redpiler_paused {
+                        self.tickn(batch_size as u64);
+                        self.redpiler.flush(&mut self.world);
+                        self.check_redpiler_breakpoints();
+                    }
                 } else {
                     for i in 0..batch_size {
                         self.tick();
@@ -1037,7 +1408,10 @@ impl Plot {
                 && !self.redpiler.is_active()
                 && (self.tps == Tps::Unlimited || self.timings.is_running_behind())
             {
-                self.start_redpiler(Default::default());
+                self.start_redpiler(CompilerOptions {
+                    incremental: true,
+                    ..Default::default()
+                });
             }
 
             let now = Instant::now();
@@ -1095,41 +1469,17 @@ impl Plot {
 
     fn from_data(
         plot_data: PlotData,
-        x: i32,
-        z: i32,
+        group: PlotGroup,
         rx: BusReader<BroadcastMessage>,
         tx: Sender<Message>,
         priv_rx: Receiver<PrivMessage>,
         always_running: bool,
     ) -> Plot {
-        let chunk_x_offset = x << PLOT_SCALE;
-        let chunk_z_offset = z << PLOT_SCALE;
-        let chunks: Vec<Chunk> = plot_data
-            .chunk_data
-            .into_iter()
-            .enumerate()
-            .map(|(i, c)| {
-                c.load(
-                    chunk_x_offset + i as i32 / PLOT_WIDTH,
-                    chunk_z_offset + i as i32 % PLOT_WIDTH,
-                )
-            })
-            .collect();
-        if chunks.len() != NUM_CHUNKS {
-            error!("This plot has the wrong number of chunks!");
-            let possible_scale = (chunks.len() as f64).sqrt().log2();
-            error!("Note: it most likely came from a server running plot scale {}, this server is running a plot scale of {}", possible_scale, PLOT_SCALE);
-        }
-        let world = PlotWorld {
-            x,
-            z,
-            chunks,
-            to_be_ticked: plot_data.pending_ticks,
-            packet_senders: Vec::new(),
-        };
+        let (x, z) = (group.min_x, group.min_z);
+        let world = PlotWorld::from_data(group, plot_data.chunk_data, plot_data.pending_ticks);
         let tps = plot_data.tps;
         let world_send_rate = plot_data.world_send_rate;
-        Plot {
+        let mut plot = Plot {
             last_player_time: Instant::now(),
             last_update_time: Instant::now(),
             last_world_send_time: Instant::now(),
@@ -1146,13 +1496,22 @@ impl Plot {
             tps,
             world_send_rate,
             always_running,
+            unloading_for_merge: false,
             redpiler: Default::default(),
+            redpiler_snapshots: HashMap::new(),
+            redpiler_paused: false,
+            redpiler_bounds: None,
+            worldedit_history: WorldEditHistory::load(group),
             timings: TimingsMonitor::new(tps),
             owner: database::get_plot_owner(x, z).map(|s| s.parse::<HyphenatedUUID>().unwrap().0),
             async_rt: Plot::create_async_rt(),
             scoreboard: Default::default(),
             world,
+        };
+        if let Some(state) = plot_data.redpiler_state {
+            plot.resume_redpiler(&state);
         }
+        plot
     }
 
     fn load(
@@ -1163,23 +1522,60 @@ impl Plot {
         priv_rx: Receiver<PrivMessage>,
         always_running: bool,
     ) -> Result<Plot, (Error, Sender<Message>)> {
-        let plot_path = format!("./world/plots/p{},{}", x, z);
-        Ok(if Path::new(&plot_path).exists() {
-            match data::load_plot(plot_path) {
-                Ok(data) => Plot::from_data(data, x, z, rx, tx, priv_rx, always_running),
-                Err(err) => {
-                    return Result::Err((
-                        err.context(format!("error loading plot {},{}", x, z)),
-                        tx,
-                    ))
-                }
+        let group = database::get_plot_group(x, z);
+        match data::load_group(group) {
+            Ok(data) => Ok(Plot::from_data(
+                data,
+                group,
+                rx,
+                tx,
+                priv_rx,
+                always_running,
+            )),
+            Err(err) => Err((err.context(format!("error loading plot {},{}", x, z)), tx)),
+        }
+    }
+
+    /// Serializes the running circuit if it was compiled with `--persist`. The world is flushed
+    /// first so that it matches the saved state.
+    fn save_redpiler(&mut self) -> Option<Vec<u8>> {
+        if !self.redpiler.is_active() {
+            return None;
+        }
+        if self.redpiler_bounds.is_some() {
+            // The rest of the plot isn't part of the saved circuit
+            debug!("Not saving redpiler state compiled from a selection");
+            return None;
+        }
+        self.redpiler.flush(&mut self.world);
+        let circuit = self.redpiler.save(&self.world, self.redpiler_bounds())?;
+        match bincode::serialize(&circuit) {
+            Ok(state) => Some(state),
+            Err(err) => {
+                warn!("Failed to serialize redpiler state: {}", err);
+                None
             }
-        } else {
-            Plot::from_data(data::empty_plot(), x, z, rx, tx, priv_rx, always_running)
-        })
+        }
+    }
+
+    fn resume_redpiler(&mut self, state: &[u8]) {
+        let bounds = self.redpiler_bounds();
+        let Some(options) = resume_circuit(&mut self.redpiler, &mut self.world, bounds, state)
+        else {
+            return;
+        };
+        self.scoreboard
+            .set_redpiler_state(&self.players, RedpilerState::Running);
+        self.scoreboard
+            .set_redpiler_options(&self.players, &options);
     }
 
     fn save(&mut self) {
+        let redpiler_state = self.save_redpiler();
+        self.save_with_redpiler_state(redpiler_state);
+    }
+
+    fn save_with_redpiler_state(&mut self, redpiler_state: Option<Vec<u8>>) {
         let world = &mut self.world;
         let chunk_data: Vec<ChunkData> = world.chunks.iter_mut().map(ChunkData::new).collect();
         let data = PlotData {
@@ -1187,9 +1583,12 @@ impl Plot {
             world_send_rate: self.world_send_rate,
             chunk_data,
             pending_ticks: world.to_be_ticked.clone(),
+            redpiler_state,
         };
-        data.save_to_file(format!("./world/plots/p{},{}", world.x, world.z))
-            .unwrap();
+        data::save_group(world.group(), data).unwrap();
+        if let Err(err) = self.worldedit_history.save(world.group()) {
+            error!("error saving worldedit history: {:?}", err);
+        }
 
         self.reset_timings();
     }
@@ -1223,7 +1622,9 @@ impl Plot {
             }
         }
 
-        self.save();
+        if !self.unloading_for_merge {
+            self.save();
+        }
     }
 
     /// This function is used in case of an error. It will try to send the player to spawn if this
@@ -1267,6 +1668,62 @@ impl Plot {
     }
 }
 
+/// Resumes a circuit saved with the plot, returning its options if it could be resumed. Circuits
+/// that don't fit the world anymore are left alone, and the world keeps ticking on its own.
+fn resume_circuit(
+    redpiler: &mut Compiler,
+    world: &mut PlotWorld,
+    bounds: (BlockPos, BlockPos),
+    state: &[u8],
+) -> Option<CompilerOptions> {
+    let circuit: SavedCircuit = match bincode::deserialize(state) {
+        Ok(circuit) => circuit,
+        Err(err) => {
+            warn!("Failed to deserialize redpiler state: {}", err);
+            return None;
+        }
+    };
+    let options = circuit.options().clone();
+    if !redpiler.resume(circuit, world, bounds, Default::default()) {
+        warn!("Saved redpiler state does not match the compiled circuit");
+        return None;
+    }
+    debug!("Resumed redpiler");
+    // The backend has its own copy of the pending ticks
+    world.to_be_ticked.clear();
+    Some(options)
+}
+
+fn in_region(pos: BlockPos, min: BlockPos, max: BlockPos) -> bool {
+    (min.x..=max.x).contains(&pos.x)
+        && (min.y..=max.y).contains(&pos.y)
+        && (min.z..=max.z).contains(&pos.z)
+}
+
+/// The most plots a merged group can be made of, since the whole group is kept in memory and
+/// ticked together.
+const MAX_MERGED_PLOTS: i32 = 16;
+
+/// The most diagnostics that are sent to a player at once, so a plot full of unsupported blocks
+/// doesn't flood the chat.
+const MAX_SENT_DIAGNOSTICS: usize = 10;
+
+/// Reports the warnings and errors of a redpiler compile to the player.
+fn send_diagnostics(player: &impl PacketSender, diagnostics: &Diagnostics) {
+    for diagnostic in diagnostics.iter().take(MAX_SENT_DIAGNOSTICS) {
+        match diagnostic.severity {
+            Severity::Warning => player.send_system_message(&diagnostic.to_string()),
+            Severity::Error => player.send_error_message(&diagnostic.to_string()),
+        }
+    }
+    if diagnostics.len() > MAX_SENT_DIAGNOSTICS {
+        player.send_system_message(&format!(
+            "...and {} more",
+            diagnostics.len() - MAX_SENT_DIAGNOSTICS
+        ));
+    }
+}
+
 impl Drop for Plot {
     fn drop(&mut self) {
         if !self.players.is_empty() {
@@ -1292,12 +1749,16 @@ impl Drop for Plot {
             .send(Message::PlotUnload(world.x, world.z))
             .unwrap();
 
+        if self.unloading_for_merge {
+            return;
+        }
+        let redpiler_state = self.save_redpiler();
         self.reset_redpiler();
         self.world
             .chunks
             .iter_mut()
             .for_each(|chunk| chunk.compress());
-        self.save();
+        self.save_with_redpiler_state(redpiler_state);
     }
 }
 
@@ -1312,3 +1773,92 @@ fn chunk_save_and_load_test() {
     assert_eq!(loaded_chunk.get_block(13, 62, 12), 331);
     assert_eq!(loaded_chunk.get_block(13, 64, 12), 0);
 }
+
+#[test]
+fn merged_group_does_not_resume_plot_circuit() {
+    use mchprs_blocks::blocks::LeverFace;
+
+    let plot_chunks = |plot_x: i32| -> Vec<Chunk> {
+        (0..PLOT_WIDTH)
+            .flat_map(|x| (0..PLOT_WIDTH).map(move |z| (x, z)))
+            .map(|(x, z)| Plot::generate_chunk(8, (plot_x << PLOT_SCALE) + x, z))
+            .collect()
+    };
+
+    // Run a persisted circuit on plot 0,0 alone
+    let mut world = PlotWorld {
+        x: 0,
+        z: 0,
+        size_x: 1,
+        size_z: 1,
+        chunks: plot_chunks(0),
+        to_be_ticked: Vec::new(),
+        packet_senders: Vec::new(),
+        changed_blocks: None,
+    };
+    let lever_pos = BlockPos::new(3, 8, 3);
+    world.set_block(
+        lever_pos,
+        Block::Lever {
+            face: LeverFace::Floor,
+            facing: BlockDirection::West,
+            powered: false,
+        },
+    );
+    let bounds = world.get_corners();
+    let options = CompilerOptions {
+        persist: true,
+        ..Default::default()
+    };
+    let mut compiler = Compiler::default();
+    assert!(compiler
+        .compile(&world, bounds, options, Vec::new(), Default::default())
+        .is_ok());
+    compiler.on_use_block(lever_pos);
+    compiler.flush(&mut world);
+    let state = bincode::serialize(&compiler.save(&world, bounds).unwrap()).unwrap();
+
+    let plot_data = |world: &mut PlotWorld, redpiler_state| PlotData {
+        tps: Tps::Limited(10),
+        world_send_rate: WorldSendRate::default(),
+        chunk_data: world.chunks.iter_mut().map(ChunkData::new).collect(),
+        pending_ticks: world.to_be_ticked.clone(),
+        redpiler_state,
+    };
+    let first = plot_data(&mut world, Some(state));
+    let mut second_world = PlotWorld {
+        x: 1,
+        z: 0,
+        size_x: 1,
+        size_z: 1,
+        chunks: plot_chunks(1),
+        to_be_ticked: vec![TickEntry {
+            ticks_left: 1,
+            tick_priority: TickPriority::Normal,
+            pos: BlockPos::new(PLOT_BLOCK_WIDTH + 3, 8, 3),
+        }],
+        packet_senders: Vec::new(),
+        changed_blocks: None,
+    };
+    let second = plot_data(&mut second_world, None);
+
+    // The first plot's circuit must not take over the merged group
+    let group = PlotGroup {
+        min_x: 0,
+        min_z: 0,
+        max_x: 1,
+        max_z: 0,
+    };
+    let data = data::join_group(group, vec![first, second]).unwrap();
+    let mut merged = PlotWorld::from_data(group, data.chunk_data, data.pending_ticks);
+    let mut redpiler = Compiler::default();
+    let state = data.redpiler_state.unwrap();
+    let bounds = merged.get_corners();
+    assert!(resume_circuit(&mut redpiler, &mut merged, bounds, &state).is_none());
+    assert!(!redpiler.is_active());
+    assert_eq!(merged.to_be_ticked.len(), 1);
+    assert!(matches!(
+        merged.get_block(lever_pos),
+        Block::Lever { powered: true, .. }
+    ));
+}
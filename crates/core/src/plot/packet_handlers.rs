@@ -1,8 +1,9 @@
-use super::Plot;
+use super::worldedit::library;
+use super::{worldedit, Plot};
 use crate::config::CONFIG;
 use crate::player::{PacketSender, PlayerPos, SkinParts};
 use crate::server::Message;
-use crate::utils::{self, HyphenatedUUID};
+use crate::utils;
 use mchprs_blocks::block_entities::BlockEntity;
 use mchprs_blocks::items::{Item, ItemStack};
 use mchprs_blocks::BlockPos;
@@ -34,10 +35,10 @@ impl ServerBoundPacketHandler for Plot {
             return;
         }
 
-        let mut path = PathBuf::from("./schems");
-        if CONFIG.schemati {
-            let uuid = self.players[player_idx].uuid;
-            path.push(HyphenatedUUID(uuid).to_string());
+        let mut dirs = vec![library::player_dir(self.players[player_idx].uuid)];
+        if !CONFIG.schemati {
+            // Schematics saved before there were per player folders
+            dirs.push(PathBuf::from("./schems"));
         }
 
         let current = &packet.text[7..];
@@ -48,28 +49,30 @@ impl ServerBoundPacketHandler for Plot {
             matches: Vec::new(),
         };
 
-        let dir = match fs::read_dir(path) {
-            Ok(dir) => dir,
-            Err(err) => {
-                if err.kind() != std::io::ErrorKind::NotFound {
-                    error!("There was an error completing //load");
-                    error!("{}", err);
+        for path in dirs {
+            let dir = match fs::read_dir(path) {
+                Ok(dir) => dir,
+                Err(err) => {
+                    if err.kind() != std::io::ErrorKind::NotFound {
+                        error!("There was an error completing //load");
+                        error!("{}", err);
+                    }
+                    continue;
                 }
-                return;
-            }
-        };
+            };
 
-        for entry in dir {
-            let entry = entry.unwrap();
-            if entry.file_type().unwrap().is_file() {
-                let name = entry.file_name();
-                let name = name.to_string_lossy();
-                if name.starts_with(current) {
-                    let m = CCommandSuggestionsResponseMatch {
-                        match_: name.to_string(),
-                        tooltip: None,
-                    };
-                    res.matches.push(m);
+            for entry in dir {
+                let entry = entry.unwrap();
+                if entry.file_type().unwrap().is_file() {
+                    let name = entry.file_name();
+                    let name = name.to_string_lossy();
+                    if name.starts_with(current) && library::is_valid_name(&name) {
+                        let m = CCommandSuggestionsResponseMatch {
+                            match_: name.to_string(),
+                            tooltip: None,
+                        };
+                        res.matches.push(m);
+                    }
                 }
             }
         }
@@ -156,6 +159,7 @@ impl ServerBoundPacketHandler for Plot {
 
     fn handle_use_item_on(&mut self, use_item_on: SUseItemOn, player: usize) {
         self.handle_use_item_impl(&use_item_on, player);
+        self.finish_redpiler_edit();
 
         let acknowledge_block_change = CAcknowledgeBlockChange {
             sequence_id: use_item_on.sequence,
@@ -164,6 +168,19 @@ impl ServerBoundPacketHandler for Plot {
         self.players[player].send_packet(&acknowledge_block_change);
     }
 
+    fn handle_use_item(&mut self, use_item: SUseItem, player: usize) {
+        // Right clicking the air is only used for worldedit tools
+        if use_item.hand == 0 {
+            worldedit::tool::use_tool(self, player, None);
+        }
+
+        let acknowledge_block_change = CAcknowledgeBlockChange {
+            sequence_id: use_item.sequence,
+        }
+        .encode();
+        self.players[player].send_packet(&acknowledge_block_change);
+    }
+
     fn handle_chat_command(&mut self, chat_command: SChatCommand, player: usize) {
         self.players[player]
             .command_queue
@@ -12,6 +12,7 @@ pub enum RedpilerState {
     Stopped,
     Compiling,
     Running,
+    Paused,
 }
 
 impl RedpilerState {
@@ -20,6 +21,7 @@ impl RedpilerState {
             RedpilerState::Stopped => "§d§lStopped",
             RedpilerState::Compiling => "§e§lCompiling",
             RedpilerState::Running => "§a§lRunning",
+            RedpilerState::Paused => "§6§lPaused",
         }
     }
 }
@@ -138,6 +140,15 @@ impl Scoreboard {
         if options.wire_dot_out {
             flags.push("§b- wire dot out");
         }
+        if options.persist {
+            flags.push("§b- persist");
+        }
+        if options.incremental {
+            flags.push("§b- incremental");
+        }
+        if options.selection {
+            flags.push("§b- selection");
+        }
 
         if !flags.is_empty() {
             new_lines.push("§7Flags:".to_string());
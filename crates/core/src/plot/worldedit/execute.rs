@@ -1,17 +1,20 @@
+use super::library::{self, SchematicMetadata, SchematicRef};
+use super::tool::{bind_tool, unbind_tool, WorldEditTool, MAX_BRUSH_SIZE};
 use super::*;
 use crate::config::CONFIG;
 use crate::player::PacketSender;
 use crate::plot::PLOT_BLOCK_HEIGHT;
 use crate::utils::{self, HyphenatedUUID};
 use mchprs_blocks::block_entities::InventoryEntry;
-use mchprs_blocks::blocks::{Block, FlipDirection, HopperFacing, RotateAmt};
+use mchprs_blocks::blocks::{Block, CrafterOrientation, FlipDirection, HopperFacing, RotateAmt};
 use mchprs_blocks::items::{Item, ItemStack};
 use mchprs_blocks::{BlockDirection, BlockFace, BlockFacing, BlockPos};
 use mchprs_network::packets::clientbound::*;
 use mchprs_schematic::{load_schematic, paste_clipboard, save_schematic, WorldEditClipboard};
 use mchprs_text::{ColorCode, TextComponentBuilder};
+use std::fs;
 use std::path::PathBuf;
-use std::time::Instant;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use tracing::error;
 
 pub(super) fn execute_wand(ctx: CommandExecuteContext<'_>) {
@@ -36,27 +39,97 @@ pub(super) fn execute_wand(ctx: CommandExecuteContext<'_>) {
     }
 }
 
-pub(super) fn execute_set(ctx: CommandExecuteContext<'_>) {
-    let start_time = Instant::now();
-    let pattern = ctx.arguments[0].unwrap_pattern();
+pub(super) fn execute_brush_sphere(ctx: CommandExecuteContext<'_>) {
+    let pattern = ctx.arguments[0].unwrap_pattern().clone();
+    let radius = ctx.arguments[1].unwrap_uint();
+    if radius > MAX_BRUSH_SIZE {
+        ctx.player.send_error_message(&format!(
+            "The radius can't be larger than {}.",
+            MAX_BRUSH_SIZE
+        ));
+        return;
+    }
+    let mask = ctx
+        .flag_argument('m')
+        .map(|mask| mask.unwrap_mask().clone());
+    bind_tool(
+        ctx.player,
+        WorldEditTool::SphereBrush {
+            pattern,
+            radius,
+            mask,
+        },
+    );
+}
 
-    let mut operation = worldedit_start_operation(ctx.player);
-    capture_undo(
-        ctx.plot,
+pub(super) fn execute_brush_cylinder(ctx: CommandExecuteContext<'_>) {
+    let pattern = ctx.arguments[0].unwrap_pattern().clone();
+    let radius = ctx.arguments[1].unwrap_uint();
+    let height = ctx.arguments[2].unwrap_uint();
+    if radius > MAX_BRUSH_SIZE || height > MAX_BRUSH_SIZE {
+        ctx.player.send_error_message(&format!(
+            "The radius and height can't be larger than {}.",
+            MAX_BRUSH_SIZE
+        ));
+        return;
+    }
+    let mask = ctx
+        .flag_argument('m')
+        .map(|mask| mask.unwrap_mask().clone());
+    bind_tool(
         ctx.player,
-        ctx.player.first_position.unwrap(),
-        ctx.player.second_position.unwrap(),
+        WorldEditTool::CylinderBrush {
+            pattern,
+            radius,
+            height,
+            mask,
+        },
     );
-    for x in operation.x_range() {
-        for y in operation.y_range() {
-            for z in operation.z_range() {
-                let block_pos = BlockPos::new(x, y, z);
-                let block_id = pattern.pick().get_id();
-
-                if ctx.plot.set_block_raw(block_pos, block_id) {
-                    operation.update_block();
-                }
-            }
+}
+
+pub(super) fn execute_brush_clipboard(ctx: CommandExecuteContext<'_>) {
+    let clipboard = ctx.player.worldedit_clipboard.clone().unwrap();
+    let ignore_air = ctx.has_flag('a');
+    let mask = ctx
+        .flag_argument('m')
+        .map(|mask| mask.unwrap_mask().clone());
+    bind_tool(
+        ctx.player,
+        WorldEditTool::ClipboardBrush {
+            clipboard,
+            ignore_air,
+            mask,
+        },
+    );
+}
+
+pub(super) fn execute_tool_toggle(ctx: CommandExecuteContext<'_>) {
+    bind_tool(ctx.player, WorldEditTool::DiodeToggle);
+}
+
+pub(super) fn execute_none(ctx: CommandExecuteContext<'_>) {
+    unbind_tool(ctx.player);
+}
+
+pub(super) fn execute_set(mut ctx: CommandExecuteContext<'_>) {
+    let start_time = Instant::now();
+    let pattern = ctx.arguments[0].unwrap_pattern().clone();
+
+    let mask = ctx.edit_mask();
+
+    let mut operation = worldedit_start_operation(ctx.player);
+    let (first_pos, second_pos) = operation.bounds();
+    ctx.capture_undo(first_pos, second_pos);
+    for block_pos in operation.blocks() {
+        if let Some(mask) = &mask
+            && !mask.matches(ctx.plot, block_pos)
+        {
+            continue;
+        }
+        let block_id = pattern.pick().get_id();
+
+        if ctx.plot.set_block_raw(block_pos, block_id) {
+            operation.update_block();
         }
     }
 
@@ -69,31 +142,25 @@ pub(super) fn execute_set(ctx: CommandExecuteContext<'_>) {
     ));
 }
 
-pub(super) fn execute_replace(ctx: CommandExecuteContext<'_>) {
+pub(super) fn execute_replace(mut ctx: CommandExecuteContext<'_>) {
     let start_time = Instant::now();
 
-    let filter = ctx.arguments[0].unwrap_mask();
-    let pattern = ctx.arguments[1].unwrap_pattern();
+    let filter = ctx.arguments[0].unwrap_mask().clone();
+    let pattern = ctx.arguments[1].unwrap_pattern().clone();
+    let global_mask = ctx.player.worldedit_global_mask.clone();
 
     let mut operation = worldedit_start_operation(ctx.player);
-    capture_undo(
-        ctx.plot,
-        ctx.player,
-        ctx.player.first_position.unwrap(),
-        ctx.player.second_position.unwrap(),
-    );
-    for x in operation.x_range() {
-        for y in operation.y_range() {
-            for z in operation.z_range() {
-                let block_pos = BlockPos::new(x, y, z);
-
-                if filter.matches(ctx.plot.get_block(block_pos)) {
-                    let block_id = pattern.pick().get_id();
-
-                    if ctx.plot.set_block_raw(block_pos, block_id) {
-                        operation.update_block();
-                    }
-                }
+    let (first_pos, second_pos) = operation.bounds();
+    ctx.capture_undo(first_pos, second_pos);
+    for block_pos in operation.blocks() {
+        let global_match = global_mask
+            .as_ref()
+            .is_none_or(|mask| mask.matches(ctx.plot, block_pos));
+        if global_match && filter.matches(ctx.plot, block_pos) {
+            let block_id = pattern.pick().get_id();
+
+            if ctx.plot.set_block_raw(block_pos, block_id) {
+                operation.update_block();
             }
         }
     }
@@ -114,14 +181,9 @@ pub(super) fn execute_count(ctx: CommandExecuteContext<'_>) {
 
     let mut blocks_counted = 0;
     let operation = worldedit_start_operation(ctx.player);
-    for x in operation.x_range() {
-        for y in operation.y_range() {
-            for z in operation.z_range() {
-                let block_pos = BlockPos::new(x, y, z);
-                if filter.matches(ctx.plot.get_block(block_pos)) {
-                    blocks_counted += 1;
-                }
-            }
+    for block_pos in operation.blocks() {
+        if filter.matches(ctx.plot, block_pos) {
+            blocks_counted += 1;
         }
     }
 
@@ -136,12 +198,8 @@ pub(super) fn execute_copy(ctx: CommandExecuteContext<'_>) {
     let start_time = Instant::now();
 
     let origin = ctx.player.pos.block_pos();
-    let clipboard = create_clipboard(
-        ctx.plot,
-        origin,
-        ctx.player.first_position.unwrap(),
-        ctx.player.second_position.unwrap(),
-    );
+    let region = ctx.player.worldedit_region().unwrap();
+    let clipboard = create_region_clipboard(ctx.plot, origin, &region);
     ctx.player.worldedit_clipboard = Some(clipboard);
 
     ctx.player.send_worldedit_message(&format!(
@@ -150,18 +208,18 @@ pub(super) fn execute_copy(ctx: CommandExecuteContext<'_>) {
     ));
 }
 
-pub(super) fn execute_cut(ctx: CommandExecuteContext<'_>) {
+pub(super) fn execute_cut(mut ctx: CommandExecuteContext<'_>) {
     let start_time = Instant::now();
 
-    let first_pos = ctx.player.first_position.unwrap();
-    let second_pos = ctx.player.second_position.unwrap();
+    let region = ctx.player.worldedit_region().unwrap();
+    let (first_pos, second_pos) = region.bounds();
 
-    capture_undo(ctx.plot, ctx.player, first_pos, second_pos);
+    ctx.capture_undo(first_pos, second_pos);
 
     let origin = ctx.player.pos.block_pos();
-    let clipboard = create_clipboard(ctx.plot, origin, first_pos, second_pos);
+    let clipboard = create_region_clipboard(ctx.plot, origin, &region);
     ctx.player.worldedit_clipboard = Some(clipboard);
-    clear_area(ctx.plot, first_pos, second_pos);
+    clear_area(ctx.plot, &region, None);
 
     ctx.player.send_worldedit_message(&format!(
         "Your selection was cut. ({:?})",
@@ -191,18 +249,22 @@ pub(super) fn execute_move(mut ctx: CommandExecuteContext<'_>) {
             ),
         ],
         pos: first_pos.min(second_pos),
-        plot_x: ctx.plot.x,
-        plot_z: ctx.plot.z,
     };
-    ctx.player.worldedit_undo.push(undo);
+    ctx.record_undo(undo);
 
+    let mask = ctx.edit_mask();
     let clipboard = create_clipboard(ctx.plot, zero_pos, first_pos, second_pos);
-    clear_area(ctx.plot, first_pos, second_pos);
-    paste_clipboard(
+    clear_area(
+        ctx.plot,
+        &Region::cuboid(first_pos, second_pos),
+        mask.as_ref(),
+    );
+    paste_clipboard_masked(
         ctx.plot,
         &clipboard,
         direction.offset_pos(zero_pos, move_amt as i32),
         ctx.has_flag('a'),
+        mask.as_ref(),
     );
 
     if ctx.has_flag('s') {
@@ -219,7 +281,7 @@ pub(super) fn execute_move(mut ctx: CommandExecuteContext<'_>) {
     ));
 }
 
-pub(super) fn execute_paste(ctx: CommandExecuteContext<'_>) {
+pub(super) fn execute_paste(mut ctx: CommandExecuteContext<'_>) {
     let start_time = Instant::now();
 
     if ctx.player.worldedit_clipboard.is_some() {
@@ -235,12 +297,16 @@ pub(super) fn execute_paste(ctx: CommandExecuteContext<'_>) {
             offset_y + cb.size_y as i32 - 1,
             offset_z + cb.size_z as i32 - 1,
         );
-        capture_undo(ctx.plot, ctx.player, first_pos, second_pos);
-        paste_clipboard(ctx.plot, cb, pos, ctx.has_flag('a'));
+        let mask = ctx.edit_mask();
+        ctx.capture_undo(first_pos, second_pos);
+        paste_clipboard_masked(ctx.plot, cb, pos, ctx.has_flag('a'), mask.as_ref());
         if ctx.has_flag('u') {
             update(ctx.plot, first_pos, second_pos);
         }
         if ctx.has_flag('s') {
+            if ctx.player.worldedit_selector != RegionSelector::Cuboid {
+                ctx.player.worldedit_set_selector(RegionSelector::Cuboid);
+            }
             ctx.player.worldedit_set_first_position(first_pos);
             ctx.player.worldedit_set_second_position(second_pos);
         }
@@ -253,24 +319,28 @@ pub(super) fn execute_paste(ctx: CommandExecuteContext<'_>) {
     }
 }
 
-static SCHEMATI_VALIDATE_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"[a-zA-Z0-9_.]+\.schem(atic)?").unwrap());
-
 pub(super) fn execute_load(ctx: CommandExecuteContext<'_>) {
     let start_time = Instant::now();
 
-    let mut file_name = ctx.arguments[0].unwrap_string().clone();
-    if !SCHEMATI_VALIDATE_REGEX.is_match(&file_name) {
-        ctx.player.send_error_message("Filename is invalid");
+    let name = ctx.arguments[0].unwrap_string();
+    let schematic = match SchematicRef::parse(ctx.player, name) {
+        Ok(schematic) => schematic,
+        Err(err) => {
+            ctx.player.send_error_message(&err);
+            return;
+        }
+    };
+    if !schematic.can_read(ctx.player) {
+        ctx.player.send_no_permission_message();
         return;
     }
 
-    if CONFIG.schemati {
-        let prefix = HyphenatedUUID(ctx.player.uuid).to_string() + "/";
-        file_name.insert_str(0, &prefix);
+    let mut path = schematic.path();
+    // Schematics saved before there were per player folders
+    let legacy_path = PathBuf::from("./schems").join(&schematic.name);
+    if !CONFIG.schemati && schematic.is_owned_by(ctx.player) && !path.exists() {
+        path = legacy_path;
     }
-
-    let path = PathBuf::from("./schems").join(file_name);
     let clipboard = load_schematic(&path);
     match clipboard {
         Ok(cb) => {
@@ -300,20 +370,27 @@ pub(super) fn execute_load(ctx: CommandExecuteContext<'_>) {
 pub(super) fn execute_save(ctx: CommandExecuteContext<'_>) {
     let start_time = Instant::now();
 
-    let mut file_name = ctx.arguments[0].unwrap_string().clone();
-    if !SCHEMATI_VALIDATE_REGEX.is_match(&file_name) {
+    let file_name = ctx.arguments[0].unwrap_string();
+    if !library::is_valid_name(file_name) {
         ctx.player.send_error_message("Filename is invalid");
         return;
     }
+    let schematic = SchematicRef {
+        owner: ctx.player.uuid,
+        name: file_name.clone(),
+    };
 
-    if CONFIG.schemati {
-        let prefix = HyphenatedUUID(ctx.player.uuid).to_string() + "/";
-        file_name.insert_str(0, &prefix);
-    }
-
-    let path = PathBuf::from("./schems").join(file_name);
     let clipboard = ctx.player.worldedit_clipboard.as_ref().unwrap();
-    match save_schematic(&path, clipboard) {
+    let metadata = SchematicMetadata::new(
+        ctx.player,
+        [clipboard.size_x, clipboard.size_y, clipboard.size_z],
+        ctx.arguments[1].unwrap_string().clone(),
+    );
+    let result = fs::create_dir_all(library::player_dir(ctx.player.uuid))
+        .map_err(anyhow::Error::from)
+        .and_then(|_| save_schematic(&schematic.path(), clipboard))
+        .and_then(|_| schematic.save_metadata(&metadata));
+    match result {
         Ok(_) => {
             ctx.player.send_worldedit_message(&format!(
                 "The schematic was saved sucessfuly. ({:?})",
@@ -329,7 +406,240 @@ pub(super) fn execute_save(ctx: CommandExecuteContext<'_>) {
     }
 }
 
-pub(super) fn execute_stack(ctx: CommandExecuteContext<'_>) {
+pub(super) fn execute_schem_list(ctx: CommandExecuteContext<'_>) {
+    let page = ctx.arguments[0].unwrap_uint().max(1) as usize;
+    let owner = match ctx.flag_argument('p') {
+        Some(name) => match library::find_player(name.unwrap_string()) {
+            Ok(owner) => owner,
+            Err(err) => {
+                ctx.player.send_error_message(&err);
+                return;
+            }
+        },
+        None => ctx.player.uuid,
+    };
+    let names = match library::list_schematics(owner) {
+        Ok(names) => names,
+        Err(err) => {
+            error!("There was an error listing schematics: {:?}", err);
+            ctx.player
+                .send_error_message("There was an error listing the schematics.");
+            return;
+        }
+    };
+    let mut schematics: Vec<SchematicRef> = names
+        .into_iter()
+        .map(|name| SchematicRef { owner, name })
+        .collect();
+    // Without the permission, only the schematics shared with the player are listed
+    if owner != ctx.player.uuid && !ctx.player.has_permission("worldedit.schematic.list.others") {
+        schematics.retain(|schematic| schematic.can_read(ctx.player));
+    }
+
+    if schematics.is_empty() {
+        ctx.player
+            .send_error_message("There are no schematics to list.");
+        return;
+    }
+    let pages = schematics.len().div_ceil(LIST_PAGE_SIZE);
+    if page > pages {
+        ctx.player
+            .send_error_message(&format!("There are only {} pages of schematics.", pages));
+        return;
+    }
+
+    let owner_name = if owner == ctx.player.uuid {
+        "Your".to_owned()
+    } else {
+        format!("{}'s", library::username(owner))
+    };
+    ctx.player.send_worldedit_message(&format!(
+        "{} schematics (page {}/{}):",
+        owner_name, page, pages
+    ));
+    for schematic in schematics
+        .iter()
+        .skip((page - 1) * LIST_PAGE_SIZE)
+        .take(LIST_PAGE_SIZE)
+    {
+        let mut line = vec![TextComponentBuilder::new(schematic.name.clone())
+            .color_code(ColorCode::Yellow)
+            .finish()];
+        if let Some(metadata) = schematic.metadata() {
+            let [x, y, z] = metadata.size;
+            line.push(
+                TextComponentBuilder::new(format!(
+                    " {}x{}x{}, {} ago",
+                    x,
+                    y,
+                    z,
+                    format_age(unix_time().saturating_sub(metadata.created))
+                ))
+                .color_code(ColorCode::Gray)
+                .finish(),
+            );
+        }
+        ctx.player.send_chat_message(&line);
+    }
+    if page < pages {
+        ctx.player
+            .send_worldedit_message(&format!("Use //schem list {} to see more.", page + 1));
+    }
+}
+
+/// Parses the schematic argument, sending an error if it doesn't exist.
+fn schematic_argument(ctx: &CommandExecuteContext<'_>) -> Option<SchematicRef> {
+    let name = ctx.arguments[0].unwrap_string();
+    match SchematicRef::parse(ctx.player, name) {
+        Ok(schematic) => Some(schematic),
+        Err(err) => {
+            ctx.player.send_error_message(&err);
+            None
+        }
+    }
+}
+
+/// Tells the player if the schematic doesn't exist. This is only checked after the permission
+/// checks, so that players can't find out which schematics other players have.
+fn check_schematic_exists(ctx: &CommandExecuteContext<'_>, schematic: &SchematicRef) -> bool {
+    if !schematic.exists() {
+        ctx.player
+            .send_error_message("The specified schematic file could not be found.");
+        return false;
+    }
+    true
+}
+
+pub(super) fn execute_schem_info(ctx: CommandExecuteContext<'_>) {
+    let Some(schematic) = schematic_argument(&ctx) else {
+        return;
+    };
+    if !schematic.can_read(ctx.player) {
+        ctx.player.send_no_permission_message();
+        return;
+    }
+    if !check_schematic_exists(&ctx, &schematic) {
+        return;
+    }
+    let metadata = match schematic.metadata_or_default() {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            error!("There was an error reading a schematic: {:?}", err);
+            ctx.player
+                .send_error_message("There was an error reading the schematic.");
+            return;
+        }
+    };
+
+    let [x, y, z] = metadata.size;
+    let mut lines = vec![
+        format!("Schematic {}:", schematic.name),
+        format!("Author: {}", metadata.author_name),
+        format!("Size: {}x{}x{}", x, y, z),
+        format!(
+            "Saved: {} ago",
+            format_age(unix_time().saturating_sub(metadata.created))
+        ),
+    ];
+    if !metadata.description.is_empty() {
+        lines.push(format!("Description: {}", metadata.description));
+    }
+    if schematic.is_owned_by(ctx.player) && !metadata.shared_with.is_empty() {
+        let names: Vec<String> = metadata
+            .shared_with
+            .iter()
+            .map(|uuid| library::username(uuid.0))
+            .collect();
+        lines.push(format!("Shared with: {}", names.join(", ")));
+    }
+    for line in lines {
+        ctx.player.send_worldedit_message(&line);
+    }
+}
+
+pub(super) fn execute_schem_delete(ctx: CommandExecuteContext<'_>) {
+    let Some(schematic) = schematic_argument(&ctx) else {
+        return;
+    };
+    if !schematic.is_owned_by(ctx.player)
+        && !ctx
+            .player
+            .has_permission("worldedit.schematic.delete.others")
+    {
+        ctx.player.send_no_permission_message();
+        return;
+    }
+    if !check_schematic_exists(&ctx, &schematic) {
+        return;
+    }
+    match schematic.delete() {
+        Ok(_) => ctx
+            .player
+            .send_worldedit_message(&format!("Deleted {}.", schematic.name)),
+        Err(err) => {
+            error!("There was an error deleting a schematic: {:?}", err);
+            ctx.player
+                .send_error_message("There was an error deleting the schematic.");
+        }
+    }
+}
+
+pub(super) fn execute_schem_share(ctx: CommandExecuteContext<'_>) {
+    let Some(schematic) = schematic_argument(&ctx) else {
+        return;
+    };
+    if !schematic.is_owned_by(ctx.player) {
+        ctx.player
+            .send_error_message("You can only share your own schematics.");
+        return;
+    }
+    if !check_schematic_exists(&ctx, &schematic) {
+        return;
+    }
+    let player_name = ctx.arguments[1].unwrap_string();
+    let uuid = match library::find_player(player_name) {
+        Ok(uuid) => uuid,
+        Err(err) => {
+            ctx.player.send_error_message(&err);
+            return;
+        }
+    };
+
+    let mut metadata = match schematic.metadata_or_default() {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            error!("There was an error reading a schematic: {:?}", err);
+            ctx.player
+                .send_error_message("There was an error reading the schematic.");
+            return;
+        }
+    };
+    let message = if ctx.has_flag('r') {
+        metadata.shared_with.retain(|shared| shared.0 != uuid);
+        format!(
+            "{} is no longer shared with {}.",
+            schematic.name, player_name
+        )
+    } else {
+        if !metadata.is_shared_with(uuid) {
+            metadata.shared_with.push(HyphenatedUUID(uuid));
+        }
+        format!(
+            "Shared {} with {}. They can load it with //load {}/{}",
+            schematic.name, player_name, ctx.player.username, schematic.name
+        )
+    };
+    match schematic.save_metadata(&metadata) {
+        Ok(_) => ctx.player.send_worldedit_message(&message),
+        Err(err) => {
+            error!("There was an error sharing a schematic: {:?}", err);
+            ctx.player
+                .send_error_message("There was an error sharing the schematic.");
+        }
+    }
+}
+
+pub(super) fn execute_stack(mut ctx: CommandExecuteContext<'_>) {
     let start_time = Instant::now();
 
     let stack_amt = ctx.arguments[0].unwrap_uint();
@@ -342,6 +652,7 @@ pub(super) fn execute_stack(ctx: CommandExecuteContext<'_>) {
         BlockFacing::East | BlockFacing::West => clipboard.size_x,
         BlockFacing::Up | BlockFacing::Down => clipboard.size_y,
     };
+    let mask = ctx.edit_mask();
     let mut undo_cbs = Vec::new();
     for i in 1..stack_amt + 1 {
         let offset = (i * stack_offset) as i32;
@@ -352,15 +663,19 @@ pub(super) fn execute_stack(ctx: CommandExecuteContext<'_>) {
             block_pos,
             direction.offset_pos(pos2, offset),
         ));
-        paste_clipboard(ctx.plot, &clipboard, block_pos, ctx.has_flag('a'));
+        paste_clipboard_masked(
+            ctx.plot,
+            &clipboard,
+            block_pos,
+            ctx.has_flag('a'),
+            mask.as_ref(),
+        );
     }
     let undo = WorldEditUndo {
         clipboards: undo_cbs,
         pos: pos1,
-        plot_x: ctx.plot.x,
-        plot_z: ctx.plot.z,
     };
-    ctx.player.worldedit_undo.push(undo);
+    ctx.record_undo(undo);
 
     ctx.player.send_worldedit_message(&format!(
         "Your selection was stacked. ({:?})",
@@ -369,87 +684,161 @@ pub(super) fn execute_stack(ctx: CommandExecuteContext<'_>) {
 }
 
 pub(super) fn execute_undo(ctx: CommandExecuteContext<'_>) {
-    if ctx.player.worldedit_undo.is_empty() {
+    let Some(id) = ctx.history.last_done(ctx.player.uuid) else {
         ctx.player
             .send_error_message("There is nothing left to undo.");
         return;
+    };
+    ctx.history.toggle(ctx.plot, id);
+    ctx.player.send_worldedit_message("Undo successful.");
+}
+
+pub(super) fn execute_redo(ctx: CommandExecuteContext<'_>) {
+    let Some(id) = ctx.history.last_undone(ctx.player.uuid) else {
+        ctx.player
+            .send_error_message("There is nothing left to redo.");
+        return;
+    };
+    ctx.history.toggle(ctx.plot, id);
+    ctx.player.send_worldedit_message("Redo successful.");
+}
+
+/// How many lines are shown on one page of `//history list` and `//schem list`.
+const LIST_PAGE_SIZE: usize = 10;
+
+pub(super) fn execute_history_list(ctx: CommandExecuteContext<'_>) {
+    let page = ctx.arguments[0].unwrap_uint().max(1) as usize;
+    let entries = ctx.history.entries();
+    if entries.is_empty() {
+        ctx.player
+            .send_error_message("This plot has no WorldEdit history.");
+        return;
     }
-    let undo = ctx.player.worldedit_undo.pop().unwrap();
-    if undo.plot_x != ctx.plot.x || undo.plot_z != ctx.plot.z {
+    let pages = entries.len().div_ceil(LIST_PAGE_SIZE);
+    if page > pages {
         ctx.player
-            .send_error_message("Cannot undo outside of your current plot.");
+            .send_error_message(&format!("There are only {} pages of history.", pages));
         return;
     }
-    let redo = WorldEditUndo {
-        clipboards: undo
-            .clipboards
-            .iter()
-            .map(|clipboard| {
-                let first_pos = BlockPos {
-                    x: undo.pos.x - clipboard.offset_x,
-                    y: undo.pos.y - clipboard.offset_y,
-                    z: undo.pos.z - clipboard.offset_z,
-                };
-                let second_pos = BlockPos {
-                    x: first_pos.x + clipboard.size_x as i32 - 1,
-                    y: first_pos.y + clipboard.size_y as i32 - 1,
-                    z: first_pos.z + clipboard.size_z as i32 - 1,
-                };
-                create_clipboard(ctx.plot, undo.pos, first_pos, second_pos)
-            })
-            .collect(),
-        ..undo
-    };
-    for clipboard in &undo.clipboards {
-        paste_clipboard(ctx.plot, clipboard, undo.pos, false);
+
+    ctx.player
+        .send_worldedit_message(&format!("History of this plot (page {}/{}):", page, pages));
+    let now = unix_time();
+    for entry in entries
+        .iter()
+        .rev()
+        .skip((page - 1) * LIST_PAGE_SIZE)
+        .take(LIST_PAGE_SIZE)
+    {
+        let mut line = vec![
+            TextComponentBuilder::new(format!("#{} ", entry.id))
+                .color_code(ColorCode::Gold)
+                .finish(),
+            TextComponentBuilder::new(format!("{}: ", entry.author_name))
+                .color_code(ColorCode::Yellow)
+                .finish(),
+            TextComponentBuilder::new(entry.description.clone())
+                .color_code(ColorCode::LightPurple)
+                .finish(),
+            TextComponentBuilder::new(format!(
+                " {} ago",
+                format_age(now.saturating_sub(entry.time))
+            ))
+            .color_code(ColorCode::Gray)
+            .finish(),
+        ];
+        if entry.is_undone() {
+            line.push(
+                TextComponentBuilder::new(" (undone)".to_owned())
+                    .color_code(ColorCode::DarkGray)
+                    .finish(),
+            );
+        }
+        ctx.player.send_chat_message(&line);
+    }
+    if page < pages {
+        ctx.player
+            .send_worldedit_message(&format!("Use //history list {} for older edits.", page + 1));
     }
-    ctx.player.worldedit_redo.push(redo);
 }
 
-pub(super) fn execute_redo(ctx: CommandExecuteContext<'_>) {
-    if ctx.player.worldedit_redo.is_empty() {
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m", seconds / 60),
+        3600..86400 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+/// Parses a history entry id like `12` or `#12`.
+fn history_entry_id(ctx: &CommandExecuteContext<'_>) -> Option<u32> {
+    let arg = ctx.arguments[0].unwrap_string();
+    let id = arg.strip_prefix('#').unwrap_or(arg).parse().ok();
+    let entry = id.and_then(|id| ctx.history.get(id));
+    if entry.is_none() {
         ctx.player
-            .send_error_message("There is nothing left to redo.");
-        return;
+            .send_error_message(&format!("There is no history entry {}.", arg));
     }
-    let redo = ctx.player.worldedit_redo.pop().unwrap();
-    if redo.plot_x != ctx.plot.x || redo.plot_z != ctx.plot.z {
+    Some(entry?.id)
+}
+
+pub(super) fn execute_history_undo(ctx: CommandExecuteContext<'_>) {
+    let Some(id) = history_entry_id(&ctx) else {
+        return;
+    };
+    if ctx.history.get(id).unwrap().is_undone() {
         ctx.player
-            .send_error_message("Cannot redo outside of your current plot.");
+            .send_error_message(&format!("#{} is already undone.", id));
         return;
     }
-    let undo = WorldEditUndo {
-        clipboards: redo
-            .clipboards
-            .iter()
-            .map(|clipboard| {
-                let first_pos = BlockPos {
-                    x: redo.pos.x - clipboard.offset_x,
-                    y: redo.pos.y - clipboard.offset_y,
-                    z: redo.pos.z - clipboard.offset_z,
-                };
-                let second_pos = BlockPos {
-                    x: first_pos.x + clipboard.size_x as i32 - 1,
-                    y: first_pos.y + clipboard.size_y as i32 - 1,
-                    z: first_pos.z + clipboard.size_z as i32 - 1,
-                };
-                create_clipboard(ctx.plot, redo.pos, first_pos, second_pos)
-            })
-            .collect(),
-        ..redo
+    ctx.history.toggle(ctx.plot, id);
+    ctx.player
+        .send_worldedit_message(&format!("Undid #{}.", id));
+}
+
+pub(super) fn execute_history_redo(ctx: CommandExecuteContext<'_>) {
+    let Some(id) = history_entry_id(&ctx) else {
+        return;
     };
-    for clipboard in &redo.clipboards {
-        paste_clipboard(ctx.plot, clipboard, redo.pos, false);
+    if !ctx.history.get(id).unwrap().is_undone() {
+        ctx.player
+            .send_error_message(&format!("#{} is not undone.", id));
+        return;
     }
-    ctx.player.worldedit_undo.push(undo);
+    ctx.history.toggle(ctx.plot, id);
+    ctx.player
+        .send_worldedit_message(&format!("Redid #{}.", id));
 }
 
 pub(super) fn execute_sel(ctx: CommandExecuteContext<'_>) {
+    let selector = ctx.arguments[0].unwrap_region_selector();
     let player = ctx.player;
-    player.first_position = None;
-    player.second_position = None;
-    player.send_worldedit_message("Selection cleared.");
-    player.worldedit_send_cui("s|cuboid");
+    let changed = selector != player.worldedit_selector;
+    player.worldedit_set_selector(selector);
+    if changed {
+        player.send_worldedit_message(selector.usage());
+    } else {
+        player.send_worldedit_message("Selection cleared.");
+    }
+}
+
+pub(super) fn execute_gmask(ctx: CommandExecuteContext<'_>) {
+    let mask = ctx.arguments[0].unwrap_optional_mask().cloned();
+    let player = ctx.player;
+    if mask.is_some() {
+        player.send_worldedit_message("Global mask set.");
+    } else {
+        player.send_worldedit_message("Global mask disabled.");
+    }
+    player.worldedit_global_mask = mask;
 }
 
 pub(super) fn execute_pos1(ctx: CommandExecuteContext<'_>) {
@@ -522,30 +911,8 @@ pub(super) fn execute_shift(ctx: CommandExecuteContext<'_>) {
     let amount = ctx.arguments[0].unwrap_uint();
     let direction = ctx.arguments[1].unwrap_direction();
     let player = ctx.player;
-    let first_pos = player.first_position.unwrap();
-    let second_pos = player.second_position.unwrap();
-
-    let mut move_both_points = |x, y, z| {
-        player.worldedit_set_first_position(BlockPos::new(
-            first_pos.x + x,
-            first_pos.y + y,
-            first_pos.z + z,
-        ));
-        player.worldedit_set_second_position(BlockPos::new(
-            second_pos.x + x,
-            second_pos.y + y,
-            second_pos.z + z,
-        ));
-    };
 
-    match direction {
-        BlockFacing::Up => move_both_points(0, amount as i32, 0),
-        BlockFacing::Down => move_both_points(0, -(amount as i32), 0),
-        BlockFacing::East => move_both_points(amount as i32, 0, 0),
-        BlockFacing::West => move_both_points(-(amount as i32), 0, 0),
-        BlockFacing::South => move_both_points(0, 0, amount as i32),
-        BlockFacing::North => move_both_points(0, 0, -(amount as i32)),
-    }
+    player.worldedit_shift_selection(direction.offset_pos(BlockPos::zero(), amount as i32));
 
     player.send_worldedit_message(&format!("Region shifted {} block(s).", amount));
 }
@@ -951,7 +1318,7 @@ pub(super) fn execute_descend(ctx: CommandExecuteContext<'_>) {
     }
 }
 
-pub(super) fn execute_rstack(ctx: CommandExecuteContext<'_>) {
+pub(super) fn execute_rstack(mut ctx: CommandExecuteContext<'_>) {
     let start_time = Instant::now();
 
     let stack_amt = ctx.arguments[0].unwrap_uint();
@@ -974,12 +1341,10 @@ pub(super) fn execute_rstack(ctx: CommandExecuteContext<'_>) {
         paste_clipboard(ctx.plot, &clipboard, block_pos, !ctx.has_flag('a'));
     }
     undo_cbs.reverse();
-    let undo = WorldEditUndo {
+    ctx.record_undo(WorldEditUndo {
         clipboards: undo_cbs,
         pos: pos1,
-        plot_x: ctx.plot.x,
-        plot_z: ctx.plot.z,
-    };
+    });
 
     if ctx.has_flag('e') {
         expand_selection(
@@ -990,8 +1355,6 @@ pub(super) fn execute_rstack(ctx: CommandExecuteContext<'_>) {
     }
 
     let player = ctx.player;
-    player.worldedit_undo.push(undo);
-
     player.send_worldedit_message(&format!(
         "Your selection was stacked successfully. ({:?})",
         start_time.elapsed()
@@ -1003,10 +1366,8 @@ pub(super) fn execute_update(ctx: CommandExecuteContext<'_>) {
 
     let (first_pos, second_pos) = if ctx.has_flag('p') {
         ctx.plot.get_corners()
-    } else if let (Some(first_pos), Some(second_pos)) =
-        (ctx.player.first_position, ctx.player.second_position)
-    {
-        (first_pos, second_pos)
+    } else if let Some(region) = ctx.player.worldedit_region() {
+        region.bounds()
     } else {
         ctx.player
             .send_error_message("Your selection is incomplete.");
@@ -1040,59 +1401,71 @@ pub(super) fn execute_replace_container(ctx: CommandExecuteContext<'_>) {
             enabled: false,
             facing: HopperFacing::Down,
         },
+        ContainerType::Crafter => Block::Crafter {
+            crafting: false,
+            orientation: CrafterOrientation::NorthUp,
+            triggered: false,
+        },
     };
     let slots = to.num_slots() as u32;
 
     let operation = worldedit_start_operation(ctx.player);
-    for x in operation.x_range() {
-        for y in operation.y_range() {
-            for z in operation.z_range() {
-                let pos = BlockPos::new(x, y, z);
-                let block = ctx.plot.get_block(pos);
-
-                if !matches!(
-                    block,
-                    Block::Furnace { .. } | Block::Barrel { .. } | Block::Hopper { .. }
-                ) {
-                    continue;
-                }
-                let block_entity = ctx.plot.get_block_entity(pos);
-                if let Some(BlockEntity::Container {
-                    comparator_override,
-                    ty,
-                    ..
-                }) = block_entity
-                {
-                    if *ty != from {
-                        continue;
-                    }
-                    let ss = *comparator_override;
-
-                    let items_needed = match ss {
-                        0 => 0,
-                        15 => slots * 64,
-                        _ => ((32 * slots * ss as u32) as f32 / 7.0 - 1.0).ceil() as u32,
-                    } as usize;
-                    let mut inventory = Vec::new();
-                    for (slot, items_added) in (0..items_needed).step_by(64).enumerate() {
-                        let count = (items_needed - items_added).min(64);
-                        inventory.push(InventoryEntry {
-                            id: Item::Redstone {}.get_id(),
-                            slot: slot as i8,
-                            count: count as i8,
-                            nbt: None,
-                        });
-                    }
-
-                    let new_entity = BlockEntity::Container {
-                        comparator_override: ss,
-                        inventory,
-                        ty: to,
-                    };
-                    ctx.plot.set_block_entity(pos, new_entity);
-                    ctx.plot.set_block(pos, new_block);
+    for pos in operation.blocks() {
+        let block = ctx.plot.get_block(pos);
+
+        if !matches!(
+            block,
+            Block::Furnace { .. }
+                | Block::Barrel { .. }
+                | Block::Hopper { .. }
+                | Block::Crafter { .. }
+        ) {
+            continue;
+        }
+        let block_entity = ctx.plot.get_block_entity(pos);
+        if let Some(BlockEntity::Container {
+            comparator_override,
+            ty,
+            ..
+        }) = block_entity
+        {
+            if *ty != from {
+                continue;
+            }
+            let mut ss = *comparator_override;
+
+            // Crafters hold one item per slot and can only output up to 9
+            let stack_size = match to {
+                ContainerType::Crafter => {
+                    ss = ss.min(9);
+                    1
                 }
+                _ => 64,
+            };
+            let items_needed = match (to, ss) {
+                (ContainerType::Crafter, _) => ss as u32,
+                (_, 0) => 0,
+                (_, 15) => slots * 64,
+                _ => ((32 * slots * ss as u32) as f32 / 7.0 - 1.0).ceil() as u32,
+            } as usize;
+            let mut inventory = Vec::new();
+            for (slot, items_added) in (0..items_needed).step_by(stack_size).enumerate() {
+                let count = (items_needed - items_added).min(stack_size);
+                inventory.push(InventoryEntry {
+                    id: Item::Redstone {}.get_id(),
+                    slot: slot as i8,
+                    count: count as i8,
+                    nbt: None,
+                });
             }
+
+            let new_entity = BlockEntity::Container {
+                comparator_override: ss,
+                inventory,
+                ty: to,
+            };
+            ctx.plot.set_block_entity(pos, new_entity);
+            ctx.plot.set_block(pos, new_block);
         }
     }
 
@@ -0,0 +1,443 @@
+//! The WorldEdit history of a plot. It is saved with the plot so that edits can still be undone
+//! after relogging, and by any player who can use WorldEdit on the plot.
+
+use super::WorldEditUndo;
+use crate::config::CONFIG;
+use crate::player::Player;
+use crate::plot::data::history_path;
+use crate::plot::database::PlotGroup;
+use crate::plot::PlotWorld;
+use anyhow::{bail, Context, Result};
+use mchprs_blocks::BlockPos;
+use mchprs_save_data::history::{ClipboardData, HistoryData, HistoryEntryData};
+use mchprs_schematic::{create_clipboard, paste_clipboard, WorldEditClipboard};
+use mchprs_world::storage::PalettedBitBuffer;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::error;
+
+pub struct HistoryEntry {
+    /// Entries are numbered in the order they were made, starting at 1.
+    pub id: u32,
+    pub author: u128,
+    pub author_name: String,
+    /// The command or tool that made the edit.
+    pub description: String,
+    /// When the edit was made, in seconds since the unix epoch.
+    pub time: u64,
+    /// If the edit is undone, the order it was undone in. This decides what `//redo` redoes.
+    undone: Option<u32>,
+    /// The blocks to put back to undo the edit, or to redo it if it is undone.
+    changes: WorldEditUndo,
+}
+
+impl HistoryEntry {
+    pub fn is_undone(&self) -> bool {
+        self.undone.is_some()
+    }
+
+    fn block_count(&self) -> u64 {
+        self.changes
+            .clipboards
+            .iter()
+            .map(|cb| cb.size_x as u64 * cb.size_y as u64 * cb.size_z as u64)
+            .sum()
+    }
+
+    fn from_data(data: HistoryEntryData) -> Result<HistoryEntry> {
+        Ok(HistoryEntry {
+            id: data.id,
+            author: data.author,
+            author_name: data.author_name,
+            description: data.description,
+            time: data.time,
+            undone: data.undone,
+            changes: WorldEditUndo {
+                clipboards: data
+                    .clipboards
+                    .into_iter()
+                    .map(clipboard_from_data)
+                    .collect::<Result<_>>()?,
+                pos: data.pos,
+            },
+        })
+    }
+
+    fn to_data(&self) -> HistoryEntryData {
+        HistoryEntryData {
+            id: self.id,
+            author: self.author,
+            author_name: self.author_name.clone(),
+            description: self.description.clone(),
+            time: self.time,
+            undone: self.undone,
+            pos: self.changes.pos,
+            clipboards: self
+                .changes
+                .clipboards
+                .iter()
+                .map(clipboard_to_data)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct WorldEditHistory {
+    next_id: u32,
+    next_undo: u32,
+    entries: Vec<HistoryEntry>,
+    /// Whether the history has changed since it was loaded or saved.
+    changed: bool,
+}
+
+impl WorldEditHistory {
+    /// Loads the history of every plot in the group. Plots that were merged into the group have
+    /// their histories joined, ordered by time.
+    pub fn load(group: PlotGroup) -> WorldEditHistory {
+        let mut histories = Vec::new();
+        for (plot_x, plot_z) in group.plots() {
+            let path = history_path(plot_x, plot_z);
+            if !Path::new(&path).exists() {
+                continue;
+            }
+            match HistoryData::load_from_file(&path) {
+                Ok(data) => histories.push(data),
+                Err(err) => error!("error loading worldedit history at {}: {}", path, err),
+            }
+        }
+
+        if histories.len() <= 1 {
+            let data = histories.pop().unwrap_or_default();
+            return WorldEditHistory {
+                next_id: data.next_id,
+                next_undo: data.next_undo,
+                entries: entries_from_data(data.entries).collect(),
+                changed: false,
+            };
+        }
+
+        let next_undo = histories.iter().map(|data| data.next_undo).max().unwrap();
+        let mut entries: Vec<HistoryEntryData> = histories
+            .into_iter()
+            .flat_map(|data| data.entries)
+            .collect();
+        entries.sort_by_key(|entry| entry.time);
+        let mut history = WorldEditHistory {
+            next_id: 0,
+            next_undo,
+            entries: Vec::new(),
+            changed: true,
+        };
+        for mut entry in entries_from_data(entries) {
+            history.next_id += 1;
+            entry.id = history.next_id;
+            history.entries.push(entry);
+        }
+        history.trim();
+        history
+    }
+
+    /// Saves the history if it changed. It is saved with the first plot of the group.
+    pub fn save(&mut self, group: PlotGroup) -> Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+        let data = HistoryData {
+            next_id: self.next_id,
+            next_undo: self.next_undo,
+            entries: self.entries.iter().map(HistoryEntry::to_data).collect(),
+        };
+        let path = history_path(group.min_x, group.min_z);
+        fs::create_dir_all(Path::new(&path).parent().unwrap())?;
+        data.save_to_file(&path)?;
+        // The histories of the other plots were joined into this one when it was loaded
+        for (plot_x, plot_z) in group.plots().skip(1) {
+            let path = history_path(plot_x, plot_z);
+            if Path::new(&path).exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        self.changed = false;
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub fn get(&self, id: u32) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Adds an edit to the history. Like with `//undo` and `//redo` in a single player's history,
+    /// the player's undone edits can't be redone anymore after a new edit.
+    pub fn record(&mut self, player: &Player, description: &str, changes: WorldEditUndo) {
+        self.entries
+            .retain(|entry| entry.author != player.uuid || entry.undone.is_none());
+        self.next_id += 1;
+        self.entries.push(HistoryEntry {
+            id: self.next_id,
+            author: player.uuid,
+            author_name: player.username.clone(),
+            description: description.to_owned(),
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            undone: None,
+            changes,
+        });
+        self.trim();
+        self.changed = true;
+    }
+
+    /// The player's latest edit that isn't undone.
+    pub fn last_done(&self, author: u128) -> Option<u32> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.author == author && entry.undone.is_none())
+            .map(|entry| entry.id)
+    }
+
+    /// The player's edit that was undone last.
+    pub fn last_undone(&self, author: u128) -> Option<u32> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.author == author)
+            .filter_map(|entry| Some((entry.undone?, entry.id)))
+            .max()
+            .map(|(_, id)| id)
+    }
+
+    /// Undoes the entry if it is done, or redoes it if it is undone.
+    pub fn toggle(&mut self, plot: &mut PlotWorld, id: u32) {
+        let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) else {
+            return;
+        };
+        let changes = &entry.changes;
+        let current = WorldEditUndo {
+            clipboards: changes
+                .clipboards
+                .iter()
+                .map(|clipboard| {
+                    let first_pos = changes.pos
+                        - BlockPos::new(clipboard.offset_x, clipboard.offset_y, clipboard.offset_z);
+                    let second_pos = first_pos
+                        + BlockPos::new(
+                            clipboard.size_x as i32 - 1,
+                            clipboard.size_y as i32 - 1,
+                            clipboard.size_z as i32 - 1,
+                        );
+                    create_clipboard(plot, changes.pos, first_pos, second_pos)
+                })
+                .collect(),
+            pos: changes.pos,
+        };
+        for clipboard in &changes.clipboards {
+            paste_clipboard(plot, clipboard, changes.pos, false);
+        }
+        entry.changes = current;
+        entry.undone = match entry.undone {
+            Some(_) => None,
+            None => {
+                self.next_undo += 1;
+                Some(self.next_undo)
+            }
+        };
+        self.changed = true;
+    }
+
+    /// Removes the oldest entries until the history is within the size limits. The latest entry
+    /// is always kept, even if it is larger than the limit on its own.
+    fn trim(&mut self) {
+        let max_entries = CONFIG.worldedit_history_entries.max(1) as usize;
+        let max_blocks = CONFIG.worldedit_history_blocks.max(0) as u64;
+        let mut blocks: u64 = self.entries.iter().map(HistoryEntry::block_count).sum();
+        let mut removed = 0;
+        while self.entries.len() - removed > 1
+            && (self.entries.len() - removed > max_entries || blocks > max_blocks)
+        {
+            blocks -= self.entries[removed].block_count();
+            removed += 1;
+        }
+        self.entries.drain(..removed);
+    }
+}
+
+fn clipboard_to_data(clipboard: &WorldEditClipboard) -> ClipboardData {
+    ClipboardData {
+        offset_x: clipboard.offset_x,
+        offset_y: clipboard.offset_y,
+        offset_z: clipboard.offset_z,
+        size_x: clipboard.size_x,
+        size_y: clipboard.size_y,
+        size_z: clipboard.size_z,
+        bits_per_entry: clipboard.data.bits_per_entry(),
+        data: clipboard.data.data().to_vec(),
+        palette: clipboard.data.palette().to_vec(),
+        block_entities: clipboard.block_entities.clone(),
+    }
+}
+
+/// Loads the entries of a saved history. Entries that can't be loaded are left out, so that a
+/// corrupt entry doesn't take the rest of the history with it.
+fn entries_from_data(entries: Vec<HistoryEntryData>) -> impl Iterator<Item = HistoryEntry> {
+    entries.into_iter().filter_map(|data| {
+        let id = data.id;
+        HistoryEntry::from_data(data)
+            .inspect_err(|err| error!("error loading worldedit history entry {}: {}", id, err))
+            .ok()
+    })
+}
+
+fn clipboard_from_data(data: ClipboardData) -> Result<WorldEditClipboard> {
+    let entries = data
+        .size_x
+        .checked_mul(data.size_y)
+        .and_then(|entries| entries.checked_mul(data.size_z))
+        .and_then(|entries| usize::try_from(entries).ok())
+        .context("clipboard is too large")?;
+    let Some(buffer) =
+        PalettedBitBuffer::try_load(entries, data.bits_per_entry, data.data, data.palette, 9)
+    else {
+        bail!(
+            "clipboard data doesn't match its size of {}x{}x{}",
+            data.size_x,
+            data.size_y,
+            data.size_z
+        );
+    };
+    Ok(WorldEditClipboard {
+        offset_x: data.offset_x,
+        offset_y: data.offset_y,
+        offset_z: data.offset_z,
+        size_x: data.size_x,
+        size_y: data.size_y,
+        size_z: data.size_z,
+        data: buffer,
+        block_entities: data.block_entities,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u32, author: u128, undone: Option<u32>) -> HistoryEntry {
+        HistoryEntry {
+            id,
+            author,
+            author_name: String::new(),
+            description: String::new(),
+            time: 0,
+            undone,
+            changes: WorldEditUndo {
+                clipboards: Vec::new(),
+                pos: BlockPos::zero(),
+            },
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_order() {
+        let history = WorldEditHistory {
+            next_id: 4,
+            next_undo: 2,
+            entries: vec![
+                entry(1, 1, Some(2)),
+                entry(2, 1, Some(1)),
+                entry(3, 2, None),
+                entry(4, 1, None),
+            ],
+            changed: false,
+        };
+        assert_eq!(history.last_done(1), Some(4));
+        assert_eq!(history.last_undone(1), Some(1));
+        assert_eq!(history.last_done(2), Some(3));
+        assert_eq!(history.last_undone(2), None);
+    }
+
+    #[test]
+    fn clipboard_data_round_trip() {
+        let mut data = PalettedBitBuffer::new(8, 9);
+        for (i, block) in [0, 5, 5, 9000, 0, 12, 12, 1].into_iter().enumerate() {
+            data.set_entry(i, block);
+        }
+        let clipboard = WorldEditClipboard {
+            offset_x: 1,
+            offset_y: -2,
+            offset_z: 3,
+            size_x: 2,
+            size_y: 2,
+            size_z: 2,
+            data,
+            block_entities: Default::default(),
+        };
+        let loaded = clipboard_from_data(clipboard_to_data(&clipboard)).unwrap();
+        assert_eq!(loaded.offset_y, -2);
+        assert_eq!((loaded.size_x, loaded.size_y, loaded.size_z), (2, 2, 2));
+        for i in 0..8 {
+            assert_eq!(loaded.data.get_entry(i), clipboard.data.get_entry(i));
+        }
+    }
+
+    #[test]
+    fn corrupt_clipboard_data() {
+        let clipboard = WorldEditClipboard {
+            offset_x: 0,
+            offset_y: 0,
+            offset_z: 0,
+            size_x: 4,
+            size_y: 4,
+            size_z: 4,
+            data: PalettedBitBuffer::new(64, 9),
+            block_entities: Default::default(),
+        };
+        let valid = clipboard_to_data(&clipboard);
+        assert!(clipboard_from_data(valid.clone()).is_ok());
+
+        let mut overflowing = valid.clone();
+        overflowing.size_x = u32::MAX;
+        assert!(clipboard_from_data(overflowing).is_err());
+
+        let mut short = valid.clone();
+        short.data.pop();
+        assert!(clipboard_from_data(short).is_err());
+
+        let mut bad_bits = valid.clone();
+        bad_bits.bits_per_entry = 0;
+        assert!(clipboard_from_data(bad_bits).is_err());
+
+        let mut missing_palette = valid;
+        missing_palette.palette.clear();
+        assert!(clipboard_from_data(missing_palette).is_err());
+    }
+
+    #[test]
+    fn corrupt_entries_are_dropped() {
+        let clipboard = WorldEditClipboard {
+            offset_x: 0,
+            offset_y: 0,
+            offset_z: 0,
+            size_x: 1,
+            size_y: 1,
+            size_z: 1,
+            data: PalettedBitBuffer::new(1, 9),
+            block_entities: Default::default(),
+        };
+        let mut good = entry(1, 1, None);
+        good.changes.clipboards.push(clipboard);
+        let good = good.to_data();
+        let mut corrupt = good.clone();
+        corrupt.id = 2;
+        corrupt.clipboards[0].data.clear();
+
+        let ids: Vec<u32> = entries_from_data(vec![good, corrupt])
+            .map(|entry| entry.id)
+            .collect();
+        assert_eq!(ids, vec![1]);
+    }
+}
@@ -0,0 +1,210 @@
+//! Every player has their own folder of schematics in `./schems/`, named after their uuid. Next to
+//! each schematic is a json file with its metadata, which also lists the players it's shared with.
+
+use crate::player::Player;
+use crate::plot::database;
+use crate::utils::HyphenatedUUID;
+use anyhow::{Context, Result};
+use mchprs_schematic::load_schematic;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static SCHEMATIC_NAME_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-zA-Z0-9_.]+\.schem(atic)?$").unwrap());
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SchematicMetadata {
+    pub author: HyphenatedUUID,
+    pub author_name: String,
+    /// When the schematic was saved, in seconds since the unix epoch.
+    pub created: u64,
+    pub description: String,
+    pub size: [u32; 3],
+    #[serde(default)]
+    pub shared_with: Vec<HyphenatedUUID>,
+}
+
+impl SchematicMetadata {
+    pub fn new(player: &Player, size: [u32; 3], description: String) -> SchematicMetadata {
+        SchematicMetadata {
+            author: HyphenatedUUID(player.uuid),
+            author_name: player.username.clone(),
+            created: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            description,
+            size,
+            shared_with: Vec::new(),
+        }
+    }
+
+    pub fn is_shared_with(&self, uuid: u128) -> bool {
+        self.shared_with.iter().any(|shared| shared.0 == uuid)
+    }
+}
+
+/// The folder with the player's schematics.
+pub fn player_dir(uuid: u128) -> PathBuf {
+    PathBuf::from("./schems").join(HyphenatedUUID(uuid).to_string())
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    SCHEMATIC_NAME_REGEX.is_match(name)
+}
+
+/// A schematic in a player's folder, written as `name` for the player's own schematics or
+/// `owner/name` for another player's.
+pub struct SchematicRef {
+    pub owner: u128,
+    pub name: String,
+}
+
+impl SchematicRef {
+    pub fn parse(player: &Player, input: &str) -> Result<SchematicRef, String> {
+        let (owner, name) = match input.split_once('/') {
+            Some((owner_name, name)) => (find_player(owner_name)?, name),
+            None => (player.uuid, input),
+        };
+        if !is_valid_name(name) {
+            return Err("Filename is invalid".to_owned());
+        }
+        Ok(SchematicRef {
+            owner,
+            name: name.to_owned(),
+        })
+    }
+
+    pub fn is_owned_by(&self, player: &Player) -> bool {
+        self.owner == player.uuid
+    }
+
+    pub fn path(&self) -> PathBuf {
+        player_dir(self.owner).join(&self.name)
+    }
+
+    fn metadata_path(&self) -> PathBuf {
+        player_dir(self.owner).join(format!("{}.json", self.name))
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path().is_file()
+    }
+
+    /// Whether the player can load the schematic and see its info. Players can always read their
+    /// own schematics and the ones shared with them.
+    pub fn can_read(&self, player: &Player) -> bool {
+        self.is_owned_by(player)
+            || player.has_permission("worldedit.schematic.load.others")
+            || self
+                .metadata()
+                .is_some_and(|metadata| metadata.is_shared_with(player.uuid))
+    }
+
+    /// Returns the metadata of the schematic, if it was saved with it.
+    pub fn metadata(&self) -> Option<SchematicMetadata> {
+        let json = fs::read_to_string(self.metadata_path()).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Returns the metadata of the schematic. For schematics saved without metadata, it is made up
+    /// from the file itself.
+    pub fn metadata_or_default(&self) -> Result<SchematicMetadata> {
+        if let Some(metadata) = self.metadata() {
+            return Ok(metadata);
+        }
+        let clipboard = load_schematic(&self.path())?;
+        let created = fs::metadata(self.path())?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Ok(SchematicMetadata {
+            author: HyphenatedUUID(self.owner),
+            author_name: username(self.owner),
+            created,
+            description: String::new(),
+            size: [clipboard.size_x, clipboard.size_y, clipboard.size_z],
+            shared_with: Vec::new(),
+        })
+    }
+
+    pub fn save_metadata(&self, metadata: &SchematicMetadata) -> Result<()> {
+        let json = serde_json::to_string_pretty(metadata)?;
+        fs::write(self.metadata_path(), json).context("writing schematic metadata")
+    }
+
+    pub fn delete(&self) -> Result<()> {
+        fs::remove_file(self.path())?;
+        let metadata_path = self.metadata_path();
+        if metadata_path.exists() {
+            fs::remove_file(metadata_path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Looks up a player who has joined the server before by their name.
+pub fn find_player(name: &str) -> Result<u128, String> {
+    database::get_user_uuid(name)
+        .and_then(|uuid| uuid.parse::<HyphenatedUUID>().ok())
+        .map(|uuid| uuid.0)
+        .ok_or_else(|| format!("Player {} has never joined the server.", name))
+}
+
+/// The name of the player, or their uuid if they have never joined.
+pub fn username(uuid: u128) -> String {
+    database::get_cached_username(format!("{:032x}", uuid))
+        .unwrap_or_else(|| HyphenatedUUID(uuid).to_string())
+}
+
+/// Returns the names of the schematics in the player's folder, sorted by name.
+pub fn list_schematics(owner: u128) -> Result<Vec<String>> {
+    let dir = player_dir(owner);
+    if !Path::new(&dir).exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_file() && is_valid_name(&name) {
+            names.push(name);
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schematic_names() {
+        assert!(is_valid_name("door.schem"));
+        assert!(is_valid_name("old_door.v2.schematic"));
+        assert!(!is_valid_name("door"));
+        assert!(!is_valid_name("../door.schem"));
+        assert!(!is_valid_name("door.schem.json"));
+    }
+
+    #[test]
+    fn metadata_without_shares() {
+        let json = r#"{
+            "author": "069a79f4-44e9-4726-a5be-fca90e38aaf5",
+            "author_name": "Notch",
+            "created": 1700000000,
+            "description": "A piston door",
+            "size": [5, 4, 3]
+        }"#;
+        let metadata: SchematicMetadata = serde_json::from_str(json).unwrap();
+        assert_eq!(metadata.author.0, 0x069a79f444e94726a5befca90e38aaf5);
+        assert!(metadata.shared_with.is_empty());
+        assert!(!metadata.is_shared_with(metadata.author.0));
+    }
+}
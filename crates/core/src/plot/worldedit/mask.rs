@@ -0,0 +1,312 @@
+//! Masks decide which blocks a WorldEdit operation is allowed to change.
+//!
+//! | Syntax | Matches |
+//! |--------|---------|
+//! | `stone`, `repeater[delay=3]` | Blocks of that type, with the given properties if any |
+//! | `1234` | The exact block state id |
+//! | `#existing` | Any block that isn't air |
+//! | `#region`, `#sel` | Blocks inside the player's selection |
+//! | `!mask` | Blocks not matching `mask` |
+//! | `>mask`, `<mask` | Blocks directly above or below a block matching `mask` |
+//! | `a&b` | Blocks matching both `a` and `b` |
+//! | `a,b` | Blocks matching either `a` or `b` |
+//!
+//! `&` binds tighter than `,`, and parentheses can be used for grouping.
+
+use super::region::Region;
+use mchprs_blocks::blocks::Block;
+use mchprs_blocks::BlockPos;
+use mchprs_world::World;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum WorldEditMask {
+    /// Matches blocks with this name and all of the property values.
+    Block {
+        name: &'static str,
+        properties: Vec<(String, String)>,
+    },
+    /// Matches one exact block state.
+    BlockId(u32),
+    Existing,
+    Region(Region),
+    Not(Box<WorldEditMask>),
+    /// Matches if the block at the offset matches the inner mask.
+    Offset(Box<WorldEditMask>, BlockPos),
+    And(Vec<WorldEditMask>),
+    Or(Vec<WorldEditMask>),
+}
+
+#[derive(Debug)]
+pub enum MaskParseError {
+    UnknownBlock(String),
+    UnknownProperty(String),
+    NoSelection,
+    InvalidMask(String),
+}
+
+impl fmt::Display for MaskParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaskParseError::UnknownBlock(block) => write!(f, "unknown block: {}", block),
+            MaskParseError::UnknownProperty(property) => {
+                write!(f, "unknown property: {}", property)
+            }
+            MaskParseError::NoSelection => write!(f, "#region needs a complete selection"),
+            MaskParseError::InvalidMask(mask) => write!(f, "invalid mask: {}", mask),
+        }
+    }
+}
+
+impl WorldEditMask {
+    /// Parses a mask. `selection` is the region used by `#region`.
+    pub fn parse(input: &str, selection: Option<&Region>) -> Result<WorldEditMask, MaskParseError> {
+        let mut parser = MaskParser {
+            input,
+            pos: 0,
+            selection,
+        };
+        let mask = parser.parse_or()?;
+        if parser.pos != input.len() {
+            return Err(MaskParseError::InvalidMask(input.to_owned()));
+        }
+        Ok(mask)
+    }
+
+    pub fn matches(&self, world: &impl World, pos: BlockPos) -> bool {
+        match self {
+            WorldEditMask::Block { .. } | WorldEditMask::BlockId(_) | WorldEditMask::Existing => {
+                self.matches_block(world.get_block(pos))
+            }
+            WorldEditMask::Region(region) => region.contains(pos),
+            WorldEditMask::Not(mask) => !mask.matches(world, pos),
+            WorldEditMask::Offset(mask, offset) => mask.matches(world, pos + *offset),
+            WorldEditMask::And(masks) => masks.iter().all(|mask| mask.matches(world, pos)),
+            WorldEditMask::Or(masks) => masks.iter().any(|mask| mask.matches(world, pos)),
+        }
+    }
+
+    fn matches_block(&self, block: Block) -> bool {
+        match self {
+            WorldEditMask::Block { name, properties } => {
+                if block.get_name() != *name {
+                    return false;
+                }
+                if properties.is_empty() {
+                    return true;
+                }
+                let block_properties = block.properties();
+                properties
+                    .iter()
+                    .all(|(key, value)| block_properties.get(key.as_str()) == Some(value))
+            }
+            WorldEditMask::BlockId(id) => block.get_id() == *id,
+            WorldEditMask::Existing => block != Block::Air,
+            _ => unreachable!("not a block mask"),
+        }
+    }
+}
+
+struct MaskParser<'a> {
+    input: &'a str,
+    pos: usize,
+    selection: Option<&'a Region>,
+}
+
+impl MaskParser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn invalid(&self) -> MaskParseError {
+        MaskParseError::InvalidMask(self.input.to_owned())
+    }
+
+    fn parse_or(&mut self) -> Result<WorldEditMask, MaskParseError> {
+        let mut masks = vec![self.parse_and()?];
+        while self.eat(',') {
+            masks.push(self.parse_and()?);
+        }
+        Ok(if masks.len() == 1 {
+            masks.pop().unwrap()
+        } else {
+            WorldEditMask::Or(masks)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<WorldEditMask, MaskParseError> {
+        let mut masks = vec![self.parse_unary()?];
+        while self.eat('&') {
+            masks.push(self.parse_unary()?);
+        }
+        Ok(if masks.len() == 1 {
+            masks.pop().unwrap()
+        } else {
+            WorldEditMask::And(masks)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<WorldEditMask, MaskParseError> {
+        if self.eat('!') {
+            return Ok(WorldEditMask::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat('>') {
+            let mask = self.parse_unary()?;
+            return Ok(WorldEditMask::Offset(
+                Box::new(mask),
+                BlockPos::new(0, -1, 0),
+            ));
+        }
+        if self.eat('<') {
+            let mask = self.parse_unary()?;
+            return Ok(WorldEditMask::Offset(
+                Box::new(mask),
+                BlockPos::new(0, 1, 0),
+            ));
+        }
+        if self.eat('(') {
+            let mask = self.parse_or()?;
+            if !self.eat(')') {
+                return Err(self.invalid());
+            }
+            return Ok(mask);
+        }
+        self.parse_atom()
+    }
+
+    fn parse_atom(&mut self) -> Result<WorldEditMask, MaskParseError> {
+        let start = self.pos;
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            match c {
+                '[' => depth += 1,
+                ']' if depth > 0 => depth -= 1,
+                ',' | '&' | ')' if depth == 0 => break,
+                _ => {}
+            }
+            self.pos += c.len_utf8();
+        }
+        let atom = &self.input[start..self.pos];
+        match atom {
+            "" => Err(self.invalid()),
+            "#existing" => Ok(WorldEditMask::Existing),
+            "#region" | "#sel" | "#selection" => match self.selection {
+                Some(region) => Ok(WorldEditMask::Region(region.clone())),
+                None => Err(MaskParseError::NoSelection),
+            },
+            _ if atom.bytes().all(|b| b.is_ascii_digit()) => atom
+                .parse()
+                .map(WorldEditMask::BlockId)
+                .map_err(|_| self.invalid()),
+            _ => parse_block(atom),
+        }
+    }
+}
+
+fn parse_block(atom: &str) -> Result<WorldEditMask, MaskParseError> {
+    let (name, properties) = match atom.split_once('[') {
+        Some((name, properties)) => match properties.strip_suffix(']') {
+            Some(properties) => (name, Some(properties)),
+            None => return Err(MaskParseError::InvalidMask(atom.to_owned())),
+        },
+        None => (atom, None),
+    };
+    let full_name = if name.contains(':') {
+        name.to_owned()
+    } else {
+        format!("minecraft:{}", name)
+    };
+    let block = Block::from_name(&full_name)
+        .ok_or_else(|| MaskParseError::UnknownBlock(name.to_owned()))?;
+
+    let known_properties = block.properties();
+    let mut parsed = Vec::new();
+    for property in properties.into_iter().flat_map(|p| p.split(',')) {
+        let Some((key, value)) = property.split_once('=') else {
+            return Err(MaskParseError::InvalidMask(atom.to_owned()));
+        };
+        if !known_properties.contains_key(key) {
+            return Err(MaskParseError::UnknownProperty(property.to_owned()));
+        }
+        parsed.push((key.to_owned(), value.to_owned()));
+    }
+    Ok(WorldEditMask::Block {
+        name: block.get_name(),
+        properties: parsed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> WorldEditMask {
+        match WorldEditMask::parse(input, None) {
+            Ok(mask) => mask,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn block_properties() {
+        let mask = parse("repeater[delay=3]");
+        let mut repeater = Block::from_name("minecraft:repeater").unwrap();
+        assert!(!mask.matches_block(repeater));
+        repeater.set_properties([("delay", "3"), ("facing", "east")].into());
+        assert!(mask.matches_block(repeater));
+
+        assert!(parse("minecraft:repeater").matches_block(repeater));
+        assert!(!parse("comparator").matches_block(repeater));
+        assert!(parse(&repeater.get_id().to_string()).matches_block(repeater));
+        assert!(parse("#existing").matches_block(repeater));
+        assert!(!parse("#existing").matches_block(Block::Air));
+    }
+
+    #[test]
+    fn combinations() {
+        assert!(matches!(
+            parse("!stone,>glass&<air"),
+            WorldEditMask::Or(masks) if matches!(
+                masks.as_slice(),
+                [WorldEditMask::Not(_), WorldEditMask::And(and)] if and.len() == 2
+            )
+        ));
+        assert!(matches!(
+            parse("!(stone,glass)"),
+            WorldEditMask::Not(mask) if matches!(*mask, WorldEditMask::Or(_))
+        ));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            WorldEditMask::parse("stone_bricks_but_not_really", None),
+            Err(MaskParseError::UnknownBlock(_))
+        ));
+        assert!(matches!(
+            WorldEditMask::parse("repeater[speed=3]", None),
+            Err(MaskParseError::UnknownProperty(_))
+        ));
+        assert!(matches!(
+            WorldEditMask::parse("#region", None),
+            Err(MaskParseError::NoSelection)
+        ));
+        assert!(matches!(
+            WorldEditMask::parse("(stone", None),
+            Err(MaskParseError::InvalidMask(_))
+        ));
+        assert!(matches!(
+            WorldEditMask::parse("stone,", None),
+            Err(MaskParseError::InvalidMask(_))
+        ));
+    }
+}
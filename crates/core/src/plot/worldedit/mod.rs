@@ -1,24 +1,31 @@
 //! [Worldedit](https://github.com/EngineHub/WorldEdit) and [RedstoneTools](https://github.com/paulikauro/RedstoneTools) implementation
 
 mod execute;
+pub mod history;
+pub mod library;
+pub mod mask;
+pub mod region;
+pub mod tool;
 
 use super::commands::CommandFlags;
 use super::{Plot, PlotWorld};
 use crate::player::{PacketSender, Player, PlayerPos};
 use execute::*;
+use history::WorldEditHistory;
+use mask::WorldEditMask;
 use mchprs_blocks::block_entities::{BlockEntity, ContainerType};
 use mchprs_blocks::blocks::Block;
 use mchprs_blocks::{BlockFacing, BlockPos};
 use mchprs_network::packets::clientbound::{CCommandsNode, CDeclareCommandsNodeParser};
-use mchprs_schematic::{create_clipboard, WorldEditClipboard};
+use mchprs_schematic::{create_clipboard, paste_clipboard, WorldEditClipboard};
 use mchprs_utils::map;
 use mchprs_world::storage::PalettedBitBuffer;
 use mchprs_world::{for_each_block_mut_optimized, World};
 use rand::RngExt;
 use regex::Regex;
+use region::{Region, RegionSelector};
 use std::collections::HashMap;
 use std::fmt;
-use std::ops::RangeInclusive;
 use std::str::FromStr;
 use std::sync::LazyLock;
 
@@ -30,32 +37,52 @@ pub fn execute_command(
     command: &str,
     args: &mut Vec<&str>,
 ) -> bool {
-    let player = &mut plot.players[player_idx];
-    let command = if let Some(command) = COMMANDS.get(command) {
+    // Saved in the history to show what made an edit
+    let command_line = std::iter::once(format!("/{}", command))
+        .chain(args.iter().map(|arg| arg.to_string()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let command = match ALIASES.get(command) {
+        Some(alias) => {
+            let mut alias: Vec<&str> = alias.split(' ').collect();
+            let command = alias.remove(0);
+            args.append(&mut alias);
+            command
+        }
+        None => command,
+    };
+    // Subcommands like `//brush sphere` are stored with their parent's name
+    let subcommand = args
+        .first()
+        .and_then(|arg| COMMANDS.get(format!("{} {}", command, arg).as_str()));
+    let command = if let Some(subcommand) = subcommand {
+        args.remove(0);
+        subcommand
+    } else if let Some(command) = COMMANDS.get(command) {
         command
-    } else if let Some(command) = ALIASES.get(command) {
-        let mut alias: Vec<&str> = command.split(' ').collect();
-        let command = alias.remove(0);
-        args.append(&mut alias);
-        &COMMANDS[command]
     } else {
-        return false;
+        let prefix = format!("{} ", command);
+        let mut subcommands: Vec<&str> = COMMANDS
+            .keys()
+            .filter_map(|name| name.strip_prefix(&prefix))
+            .collect();
+        if subcommands.is_empty() {
+            return false;
+        }
+        subcommands.sort_unstable();
+        plot.players[player_idx].send_error_message(&format!(
+            "Usage: /{} <{}>",
+            command,
+            subcommands.join("|")
+        ));
+        return true;
     };
 
-    let wea = player.has_permission("plots.worldedit.bypass");
-    if !wea {
-        if let Some(owner) = plot.owner {
-            if owner != player.uuid {
-                // tried to worldedit on plot that wasn't theirs
-                player.send_no_permission_message();
-                return true;
-            }
-        } else {
-            // tried to worldedit on unclaimed plot
-            player.send_no_permission_message();
-            return true;
-        }
+    if !can_use_worldedit(plot, player_idx) {
+        plot.players[player_idx].send_no_permission_message();
+        return true;
     }
+    let player = &mut plot.players[player_idx];
 
     if !command.permission_node.is_empty() && !player.has_permission(command.permission_node) {
         player.send_no_permission_message();
@@ -63,20 +90,33 @@ pub fn execute_command(
     }
 
     if command.requires_positions {
-        let plot_x = plot.world.x;
-        let plot_z = plot.world.z;
-        if player.first_position.is_none() || player.second_position.is_none() {
+        let Some(region) = player.worldedit_region() else {
             player.send_error_message("Make a region selection first.");
             return true;
+        };
+        if region.is_cuboid() {
+            let first_pos = player.first_position.unwrap();
+            let second_pos = player.second_position.unwrap();
+            if !plot.world.in_plot_bounds(first_pos.x, first_pos.z) {
+                player.send_system_message("First position is outside plot bounds!");
+                return true;
+            }
+            if !plot.world.in_plot_bounds(second_pos.x, second_pos.z) {
+                player.send_system_message("Second position is outside plot bounds!");
+                return true;
+            }
+        } else {
+            let (min, max) = region.bounds();
+            if !plot.world.in_plot_bounds(min.x, min.z) || !plot.world.in_plot_bounds(max.x, max.z)
+            {
+                player.send_system_message("Your selection is outside plot bounds!");
+                return true;
+            }
         }
-        let first_pos = player.first_position.unwrap();
-        let second_pos = player.second_position.unwrap();
-        if !Plot::in_plot_bounds(plot_x, plot_z, first_pos.x, first_pos.z) {
-            player.send_system_message("First position is outside plot bounds!");
-            return true;
-        }
-        if !Plot::in_plot_bounds(plot_x, plot_z, second_pos.x, second_pos.z) {
-            player.send_system_message("Second position is outside plot bounds!");
+        if !region.is_cuboid() && !command.any_region {
+            player.send_error_message(
+                "This command only works on cuboid selections. Use //sel cuboid first.",
+            );
             return true;
         }
     }
@@ -89,6 +129,7 @@ pub fn execute_command(
     let flag_descs = command.flags;
 
     let mut ctx_flags = Vec::new();
+    let mut flag_arguments = Vec::new();
 
     let total_arg_count = args.len();
     let mut arg_iter = args.iter().copied().enumerate();
@@ -117,7 +158,14 @@ pub fn execute_command(
                         ));
                         return true;
                     }
-                    arg_iter.next();
+                    let (_, value) = arg_iter.next().unwrap();
+                    match Argument::parse_value(player, argument_type, value) {
+                        Ok(argument) => flag_arguments.push((flag, argument)),
+                        Err(err) => {
+                            player.send_error_message(&err.to_string());
+                            return true;
+                        }
+                    }
                     with_argument = true;
                 }
                 ctx_flags.push(flag);
@@ -129,6 +177,14 @@ pub fn execute_command(
 
     let arg_descs = command.arguments;
 
+    let text;
+    if let Some(last) = arg_descs.last()
+        && matches!(last.argument_type, ArgumentType::Text)
+        && args.len() > arg_descs.len()
+    {
+        text = args.split_off(arg_descs.len() - 1).join(" ");
+        args.push(&text);
+    }
     if args.len() > arg_descs.len() {
         player.send_error_message("Too many arguments.");
         return true;
@@ -151,13 +207,22 @@ pub fn execute_command(
     let ctx = CommandExecuteContext {
         plot: &mut plot.world,
         player: &mut plot.players[player_idx],
+        history: &mut plot.worldedit_history,
+        command_line,
         arguments,
         flags: ctx_flags,
+        flag_arguments,
     };
     (command.execute_fn)(ctx);
     true
 }
 
+/// Players can only use worldedit on their own plots, unless they can bypass it.
+fn can_use_worldedit(plot: &Plot, player_idx: usize) -> bool {
+    let player = &plot.players[player_idx];
+    player.has_permission("plots.worldedit.bypass") || plot.owner == Some(player.uuid)
+}
+
 #[derive(Debug)]
 struct ArgumentParseError {
     arg_type: ArgumentType,
@@ -194,9 +259,14 @@ enum ArgumentType {
     /// Used for diag directions in redstonetools commands
     DirectionVector,
     Mask,
+    /// A mask that can be left out to disable it.
+    OptionalMask,
     Pattern,
     String,
+    /// The rest of the command, spaces included. It can only be the last argument.
+    Text,
     ContainerType,
+    RegionSelector,
 }
 
 #[derive(Debug, Clone)]
@@ -205,9 +275,11 @@ enum Argument {
     Direction(BlockFacing),
     DirectionVector(BlockPos),
     Pattern(WorldEditPattern),
-    Mask(WorldEditPattern),
+    Mask(WorldEditMask),
+    OptionalMask(Option<WorldEditMask>),
     String(String),
     ContainerType(ContainerType),
+    RegionSelector(RegionSelector),
 }
 
 impl Argument {
@@ -239,13 +311,20 @@ impl Argument {
         }
     }
 
-    fn unwrap_mask(&self) -> &WorldEditPattern {
+    fn unwrap_mask(&self) -> &WorldEditMask {
         match self {
             Argument::Mask(val) => val,
             _ => panic!("Argument was not a Mask"),
         }
     }
 
+    fn unwrap_optional_mask(&self) -> Option<&WorldEditMask> {
+        match self {
+            Argument::OptionalMask(val) => val.as_ref(),
+            _ => panic!("Argument was not an OptionalMask"),
+        }
+    }
+
     fn unwrap_string(&self) -> &String {
         match self {
             Argument::String(val) => val,
@@ -260,6 +339,13 @@ impl Argument {
         }
     }
 
+    fn unwrap_region_selector(&self) -> RegionSelector {
+        match self {
+            Argument::RegionSelector(val) => *val,
+            _ => panic!("Argument was not a RegionSelector"),
+        }
+    }
+
     fn get_default(player: &Player, desc: &ArgumentDescription) -> ArgumentParseResult {
         if let Some(default) = &desc.default {
             return Ok(default.clone());
@@ -271,6 +357,9 @@ impl Argument {
                 Argument::parse(player, desc, Some("me"))
             }
             ArgumentType::UnsignedInteger => Ok(Argument::UnsignedInteger(1)),
+            ArgumentType::RegionSelector => Ok(Argument::RegionSelector(player.worldedit_selector)),
+            ArgumentType::OptionalMask => Ok(Argument::OptionalMask(None)),
+            ArgumentType::Text => Ok(Argument::String(String::new())),
             _ => Err(ArgumentParseError::new(
                 arg_type,
                 "argument can't be inferred",
@@ -283,11 +372,13 @@ impl Argument {
         desc: &ArgumentDescription,
         arg: Option<&str>,
     ) -> ArgumentParseResult {
-        if arg.is_none() {
-            return Argument::get_default(player, desc);
+        match arg {
+            Some(arg) => Argument::parse_value(player, desc.argument_type, arg),
+            None => Argument::get_default(player, desc),
         }
-        let arg = arg.unwrap();
-        let arg_type = desc.argument_type;
+    }
+
+    fn parse_value(player: &Player, arg_type: ArgumentType, arg: &str) -> ArgumentParseResult {
         match arg_type {
             ArgumentType::Direction => {
                 let player_facing = player.get_facing();
@@ -308,12 +399,14 @@ impl Argument {
                 Ok(pattern) => Ok(Argument::Pattern(pattern)),
                 Err(err) => Err(ArgumentParseError::new(arg_type, &err.to_string())),
             },
-            // Masks are net yet implemented, so in the meantime they can be treated as patterns
-            ArgumentType::Mask => match WorldEditPattern::from_str(arg) {
-                Ok(pattern) => Ok(Argument::Mask(pattern)),
-                Err(err) => Err(ArgumentParseError::new(arg_type, &err.to_string())),
-            },
-            ArgumentType::String => Ok(Argument::String(arg.to_owned())),
+            ArgumentType::Mask | ArgumentType::OptionalMask => {
+                match WorldEditMask::parse(arg, player.worldedit_region().as_ref()) {
+                    Ok(mask) if matches!(arg_type, ArgumentType::Mask) => Ok(Argument::Mask(mask)),
+                    Ok(mask) => Ok(Argument::OptionalMask(Some(mask))),
+                    Err(err) => Err(ArgumentParseError::new(arg_type, &err.to_string())),
+                }
+            }
+            ArgumentType::String | ArgumentType::Text => Ok(Argument::String(arg.to_owned())),
             ArgumentType::DirectionVector => {
                 let mut vec = BlockPos::new(0, 0, 0);
                 let player_facing = player.get_facing();
@@ -358,6 +451,10 @@ impl Argument {
                     "error parsing container type",
                 )),
             },
+            ArgumentType::RegionSelector => match arg.parse::<RegionSelector>() {
+                Ok(selector) => Ok(Argument::RegionSelector(selector)),
+                Err(err) => Err(ArgumentParseError::new(arg_type, &err.to_string())),
+            },
         }
     }
 }
@@ -395,10 +492,17 @@ struct FlagDescription {
 }
 
 macro_rules! flag {
+    ($name:literal, None, $desc:literal) => {
+        FlagDescription {
+            letter: $name,
+            argument_type: None,
+            description: $desc,
+        }
+    };
     ($name:literal, $type:ident, $desc:literal) => {
         FlagDescription {
             letter: $name,
-            argument_type: $type,
+            argument_type: Some(ArgumentType::$type),
             description: $desc,
         }
     };
@@ -407,20 +511,61 @@ macro_rules! flag {
 struct CommandExecuteContext<'a> {
     plot: &'a mut PlotWorld,
     player: &'a mut Player,
+    history: &'a mut WorldEditHistory,
+    /// The command as the player typed it.
+    command_line: String,
     arguments: Vec<Argument>,
     flags: Vec<char>,
+    flag_arguments: Vec<(char, Argument)>,
 }
 
 impl CommandExecuteContext<'_> {
     fn has_flag(&self, c: char) -> bool {
         self.flags.contains(&c)
     }
+
+    fn flag_argument(&self, c: char) -> Option<&Argument> {
+        self.flag_arguments
+            .iter()
+            .find(|(flag, _)| *flag == c)
+            .map(|(_, argument)| argument)
+    }
+
+    /// Returns the mask limiting which blocks the command changes, combining the `-m` flag with
+    /// the player's global mask.
+    /// Adds the blocks in the area to the plot's history before they are edited.
+    fn capture_undo(&mut self, first_pos: BlockPos, second_pos: BlockPos) {
+        let undo = capture_undo(self.plot, first_pos, second_pos);
+        self.record_undo(undo);
+    }
+
+    fn record_undo(&mut self, undo: WorldEditUndo) {
+        self.history.record(self.player, &self.command_line, undo);
+    }
+
+    fn edit_mask(&self) -> Option<WorldEditMask> {
+        let flag_mask = self.flag_argument('m').map(Argument::unwrap_mask);
+        combine_masks(flag_mask, self.player.worldedit_global_mask.as_ref())
+    }
+}
+
+fn combine_masks(
+    mask: Option<&WorldEditMask>,
+    global_mask: Option<&WorldEditMask>,
+) -> Option<WorldEditMask> {
+    match (mask, global_mask) {
+        (Some(mask), Some(global)) => Some(WorldEditMask::And(vec![mask.clone(), global.clone()])),
+        (Some(mask), None) | (None, Some(mask)) => Some(mask.clone()),
+        (None, None) => None,
+    }
 }
 
 struct WorldeditCommand {
     arguments: &'static [ArgumentDescription],
     flags: &'static [FlagDescription],
     requires_positions: bool,
+    /// Whether the command works on any shape of selection, not just cuboids.
+    any_region: bool,
     requires_clipboard: bool,
     execute_fn: fn(CommandExecuteContext<'_>),
     description: &'static str,
@@ -438,6 +583,7 @@ impl Default for WorldeditCommand {
             description: "",
             requires_clipboard: false,
             requires_positions: false,
+            any_region: false,
             permission_node: "",
             mutates_world: true,
             normal_completions: true,
@@ -505,6 +651,9 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
             ..Default::default()
         },
         "/sel" => WorldeditCommand {
+            arguments: &[
+                argument!("type", RegionSelector, "The shape to select: cuboid, poly, cyl, sphere or convex")
+            ],
             execute_fn: execute_sel,
             description: "Choose a region selector",
             mutates_world: false,
@@ -515,8 +664,12 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
                 argument!("pattern", Pattern, "The pattern of blocks to set")
             ],
             requires_positions: true,
+            any_region: true,
             execute_fn: execute_set,
             description: "Sets all the blocks in the region",
+            flags: &[
+                flag!('m', Mask, "Only change blocks matching this mask")
+            ],
             permission_node: "worldedit.region.stack",
             ..Default::default()
         },
@@ -526,6 +679,7 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
                 argument!("to", Pattern, "The pattern of blocks to replace with")
             ],
             requires_positions: true,
+            any_region: true,
             execute_fn: execute_replace,
             description: "Replace all blocks in a selection with another",
             permission_node: "worldedit.region.replace",
@@ -533,6 +687,7 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
         },
         "/copy" => WorldeditCommand {
             requires_positions: true,
+            any_region: true,
             execute_fn: execute_copy,
             description: "Copy the selection to the clipboard",
             permission_node: "worldedit.clipboard.copy",
@@ -541,6 +696,7 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
         },
         "/cut" => WorldeditCommand {
             requires_positions: true,
+            any_region: true,
             execute_fn: execute_cut,
             description: "Cut the selection to the clipboard",
             permission_node: "worldedit.clipboard.cut",
@@ -554,6 +710,7 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
                 flag!('a', None, "Skip air blocks"),
                 flag!('u', None, "Also update all affected blocks"),
                 flag!('s', None, "Select the volume of the pasted in schematic"),
+                flag!('m', Mask, "Only change blocks matching this mask"),
             ],
             permission_node: "worldedit.clipboard.paste",
             ..Default::default()
@@ -570,6 +727,34 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
             permission_node: "worldedit.history.redo",
             ..Default::default()
         },
+        "/history list" => WorldeditCommand {
+            arguments: &[
+                argument!("page", UnsignedInteger, "The page to show", 1)
+            ],
+            execute_fn: execute_history_list,
+            description: "Lists the edits made on this plot",
+            permission_node: "worldedit.history.list",
+            mutates_world: false,
+            ..Default::default()
+        },
+        "/history undo" => WorldeditCommand {
+            arguments: &[
+                argument!("entry", String, "The entry to undo, as shown in //history list")
+            ],
+            execute_fn: execute_history_undo,
+            description: "Undoes a specific edit from the plot's history",
+            permission_node: "worldedit.history.undo",
+            ..Default::default()
+        },
+        "/history redo" => WorldeditCommand {
+            arguments: &[
+                argument!("entry", String, "The entry to redo, as shown in //history list")
+            ],
+            execute_fn: execute_history_redo,
+            description: "Redoes a specific edit from the plot's history",
+            permission_node: "worldedit.history.redo",
+            ..Default::default()
+        },
         "/stack" => WorldeditCommand {
             arguments: &[
                 argument!("count", UnsignedInteger, "# of copies to stack"),
@@ -579,7 +764,8 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
             execute_fn: execute_stack,
             description: "Repeat the contents of the selection",
             flags: &[
-                flag!('a', None, "Ignore air blocks")
+                flag!('a', None, "Ignore air blocks"),
+                flag!('m', Mask, "Only change blocks matching this mask")
             ],
             permission_node: "worldedit.region.stack",
             ..Default::default()
@@ -594,7 +780,8 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
             description: "Move the contents of the selection",
             flags: &[
                 flag!('a', None, "Ignore air blocks"),
-                flag!('s', None, "Shift the selection to the target location")
+                flag!('s', None, "Shift the selection to the target location"),
+                flag!('m', Mask, "Only change blocks matching this mask")
             ],
             permission_node: "worldedit.region.move",
             ..Default::default()
@@ -604,15 +791,26 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
                 argument!("mask", Mask, "The mask of blocks to match")
             ],
             requires_positions: true,
+            any_region: true,
             execute_fn: execute_count,
             description: "Counts the number of blocks matching a mask",
             permission_node: "worldedit.analysis.count",
             mutates_world: false,
             ..Default::default()
         },
+        "/gmask" => WorldeditCommand {
+            arguments: &[
+                argument!("mask", OptionalMask, "The mask to set, or nothing to disable it")
+            ],
+            execute_fn: execute_gmask,
+            description: "Sets a mask limiting which blocks //set, //replace, //stack, //paste and //move change",
+            permission_node: "worldedit.global-mask",
+            mutates_world: false,
+            ..Default::default()
+        },
         "/load" => WorldeditCommand {
             arguments: &[
-                argument!("name", String, "The file name of the schematic to load")
+                argument!("name", String, "The file name of the schematic to load, or owner/name for another player's")
             ],
             execute_fn: execute_load,
             description: "Loads a schematic file into the clipboard",
@@ -623,7 +821,8 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
         },
         "/save" => WorldeditCommand {
             arguments: &[
-                argument!("name", String, "The file name of the schematic to save")
+                argument!("name", String, "The file name of the schematic to save"),
+                argument!("description", Text, "A description shown in //schem info")
             ],
             requires_clipboard: true,
             execute_fn: execute_save,
@@ -632,6 +831,53 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
             mutates_world: false,
             ..Default::default()
         },
+        "/schem list" => WorldeditCommand {
+            arguments: &[
+                argument!("page", UnsignedInteger, "The page to show", 1)
+            ],
+            flags: &[
+                flag!('p', String, "List this player's schematics instead of your own")
+            ],
+            execute_fn: execute_schem_list,
+            description: "Lists your saved schematics",
+            permission_node: "worldedit.schematic.list",
+            mutates_world: false,
+            ..Default::default()
+        },
+        "/schem info" => WorldeditCommand {
+            arguments: &[
+                argument!("name", String, "The schematic, or owner/name for another player's")
+            ],
+            execute_fn: execute_schem_info,
+            description: "Shows who saved a schematic, when, and its size",
+            permission_node: "worldedit.schematic.info",
+            mutates_world: false,
+            ..Default::default()
+        },
+        "/schem delete" => WorldeditCommand {
+            arguments: &[
+                argument!("name", String, "The schematic, or owner/name for another player's")
+            ],
+            execute_fn: execute_schem_delete,
+            description: "Deletes a saved schematic",
+            permission_node: "worldedit.schematic.delete",
+            mutates_world: false,
+            ..Default::default()
+        },
+        "/schem share" => WorldeditCommand {
+            arguments: &[
+                argument!("name", String, "The schematic to share"),
+                argument!("player", String, "The player to share it with")
+            ],
+            flags: &[
+                flag!('r', None, "Stop sharing the schematic with the player")
+            ],
+            execute_fn: execute_schem_share,
+            description: "Lets another player load one of your schematics",
+            permission_node: "worldedit.schematic.share",
+            mutates_world: false,
+            ..Default::default()
+        },
         "/expand" => WorldeditCommand {
             arguments: &[
                 argument!("amount", UnsignedInteger, "Amount to expand the selection by"),
@@ -662,6 +908,7 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
                 argument!("direction", Direction, "Direction to shift")
             ],
             requires_positions: true,
+            any_region: true,
             execute_fn: execute_shift,
             description: "Shift the selection area",
             permission_node: "worldedit.selection.shift",
@@ -731,6 +978,60 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
             mutates_world: false,
            ..Default::default()
         },
+        "/brush sphere" => WorldeditCommand {
+            execute_fn: execute_brush_sphere,
+            description: "Binds a brush filling spheres to the held item",
+            arguments: &[
+                argument!("pattern", Pattern, "The pattern of blocks to fill with"),
+                argument!("radius", UnsignedInteger, "The radius of the sphere", 2)
+            ],
+            flags: &[
+                flag!('m', Mask, "Only change blocks matching this mask")
+            ],
+            permission_node: "worldedit.brush.sphere",
+            mutates_world: false,
+            ..Default::default()
+        },
+        "/brush cylinder" => WorldeditCommand {
+            execute_fn: execute_brush_cylinder,
+            description: "Binds a brush filling cylinders to the held item",
+            arguments: &[
+                argument!("pattern", Pattern, "The pattern of blocks to fill with"),
+                argument!("radius", UnsignedInteger, "The radius of the cylinder", 2),
+                argument!("height", UnsignedInteger, "The height of the cylinder", 1)
+            ],
+            flags: &[
+                flag!('m', Mask, "Only change blocks matching this mask")
+            ],
+            permission_node: "worldedit.brush.cylinder",
+            mutates_world: false,
+            ..Default::default()
+        },
+        "/brush clipboard" => WorldeditCommand {
+            execute_fn: execute_brush_clipboard,
+            description: "Binds a brush pasting the clipboard on the targeted block to the held item",
+            flags: &[
+                flag!('a', None, "Skip air blocks"),
+                flag!('m', Mask, "Only change blocks matching this mask")
+            ],
+            requires_clipboard: true,
+            permission_node: "worldedit.brush.clipboard",
+            mutates_world: false,
+            ..Default::default()
+        },
+        "/tool toggle" => WorldeditCommand {
+            execute_fn: execute_tool_toggle,
+            description: "Binds a tool cycling repeater delays and comparator modes to the held item",
+            permission_node: "worldedit.tool.toggle",
+            mutates_world: false,
+            ..Default::default()
+        },
+        "/none" => WorldeditCommand {
+            execute_fn: execute_none,
+            description: "Unbinds the tool from the held item",
+            mutates_world: false,
+            ..Default::default()
+        },
         "/replacecontainer" => WorldeditCommand {
             arguments: &[
                 argument!("from", ContainerType, "The container type to replace"),
@@ -740,6 +1041,7 @@ static COMMANDS: LazyLock<HashMap<&'static str, WorldeditCommand>> = LazyLock::n
            description: "Replaces all container types in the selection",
            permission_node: "mchprs.we.replacecontainer",
            requires_positions: true,
+           any_region: true,
            ..Default::default()
         }
     }
@@ -753,6 +1055,7 @@ static ALIASES: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(||
         "/1" => "/pos1",
         "/2" => "/pos2",
         "/c" => "/copy",
+        "/schematic" => "/schem",
         "/x" => "/cut",
         "/v" => "/paste",
         "/va" => "/paste -a",
@@ -764,7 +1067,9 @@ static ALIASES: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(||
         "/h1" => "/hpos1",
         "/h2" => "/hpos2",
         "/rs" => "/rstack",
-        "/rc" => "/replacecontainer"
+        "/rc" => "/replacecontainer",
+        "gmask" => "/gmask",
+        "/br" => "/brush"
     }
 });
 
@@ -774,12 +1079,11 @@ pub struct WorldEditPatternPart {
     pub block_id: u32,
 }
 
+/// The blocks to paste back to undo an edit.
 #[derive(Clone, Debug)]
 pub struct WorldEditUndo {
     clipboards: Vec<WorldEditClipboard>,
     pos: BlockPos,
-    plot_x: i32,
-    plot_z: i32,
 }
 
 pub enum PatternParseError {
@@ -853,11 +1157,6 @@ impl FromStr for WorldEditPattern {
 }
 
 impl WorldEditPattern {
-    pub fn matches(&self, block: Block) -> bool {
-        let block_id = block.get_id();
-        self.parts.iter().any(|part| part.block_id == block_id)
-    }
-
     pub fn pick(&self) -> Block {
         let mut weight_sum = 0.0;
         for part in &self.parts {
@@ -886,25 +1185,14 @@ impl WorldEditPattern {
 
 struct WorldEditOperation {
     blocks_updated: usize,
-    x_range: RangeInclusive<i32>,
-    y_range: RangeInclusive<i32>,
-    z_range: RangeInclusive<i32>,
+    region: Region,
 }
 
 impl WorldEditOperation {
-    fn new(first_pos: BlockPos, second_pos: BlockPos) -> WorldEditOperation {
-        let start_pos = first_pos.min(second_pos);
-        let end_pos = first_pos.max(second_pos);
-
-        let x_range = start_pos.x..=end_pos.x;
-        let y_range = start_pos.y..=end_pos.y;
-        let z_range = start_pos.z..=end_pos.z;
-
+    fn new(region: Region) -> WorldEditOperation {
         WorldEditOperation {
             blocks_updated: 0,
-            x_range,
-            y_range,
-            z_range,
+            region,
         }
     }
 
@@ -916,14 +1204,12 @@ impl WorldEditOperation {
         self.blocks_updated
     }
 
-    fn x_range(&self) -> RangeInclusive<i32> {
-        self.x_range.clone()
+    fn bounds(&self) -> (BlockPos, BlockPos) {
+        self.region.bounds()
     }
-    fn y_range(&self) -> RangeInclusive<i32> {
-        self.y_range.clone()
-    }
-    fn z_range(&self) -> RangeInclusive<i32> {
-        self.z_range.clone()
+
+    fn blocks(&self) -> impl Iterator<Item = BlockPos> + use<> {
+        self.region.blocks()
     }
 }
 
@@ -966,19 +1252,14 @@ pub fn ray_trace_block(
 }
 
 fn worldedit_start_operation(player: &mut Player) -> WorldEditOperation {
-    let first_pos = player.first_position.unwrap();
-    let second_pos = player.second_position.unwrap();
-    WorldEditOperation::new(first_pos, second_pos)
+    WorldEditOperation::new(player.worldedit_region().unwrap())
 }
 
-fn clear_area(plot: &mut PlotWorld, first_pos: BlockPos, second_pos: BlockPos) {
-    let start_pos = first_pos.min(second_pos);
-    let end_pos = first_pos.max(second_pos);
-    for y in start_pos.y..=end_pos.y {
-        for z in start_pos.z..=end_pos.z {
-            for x in start_pos.x..=end_pos.x {
-                plot.set_block_raw(BlockPos::new(x, y, z), 0);
-            }
+fn clear_area(plot: &mut PlotWorld, region: &Region, mask: Option<&WorldEditMask>) {
+    let (start_pos, end_pos) = region.bounds();
+    for pos in region.blocks() {
+        if mask.is_none_or(|mask| mask.matches(plot, pos)) {
+            plot.set_block_raw(pos, 0);
         }
     }
     // Send modified chunks
@@ -994,23 +1275,79 @@ fn clear_area(plot: &mut PlotWorld, first_pos: BlockPos, second_pos: BlockPos) {
     }
 }
 
-fn capture_undo(
+/// Like [`paste_clipboard`], but only changes the blocks matching the mask.
+fn paste_clipboard_masked(
     plot: &mut PlotWorld,
-    player: &mut Player,
-    first_pos: BlockPos,
-    second_pos: BlockPos,
+    cb: &WorldEditClipboard,
+    pos: BlockPos,
+    ignore_air: bool,
+    mask: Option<&WorldEditMask>,
 ) {
+    let Some(mask) = mask else {
+        paste_clipboard(plot, cb, pos, ignore_air);
+        return;
+    };
+    let offset = pos - BlockPos::new(cb.offset_x, cb.offset_y, cb.offset_z);
+    let mut block_entities = Vec::new();
+    let mut i = 0;
+    for y in 0..cb.size_y as i32 {
+        for z in 0..cb.size_z as i32 {
+            for x in 0..cb.size_x as i32 {
+                let entry = cb.data.get_entry(i);
+                i += 1;
+                let relative = BlockPos::new(x, y, z);
+                let pos = offset + relative;
+                if ignore_air && entry == 0 || !mask.matches(plot, pos) {
+                    continue;
+                }
+                plot.set_block_raw(pos, entry);
+                if let Some(block_entity) = cb.block_entities.get(&relative) {
+                    block_entities.push((pos, block_entity.clone()));
+                }
+            }
+        }
+    }
+    // Send block changes before we send block entity data, otherwise it'll be ignored
+    plot.flush_block_changes();
+    for (pos, block_entity) in block_entities {
+        plot.set_block_entity(pos, block_entity);
+    }
+}
+
+/// Copies the blocks in the region's bounds. Blocks outside of the region are copied as air.
+fn create_region_clipboard(
+    plot: &mut PlotWorld,
+    origin: BlockPos,
+    region: &Region,
+) -> WorldEditClipboard {
+    let (start_pos, end_pos) = region.bounds();
+    let mut clipboard = create_clipboard(plot, origin, start_pos, end_pos);
+    if region.is_cuboid() {
+        return clipboard;
+    }
+    let mut i = 0;
+    for y in start_pos.y..=end_pos.y {
+        for z in start_pos.z..=end_pos.z {
+            for x in start_pos.x..=end_pos.x {
+                let pos = BlockPos::new(x, y, z);
+                if !region.contains(pos) {
+                    clipboard.data.set_entry(i, 0);
+                    clipboard.block_entities.remove(&(pos - start_pos));
+                }
+                i += 1;
+            }
+        }
+    }
+    clipboard
+}
+
+fn capture_undo(plot: &mut PlotWorld, first_pos: BlockPos, second_pos: BlockPos) -> WorldEditUndo {
     let origin = first_pos.min(second_pos);
     let cb = create_clipboard(plot, origin, first_pos, second_pos);
-    let undo = WorldEditUndo {
+    WorldEditUndo {
         clipboards: vec![cb],
         pos: origin,
-        plot_x: plot.x,
-        plot_z: plot.z,
-    };
-
-    player.worldedit_undo.push(undo);
-    player.worldedit_redo.clear();
+    }
 }
 
 fn expand_selection(player: &mut Player, amount: BlockPos, contract: bool) {
@@ -1065,6 +1402,30 @@ fn update(plot: &mut PlotWorld, first_pos: BlockPos, second_pos: BlockPos) {
     });
 }
 
+/// Returns the index of the top level literal node with this name, adding it if it doesn't exist
+/// yet. It's the parent of subcommands, which are the only way to execute it.
+fn literal_node(nodes: &mut Vec<CCommandsNode>, name: &'static str) -> usize {
+    let existing = nodes[0]
+        .children
+        .iter()
+        .map(|&idx| idx as usize)
+        .find(|&idx| nodes[idx].name == Some(name));
+    if let Some(idx) = existing {
+        return idx;
+    }
+    let idx = nodes.len();
+    nodes[0].children.push(idx as i32);
+    nodes.push(CCommandsNode {
+        flags: CommandFlags::LITERAL.bits() as i8,
+        children: Vec::new(),
+        redirect_node: None,
+        name: Some(name),
+        parser: None,
+        suggestions_type: None,
+    });
+    idx
+}
+
 pub fn add_command_completions(nodes: &mut Vec<CCommandsNode>) {
     type Parser = CDeclareCommandsNodeParser;
     for (name, command) in &*COMMANDS {
@@ -1072,8 +1433,12 @@ pub fn add_command_completions(nodes: &mut Vec<CCommandsNode>) {
             // These commands have completions manually defined in plot::commands
             continue;
         }
+        let (parent_idx, name) = match name.split_once(' ') {
+            Some((parent, subcommand)) => (literal_node(nodes, parent), subcommand),
+            None => (0, *name),
+        };
         let command_idx = nodes.len() as i32;
-        nodes[0].children.push(command_idx);
+        nodes[parent_idx].children.push(command_idx);
         nodes.push(CCommandsNode {
             flags: (CommandFlags::LITERAL | CommandFlags::EXECUTABLE).bits() as i8,
             children: Vec::new(),
@@ -1093,6 +1458,7 @@ pub fn add_command_completions(nodes: &mut Vec<CCommandsNode>) {
                 name: Some(arg.name),
                 parser: Some(match arg.argument_type {
                     ArgumentType::UnsignedInteger => Parser::Integer(0, i32::MAX),
+                    ArgumentType::Text => Parser::String(2),
                     _ => Parser::String(0),
                 }),
                 suggestions_type: None,
@@ -1123,3 +1489,39 @@ pub fn add_command_completions(nodes: &mut Vec<CCommandsNode>) {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subcommand_completions() {
+        let mut nodes = vec![CCommandsNode {
+            flags: CommandFlags::ROOT.bits() as i8,
+            children: Vec::new(),
+            redirect_node: None,
+            name: None,
+            parser: None,
+            suggestions_type: None,
+        }];
+        add_command_completions(&mut nodes);
+
+        let brush: Vec<usize> = nodes[0]
+            .children
+            .iter()
+            .map(|&idx| idx as usize)
+            .filter(|&idx| nodes[idx].name == Some("/brush"))
+            .collect();
+        assert_eq!(brush.len(), 1);
+        let mut subcommands: Vec<&str> = nodes[brush[0]]
+            .children
+            .iter()
+            .filter_map(|&idx| nodes[idx as usize].name)
+            .collect();
+        subcommands.sort_unstable();
+        assert_eq!(subcommands, ["clipboard", "cylinder", "sphere"]);
+
+        let alias = nodes.iter().find(|node| node.name == Some("/br")).unwrap();
+        assert_eq!(alias.redirect_node, Some(brush[0] as i32));
+    }
+}
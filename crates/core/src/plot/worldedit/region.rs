@@ -0,0 +1,522 @@
+//! Selection regions for the WorldEdit commands. A player picks the shape with `//sel` and then
+//! builds the selection with the first and second position, which mean something different for
+//! each shape.
+
+use mchprs_blocks::BlockPos;
+use std::fmt;
+use std::str::FromStr;
+
+/// The most vertices a convex hull selection can have. Building the hull checks every triple of
+/// vertices against every other vertex.
+pub const MAX_HULL_VERTICES: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RegionSelector {
+    /// The box between the first and second position.
+    #[default]
+    Cuboid,
+    /// A polygon on the x/z plane. The first position starts the polygon and every second
+    /// position adds a vertex. It spans the heights of all vertices.
+    Poly2d,
+    /// The first position is the center and the second position sets the radius on the x and z
+    /// axes. It spans the heights of both positions.
+    Cylinder,
+    /// The first position is the center and the second position is on the surface.
+    Sphere,
+    /// The smallest convex shape containing every vertex. The first position starts the
+    /// selection and every second position adds a vertex.
+    ConvexHull,
+}
+
+impl RegionSelector {
+    pub fn name(self) -> &'static str {
+        match self {
+            RegionSelector::Cuboid => "cuboid",
+            RegionSelector::Poly2d => "poly",
+            RegionSelector::Cylinder => "cyl",
+            RegionSelector::Sphere => "sphere",
+            RegionSelector::ConvexHull => "convex",
+        }
+    }
+
+    /// The name of the shape in the WorldEdit CUI protocol.
+    pub fn cui_name(self) -> &'static str {
+        match self {
+            RegionSelector::Cuboid => "cuboid",
+            RegionSelector::Poly2d => "polygon2d",
+            RegionSelector::Cylinder => "cylinder",
+            RegionSelector::Sphere => "ellipsoid",
+            RegionSelector::ConvexHull => "polyhedron",
+        }
+    }
+
+    /// Whether the selection is a list of vertices instead of two positions.
+    pub fn uses_vertices(self) -> bool {
+        matches!(self, RegionSelector::Poly2d | RegionSelector::ConvexHull)
+    }
+
+    pub fn usage(self) -> &'static str {
+        match self {
+            RegionSelector::Cuboid => "Cuboid: left click for point 1, right click for point 2",
+            RegionSelector::Poly2d => {
+                "2D polygon selector: left click for the first point, right click to add points"
+            }
+            RegionSelector::Cylinder => {
+                "Cylindrical selector: left click for the center, right click to set the radius"
+            }
+            RegionSelector::Sphere => {
+                "Sphere selector: left click for the center, right click to set the radius"
+            }
+            RegionSelector::ConvexHull => {
+                "Convex polyhedral selector: left click for the first vertex, right click to add vertices"
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseSelectorError;
+
+impl fmt::Display for ParseSelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected cuboid, poly, cyl, sphere or convex")
+    }
+}
+
+impl FromStr for RegionSelector {
+    type Err = ParseSelectorError;
+
+    fn from_str(s: &str) -> Result<RegionSelector, ParseSelectorError> {
+        Ok(match s {
+            "cuboid" => RegionSelector::Cuboid,
+            "poly" | "poly2d" | "polygon" => RegionSelector::Poly2d,
+            "cyl" | "cylinder" => RegionSelector::Cylinder,
+            "sphere" => RegionSelector::Sphere,
+            "convex" | "hull" | "polyhedral" => RegionSelector::ConvexHull,
+            _ => return Err(ParseSelectorError),
+        })
+    }
+}
+
+/// A face of a convex hull, containing the points where `normal · pos <= offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HalfSpace {
+    normal: [i64; 3],
+    offset: i64,
+}
+
+impl HalfSpace {
+    fn new(normal: [i64; 3], offset: i64) -> HalfSpace {
+        // Planes found from different vertices are deduplicated
+        let gcd = normal
+            .iter()
+            .chain([&offset])
+            .fold(0, |gcd, &n| num_gcd(gcd, n.abs()));
+        HalfSpace {
+            normal: normal.map(|n| n / gcd),
+            offset: offset / gcd,
+        }
+    }
+
+    fn contains(&self, pos: [i64; 3]) -> bool {
+        dot(self.normal, pos) <= self.offset
+    }
+}
+
+fn num_gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        num_gcd(b, a % b)
+    }
+}
+
+fn vec3(pos: BlockPos) -> [i64; 3] {
+    [pos.x as i64, pos.y as i64, pos.z as i64]
+}
+
+fn sub(a: [i64; 3], b: [i64; 3]) -> [i64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [i64; 3], b: [i64; 3]) -> i64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [i64; 3], b: [i64; 3]) -> [i64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[derive(Debug, Clone)]
+pub enum Region {
+    Cuboid {
+        min: BlockPos,
+        max: BlockPos,
+    },
+    Polygon {
+        points: Vec<(i32, i32)>,
+        min_y: i32,
+        max_y: i32,
+    },
+    Cylinder {
+        center: BlockPos,
+        radius_x: i32,
+        radius_z: i32,
+        min_y: i32,
+        max_y: i32,
+    },
+    Sphere {
+        center: BlockPos,
+        radius: f64,
+    },
+    ConvexHull {
+        min: BlockPos,
+        max: BlockPos,
+        faces: Vec<HalfSpace>,
+    },
+}
+
+impl Region {
+    pub fn cuboid(first: BlockPos, second: BlockPos) -> Region {
+        Region::Cuboid {
+            min: first.min(second),
+            max: first.max(second),
+        }
+    }
+
+    /// Builds the region of a selection. Returns `None` if the selection is incomplete.
+    pub fn new(
+        selector: RegionSelector,
+        first: Option<BlockPos>,
+        second: Option<BlockPos>,
+        vertices: &[BlockPos],
+    ) -> Option<Region> {
+        match selector {
+            RegionSelector::Cuboid => Some(Region::cuboid(first?, second?)),
+            RegionSelector::Poly2d => {
+                if vertices.len() < 3 {
+                    return None;
+                }
+                Some(Region::Polygon {
+                    points: vertices.iter().map(|pos| (pos.x, pos.z)).collect(),
+                    min_y: vertices.iter().map(|pos| pos.y).min()?,
+                    max_y: vertices.iter().map(|pos| pos.y).max()?,
+                })
+            }
+            RegionSelector::Cylinder => {
+                let (center, edge) = (first?, second?);
+                Some(Region::Cylinder {
+                    center,
+                    radius_x: (edge.x - center.x).abs(),
+                    radius_z: (edge.z - center.z).abs(),
+                    min_y: center.y.min(edge.y),
+                    max_y: center.y.max(edge.y),
+                })
+            }
+            RegionSelector::Sphere => {
+                let (center, edge) = (first?, second?);
+                let [x, y, z] = vec3(edge - center);
+                Some(Region::Sphere {
+                    center,
+                    radius: ((x * x + y * y + z * z) as f64).sqrt(),
+                })
+            }
+            RegionSelector::ConvexHull => {
+                let min = vertices.iter().copied().reduce(BlockPos::min)?;
+                let max = vertices.iter().copied().reduce(BlockPos::max)?;
+                Some(Region::ConvexHull {
+                    min,
+                    max,
+                    faces: hull_faces(vertices)?,
+                })
+            }
+        }
+    }
+
+    pub fn is_cuboid(&self) -> bool {
+        matches!(self, Region::Cuboid { .. })
+    }
+
+    /// Returns the smallest box containing the whole region.
+    pub fn bounds(&self) -> (BlockPos, BlockPos) {
+        match self {
+            Region::Cuboid { min, max } | Region::ConvexHull { min, max, .. } => (*min, *max),
+            Region::Polygon {
+                points,
+                min_y,
+                max_y,
+            } => {
+                let min_x = points.iter().map(|p| p.0).min().unwrap();
+                let max_x = points.iter().map(|p| p.0).max().unwrap();
+                let min_z = points.iter().map(|p| p.1).min().unwrap();
+                let max_z = points.iter().map(|p| p.1).max().unwrap();
+                (
+                    BlockPos::new(min_x, *min_y, min_z),
+                    BlockPos::new(max_x, *max_y, max_z),
+                )
+            }
+            Region::Cylinder {
+                center,
+                radius_x,
+                radius_z,
+                min_y,
+                max_y,
+            } => (
+                BlockPos::new(center.x - radius_x, *min_y, center.z - radius_z),
+                BlockPos::new(center.x + radius_x, *max_y, center.z + radius_z),
+            ),
+            Region::Sphere { center, radius } => {
+                let r = (radius + 0.5).floor() as i32;
+                let r = BlockPos::new(r, r, r);
+                (*center - r, *center + r)
+            }
+        }
+    }
+
+    pub fn contains(&self, pos: BlockPos) -> bool {
+        let (min, max) = self.bounds();
+        if pos.min(min) != min || pos.max(max) != max {
+            return false;
+        }
+        match self {
+            Region::Cuboid { .. } => true,
+            Region::Polygon { points, .. } => polygon_contains(points, pos.x, pos.z),
+            Region::Cylinder {
+                center,
+                radius_x,
+                radius_z,
+                ..
+            } => {
+                // Half a block is added so the edges aren't just single blocks
+                let dx = (pos.x - center.x) as f64 / (*radius_x as f64 + 0.5);
+                let dz = (pos.z - center.z) as f64 / (*radius_z as f64 + 0.5);
+                dx * dx + dz * dz <= 1.0
+            }
+            Region::Sphere { center, radius } => {
+                let [x, y, z] = vec3(pos - *center);
+                ((x * x + y * y + z * z) as f64) <= (radius + 0.5) * (radius + 0.5)
+            }
+            Region::ConvexHull { faces, .. } => faces.iter().all(|face| face.contains(vec3(pos))),
+        }
+    }
+
+    /// Iterates over every block in the region, in the same x, y, z order as a cuboid selection
+    /// was always iterated in.
+    pub fn blocks(&self) -> impl Iterator<Item = BlockPos> + use<> {
+        let region = self.clone();
+        let (min, max) = region.bounds();
+        (min.x..=max.x)
+            .flat_map(move |x| {
+                (min.y..=max.y)
+                    .flat_map(move |y| (min.z..=max.z).map(move |z| BlockPos::new(x, y, z)))
+            })
+            .filter(move |&pos| region.contains(pos))
+    }
+
+    pub fn shift(&mut self, offset: BlockPos) {
+        match self {
+            Region::Cuboid { min, max } => {
+                *min = *min + offset;
+                *max = *max + offset;
+            }
+            Region::Polygon {
+                points,
+                min_y,
+                max_y,
+            } => {
+                for point in points {
+                    *point = (point.0 + offset.x, point.1 + offset.z);
+                }
+                *min_y += offset.y;
+                *max_y += offset.y;
+            }
+            Region::Cylinder {
+                center,
+                min_y,
+                max_y,
+                ..
+            } => {
+                *center = *center + offset;
+                *min_y += offset.y;
+                *max_y += offset.y;
+            }
+            Region::Sphere { center, .. } => *center = *center + offset,
+            Region::ConvexHull { min, max, faces } => {
+                *min = *min + offset;
+                *max = *max + offset;
+                for face in faces {
+                    face.offset += dot(face.normal, vec3(offset));
+                }
+            }
+        }
+    }
+}
+
+/// Checks if the block is inside of the polygon or on one of its edges.
+fn polygon_contains(points: &[(i32, i32)], x: i32, z: i32) -> bool {
+    let mut inside = false;
+    for (i, &(x1, z1)) in points.iter().enumerate() {
+        let (x2, z2) = points[(i + 1) % points.len()];
+        let on_line = (x2 - x1) as i64 * (z - z1) as i64 == (z2 - z1) as i64 * (x - x1) as i64;
+        if on_line
+            && (x1.min(x2)..=x1.max(x2)).contains(&x)
+            && (z1.min(z2)..=z1.max(z2)).contains(&z)
+        {
+            return true;
+        }
+        if (z1 > z) != (z2 > z) {
+            let cross_x = x1 as f64 + (z - z1) as f64 * (x2 - x1) as f64 / (z2 - z1) as f64;
+            if (x as f64) < cross_x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// Finds the faces of the convex hull of the vertices. Every plane through three vertices that
+/// has all other vertices on one side is a face. If all vertices are on one plane, the hull is
+/// flat and bounded by the lines through two vertices instead. Returns `None` if the vertices are
+/// all on one line.
+fn hull_faces(vertices: &[BlockPos]) -> Option<Vec<HalfSpace>> {
+    let vertices: Vec<[i64; 3]> = vertices.iter().copied().map(vec3).collect();
+    let mut faces = Vec::new();
+    let mut add_if_face = |normal: [i64; 3], on: [i64; 3]| {
+        let offset = dot(normal, on);
+        let (mut above, mut below) = (false, false);
+        for &vertex in &vertices {
+            let side = dot(normal, vertex) - offset;
+            above |= side > 0;
+            below |= side < 0;
+        }
+        if !above {
+            faces.push(HalfSpace::new(normal, offset));
+        }
+        if !below {
+            faces.push(HalfSpace::new(normal.map(|n| -n), -offset));
+        }
+    };
+
+    let mut plane_normal = None;
+    for (i, &a) in vertices.iter().enumerate() {
+        for (j, &b) in vertices.iter().enumerate().skip(i + 1) {
+            for &c in &vertices[j + 1..] {
+                let normal = cross(sub(b, a), sub(c, a));
+                if normal != [0; 3] {
+                    plane_normal.get_or_insert(normal);
+                    add_if_face(normal, a);
+                }
+            }
+        }
+    }
+    let plane_normal = plane_normal?;
+    let flat = vertices
+        .iter()
+        .all(|&vertex| dot(plane_normal, sub(vertex, vertices[0])) == 0);
+    if flat {
+        for (i, &a) in vertices.iter().enumerate() {
+            for &b in &vertices[i + 1..] {
+                let normal = cross(sub(b, a), plane_normal);
+                if normal != [0; 3] {
+                    add_if_face(normal, a);
+                }
+            }
+        }
+    }
+
+    let mut unique = Vec::new();
+    for face in faces {
+        if !unique.contains(&face) {
+            unique.push(face);
+        }
+    }
+    Some(unique)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(region: &Region) -> usize {
+        region.blocks().count()
+    }
+
+    #[test]
+    fn polygon_triangle() {
+        let vertices = [
+            BlockPos::new(0, 0, 0),
+            BlockPos::new(4, 2, 0),
+            BlockPos::new(0, 1, 4),
+        ];
+        let region = Region::new(RegionSelector::Poly2d, None, None, &vertices).unwrap();
+        assert_eq!(
+            region.bounds(),
+            (BlockPos::new(0, 0, 0), BlockPos::new(4, 2, 4))
+        );
+        assert!(region.contains(BlockPos::new(2, 1, 2)));
+        assert!(region.contains(BlockPos::new(1, 0, 1)));
+        assert!(!region.contains(BlockPos::new(3, 0, 3)));
+        // 15 blocks per layer
+        assert_eq!(count(&region), 45);
+    }
+
+    #[test]
+    fn cylinder_and_sphere() {
+        let center = BlockPos::new(10, 5, 10);
+        let cylinder = Region::new(
+            RegionSelector::Cylinder,
+            Some(center),
+            Some(BlockPos::new(12, 6, 10)),
+            &[],
+        )
+        .unwrap();
+        assert!(cylinder.contains(BlockPos::new(12, 6, 10)));
+        assert!(!cylinder.contains(BlockPos::new(10, 6, 11)));
+        assert!(!cylinder.contains(BlockPos::new(10, 7, 10)));
+
+        let sphere = Region::new(
+            RegionSelector::Sphere,
+            Some(center),
+            Some(BlockPos::new(10, 8, 10)),
+            &[],
+        )
+        .unwrap();
+        assert!(sphere.contains(BlockPos::new(7, 5, 10)));
+        assert!(sphere.contains(BlockPos::new(11, 6, 11)));
+        assert!(!sphere.contains(BlockPos::new(13, 7, 12)));
+    }
+
+    #[test]
+    fn convex_hull() {
+        let vertices = [
+            BlockPos::new(0, 0, 0),
+            BlockPos::new(4, 0, 0),
+            BlockPos::new(0, 4, 0),
+            BlockPos::new(0, 0, 4),
+        ];
+        let mut region = Region::new(RegionSelector::ConvexHull, None, None, &vertices).unwrap();
+        assert!(region.contains(BlockPos::new(1, 1, 1)));
+        assert!(region.contains(BlockPos::new(2, 2, 0)));
+        assert!(!region.contains(BlockPos::new(2, 2, 1)));
+
+        region.shift(BlockPos::new(1, 0, 0));
+        assert!(!region.contains(BlockPos::new(0, 0, 0)));
+        assert!(region.contains(BlockPos::new(3, 2, 0)));
+
+        // A flat hull only contains the blocks on its plane
+        let flat = [
+            BlockPos::new(0, 3, 0),
+            BlockPos::new(4, 3, 0),
+            BlockPos::new(0, 3, 4),
+        ];
+        let region = Region::new(RegionSelector::ConvexHull, None, None, &flat).unwrap();
+        assert_eq!(count(&region), 15);
+
+        let line = [BlockPos::new(0, 0, 0), BlockPos::new(2, 2, 2)];
+        assert!(Region::new(RegionSelector::ConvexHull, None, None, &line).is_none());
+    }
+}
@@ -0,0 +1,264 @@
+//! Tools are bound to an item with `//brush` or `//tool` and used by right clicking with it.
+//! Brushes edit the area around the block the player is looking at, so they can be used from a
+//! distance.
+
+use super::mask::WorldEditMask;
+use super::region::Region;
+use super::{
+    can_use_worldedit, capture_undo, combine_masks, paste_clipboard_masked, WorldEditUndo,
+};
+use super::{ray_trace_block, WorldEditPattern};
+use crate::player::{PacketSender, Player};
+use crate::plot::{in_region, Plot, PlotWorld};
+use mchprs_blocks::items::Item;
+use mchprs_blocks::{BlockFace, BlockPos};
+use mchprs_redstone as redstone;
+use mchprs_schematic::WorldEditClipboard;
+use mchprs_world::World;
+use std::time::{Duration, Instant};
+
+/// The largest radius or height a brush can have.
+pub const MAX_BRUSH_SIZE: u32 = 32;
+
+/// How far away the targeted block can be.
+const MAX_TOOL_DISTANCE: f64 = 300.0;
+
+/// After right clicking a block, most items also send a use item packet. Uses within this time of
+/// a use on a block are the same click.
+const SAME_CLICK_TIME: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub enum WorldEditTool {
+    /// Fills a sphere around the target block.
+    SphereBrush {
+        pattern: WorldEditPattern,
+        radius: u32,
+        mask: Option<WorldEditMask>,
+    },
+    /// Fills a cylinder starting at the target block and going up.
+    CylinderBrush {
+        pattern: WorldEditPattern,
+        radius: u32,
+        height: u32,
+        mask: Option<WorldEditMask>,
+    },
+    /// Pastes the clipboard on top of the target block, as if the player was standing on it.
+    ClipboardBrush {
+        clipboard: WorldEditClipboard,
+        ignore_air: bool,
+        mask: Option<WorldEditMask>,
+    },
+    /// Cycles the delay of repeaters and the mode of comparators.
+    DiodeToggle,
+}
+
+impl WorldEditTool {
+    fn name(&self) -> &'static str {
+        match self {
+            WorldEditTool::SphereBrush { .. } => "Sphere brush",
+            WorldEditTool::CylinderBrush { .. } => "Cylinder brush",
+            WorldEditTool::ClipboardBrush { .. } => "Clipboard brush",
+            WorldEditTool::DiodeToggle => "Repeater and comparator toggle",
+        }
+    }
+
+    fn mask(&self) -> Option<&WorldEditMask> {
+        match self {
+            WorldEditTool::SphereBrush { mask, .. }
+            | WorldEditTool::CylinderBrush { mask, .. }
+            | WorldEditTool::ClipboardBrush { mask, .. } => mask.as_ref(),
+            WorldEditTool::DiodeToggle => None,
+        }
+    }
+}
+
+fn held_item(player: &Player) -> Option<Item> {
+    player.inventory[player.selected_slot as usize + 36]
+        .as_ref()
+        .map(|item| item.item_type)
+}
+
+/// Binds the tool to the item in the player's main hand, replacing the tool bound to it before.
+pub(super) fn bind_tool(player: &mut Player, tool: WorldEditTool) {
+    let Some(item) = held_item(player) else {
+        player.send_error_message("Hold the item you want to bind the tool to.");
+        return;
+    };
+    if item == Item::WoodenAxe {
+        player.send_error_message("The wand can't be used as a tool.");
+        return;
+    }
+    player
+        .worldedit_tools
+        .retain(|(tool_item, _)| *tool_item != item);
+    player.send_worldedit_message(&format!(
+        "{} bound to {}.",
+        tool.name(),
+        item.get_name().trim_start_matches("minecraft:")
+    ));
+    player.worldedit_tools.push((item, tool));
+}
+
+/// Removes the tool bound to the item in the player's main hand.
+pub(super) fn unbind_tool(player: &mut Player) {
+    let Some(item) = held_item(player) else {
+        player.send_error_message("Hold the item you want to unbind the tool from.");
+        return;
+    };
+    let tool_count = player.worldedit_tools.len();
+    player
+        .worldedit_tools
+        .retain(|(tool_item, _)| *tool_item != item);
+    if player.worldedit_tools.len() == tool_count {
+        player.send_error_message("There is no tool bound to this item.");
+    } else {
+        player.send_worldedit_message("Tool unbound.");
+    }
+}
+
+/// Uses the tool bound to the item in the player's main hand on `target`, or on the block the
+/// player is looking at if it's `None`. Returns false if there is no tool bound to the item.
+pub fn use_tool(plot: &mut Plot, player_idx: usize, target: Option<BlockPos>) -> bool {
+    let player = &mut plot.players[player_idx];
+    let Some(item) = held_item(player) else {
+        return false;
+    };
+    let Some(tool) = player
+        .worldedit_tools
+        .iter()
+        .find(|(tool_item, _)| *tool_item == item)
+        .map(|(_, tool)| tool.clone())
+    else {
+        return false;
+    };
+
+    let now = Instant::now();
+    if target.is_some() {
+        player.worldedit_tool_used_at = Some(now);
+    } else if player
+        .worldedit_tool_used_at
+        .is_some_and(|used_at| now - used_at < SAME_CLICK_TIME)
+    {
+        return true;
+    }
+
+    if !can_use_worldedit(plot, player_idx) {
+        plot.players[player_idx].send_no_permission_message();
+        return true;
+    }
+
+    let player = &plot.players[player_idx];
+    let target = target.or_else(|| {
+        ray_trace_block(
+            &plot.world,
+            player.pos,
+            player.pitch as f64,
+            player.yaw as f64,
+            MAX_TOOL_DISTANCE,
+        )
+    });
+    let Some(target) = target else {
+        plot.players[player_idx].send_error_message("No block in sight!");
+        return true;
+    };
+    if !plot.world.in_plot_bounds(target.x, target.z) {
+        plot.players[player_idx].send_error_message("That block is outside of the plot.");
+        return true;
+    }
+
+    plot.reset_redpiler();
+    let player = &mut plot.players[player_idx];
+    let mask = combine_masks(tool.mask(), player.worldedit_global_mask.as_ref());
+    let world = &mut plot.world;
+    let undo = match &tool {
+        WorldEditTool::SphereBrush {
+            pattern, radius, ..
+        } => {
+            let region = Region::Sphere {
+                center: target,
+                radius: *radius as f64,
+            };
+            fill_region(world, &region, pattern, mask.as_ref())
+        }
+        WorldEditTool::CylinderBrush {
+            pattern,
+            radius,
+            height,
+            ..
+        } => {
+            let region = Region::Cylinder {
+                center: target,
+                radius_x: *radius as i32,
+                radius_z: *radius as i32,
+                min_y: target.y,
+                max_y: target.y + *height.max(&1) as i32 - 1,
+            };
+            fill_region(world, &region, pattern, mask.as_ref())
+        }
+        WorldEditTool::ClipboardBrush {
+            clipboard,
+            ignore_air,
+            ..
+        } => {
+            let pos = target.offset(BlockFace::Top);
+            let first_pos =
+                pos - BlockPos::new(clipboard.offset_x, clipboard.offset_y, clipboard.offset_z);
+            let second_pos = first_pos
+                + BlockPos::new(
+                    clipboard.size_x as i32 - 1,
+                    clipboard.size_y as i32 - 1,
+                    clipboard.size_z as i32 - 1,
+                );
+            let undo = capture_undo_in_plot(world, first_pos, second_pos);
+            paste_clipboard_masked(world, clipboard, pos, *ignore_air, mask.as_ref());
+            undo
+        }
+        WorldEditTool::DiodeToggle => {
+            let block = world.get_block(target);
+            if !redstone::is_diode(block) {
+                player.send_error_message("That block is not a repeater or comparator.");
+                return true;
+            }
+            if mask.is_some_and(|mask| !mask.matches(world, target)) {
+                return true;
+            }
+            let undo = capture_undo(world, target, target);
+            redstone::on_use(block, world, target);
+            undo
+        }
+    };
+    world.flush_block_changes();
+    plot.worldedit_history.record(player, tool.name(), undo);
+    true
+}
+
+/// Captures the part of the area inside the plot for `//undo`.
+fn capture_undo_in_plot(
+    plot: &mut PlotWorld,
+    first_pos: BlockPos,
+    second_pos: BlockPos,
+) -> WorldEditUndo {
+    let (plot_min, plot_max) = plot.get_corners();
+    let min = first_pos.min(second_pos).max(plot_min);
+    let max = first_pos.max(second_pos).min(plot_max);
+    capture_undo(plot, min, max)
+}
+
+fn fill_region(
+    plot: &mut PlotWorld,
+    region: &Region,
+    pattern: &WorldEditPattern,
+    mask: Option<&WorldEditMask>,
+) -> WorldEditUndo {
+    let (min, max) = region.bounds();
+    let undo = capture_undo_in_plot(plot, min, max);
+    let (plot_min, plot_max) = plot.get_corners();
+    for pos in region.blocks() {
+        if !in_region(pos, plot_min, plot_max) || mask.is_some_and(|mask| !mask.matches(plot, pos))
+        {
+            continue;
+        }
+        plot.set_block_raw(pos, pattern.pick().get_id());
+    }
+    undo
+}
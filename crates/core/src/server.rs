@@ -1,29 +1,33 @@
 use crate::config::CONFIG;
 use crate::player::{Gamemode, PacketSender, Player};
 use crate::plot::commands::DECLARE_COMMANDS;
+use crate::plot::database::PlotGroup;
 use crate::plot::{self, database, Plot, PLOT_BLOCK_HEIGHT};
+use crate::session::{self, AuthenticatedProfile, MojangSessionVerifier, SessionVerifier};
 use crate::utils::HyphenatedUUID;
 use crate::{permissions, utils};
 use backtrace::Backtrace;
 use bus::Bus;
 use hmac::{Hmac, Mac};
+use mchprs_network::encryption::{self, ServerKey, SHARED_SECRET_LEN};
 use mchprs_network::packets::clientbound::{
-    CConfigurationPluginMessage, CDisconnectLogin, CFinishConfiguration, CGameEvent,
-    CGameEventType, CLogin, CLoginPluginRequest, CLoginSuccess, CPlayerInfoActions,
+    CConfigurationPluginMessage, CDisconnectLogin, CEncryptionRequest, CFinishConfiguration,
+    CGameEvent, CGameEventType, CLogin, CLoginPluginRequest, CLoginSuccess, CPlayerInfoActions,
     CPlayerInfoAddPlayer, CPlayerInfoUpdate, CPlayerInfoUpdatePlayer, CPong, CRegistryBiome,
     CRegistryBiomeEffects, CRegistryData, CRegistryDataCodec, CRegistryDimensionType, CResponse,
     CSetCompression, CSetContainerContent, CSetHeldItem, CSynchronizePlayerPosition,
     ClientBoundPacket, UpdateTime,
 };
 use mchprs_network::packets::serverbound::{
-    SAcknowledgeFinishConfiguration, SHandshake, SLoginAcknowledged, SLoginPluginResponse,
-    SLoginStart, SPing, SRequest, ServerBoundPacketHandler, VelocityResponseData,
+    SAcknowledgeFinishConfiguration, SEncryptionResponse, SHandshake, SLoginAcknowledged,
+    SLoginPluginResponse, SLoginStart, SPing, SRequest, ServerBoundPacketHandler,
+    VelocityResponseData,
 };
 use mchprs_network::packets::{PacketEncoderExt, PlayerProperty, SlotData, COMPRESSION_THRESHOLD};
+use mchprs_network::protocol::ProtocolVersion;
 use mchprs_network::{NetworkServer, NetworkState, PlayerPacketSender};
 use mchprs_text::TextComponent;
 use mchprs_utils::map;
-use mchprs_world::{MC_VERSION, PROTOCOL_VERSION};
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -32,6 +36,7 @@ use std::fs::{self, File};
 use std::io::Cursor;
 use std::path::Path;
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
@@ -53,12 +58,18 @@ pub enum Message {
     PlayerUpdateGamemode(u128, Gamemode),
     /// This message is sent to the server thread when a plot unloads itself.
     PlotUnload(i32, i32),
+    /// This message is sent to the server thread when plots were merged into a group. The running
+    /// plots inside of the group are unloaded so that the group can be loaded as one plot.
+    PlotsMerged(PlotGroup),
     /// This message is sent to the server thread when a player runs /whitelist add.
     WhitelistAdd(u128, String, PlayerPacketSender),
     /// This message is sent to the server thread when a player runs /whitelist remove.
     WhitelistRemove(u128, PlayerPacketSender),
     /// This message is sent to the server thread when a player runs /stop.
     Shutdown,
+    /// This message is sent to the server thread when the session of a player logging in
+    /// has been checked. It contains the network client id and the profile if the check passed.
+    SessionVerified(u32, Option<AuthenticatedProfile>),
 }
 
 /// `BroadcastMessage` gets broadcasted from the server thread to all the plot threads.
@@ -83,11 +94,14 @@ pub enum BroadcastMessage {
 }
 
 /// `PrivMessage` gets send from the server thread directly to a plot thread.
-/// This only happens when a player is getting transfered to a plot.
+/// This happens when a player is getting transfered to a plot, or when the plot has to unload
+/// because it was merged with other plots.
 #[derive(Debug)]
 pub enum PrivMessage {
     PlayerEnterPlot(Player),
     PlayerTeleportOther(Player, String),
+    /// Saves the plot and sends its players back to the server before unloading.
+    UnloadForMerge,
 }
 
 /// This is the data that gets sent in the `PlayerJoinedInfo` broadcast message.
@@ -129,12 +143,26 @@ pub struct MinecraftServer {
     plot_sender: Sender<Message>,
     online_players: FxHashMap<u128, PlayerListEntry>,
     running_plots: Vec<PlotListEntry>,
+    /// The plots that are unloading to be merged. The merged group can't be loaded until they
+    /// have saved.
+    unloading_plots: Vec<(i32, i32)>,
+    /// Players entering a plot while plots are unloading to be merged.
+    waiting_players: Vec<Player>,
     whitelist: Option<Vec<WhitelistEntry>>,
+    /// The key used to encrypt connections, present when the server is in online mode.
+    server_key: Option<ServerKey>,
+    session_verifier: Arc<dyn SessionVerifier>,
 }
 
 impl MinecraftServer {
     /// Start the server
     pub fn run() {
+        MinecraftServer::run_with_session_verifier(Arc::new(MojangSessionVerifier));
+    }
+
+    /// Start the server, checking the sessions of players with `session_verifier` when it is
+    /// in online mode.
+    pub fn run_with_session_verifier(session_verifier: Arc<dyn SessionVerifier>) {
         std::panic::set_hook(Box::new(|panic_info| {
             let backtrace = Backtrace::new();
             error!("plot {}\n{:?}", panic_info, backtrace);
@@ -143,10 +171,16 @@ impl MinecraftServer {
         info!("Starting server...");
         let start_time = Instant::now();
 
+        if let Err(err) = CONFIG.validate() {
+            error!("Invalid config: {}", err);
+            return;
+        }
+
         // Create world folders if they don't exist yet
         fs::create_dir_all("./world/players").unwrap();
         fs::create_dir_all("./world/plots").unwrap();
         fs::create_dir_all("./schems").unwrap();
+        fs::create_dir_all("./traces").unwrap();
 
         plot::database::init();
 
@@ -176,6 +210,12 @@ impl MinecraftServer {
             permissions::init(permissions_config.clone()).unwrap();
         }
 
+        if CONFIG.online_mode && CONFIG.forwarding_enabled() {
+            warn!("Online mode is ignored because players are authenticated by the proxy");
+        }
+        let server_key =
+            (CONFIG.online_mode && !CONFIG.forwarding_enabled()).then(ServerKey::generate);
+
         // Create server struct
         let mut server = MinecraftServer {
             network: NetworkServer::new(bind_addr),
@@ -184,26 +224,14 @@ impl MinecraftServer {
             plot_sender: plot_tx,
             online_players: FxHashMap::default(),
             running_plots: Vec::new(),
+            unloading_plots: Vec::new(),
+            waiting_players: Vec::new(),
             whitelist,
+            server_key,
+            session_verifier,
         };
 
-        // Load the spawn area plot on server start
-        // This plot should be always active
-        let (spawn_tx, spawn_rx) = mpsc::channel();
-        Plot::load_and_run(
-            0,
-            0,
-            server.broadcaster.add_rx(),
-            server.plot_sender.clone(),
-            spawn_rx,
-            true,
-            None,
-        );
-        server.running_plots.push(PlotListEntry {
-            plot_x: 0,
-            plot_z: 0,
-            priv_message_sender: spawn_tx,
-        });
+        server.load_spawn_plot();
 
         info!("Done! Start took {:?}", start_time.elapsed());
 
@@ -222,6 +250,26 @@ impl MinecraftServer {
         }
     }
 
+    /// Loads the spawn area plot, which should always be active.
+    fn load_spawn_plot(&mut self) {
+        let spawn = database::get_plot_group(0, 0);
+        let (spawn_tx, spawn_rx) = mpsc::channel();
+        Plot::load_and_run(
+            spawn.min_x,
+            spawn.min_z,
+            self.broadcaster.add_rx(),
+            self.plot_sender.clone(),
+            spawn_rx,
+            true,
+            None,
+        );
+        self.running_plots.push(PlotListEntry {
+            plot_x: spawn.min_x,
+            plot_z: spawn.min_z,
+            priv_message_sender: spawn_tx,
+        });
+    }
+
     /// Removes the plot entry from the `running_plots` list
     fn handle_plot_unload(&mut self, plot_x: i32, plot_z: i32) {
         let index = self
@@ -231,11 +279,52 @@ impl MinecraftServer {
         if let Some(index) = index {
             self.running_plots.remove(index);
         }
+
+        let index = self
+            .unloading_plots
+            .iter()
+            .position(|&p| p == (plot_x, plot_z));
+        if let Some(index) = index {
+            self.unloading_plots.remove(index);
+            if self.unloading_plots.is_empty() {
+                self.finish_merge();
+            }
+        }
+    }
+
+    /// Unloads the running plots inside of a newly merged group.
+    fn handle_plots_merged(&mut self, group: PlotGroup) {
+        for plot in &self.running_plots {
+            if group.contains(plot.plot_x, plot.plot_z) {
+                let _ = plot.priv_message_sender.send(PrivMessage::UnloadForMerge);
+                self.unloading_plots.push((plot.plot_x, plot.plot_z));
+            }
+        }
+        if self.unloading_plots.is_empty() {
+            self.finish_merge();
+        }
+    }
+
+    /// Called once every plot that was merged has saved and unloaded.
+    fn finish_merge(&mut self) {
+        let spawn = database::get_plot_group(0, 0);
+        let spawn_loaded = self
+            .running_plots
+            .iter()
+            .any(|p| p.plot_x == spawn.min_x && p.plot_z == spawn.min_z);
+        if !spawn_loaded {
+            self.load_spawn_plot();
+        }
+        for player in std::mem::take(&mut self.waiting_players) {
+            self.send_player_to_plot(player, false);
+        }
     }
 
     fn graceful_shutdown(&mut self) {
         info!("Commencing graceful shutdown...");
         self.broadcaster.broadcast(BroadcastMessage::Shutdown);
+        // Plots loaded after a merge would miss the shutdown message
+        self.unloading_plots.clear();
         // Wait for all plots to save and unload
         while !self.running_plots.is_empty() {
             while let Ok(message) = self.receiver.try_recv() {
@@ -255,6 +344,9 @@ impl MinecraftServer {
 
     fn send_player_to_plot(&mut self, player: Player, new_entry: bool) {
         let (plot_x, plot_z) = player.pos.plot_pos();
+        // Merged plots are run by the thread of the group's first plot
+        let group = database::get_plot_group(plot_x, plot_z);
+        let (plot_x, plot_z) = (group.min_x, group.min_z);
 
         if new_entry {
             let player_list_entry = PlayerListEntry {
@@ -269,6 +361,11 @@ impl MinecraftServer {
             self.update_player_entry(player.uuid, plot_x, plot_z);
         }
 
+        if !self.unloading_plots.is_empty() {
+            self.waiting_players.push(player);
+            return;
+        }
+
         let plot_loaded = self
             .running_plots
             .iter()
@@ -435,9 +532,7 @@ impl MinecraftServer {
         let username = login_start.name;
         clients[client_idx].username = Some(username.clone());
 
-        if let Some(velocity_config) = &CONFIG.velocity
-            && velocity_config.enabled
-        {
+        if CONFIG.velocity_enabled() {
             let message_id = rand::random();
             clients[client_idx].forwarding_message_id = Some(message_id);
             let plugin_message = CLoginPluginRequest {
@@ -450,6 +545,47 @@ impl MinecraftServer {
             return;
         }
 
+        if let Some(server_key) = &self.server_key {
+            let verify_token = rand::random();
+            clients[client_idx].verify_token = Some(verify_token);
+            let encryption_request = CEncryptionRequest {
+                server_id: String::new(),
+                public_key: server_key.public_key_der().to_vec(),
+                verify_token: verify_token.to_vec(),
+            }
+            .encode();
+            clients[client_idx].send_packet(&encryption_request);
+            return;
+        }
+
+        self.complete_player_login(client_idx);
+    }
+
+    fn disconnect_handshaking_client(&mut self, client_idx: usize, reason: &str) {
+        let client = &self.network.handshaking_clients[client_idx];
+        let disconnect = CDisconnectLogin {
+            reason: json!({ "text": reason }).to_string(),
+        }
+        .encode();
+        client.send_packet(&disconnect);
+        client.close_connection();
+    }
+
+    fn handle_session_verified(&mut self, client_id: u32, profile: Option<AuthenticatedProfile>) {
+        let clients = &mut self.network.handshaking_clients;
+        // The client may have disconnected while its session was being checked
+        let Some(client_idx) = clients.iter().position(|client| client.id() == client_id) else {
+            return;
+        };
+
+        let Some(profile) = profile else {
+            self.disconnect_handshaking_client(client_idx, "Failed to verify username!");
+            return;
+        };
+        let client = &mut clients[client_idx];
+        client.username = Some(profile.username);
+        client.uuid = Some(profile.uuid);
+        client.properties = profile.properties;
         self.complete_player_login(client_idx);
     }
 
@@ -465,7 +601,7 @@ impl MinecraftServer {
         clients[client_idx].set_compressed(true);
 
         if let Some(whitelist) = &self.whitelist {
-            // uuid will only be present if velocity is enabled in config
+            // uuid will only be present if forwarding or online mode is enabled in config
             let whitelisted = if let Some(uuid) = clients[client_idx].uuid {
                 whitelist.iter().any(|entry| entry.uuid.0 == uuid)
             } else {
@@ -526,6 +662,7 @@ impl MinecraftServer {
                     .broadcast(BroadcastMessage::PlayerLeft(uuid));
             }
             Message::PlotUnload(plot_x, plot_z) => self.handle_plot_unload(plot_x, plot_z),
+            Message::PlotsMerged(group) => self.handle_plots_merged(group),
             Message::ChatInfo(uuid, username, message) => {
                 info!("<{}> {}", username, message);
                 self.broadcaster.broadcast(BroadcastMessage::Chat(
@@ -544,6 +681,9 @@ impl MinecraftServer {
             Message::Shutdown => {
                 self.graceful_shutdown();
             }
+            Message::SessionVerified(client_id, profile) => {
+                self.handle_session_verified(client_id, profile);
+            }
             Message::PlayerTeleportOther(player, other_username) => {
                 let username_lower = other_username.to_lowercase();
                 if let Some((_, other_player)) = self
@@ -664,25 +804,44 @@ impl ServerBoundPacketHandler for MinecraftServer {
             // TODO: Handle invalid next state
             _ => return,
         };
-        if next_state == NetworkState::Login && handshake.protocol_version != PROTOCOL_VERSION {
-            warn!("A player tried to connect using the wrong version");
-            let disconnect = CDisconnectLogin {
-                reason: json!({ "text": format!("Version mismatch, I'm on {}!", MC_VERSION) })
-                    .to_string(),
+        client.protocol_version = ProtocolVersion::from_id(handshake.protocol_version);
+        if next_state == NetworkState::Login && client.protocol_version.is_none() {
+            warn!(
+                "A player tried to connect using unsupported protocol version {}",
+                handshake.protocol_version
+            );
+            let reason = ProtocolVersion::unsupported_message(handshake.protocol_version);
+            self.disconnect_handshaking_client(client_idx, &reason);
+            return;
+        }
+
+        if next_state == NetworkState::Login && CONFIG.bungeecord_enabled() {
+            match BungeeCordForwardingData::parse(&handshake.server_address) {
+                Some(forwarding_data) => {
+                    debug!("Forwarded login from {}", forwarding_data.address);
+                    client.uuid = Some(forwarding_data.uuid);
+                    client.properties = forwarding_data.properties;
+                }
+                None => {
+                    warn!("A player tried to connect without BungeeCord forwarding data");
+                    self.disconnect_handshaking_client(
+                        client_idx,
+                        "If you wish to use IP forwarding, please enable it in your BungeeCord config as well!",
+                    );
+                }
             }
-            .encode();
-            client.send_packet(&disconnect);
-            client.close_connection();
         }
     }
 
     fn handle_request(&mut self, _request: SRequest, client_idk: usize) {
         let client = &mut self.network.handshaking_clients[client_idk];
+        // Supported clients are shown their own version so the server list marks it as compatible
+        let version = client.protocol_version.unwrap_or(ProtocolVersion::LATEST);
         let response = CResponse {
             json_response: json!({
                 "version": {
-                    "name": MC_VERSION,
-                    "protocol": PROTOCOL_VERSION
+                    "name": version.to_string(),
+                    "protocol": version.id()
                 },
                 "players": {
                     "max": CONFIG.max_players,
@@ -712,6 +871,47 @@ impl ServerBoundPacketHandler for MinecraftServer {
         self.handle_player_login_start(client_idx, login_start);
     }
 
+    fn handle_encryption_response(&mut self, packet: SEncryptionResponse, client_idx: usize) {
+        let Some(server_key) = &self.server_key else {
+            error!("Received encryption response while not in online mode");
+            return;
+        };
+        let client = &mut self.network.handshaking_clients[client_idx];
+        let Some(username) = client.username.clone() else {
+            error!("Received encryption response before login start");
+            return;
+        };
+        // The token can only be used once, so another response can't re-key the connection or
+        // verify the session a second time
+        let Some(verify_token) = client.verify_token.take() else {
+            warn!("{} sent an unexpected encryption response", username);
+            self.disconnect_handshaking_client(client_idx, "Unexpected encryption response");
+            return;
+        };
+
+        let token_matches = server_key
+            .decrypt(&packet.verify_token)
+            .is_some_and(|token| token == verify_token);
+        let shared_secret = server_key
+            .decrypt(&packet.shared_secret)
+            .and_then(|secret| <[u8; SHARED_SECRET_LEN]>::try_from(secret).ok());
+        let Some(shared_secret) = shared_secret.filter(|_| token_matches) else {
+            warn!("{} sent an invalid encryption response", username);
+            self.disconnect_handshaking_client(client_idx, "Failed to verify encryption");
+            return;
+        };
+
+        client.enable_encryption(&shared_secret);
+        let server_hash = encryption::server_hash("", &shared_secret, server_key.public_key_der());
+        session::verify_in_background(
+            self.session_verifier.clone(),
+            self.plot_sender.clone(),
+            client.id(),
+            username,
+            server_hash,
+        );
+    }
+
     fn handle_login_acknowledged(
         &mut self,
         _login_acknowledged: SLoginAcknowledged,
@@ -840,6 +1040,52 @@ impl ServerBoundPacketHandler for MinecraftServer {
     }
 }
 
+/// The player info that BungeeCord legacy forwarding packs into the handshake server address,
+/// separated by null characters.
+#[derive(Debug)]
+struct BungeeCordForwardingData {
+    address: String,
+    uuid: u128,
+    properties: Vec<PlayerProperty>,
+}
+
+#[derive(Deserialize)]
+struct BungeeCordProperty {
+    name: String,
+    value: String,
+    signature: Option<String>,
+}
+
+impl BungeeCordForwardingData {
+    fn parse(server_address: &str) -> Option<BungeeCordForwardingData> {
+        let mut parts = server_address.split('\0');
+        let _host = parts.next()?;
+        let address = parts.next()?.to_owned();
+        let uuid = parts.next()?;
+        if uuid.len() != 32 {
+            return None;
+        }
+        let uuid = u128::from_str_radix(uuid, 16).ok()?;
+        let properties = match parts.next() {
+            Some(properties) => serde_json::from_str::<Vec<BungeeCordProperty>>(properties)
+                .ok()?
+                .into_iter()
+                .map(|property| PlayerProperty {
+                    name: property.name,
+                    value: property.value,
+                    signature: property.signature,
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        Some(BungeeCordForwardingData {
+            address,
+            uuid,
+            properties,
+        })
+    }
+}
+
 pub fn get_version_string() -> String {
     format!(
         "{} v{}\n{}",
@@ -848,3 +1094,34 @@ pub fn get_version_string() -> String {
         env!("GIT_HASH")
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bungeecord_forwarding_data() {
+        let data = BungeeCordForwardingData::parse(
+            "localhost\x00127.0.0.1\x00069a79f444e94726a5befca90e38aaf5\x00\
+             [{\"name\":\"textures\",\"value\":\"abc\",\"signature\":\"def\"}]",
+        )
+        .unwrap();
+        assert_eq!(data.address, "127.0.0.1");
+        assert_eq!(data.uuid, 0x069a79f444e94726a5befca90e38aaf5);
+        assert_eq!(data.properties.len(), 1);
+        assert_eq!(data.properties[0].name, "textures");
+        assert_eq!(data.properties[0].signature.as_deref(), Some("def"));
+
+        let data = BungeeCordForwardingData::parse(
+            "localhost\x00127.0.0.1\x00069a79f444e94726a5befca90e38aaf5",
+        )
+        .unwrap();
+        assert!(data.properties.is_empty());
+    }
+
+    #[test]
+    fn parse_bungeecord_forwarding_data_rejects_plain_address() {
+        assert!(BungeeCordForwardingData::parse("localhost").is_none());
+        assert!(BungeeCordForwardingData::parse("localhost\x00127.0.0.1\x00notauuid").is_none());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::server::Message;
+use crate::utils::HyphenatedUUID;
+use anyhow::{bail, Result};
+use mchprs_network::packets::PlayerProperty;
+use serde::Deserialize;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread;
+use tracing::warn;
+
+/// A player profile confirmed by the session server.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedProfile {
+    pub uuid: u128,
+    pub username: String,
+    pub properties: Vec<PlayerProperty>,
+}
+
+/// Checks that a player logging in has authenticated with the session server.
+///
+/// Online mode uses [`MojangSessionVerifier`] by default. Other implementations can be passed to
+/// [`MinecraftServer::run_with_session_verifier`](crate::server::MinecraftServer::run_with_session_verifier),
+/// for example to stand in for the session server in tests.
+pub trait SessionVerifier: Send + Sync {
+    /// Asks whether `username` has joined the server identified by `server_hash`.
+    fn has_joined(&self, username: &str, server_hash: &str) -> Result<AuthenticatedProfile>;
+}
+
+#[derive(Deserialize)]
+struct SessionProperty {
+    name: String,
+    value: String,
+    signature: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SessionProfile {
+    id: HyphenatedUUID,
+    name: String,
+    #[serde(default)]
+    properties: Vec<SessionProperty>,
+}
+
+/// The session server request for [`SessionVerifier::has_joined`]. The username comes from the
+/// client, so it is escaped like the rest of the query.
+fn has_joined_url(username: &str, server_hash: &str) -> Result<reqwest::Url> {
+    Ok(reqwest::Url::parse_with_params(
+        "https://sessionserver.mojang.com/session/minecraft/hasJoined",
+        &[("username", username), ("serverId", server_hash)],
+    )?)
+}
+
+/// Verifies sessions using Mojang's session server.
+pub struct MojangSessionVerifier;
+
+impl SessionVerifier for MojangSessionVerifier {
+    fn has_joined(&self, username: &str, server_hash: &str) -> Result<AuthenticatedProfile> {
+        let url = has_joined_url(username, server_hash)?;
+        let client = reqwest::blocking::Client::new();
+        let res = client.get(url).send()?;
+        // The session server answers with no content if the player has not joined
+        if res.status() != reqwest::StatusCode::OK {
+            bail!("session server responded with {}", res.status());
+        }
+        let profile = res.json::<SessionProfile>()?;
+        Ok(AuthenticatedProfile {
+            uuid: profile.id.0,
+            username: profile.name,
+            properties: profile
+                .properties
+                .into_iter()
+                .map(|property| PlayerProperty {
+                    name: property.name,
+                    value: property.value,
+                    signature: property.signature,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Verifies the session on a separate thread so the server thread does not block on the request.
+/// The result is sent back to the server thread as [`Message::SessionVerified`].
+pub(crate) fn verify_in_background(
+    verifier: Arc<dyn SessionVerifier>,
+    sender: Sender<Message>,
+    client_id: u32,
+    username: String,
+    server_hash: String,
+) {
+    thread::spawn(move || {
+        let profile = match verifier.has_joined(&username, &server_hash) {
+            Ok(profile) => Some(profile),
+            Err(err) => {
+                warn!("Failed to verify session of {}: {}", username, err);
+                None
+            }
+        };
+        let _ = sender.send(Message::SessionVerified(client_id, profile));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    /// Stands in for the session server, accepting a single player joining with a known hash.
+    struct LocalSessionServer {
+        username: &'static str,
+        server_hash: &'static str,
+    }
+
+    impl SessionVerifier for LocalSessionServer {
+        fn has_joined(&self, username: &str, server_hash: &str) -> Result<AuthenticatedProfile> {
+            if username != self.username || server_hash != self.server_hash {
+                bail!("{} has not joined", username);
+            }
+            Ok(AuthenticatedProfile {
+                uuid: 0x069a79f444e94726a5befca90e38aaf5,
+                username: username.to_owned(),
+                properties: Vec::new(),
+            })
+        }
+    }
+
+    fn verify(username: &str, server_hash: &str) -> Option<AuthenticatedProfile> {
+        let verifier = Arc::new(LocalSessionServer {
+            username: "Notch",
+            server_hash: "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48",
+        });
+        let (sender, receiver) = mpsc::channel();
+        verify_in_background(
+            verifier,
+            sender,
+            7,
+            username.to_owned(),
+            server_hash.to_owned(),
+        );
+        match receiver.recv().unwrap() {
+            Message::SessionVerified(7, profile) => profile,
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn joined_player_is_verified() {
+        let profile = verify("Notch", "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48").unwrap();
+        assert_eq!(profile.uuid, 0x069a79f444e94726a5befca90e38aaf5);
+        assert_eq!(profile.username, "Notch");
+    }
+
+    #[test]
+    fn wrong_server_hash_is_rejected() {
+        assert!(verify("Notch", "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1").is_none());
+    }
+
+    #[test]
+    fn has_joined_url_escapes_username() {
+        let url = has_joined_url("a&serverId=b #c", "-1234abc").unwrap();
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(
+            query,
+            vec![
+                ("username".to_owned(), "a&serverId=b #c".to_owned()),
+                ("serverId".to_owned(), "-1234abc".to_owned()),
+            ]
+        );
+        assert_eq!(url.fragment(), None);
+    }
+}
@@ -0,0 +1,193 @@
+//! Protocol encryption used when the server runs in online mode.
+//!
+//! The server sends its RSA public key to each client in an Encryption Request. The client answers
+//! with a shared secret encrypted with that key. From then on both sides encrypt the connection
+//! with AES/CFB8, using the shared secret as both the key and the IV.
+
+use aes::Aes128;
+use cfb8::cipher::inout::InOutBuf;
+use cfb8::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use rsa::pkcs8::EncodePublicKey;
+use rsa::rand_core::OsRng;
+use rsa::{Pkcs1v15Encrypt, RsaPrivateKey};
+use sha1::{Digest, Sha1};
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+
+use crate::packets::{PacketDecoderExt, PacketEncoder};
+
+/// The length of the shared secret chosen by the client.
+pub const SHARED_SECRET_LEN: usize = 16;
+
+pub(crate) type Encryptor = cfb8::Encryptor<Aes128>;
+pub(crate) type Decryptor = cfb8::Decryptor<Aes128>;
+
+/// The key pair used to exchange the shared secret with clients.
+pub struct ServerKey {
+    private_key: RsaPrivateKey,
+    public_key_der: Vec<u8>,
+}
+
+impl ServerKey {
+    /// Generates a new 1024-bit key pair, which is the size the vanilla server uses.
+    pub fn generate() -> ServerKey {
+        let private_key =
+            RsaPrivateKey::new(&mut OsRng, 1024).expect("Failed to generate server key");
+        let public_key_der = private_key
+            .to_public_key()
+            .to_public_key_der()
+            .expect("Failed to encode server public key")
+            .into_vec();
+        ServerKey {
+            private_key,
+            public_key_der,
+        }
+    }
+
+    /// The DER encoded public key that is sent to clients.
+    pub fn public_key_der(&self) -> &[u8] {
+        &self.public_key_der
+    }
+
+    /// Decrypts data a client encrypted with the public key.
+    pub fn decrypt(&self, data: &[u8]) -> Option<Vec<u8>> {
+        self.private_key.decrypt(Pkcs1v15Encrypt, data).ok()
+    }
+}
+
+/// Computes the server hash that the client and the session server use to identify a login.
+///
+/// This is the SHA-1 digest of the inputs, formatted the way Java formats a signed `BigInteger`.
+pub fn server_hash(server_id: &str, shared_secret: &[u8], public_key_der: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(server_id.as_bytes());
+    hasher.update(shared_secret);
+    hasher.update(public_key_der);
+    let mut digest: [u8; 20] = hasher.finalize().into();
+
+    let negative = digest[0] & 0x80 != 0;
+    if negative {
+        // Two's complement to get the magnitude
+        let mut carry = true;
+        for byte in digest.iter_mut().rev() {
+            *byte = !*byte;
+            if carry {
+                (*byte, carry) = byte.overflowing_add(1);
+            }
+        }
+    }
+
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    let hex = match hex.trim_start_matches('0') {
+        "" => "0",
+        trimmed => trimmed,
+    };
+    if negative {
+        format!("-{}", hex)
+    } else {
+        hex.to_owned()
+    }
+}
+
+/// Reads from the TCP stream, decrypting the data once encryption has been enabled.
+pub(crate) struct DecryptingReader {
+    pub stream: TcpStream,
+    pub decryptor: Arc<Mutex<Option<Decryptor>>>,
+}
+
+impl Read for DecryptingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.stream.read(buf)?;
+        // The client only starts encrypting after the server has answered its Encryption
+        // Response, so any data read after the decryptor was set is encrypted.
+        if let Some(decryptor) = self.decryptor.lock().unwrap().as_mut() {
+            let (blocks, _) = InOutBuf::from(&mut buf[..len]).into_chunks();
+            decryptor.decrypt_blocks_inout_mut(blocks);
+        }
+        Ok(len)
+    }
+}
+
+impl PacketDecoderExt for DecryptingReader {}
+
+/// Writes a packet to the TCP stream, encrypting it if encryption has been enabled.
+pub(crate) fn write_packet(
+    mut stream: &TcpStream,
+    encryptor: &Mutex<Option<Encryptor>>,
+    data: &PacketEncoder,
+    compressed: bool,
+) -> io::Result<()> {
+    let mut buf = Vec::new();
+    if compressed {
+        data.write_compressed(&mut buf)?;
+    } else {
+        data.write_uncompressed(&mut buf)?;
+    }
+    // The lock is held while writing so that packets reach the stream in the order they were
+    // encrypted, even when they are sent from multiple threads.
+    let mut encryptor = encryptor.lock().unwrap();
+    if let Some(encryptor) = encryptor.as_mut() {
+        let (blocks, _) = InOutBuf::from(buf.as_mut_slice()).into_chunks();
+        encryptor.encrypt_blocks_inout_mut(blocks);
+    }
+    stream.write_all(&buf)
+}
+
+pub(crate) fn create_ciphers(shared_secret: &[u8; SHARED_SECRET_LEN]) -> (Encryptor, Decryptor) {
+    let encryptor = Encryptor::new(shared_secret.into(), shared_secret.into());
+    let decryptor = Decryptor::new(shared_secret.into(), shared_secret.into());
+    (encryptor, decryptor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_hash_matches_java_digest() {
+        assert_eq!(
+            server_hash("Notch", &[], &[]),
+            "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48"
+        );
+        assert_eq!(
+            server_hash("jeb_", &[], &[]),
+            "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1"
+        );
+        assert_eq!(
+            server_hash("simon", &[], &[]),
+            "88e16a1019277b15d58faf0541e11910eb756f6"
+        );
+    }
+
+    #[test]
+    fn shared_secret_round_trip() {
+        use rsa::pkcs8::DecodePublicKey;
+        use rsa::RsaPublicKey;
+
+        let key = ServerKey::generate();
+        let public_key = RsaPublicKey::from_public_key_der(key.public_key_der()).unwrap();
+        let secret = [7; SHARED_SECRET_LEN];
+        let encrypted = public_key
+            .encrypt(&mut OsRng, Pkcs1v15Encrypt, &secret)
+            .unwrap();
+        assert_eq!(key.decrypt(&encrypted).as_deref(), Some(secret.as_slice()));
+    }
+
+    #[test]
+    fn stream_cipher_round_trip() {
+        let (mut encryptor, _) = create_ciphers(&[3; SHARED_SECRET_LEN]);
+        let (_, mut decryptor) = create_ciphers(&[3; SHARED_SECRET_LEN]);
+        let plaintext = b"hello redstone".to_vec();
+
+        // Encrypting in pieces must continue the same stream
+        let mut data = plaintext.clone();
+        let (first, second) = data.split_at_mut(5);
+        encryptor.encrypt_blocks_inout_mut(InOutBuf::from(first).into_chunks().0);
+        encryptor.encrypt_blocks_inout_mut(InOutBuf::from(second).into_chunks().0);
+        assert_ne!(data, plaintext);
+
+        decryptor.decrypt_blocks_inout_mut(InOutBuf::from(data.as_mut_slice()).into_chunks().0);
+        assert_eq!(data, plaintext);
+    }
+}
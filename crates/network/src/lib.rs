@@ -1,11 +1,15 @@
+pub mod encryption;
 mod nbt_util;
 pub mod packets;
+pub mod protocol;
 
+use encryption::{DecryptingReader, Decryptor, Encryptor, SHARED_SECRET_LEN};
 use packets::serverbound::ServerBoundPacket;
 use packets::{read_packet, PacketEncoder, PlayerProperty};
+use protocol::ProtocolVersion;
 use std::net::{Shutdown, TcpListener, TcpStream};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{mpsc, Arc};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 use tracing::warn;
 
@@ -14,6 +18,7 @@ pub use nbt_util::NBTCompound;
 #[derive(Debug)]
 pub struct PlayerPacketSender {
     stream: Option<TcpStream>,
+    encryptor: Arc<Mutex<Option<Encryptor>>>,
 }
 
 impl PlayerPacketSender {
@@ -22,13 +27,16 @@ impl PlayerPacketSender {
         if stream.is_none() {
             warn!("Creating PlayerPacketSender with dead stream")
         }
-        PlayerPacketSender { stream }
+        PlayerPacketSender {
+            stream,
+            encryptor: conn.client.encryptor.clone(),
+        }
     }
 
     pub fn send_packet(&self, data: &PacketEncoder) {
         if let Some(stream) = &self.stream {
             // Going to assume stream is compressed since it should be after login
-            let _ = data.write_compressed(stream);
+            let _ = encryption::write_packet(stream, &self.encryptor, data, true);
         }
     }
 }
@@ -49,9 +57,17 @@ pub struct HandshakingConn {
     pub uuid: Option<u128>,
     pub forwarding_message_id: Option<i32>,
     pub properties: Vec<PlayerProperty>,
+    /// The version the client connected with, if the server supports it.
+    pub protocol_version: Option<ProtocolVersion>,
+    /// The token sent in the Encryption Request when the server is in online mode.
+    pub verify_token: Option<[u8; 4]>,
 }
 
 impl HandshakingConn {
+    pub fn id(&self) -> u32 {
+        self.client.id
+    }
+
     pub fn send_packet(&self, data: &PacketEncoder) {
         self.client.send_packet(data);
     }
@@ -64,6 +80,14 @@ impl HandshakingConn {
         self.client.compressed.store(compressed, Ordering::Relaxed)
     }
 
+    /// Encrypts the connection in both directions from now on, using the shared secret the
+    /// client sent in its Encryption Response.
+    pub fn enable_encryption(&self, shared_secret: &[u8; SHARED_SECRET_LEN]) {
+        let (encryptor, decryptor) = encryption::create_ciphers(shared_secret);
+        *self.client.encryptor.lock().unwrap() = Some(encryptor);
+        *self.client.decryptor.lock().unwrap() = Some(decryptor);
+    }
+
     pub fn close_connection(&self) {
         self.client.close_connection();
     }
@@ -110,17 +134,21 @@ pub struct NetworkClient {
     stream: TcpStream,
     packets: mpsc::Receiver<Box<dyn ServerBoundPacket>>,
     compressed: Arc<AtomicBool>,
+    encryptor: Arc<Mutex<Option<Encryptor>>>,
+    decryptor: Arc<Mutex<Option<Decryptor>>>,
 }
 
 impl NetworkClient {
     fn listen(
-        mut stream: TcpStream,
+        stream: TcpStream,
         sender: mpsc::Sender<Box<dyn ServerBoundPacket>>,
         compressed: Arc<AtomicBool>,
+        decryptor: Arc<Mutex<Option<Decryptor>>>,
     ) {
+        let mut reader = DecryptingReader { stream, decryptor };
         let mut state = NetworkState::Handshaking;
         loop {
-            let packet = match read_packet(&mut stream, &compressed, &mut state) {
+            let packet = match read_packet(&mut reader, &compressed, &mut state) {
                 Ok(packet) => packet,
                 // This will cause the client to disconnect
                 Err(_) => return,
@@ -150,11 +178,8 @@ impl NetworkClient {
     pub fn send_packet(&self, data: &PacketEncoder) {
         // TODO: every call to `send_packet` with the same PacketEncoder will
         // lead to re-encoding the packet. It might be good to cache this.
-        if self.compressed.load(Ordering::Relaxed) {
-            let _ = data.write_compressed(&self.stream);
-        } else {
-            let _ = data.write_uncompressed(&self.stream);
-        }
+        let compressed = self.compressed.load(Ordering::Relaxed);
+        let _ = encryption::write_packet(&self.stream, &self.encryptor, data, compressed);
     }
 
     pub fn close_connection(&self) {
@@ -180,8 +205,15 @@ impl NetworkServer {
             let compressed = Arc::new(AtomicBool::new(false));
             let client_stream = stream.try_clone().unwrap();
             let client_compressed = compressed.clone();
+            let decryptor = Arc::new(Mutex::new(None));
+            let client_decryptor = decryptor.clone();
             thread::spawn(move || {
-                NetworkClient::listen(client_stream, packet_sender, client_compressed);
+                NetworkClient::listen(
+                    client_stream,
+                    packet_sender,
+                    client_compressed,
+                    client_decryptor,
+                );
             });
             sender
                 .send(NetworkClient {
@@ -191,6 +223,8 @@ impl NetworkServer {
                     stream,
                     packets: packet_receiver,
                     compressed,
+                    encryptor: Arc::new(Mutex::new(None)),
+                    decryptor,
                 })
                 .unwrap();
         }
@@ -215,6 +249,8 @@ impl NetworkServer {
                     uuid: None,
                     forwarding_message_id: None,
                     properties: vec![],
+                    protocol_version: None,
+                    verify_token: None,
                 }),
                 Err(mpsc::TryRecvError::Empty) => break,
                 Err(mpsc::TryRecvError::Disconnected) => {
@@ -105,6 +105,7 @@ fn read_decompressed<T: PacketDecoderExt>(
         NetworkState::Status if packet_id == 0x00 => Box::new(SRequest::decode(reader)?),
         NetworkState::Status if packet_id == 0x01 => Box::new(SPing::decode(reader)?),
         NetworkState::Login if packet_id == 0x00 => Box::new(SLoginStart::decode(reader)?),
+        NetworkState::Login if packet_id == 0x01 => Box::new(SEncryptionResponse::decode(reader)?),
         NetworkState::Login if packet_id == 0x02 => Box::new(SLoginPluginResponse::decode(reader)?),
         NetworkState::Login if packet_id == 0x03 => {
             *state = NetworkState::Configuration;
@@ -136,6 +137,7 @@ fn read_decompressed<T: PacketDecoderExt>(
             0x32 => Box::new(SUpdateSign::decode(reader)?),
             0x33 => Box::new(SSwingArm::decode(reader)?),
             0x35 => Box::new(SUseItemOn::decode(reader)?),
+            0x36 => Box::new(SUseItem::decode(reader)?),
             _ => Box::new(SUnknown),
         },
     };
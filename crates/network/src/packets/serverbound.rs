@@ -8,6 +8,7 @@ pub trait ServerBoundPacketHandler {
     fn handle_ping(&mut self, _packet: SPing, _player_idx: usize) {}
     // Login
     fn handle_login_start(&mut self, _packet: SLoginStart, _player_idx: usize) {}
+    fn handle_encryption_response(&mut self, _packet: SEncryptionResponse, _player_idx: usize) {}
     fn handle_login_plugin_response(&mut self, _packet: SLoginPluginResponse, _player_idx: usize) {}
     fn handle_login_acknowledged(&mut self, _packet: SLoginAcknowledged, _player_idx: usize) {}
     // Configuration
@@ -43,6 +44,7 @@ pub trait ServerBoundPacketHandler {
     fn handle_player_command(&mut self, _packet: SPlayerCommand, _player_idx: usize) {}
     fn handle_swing_arm(&mut self, _packet: SSwingArm, _player_idx: usize) {}
     fn handle_use_item_on(&mut self, _packet: SUseItemOn, _player_idx: usize) {}
+    fn handle_use_item(&mut self, _packet: SUseItem, _player_idx: usize) {}
     fn handle_set_held_item(&mut self, _packet: SSetHeldItem, _player_idx: usize) {}
     fn handle_set_creative_mode_slot(&mut self, _packet: SSetCreativeModeSlot, _player_idx: usize) {
     }
@@ -141,6 +143,29 @@ impl ServerBoundPacket for SLoginStart {
     }
 }
 
+#[derive(Debug)]
+pub struct SEncryptionResponse {
+    pub shared_secret: Vec<u8>,
+    pub verify_token: Vec<u8>,
+}
+
+impl ServerBoundPacket for SEncryptionResponse {
+    fn decode<T: PacketDecoderExt>(decoder: &mut T) -> DecodeResult<Self> {
+        let shared_secret_len = decoder.read_varint()?;
+        let shared_secret = decoder.read_bytes(shared_secret_len as usize)?;
+        let verify_token_len = decoder.read_varint()?;
+        let verify_token = decoder.read_bytes(verify_token_len as usize)?;
+        Ok(SEncryptionResponse {
+            shared_secret,
+            verify_token,
+        })
+    }
+
+    fn handle(self: Box<Self>, handler: &mut dyn ServerBoundPacketHandler, player_idx: usize) {
+        handler.handle_encryption_response(*self, player_idx);
+    }
+}
+
 #[derive(Debug)]
 pub struct SLoginPluginResponse {
     pub message_id: i32,
@@ -611,6 +636,25 @@ impl ServerBoundPacket for SUseItemOn {
     }
 }
 
+#[derive(Debug)]
+pub struct SUseItem {
+    pub hand: i32,
+    pub sequence: i32,
+}
+
+impl ServerBoundPacket for SUseItem {
+    fn decode<T: PacketDecoderExt>(decoder: &mut T) -> DecodeResult<Self> {
+        Ok(SUseItem {
+            hand: decoder.read_varint()?,
+            sequence: decoder.read_varint()?,
+        })
+    }
+
+    fn handle(self: Box<Self>, handler: &mut dyn ServerBoundPacketHandler, player_idx: usize) {
+        handler.handle_use_item(*self, player_idx);
+    }
+}
+
 #[derive(Debug)]
 pub struct SSetHeldItem {
     pub slot: i16,
@@ -0,0 +1,103 @@
+//! The versions of the Minecraft protocol that the server is able to speak.
+//!
+//! A protocol version covers every Minecraft release that shares the same protocol number. All
+//! supported versions currently share their packet ids and block state ids, so packets are encoded
+//! the same way for every client. A version that differs needs its ids mapped when reading packets
+//! in [`read_packet`](crate::packets::read_packet) and when writing them in
+//! [`PacketEncoder`](crate::packets::PacketEncoder).
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProtocolVersion {
+    /// Minecraft 1.20.3 and 1.20.4
+    V1_20_3,
+}
+
+impl ProtocolVersion {
+    /// Every version the server accepts, from oldest to newest.
+    pub const SUPPORTED: &'static [ProtocolVersion] = &[ProtocolVersion::V1_20_3];
+    /// The version reported to clients that the server does not support.
+    pub const LATEST: ProtocolVersion = ProtocolVersion::V1_20_3;
+
+    /// Returns the version with the protocol number sent by a client in its handshake, if the
+    /// server supports it.
+    pub fn from_id(id: i32) -> Option<ProtocolVersion> {
+        ProtocolVersion::SUPPORTED
+            .iter()
+            .copied()
+            .find(|version| version.id() == id)
+    }
+
+    /// The protocol number of this version.
+    pub fn id(self) -> i32 {
+        match self {
+            ProtocolVersion::V1_20_3 => 765,
+        }
+    }
+
+    /// The oldest Minecraft release that uses this version.
+    pub fn first_release(self) -> &'static str {
+        match self {
+            ProtocolVersion::V1_20_3 => "1.20.3",
+        }
+    }
+
+    /// The newest Minecraft release that uses this version.
+    pub fn last_release(self) -> &'static str {
+        match self {
+            ProtocolVersion::V1_20_3 => "1.20.4",
+        }
+    }
+
+    /// Returns a message explaining why a client using the protocol number `id` cannot join.
+    pub fn unsupported_message(id: i32) -> String {
+        let oldest = ProtocolVersion::SUPPORTED[0];
+        let newest = ProtocolVersion::LATEST;
+        let supported = if oldest.first_release() == newest.last_release() {
+            oldest.first_release().to_owned()
+        } else {
+            format!("{} - {}", oldest.first_release(), newest.last_release())
+        };
+        if id < oldest.id() {
+            format!("Outdated client! This server supports Minecraft {supported}.")
+        } else {
+            format!("Outdated server! This server supports Minecraft {supported}.")
+        }
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.first_release() == self.last_release() {
+            write!(f, "{}", self.first_release())
+        } else {
+            write!(f, "{}-{}", self.first_release(), self.last_release())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supported_versions_round_trip() {
+        for &version in ProtocolVersion::SUPPORTED {
+            assert_eq!(ProtocolVersion::from_id(version.id()), Some(version));
+        }
+        assert_eq!(ProtocolVersion::from_id(0), None);
+    }
+
+    #[test]
+    fn unsupported_message_names_supported_releases() {
+        assert_eq!(
+            ProtocolVersion::unsupported_message(764),
+            "Outdated client! This server supports Minecraft 1.20.3 - 1.20.4."
+        );
+        assert_eq!(
+            ProtocolVersion::unsupported_message(767),
+            "Outdated server! This server supports Minecraft 1.20.3 - 1.20.4."
+        );
+    }
+}
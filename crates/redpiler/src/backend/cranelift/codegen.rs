@@ -0,0 +1,716 @@
+//! Generates the native update and tick functions of each node.
+//!
+//! The generated code mirrors the direct backend's `update.rs` and `tick.rs`, operating on the
+//! same `Node` structs. Anything that touches the scheduler or events goes through the
+//! `extern "C"` callbacks at the top of this file.
+
+use super::NodeFn;
+use crate::backend::direct::node::{Node, NodeId, NodeInput, NodeType};
+use crate::backend::direct::{DirectBackend, Event, TickScheduler};
+use cranelift_codegen::ir::condcodes::IntCC;
+use cranelift_codegen::ir::{
+    types, AbiParam, Endianness, FuncRef, Function, InstBuilder, MemFlags, Type, Value,
+};
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, FuncId, Linkage, Module};
+use mchprs_blocks::blocks::ComparatorMode;
+use mchprs_world::TickPriority;
+use std::mem::{offset_of, size_of};
+
+const SCHEDULE_TICK_SYMBOL: &str = "redpiler_schedule_tick";
+const PLAY_NOTE_SYMBOL: &str = "redpiler_play_note";
+
+const POWERED: usize = offset_of!(Node, powered);
+const LOCKED: usize = offset_of!(Node, locked);
+const OBSERVED: usize = offset_of!(Node, observed);
+const OUTPUT_POWER: usize = offset_of!(Node, output_power);
+const CHANGED: usize = offset_of!(Node, changed);
+const PENDING_TICK: usize = offset_of!(Node, pending_tick);
+const DEFAULT_INPUTS: usize = offset_of!(Node, default_inputs) + offset_of!(NodeInput, ss_counts);
+const SIDE_INPUTS: usize = offset_of!(Node, side_inputs) + offset_of!(NodeInput, ss_counts);
+
+unsafe extern "C" fn schedule_tick(
+    backend: *mut DirectBackend,
+    node: u32,
+    delay: u32,
+    priority: u32,
+) {
+    let priority = TickScheduler::priorities()[priority as usize];
+    // Safety: the node id was created from a valid index during code generation, and the
+    // scheduler is not borrowed while compiled code runs
+    unsafe {
+        let node_id = NodeId::from_index(node as usize);
+        (*backend)
+            .scheduler
+            .schedule_tick(node_id, delay as usize, priority);
+    }
+}
+
+unsafe extern "C" fn play_note(backend: *mut DirectBackend, noteblock_id: u32) {
+    let event = Event::NoteBlockPlay {
+        noteblock_id: noteblock_id as u16,
+    };
+    // Safety: the events are not borrowed while compiled code runs
+    unsafe { (*backend).events.push(event) };
+}
+
+fn has_update(ty: NodeType) -> bool {
+    matches!(
+        ty,
+        NodeType::Repeater { .. }
+            | NodeType::Torch
+            | NodeType::Comparator { .. }
+            | NodeType::Lamp
+            | NodeType::Trapdoor
+            | NodeType::Wire
+            | NodeType::NoteBlock { .. }
+            | NodeType::CopperBulb
+            | NodeType::Observer
+    )
+}
+
+fn has_tick(ty: NodeType) -> bool {
+    matches!(
+        ty,
+        NodeType::Repeater { .. }
+            | NodeType::Torch
+            | NodeType::Comparator { .. }
+            | NodeType::Lamp
+            | NodeType::Button
+            | NodeType::TripwireHook
+            | NodeType::Target
+            | NodeType::CopperBulb
+            | NodeType::Observer
+    )
+}
+
+pub(super) struct CompiledCode {
+    pub module: JITModule,
+    pub tick_fns: Vec<Option<NodeFn>>,
+}
+
+pub(super) fn compile(backend: &DirectBackend) -> CompiledCode {
+    let mut flag_builder = settings::builder();
+    flag_builder.set("use_colocated_libcalls", "false").unwrap();
+    flag_builder.set("is_pic", "false").unwrap();
+    flag_builder.set("opt_level", "speed").unwrap();
+    let isa = cranelift_native::builder()
+        .expect("host machine is not supported by cranelift")
+        .finish(settings::Flags::new(flag_builder))
+        .expect("failed to create cranelift isa");
+    let mut jit_builder = JITBuilder::with_isa(isa, default_libcall_names());
+    jit_builder.symbol(SCHEDULE_TICK_SYMBOL, schedule_tick as *const u8);
+    jit_builder.symbol(PLAY_NOTE_SYMBOL, play_note as *const u8);
+    let mut module = JITModule::new(jit_builder);
+
+    let ptr = module.target_config().pointer_type();
+    let mut node_sig = module.make_signature();
+    node_sig.params = vec![AbiParam::new(ptr), AbiParam::new(ptr)];
+    let mut schedule_tick_sig = module.make_signature();
+    schedule_tick_sig.params = vec![
+        AbiParam::new(ptr),
+        AbiParam::new(types::I32),
+        AbiParam::new(types::I32),
+        AbiParam::new(types::I32),
+    ];
+    let mut play_note_sig = module.make_signature();
+    play_note_sig.params = vec![AbiParam::new(ptr), AbiParam::new(types::I32)];
+
+    let callbacks = Callbacks {
+        schedule_tick: module
+            .declare_function(SCHEDULE_TICK_SYMBOL, Linkage::Import, &schedule_tick_sig)
+            .unwrap(),
+        play_note: module
+            .declare_function(PLAY_NOTE_SYMBOL, Linkage::Import, &play_note_sig)
+            .unwrap(),
+    };
+
+    let nodes = backend.nodes.inner();
+    // Node offsets are encoded as 32-bit immediates
+    assert!(std::mem::size_of_val(nodes) < i32::MAX as usize);
+    let declare = |module: &mut JITModule, present: bool| {
+        present.then(|| module.declare_anonymous_function(&node_sig).unwrap())
+    };
+    let update_fns: Vec<_> = nodes
+        .iter()
+        .map(|node| declare(&mut module, has_update(node.ty)))
+        .collect();
+    let tick_fns: Vec<_> = nodes
+        .iter()
+        .map(|node| declare(&mut module, has_tick(node.ty)))
+        .collect();
+
+    let mut ctx = module.make_context();
+    let mut func_ctx = FunctionBuilderContext::new();
+    for idx in 0..nodes.len() {
+        let node_id = backend.nodes.get(idx);
+        for (func_id, is_tick) in [(update_fns[idx], false), (tick_fns[idx], true)] {
+            let Some(func_id) = func_id else {
+                continue;
+            };
+            ctx.func.signature = node_sig.clone();
+            let mut codegen = FunctionCodegen::new(
+                &mut module,
+                &mut ctx.func,
+                &mut func_ctx,
+                &callbacks,
+                &update_fns,
+                backend,
+                node_id,
+            );
+            if is_tick {
+                codegen.tick();
+            } else {
+                codegen.update();
+            }
+            codegen.finish();
+            module
+                .define_function(func_id, &mut ctx)
+                .expect("failed to compile node function");
+            module.clear_context(&mut ctx);
+        }
+    }
+    module
+        .finalize_definitions()
+        .expect("failed to finalize node functions");
+
+    let tick_fns = tick_fns
+        .into_iter()
+        .map(|func_id| {
+            func_id.map(|func_id| {
+                let code = module.get_finalized_function(func_id);
+                // Safety: the function was defined with `node_sig`, which matches `NodeFn`
+                unsafe { std::mem::transmute::<*const u8, NodeFn>(code) }
+            })
+        })
+        .collect();
+    CompiledCode { module, tick_fns }
+}
+
+struct Callbacks {
+    schedule_tick: FuncId,
+    play_note: FuncId,
+}
+
+struct FunctionCodegen<'a> {
+    builder: FunctionBuilder<'a>,
+    module: &'a mut JITModule,
+    update_fns: &'a [Option<FuncId>],
+    backend: &'a DirectBackend,
+    node_id: NodeId,
+    ptr: Type,
+    nodes: Value,
+    backend_ptr: Value,
+    schedule_tick: FuncRef,
+    play_note: FuncRef,
+}
+
+impl<'a> FunctionCodegen<'a> {
+    fn new(
+        module: &'a mut JITModule,
+        func: &'a mut Function,
+        func_ctx: &'a mut FunctionBuilderContext,
+        callbacks: &Callbacks,
+        update_fns: &'a [Option<FuncId>],
+        backend: &'a DirectBackend,
+        node_id: NodeId,
+    ) -> Self {
+        let schedule_tick = module.declare_func_in_func(callbacks.schedule_tick, func);
+        let play_note = module.declare_func_in_func(callbacks.play_note, func);
+        let mut builder = FunctionBuilder::new(func, func_ctx);
+        let entry = builder.create_block();
+        builder.append_block_params_for_function_params(entry);
+        builder.switch_to_block(entry);
+        let nodes = builder.block_params(entry)[0];
+        let backend_ptr = builder.block_params(entry)[1];
+        FunctionCodegen {
+            builder,
+            ptr: module.target_config().pointer_type(),
+            module,
+            update_fns,
+            backend,
+            node_id,
+            nodes,
+            backend_ptr,
+            schedule_tick,
+            play_note,
+        }
+    }
+
+    fn finish(mut self) {
+        self.builder.ins().return_(&[]);
+        self.builder.seal_all_blocks();
+        self.builder.finalize();
+    }
+
+    fn flags() -> MemFlags {
+        MemFlags::trusted().with_endianness(Endianness::Little)
+    }
+
+    fn offset(node_id: NodeId, field: usize) -> i32 {
+        (node_id.index() * size_of::<Node>() + field) as i32
+    }
+
+    fn const_u8(&mut self, value: u8) -> Value {
+        self.builder.ins().iconst(types::I8, value as i64)
+    }
+
+    fn load(&mut self, field: usize) -> Value {
+        let offset = Self::offset(self.node_id, field);
+        self.builder
+            .ins()
+            .load(types::I8, Self::flags(), self.nodes, offset)
+    }
+
+    fn store(&mut self, field: usize, value: Value) {
+        let offset = Self::offset(self.node_id, field);
+        self.builder
+            .ins()
+            .store(Self::flags(), value, self.nodes, offset);
+    }
+
+    fn store_const(&mut self, field: usize, value: u8) {
+        let value = self.const_u8(value);
+        self.store(field, value);
+    }
+
+    fn ne(&mut self, a: Value, b: Value) -> Value {
+        self.builder.ins().icmp(IntCC::NotEqual, a, b)
+    }
+
+    fn not(&mut self, value: Value) -> Value {
+        self.builder.ins().bxor_imm(value, 1)
+    }
+
+    /// Selects between two constant signal strengths.
+    fn select_u8(&mut self, cond: Value, if_true: u8, if_false: u8) -> Value {
+        let if_true = self.const_u8(if_true);
+        let if_false = self.const_u8(if_false);
+        self.builder.ins().select(cond, if_true, if_false)
+    }
+
+    fn if_else(
+        &mut self,
+        cond: Value,
+        then: impl FnOnce(&mut Self),
+        otherwise: impl FnOnce(&mut Self),
+    ) {
+        let then_block = self.builder.create_block();
+        let else_block = self.builder.create_block();
+        let merge_block = self.builder.create_block();
+        self.builder
+            .ins()
+            .brif(cond, then_block, &[], else_block, &[]);
+        self.builder.switch_to_block(then_block);
+        then(self);
+        self.builder.ins().jump(merge_block, &[]);
+        self.builder.switch_to_block(else_block);
+        otherwise(self);
+        self.builder.ins().jump(merge_block, &[]);
+        self.builder.switch_to_block(merge_block);
+    }
+
+    fn if_then(&mut self, cond: Value, then: impl FnOnce(&mut Self)) {
+        self.if_else(cond, then, |_| {});
+    }
+
+    fn unless(&mut self, cond: Value, then: impl FnOnce(&mut Self)) {
+        self.if_else(cond, |_| {}, then);
+    }
+
+    /// Equivalent to `get_bool_input` and `get_bool_side`.
+    fn bool_input(&mut self, inputs: usize) -> Value {
+        let zero_bucket = self.load(inputs);
+        let all = self.const_u8(255);
+        self.ne(zero_bucket, all)
+    }
+
+    /// Equivalent to `last_index_positive`, operating on two halves of the input counts.
+    fn input_power(&mut self, inputs: usize) -> Value {
+        let offset = Self::offset(self.node_id, inputs);
+        let ins = self.builder.ins();
+        let low = ins.load(types::I64, Self::flags(), self.nodes, offset);
+        let high = self
+            .builder
+            .ins()
+            .load(types::I64, Self::flags(), self.nodes, offset + 8);
+
+        let last_index = |codegen: &mut Self, half: Value, last: i64| {
+            let ins = codegen.builder.ins();
+            let zeros = ins.clz(half);
+            let zero_bytes = codegen.builder.ins().ushr_imm(zeros, 3);
+            let last = codegen.builder.ins().iconst(types::I64, last);
+            codegen.builder.ins().isub(last, zero_bytes)
+        };
+        let zero = self.builder.ins().iconst(types::I64, 0);
+        let low_index = last_index(self, low, 7);
+        let high_index = last_index(self, high, 15);
+        let low_positive = self.ne(low, zero);
+        let high_positive = self.ne(high, zero);
+        let low_index = self.builder.ins().select(low_positive, low_index, zero);
+        let index = self
+            .builder
+            .ins()
+            .select(high_positive, high_index, low_index);
+        self.builder.ins().ireduce(types::I8, index)
+    }
+
+    /// Equivalent to `calculate_comparator_output`, including the far input override.
+    fn comparator_output(&mut self, mode: ComparatorMode, far_input: Option<u8>) -> Value {
+        let mut input_power = self.input_power(DEFAULT_INPUTS);
+        let side_input_power = self.input_power(SIDE_INPUTS);
+        let max = self.const_u8(15);
+        if let Some(far_override) = far_input {
+            let far_override = self.const_u8(far_override);
+            let not_full = self
+                .builder
+                .ins()
+                .icmp(IntCC::UnsignedLessThan, input_power, max);
+            input_power = self
+                .builder
+                .ins()
+                .select(not_full, far_override, input_power);
+        }
+        let difference = self.builder.ins().isub(input_power, side_input_power);
+        let in_range = self
+            .builder
+            .ins()
+            .icmp(IntCC::UnsignedLessThanOrEqual, difference, max);
+        let output = match mode {
+            ComparatorMode::Compare => input_power,
+            ComparatorMode::Subtract => difference,
+        };
+        let zero = self.const_u8(0);
+        self.builder.ins().select(in_range, output, zero)
+    }
+
+    fn schedule_tick(&mut self, delay: u8, priority: TickPriority) {
+        self.store_const(PENDING_TICK, 1);
+        let node = self
+            .builder
+            .ins()
+            .iconst(types::I32, self.node_id.index() as i64);
+        let delay = self.builder.ins().iconst(types::I32, delay as i64);
+        let priority = self.builder.ins().iconst(types::I32, priority as i64);
+        self.builder.ins().call(
+            self.schedule_tick,
+            &[self.backend_ptr, node, delay, priority],
+        );
+    }
+
+    /// `saturating_sub` of a signal strength and a constant distance.
+    fn sub_distance(&mut self, power: Value, distance: u8) -> Value {
+        if distance == 0 {
+            return power;
+        }
+        let distance = self.const_u8(distance);
+        let reaches = self
+            .builder
+            .ins()
+            .icmp(IntCC::UnsignedGreaterThanOrEqual, power, distance);
+        let remaining = self.builder.ins().isub(power, distance);
+        let zero = self.const_u8(0);
+        self.builder.ins().select(reaches, remaining, zero)
+    }
+
+    /// Equivalent to `DirectBackend::set_node`, with the forward links unrolled.
+    fn set_node(&mut self, powered: Value, new_power: Value) {
+        let old_power = self.load(OUTPUT_POWER);
+        self.store_const(CHANGED, 1);
+        self.store(POWERED, powered);
+        self.store(OUTPUT_POWER, new_power);
+
+        let backend = self.backend;
+        let node = &backend.nodes[self.node_id];
+        for forward_link in backend.forward_links.get(&node.fwd_link_range) {
+            let inputs = if forward_link.side() {
+                SIDE_INPUTS
+            } else {
+                DEFAULT_INPUTS
+            };
+            let update = forward_link.node();
+            let old_power = self.sub_distance(old_power, forward_link.ss());
+            let new_power = self.sub_distance(new_power, forward_link.ss());
+            let power_changed = self.ne(old_power, new_power);
+            self.if_then(power_changed, |codegen| {
+                codegen.add_to_bucket(update, inputs, old_power, -1);
+                codegen.add_to_bucket(update, inputs, new_power, 1);
+                if let Some(update_fn) = codegen.update_fns[update.index()] {
+                    let update_fn = codegen
+                        .module
+                        .declare_func_in_func(update_fn, codegen.builder.func);
+                    codegen
+                        .builder
+                        .ins()
+                        .call(update_fn, &[codegen.nodes, codegen.backend_ptr]);
+                }
+            });
+        }
+    }
+
+    fn set_node_const(&mut self, powered: bool, new_power: u8) {
+        let powered = self.const_u8(powered as u8);
+        let new_power = self.const_u8(new_power);
+        self.set_node(powered, new_power);
+    }
+
+    fn add_to_bucket(&mut self, node_id: NodeId, inputs: usize, power: Value, amount: i64) {
+        let index = self.builder.ins().uextend(self.ptr, power);
+        let addr = self.builder.ins().iadd(self.nodes, index);
+        let offset = Self::offset(node_id, inputs);
+        let count = self
+            .builder
+            .ins()
+            .load(types::I8, Self::flags(), addr, offset);
+        let amount = self.builder.ins().iconst(types::I8, amount & 0xFF);
+        let count = self.builder.ins().iadd(count, amount);
+        self.builder.ins().store(Self::flags(), count, addr, offset);
+    }
+
+    /// Generates the equivalent of `update::update_node` for this node.
+    fn update(&mut self) {
+        match self.backend.nodes[self.node_id].ty {
+            NodeType::Repeater {
+                delay,
+                facing_diode,
+            } => {
+                let locked = self.load(LOCKED);
+                let should_be_locked = self.bool_input(SIDE_INPUTS);
+                let lock_changed = self.ne(locked, should_be_locked);
+                self.if_then(lock_changed, |codegen| {
+                    codegen.store(LOCKED, should_be_locked);
+                    codegen.store_const(CHANGED, 1);
+                });
+                let pending_tick = self.load(PENDING_TICK);
+                let blocked = self.builder.ins().bor(should_be_locked, pending_tick);
+                self.unless(blocked, |codegen| {
+                    let should_be_powered = codegen.bool_input(DEFAULT_INPUTS);
+                    let powered = codegen.load(POWERED);
+                    let power_changed = codegen.ne(should_be_powered, powered);
+                    codegen.if_then(power_changed, |codegen| {
+                        if facing_diode {
+                            codegen.schedule_tick(delay, TickPriority::Highest);
+                        } else {
+                            codegen.if_else(
+                                should_be_powered,
+                                |codegen| codegen.schedule_tick(delay, TickPriority::High),
+                                |codegen| codegen.schedule_tick(delay, TickPriority::Higher),
+                            );
+                        }
+                    });
+                });
+            }
+            NodeType::Torch => {
+                let pending_tick = self.load(PENDING_TICK);
+                self.unless(pending_tick, |codegen| {
+                    let input = codegen.bool_input(DEFAULT_INPUTS);
+                    let should_be_powered = codegen.not(input);
+                    let powered = codegen.load(POWERED);
+                    let power_changed = codegen.ne(powered, should_be_powered);
+                    codegen.if_then(power_changed, |codegen| {
+                        codegen.schedule_tick(1, TickPriority::Normal);
+                    });
+                });
+            }
+            NodeType::Comparator {
+                mode,
+                far_input,
+                facing_diode,
+            } => {
+                let pending_tick = self.load(PENDING_TICK);
+                self.unless(pending_tick, |codegen| {
+                    let output_power = codegen.comparator_output(mode, far_input.map(|f| f.get()));
+                    let old_strength = codegen.load(OUTPUT_POWER);
+                    let power_changed = codegen.ne(output_power, old_strength);
+                    codegen.if_then(power_changed, |codegen| {
+                        let priority = if facing_diode {
+                            TickPriority::High
+                        } else {
+                            TickPriority::Normal
+                        };
+                        codegen.schedule_tick(1, priority);
+                    });
+                });
+            }
+            NodeType::Lamp => {
+                let should_be_lit = self.bool_input(DEFAULT_INPUTS);
+                let lit = self.load(POWERED);
+                self.if_else(
+                    lit,
+                    |codegen| {
+                        codegen.unless(should_be_lit, |codegen| {
+                            codegen.schedule_tick(2, TickPriority::Normal);
+                        });
+                    },
+                    |codegen| {
+                        codegen.if_then(should_be_lit, |codegen| {
+                            codegen.store_const(POWERED, 1);
+                            codegen.store_const(CHANGED, 1);
+                        });
+                    },
+                );
+            }
+            NodeType::Trapdoor => {
+                let should_be_powered = self.bool_input(DEFAULT_INPUTS);
+                let powered = self.load(POWERED);
+                let power_changed = self.ne(powered, should_be_powered);
+                self.if_then(power_changed, |codegen| {
+                    codegen.store(POWERED, should_be_powered);
+                    codegen.store_const(CHANGED, 1);
+                });
+            }
+            NodeType::Wire => {
+                let input_power = self.input_power(DEFAULT_INPUTS);
+                let output_power = self.load(OUTPUT_POWER);
+                let power_changed = self.ne(output_power, input_power);
+                self.if_then(power_changed, |codegen| {
+                    codegen.store(OUTPUT_POWER, input_power);
+                    codegen.store_const(CHANGED, 1);
+                });
+            }
+            NodeType::NoteBlock { noteblock_id } => {
+                let should_be_powered = self.bool_input(DEFAULT_INPUTS);
+                let powered = self.load(POWERED);
+                let power_changed = self.ne(powered, should_be_powered);
+                self.if_then(power_changed, |codegen| {
+                    codegen.store(POWERED, should_be_powered);
+                    codegen.store_const(CHANGED, 1);
+                    codegen.if_then(should_be_powered, |codegen| {
+                        let noteblock_id = codegen
+                            .builder
+                            .ins()
+                            .iconst(types::I32, noteblock_id as i64);
+                        codegen
+                            .builder
+                            .ins()
+                            .call(codegen.play_note, &[codegen.backend_ptr, noteblock_id]);
+                    });
+                });
+            }
+            NodeType::CopperBulb => {
+                let pending_tick = self.load(PENDING_TICK);
+                self.unless(pending_tick, |codegen| {
+                    let should_be_powered = codegen.bool_input(DEFAULT_INPUTS);
+                    let powered = codegen.load(POWERED);
+                    let power_changed = codegen.ne(powered, should_be_powered);
+                    codegen.if_then(power_changed, |codegen| {
+                        codegen.schedule_tick(1, TickPriority::Normal);
+                    });
+                });
+            }
+            NodeType::Observer => {
+                // Equivalent to `observed_state`
+                let input_power = self.input_power(DEFAULT_INPUTS);
+                let side_powered = self.bool_input(SIDE_INPUTS);
+                let observed = self.builder.ins().umax(input_power, side_powered);
+                let old_observed = self.load(OBSERVED);
+                let state_changed = self.ne(observed, old_observed);
+                self.if_then(state_changed, |codegen| {
+                    codegen.store(OBSERVED, observed);
+                    let pending_tick = codegen.load(PENDING_TICK);
+                    codegen.unless(pending_tick, |codegen| {
+                        codegen.schedule_tick(1, TickPriority::Normal);
+                    });
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Generates the equivalent of `DirectBackend::tick_node` for this node.
+    fn tick(&mut self) {
+        self.store_const(PENDING_TICK, 0);
+
+        match self.backend.nodes[self.node_id].ty {
+            NodeType::Repeater { delay, .. } => {
+                let locked = self.load(LOCKED);
+                self.unless(locked, |codegen| {
+                    let should_be_powered = codegen.bool_input(DEFAULT_INPUTS);
+                    let powered = codegen.load(POWERED);
+                    codegen.if_else(
+                        powered,
+                        |codegen| {
+                            codegen.unless(should_be_powered, |codegen| {
+                                codegen.set_node_const(false, 0);
+                            });
+                        },
+                        |codegen| {
+                            codegen.unless(should_be_powered, |codegen| {
+                                codegen.schedule_tick(delay, TickPriority::Higher);
+                            });
+                            codegen.set_node_const(true, 15);
+                        },
+                    );
+                });
+            }
+            NodeType::Torch => {
+                let input = self.bool_input(DEFAULT_INPUTS);
+                let should_be_powered = self.not(input);
+                let powered = self.load(POWERED);
+                let power_changed = self.ne(powered, should_be_powered);
+                self.if_then(power_changed, |codegen| {
+                    let power = codegen.select_u8(should_be_powered, 15, 0);
+                    codegen.set_node(should_be_powered, power);
+                });
+            }
+            NodeType::Comparator {
+                mode, far_input, ..
+            } => {
+                let new_strength = self.comparator_output(mode, far_input.map(|f| f.get()));
+                let old_strength = self.load(OUTPUT_POWER);
+                let power_changed = self.ne(new_strength, old_strength);
+                self.if_then(power_changed, |codegen| {
+                    let zero = codegen.const_u8(0);
+                    let powered = codegen.ne(new_strength, zero);
+                    codegen.set_node(powered, new_strength);
+                });
+            }
+            NodeType::Lamp => {
+                let should_be_lit = self.bool_input(DEFAULT_INPUTS);
+                let lit = self.load(POWERED);
+                self.if_then(lit, |codegen| {
+                    codegen.unless(should_be_lit, |codegen| {
+                        codegen.set_node_const(false, 0);
+                    });
+                });
+            }
+            NodeType::Button | NodeType::TripwireHook => {
+                let powered = self.load(POWERED);
+                self.if_then(powered, |codegen| codegen.set_node_const(false, 0));
+            }
+            NodeType::Target => {
+                let output_power = self.load(OUTPUT_POWER);
+                self.if_then(output_power, |codegen| codegen.set_node_const(false, 0));
+            }
+            NodeType::CopperBulb => {
+                let should_be_powered = self.bool_input(DEFAULT_INPUTS);
+                let powered = self.load(POWERED);
+                let power_changed = self.ne(powered, should_be_powered);
+                self.if_then(power_changed, |codegen| {
+                    // The bulb only toggles when it becomes powered
+                    let output_power = codegen.load(OUTPUT_POWER);
+                    let zero = codegen.const_u8(0);
+                    let was_lit = codegen.ne(output_power, zero);
+                    let lit = codegen.ne(was_lit, should_be_powered);
+                    let power = codegen.select_u8(lit, 15, 0);
+                    codegen.set_node(should_be_powered, power);
+                });
+            }
+            NodeType::Observer => {
+                let powered = self.load(POWERED);
+                self.if_else(
+                    powered,
+                    |codegen| codegen.set_node_const(false, 0),
+                    |codegen| {
+                        // Observers watching this one need to be scheduled before the unpower tick
+                        codegen.set_node_const(true, 15);
+                        codegen.schedule_tick(1, TickPriority::Normal);
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! The cranelift backend compiles the update and tick logic of every node to native code.
+//!
+//! It uses the same node layout and tick scheduler as the [`DirectBackend`], which it wraps. Each
+//! node gets its own functions with its type, delay and forward links baked in, so ticking a node
+//! never has to match on the node type or walk a list of forward links. Player input is rare, so
+//! it is still handled by the direct backend's interpreter.
+
+mod codegen;
+
+use super::direct::node::{Node, NodeId};
+use super::direct::DirectBackend;
+use super::{BackendSnapshot, JITBackend, NodeInspection};
+use crate::compile_graph::{CompileGraph, NodeState};
+use crate::task_monitor::TaskMonitor;
+use crate::trace::{Probe, Trace};
+use crate::CompilerOptions;
+use cranelift_jit::JITModule;
+use mchprs_blocks::BlockPos;
+use mchprs_world::{TickEntry, TickPriority, World};
+use std::sync::Arc;
+use tracing::trace;
+
+/// A compiled node function. The backend pointer is passed back to the runtime callbacks.
+type NodeFn = unsafe extern "C" fn(nodes: *mut Node, backend: *mut DirectBackend);
+
+#[derive(Default)]
+pub struct CraneliftBackend {
+    direct: DirectBackend,
+    /// The tick function of each node, or `None` if ticking the node only clears its pending tick.
+    tick_fns: Vec<Option<NodeFn>>,
+    /// Owns the memory of the compiled functions.
+    module: Option<JITModule>,
+}
+
+impl CraneliftBackend {
+    fn free_code(&mut self) {
+        self.tick_fns.clear();
+        if let Some(module) = self.module.take() {
+            // Safety: the function pointers pointing into the module were just dropped
+            unsafe { module.free_memory() };
+        }
+    }
+}
+
+/// Ticks a single node outside of the fast path in [`CraneliftBackend::tick`].
+fn tick_node(tick_fns: &[Option<NodeFn>], direct: &mut DirectBackend, node_id: NodeId) {
+    let backend: *mut DirectBackend = direct;
+    // Safety: see `CraneliftBackend::tick`. The queue being ticked has been taken out of the
+    // scheduler.
+    unsafe {
+        let nodes = (*backend).nodes.inner_mut().as_mut_ptr();
+        match tick_fns[node_id.index()] {
+            Some(tick_fn) => tick_fn(nodes, backend),
+            None => (*nodes.add(node_id.index())).pending_tick = false,
+        }
+    }
+}
+
+impl Drop for CraneliftBackend {
+    fn drop(&mut self) {
+        self.free_code();
+    }
+}
+
+impl JITBackend for CraneliftBackend {
+    fn compile(
+        &mut self,
+        graph: CompileGraph,
+        ticks: Vec<TickEntry>,
+        options: &CompilerOptions,
+        monitor: Arc<TaskMonitor>,
+    ) {
+        self.free_code();
+        self.direct.compile(graph, ticks, options, monitor);
+
+        let start = std::time::Instant::now();
+        let code = codegen::compile(&self.direct);
+        trace!("Generated native code in {:?}", start.elapsed());
+        self.tick_fns = code.tick_fns;
+        self.module = Some(code.module);
+    }
+
+    fn tick(&mut self) {
+        if self.direct.stepped_priorities != 0 {
+            let tick_fns = &self.tick_fns;
+            self.direct
+                .finish_stepped_tick(|direct, node_id| tick_node(tick_fns, direct, node_id));
+            return;
+        }
+        let backend: *mut DirectBackend = &mut self.direct;
+        // Safety: the compiled functions only access the nodes array and, through the runtime
+        // callbacks, the scheduler and events. None of them are borrowed while the functions run,
+        // and the queues for this tick have been taken out of the scheduler.
+        unsafe {
+            let mut queues = (*backend).scheduler.queues_this_tick();
+            let nodes = (*backend).nodes.inner_mut().as_mut_ptr();
+            queues.drain_each(|node_id| match self.tick_fns[node_id.index()] {
+                Some(tick_fn) => tick_fn(nodes, backend),
+                None => (*nodes.add(node_id.index())).pending_tick = false,
+            });
+            (*backend).scheduler.end_tick(queues);
+        }
+        self.direct.record_probes();
+    }
+
+    fn step_priority(&mut self) -> TickPriority {
+        let tick_fns = &self.tick_fns;
+        self.direct
+            .step_priority_with(|direct, node_id| tick_node(tick_fns, direct, node_id))
+    }
+
+    fn on_use_block(&mut self, pos: BlockPos) {
+        self.direct.on_use_block(pos);
+    }
+
+    fn set_pressure_plate(&mut self, pos: BlockPos, powered: bool) {
+        self.direct.set_pressure_plate(pos, powered);
+    }
+
+    fn set_daylight_detector(&mut self, pos: BlockPos, power: u8) {
+        self.direct.set_daylight_detector(pos, power);
+    }
+
+    fn hit_target(&mut self, pos: BlockPos, power: u8, ticks: u32) -> bool {
+        self.direct.hit_target(pos, power, ticks)
+    }
+
+    fn trigger_tripwire_hook(&mut self, pos: BlockPos, ticks: u32) -> bool {
+        self.direct.trigger_tripwire_hook(pos, ticks)
+    }
+
+    fn flush<W: World>(&mut self, world: &mut W, io_only: bool) {
+        self.direct.flush(world, io_only);
+    }
+
+    fn reset<W: World>(&mut self, world: &mut W, io_only: bool) {
+        self.direct.reset(world, io_only);
+        self.free_code();
+    }
+
+    fn has_pending_ticks(&self) -> bool {
+        self.direct.has_pending_ticks()
+    }
+
+    fn node_state(&self, pos: BlockPos) -> Option<NodeState> {
+        self.direct.node_state(pos)
+    }
+
+    fn inspect(&self, pos: BlockPos) -> Option<NodeInspection> {
+        self.direct.inspect(pos)
+    }
+
+    fn snapshot(&self) -> BackendSnapshot {
+        self.direct.snapshot()
+    }
+
+    fn restore(&mut self, snapshot: &BackendSnapshot) -> bool {
+        self.direct.restore(snapshot)
+    }
+
+    fn add_probe(&mut self, probe: Probe) -> bool {
+        self.direct.add_probe(probe)
+    }
+
+    fn clear_probes(&mut self) {
+        self.direct.clear_probes();
+    }
+
+    fn trace(&self) -> Trace {
+        self.direct.trace()
+    }
+}
@@ -11,7 +11,7 @@ use std::sync::Arc;
 use tracing::trace;
 
 use super::node::{ForwardLink, Node, NodeId, NodeInput, NodeType, Nodes, NonMaxU8};
-use super::DirectBackend;
+use super::{observed_state, DirectBackend};
 
 #[derive(Debug, Default)]
 struct FinalGraphStats {
@@ -121,6 +121,11 @@ fn compile_node(
         CNodeType::Trapdoor => NodeType::Trapdoor,
         CNodeType::Wire => NodeType::Wire,
         CNodeType::Constant => NodeType::Constant,
+        CNodeType::Observer => NodeType::Observer,
+        CNodeType::Target => NodeType::Target,
+        CNodeType::TripwireHook => NodeType::TripwireHook,
+        CNodeType::DaylightDetector => NodeType::DaylightDetector,
+        CNodeType::CopperBulb => NodeType::CopperBulb,
         CNodeType::NoteBlock { instrument, note } => {
             let noteblock_id = noteblock_info.len().try_into().unwrap();
             noteblock_info.push((
@@ -132,7 +137,7 @@ fn compile_node(
         }
     };
 
-    Node {
+    let mut node = Node {
         ty,
         default_inputs,
         side_inputs,
@@ -140,10 +145,13 @@ fn compile_node(
         powered: node.state.powered,
         output_power: node.state.output_strength,
         locked: node.state.repeater_locked,
+        observed: 0,
         pending_tick: false,
         changed: false,
         is_io: node.is_input || node.is_output,
-    }
+    };
+    node.observed = observed_state(&node);
+    node
 }
 
 pub fn compile(
@@ -1,34 +1,36 @@
 //! The direct backend does not do code generation and operates on the `CompileNode` graph directly
 
 mod compile;
-mod node;
+pub(super) mod node;
 mod tick;
 mod update;
 
-use super::JITBackend;
-use crate::backend::direct::node::ForwardLinks;
-use crate::compile_graph::CompileGraph;
+use super::{BackendSnapshot, InspectedLink, JITBackend, NodeInspection};
+use crate::backend::direct::node::{ForwardLink, ForwardLinks};
+use crate::compile_graph::{CompileGraph, LinkType, NodeState};
 use crate::task_monitor::TaskMonitor;
+use crate::trace::{Probe, Signal, Trace};
 use crate::{block_powered_mut, CompilerOptions};
 use mchprs_blocks::block_entities::BlockEntity;
 use mchprs_blocks::blocks::{Block, ComparatorMode, Instrument};
 use mchprs_blocks::BlockPos;
-use mchprs_redstone::{bool_to_ss, noteblock};
+use mchprs_redstone::{bool_to_ss, daylight_detector, noteblock};
 use mchprs_world::{TickEntry, TickPriority, World};
 use node::{Node, NodeId, NodeType, Nodes};
 use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use std::fmt::Write;
 use std::sync::Arc;
 use std::{fmt, mem};
 use tracing::{debug, warn};
 
-#[derive(Default, Clone)]
-struct Queues([Vec<NodeId>; TickScheduler::NUM_PRIORITIES]);
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub(super) struct Queues([Vec<NodeId>; TickScheduler::NUM_PRIORITIES]);
 
 impl Queues {
     #[inline(always)]
-    fn drain_each<F: FnMut(NodeId)>(&mut self, mut f: F) {
+    pub(super) fn drain_each<F: FnMut(NodeId)>(&mut self, mut f: F) {
         for q in self.0.iter_mut() {
             for n in q.iter() {
                 f(*n);
@@ -38,8 +40,8 @@ impl Queues {
     }
 }
 
-#[derive(Default)]
-struct TickScheduler {
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub(super) struct TickScheduler {
     queues_deque: [Queues; Self::NUM_QUEUES],
     pos: usize,
 }
@@ -75,20 +77,51 @@ impl TickScheduler {
         }
     }
 
-    fn schedule_tick(&mut self, node: NodeId, delay: usize, priority: TickPriority) {
+    pub(super) fn schedule_tick(&mut self, node: NodeId, delay: usize, priority: TickPriority) {
         self.queues_deque[(self.pos + delay) % Self::NUM_QUEUES].0[priority as usize].push(node);
     }
 
-    fn queues_this_tick(&mut self) -> Queues {
+    pub(super) fn queues_this_tick(&mut self) -> Queues {
         self.pos = (self.pos + 1) % Self::NUM_QUEUES;
         mem::take(&mut self.queues_deque[self.pos])
     }
 
-    fn end_tick(&mut self, queues: Queues) {
+    pub(super) fn end_tick(&mut self, queues: Queues) {
         self.queues_deque[self.pos % Self::NUM_QUEUES] = queues;
     }
 
-    fn priorities() -> [TickPriority; Self::NUM_PRIORITIES] {
+    /// Returns how many ticks from now each tick scheduled for `node` runs, with its priority.
+    fn pending_ticks(&self, node: NodeId) -> Vec<(usize, TickPriority)> {
+        let mut ticks = Vec::new();
+        for (idx, queues) in self.queues_deque.iter().enumerate() {
+            // The queues at `pos` are only filled while stepping through that tick
+            let delay = (idx + Self::NUM_QUEUES - self.pos) % Self::NUM_QUEUES;
+            for (entries, priority) in queues.0.iter().zip(Self::priorities()) {
+                if entries.contains(&node) {
+                    ticks.push((delay, priority));
+                }
+            }
+        }
+        ticks.sort();
+        ticks
+    }
+
+    /// Moves on to the next tick without taking its queues, so its priorities can be run one at a
+    /// time.
+    fn start_tick(&mut self) {
+        self.pos = (self.pos + 1) % Self::NUM_QUEUES;
+    }
+
+    fn take_queue(&mut self, priority: usize) -> Vec<NodeId> {
+        mem::take(&mut self.queues_deque[self.pos].0[priority])
+    }
+
+    fn return_queue(&mut self, priority: usize, mut queue: Vec<NodeId>) {
+        queue.clear();
+        self.queues_deque[self.pos].0[priority] = queue;
+    }
+
+    pub(super) fn priorities() -> [TickPriority; Self::NUM_PRIORITIES] {
         [
             TickPriority::Highest,
             TickPriority::Higher,
@@ -109,19 +142,43 @@ impl TickScheduler {
     }
 }
 
-enum Event {
+pub(super) enum Event {
     NoteBlockPlay { noteblock_id: u16 },
 }
 
+/// The state of a single node, which is everything in a `Node` that can change while ticking.
+#[derive(Serialize, Deserialize)]
+struct NodeSnapshot {
+    default_inputs: [u8; 16],
+    side_inputs: [u8; 16],
+    powered: bool,
+    locked: bool,
+    output_power: u8,
+    pending_tick: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct DirectSnapshot {
+    nodes: Vec<NodeSnapshot>,
+    scheduler: TickScheduler,
+    stepped_priorities: usize,
+}
+
 #[derive(Default)]
 pub struct DirectBackend {
-    nodes: Nodes,
-    forward_links: ForwardLinks,
+    pub(super) nodes: Nodes,
+    pub(super) forward_links: ForwardLinks,
     blocks: Vec<SmallVec<[(BlockPos, Block); 1]>>,
     pos_map: FxHashMap<BlockPos, NodeId>,
-    scheduler: TickScheduler,
-    events: Vec<Event>,
+    pub(super) scheduler: TickScheduler,
+    pub(super) events: Vec<Event>,
     noteblock_info: Vec<(SmallVec<[BlockPos; 1]>, Instrument, u8)>,
+    /// The number of priorities of the current tick that have run while stepping through it. This
+    /// is zero between ticks.
+    pub(super) stepped_priorities: usize,
+    /// The number of ticks since the backend was compiled.
+    ticks: u64,
+    probes: Vec<(NodeId, Signal)>,
 }
 
 impl DirectBackend {
@@ -173,16 +230,58 @@ impl DirectBackend {
 }
 
 impl JITBackend for DirectBackend {
-    fn inspect(&mut self, pos: BlockPos) {
-        let Some(node_id) = self.pos_map.get(&pos) else {
+    fn inspect(&self, pos: BlockPos) -> Option<NodeInspection> {
+        let Some(&node_id) = self.pos_map.get(&pos) else {
             debug!("could not find node at pos {}", pos);
-            return;
+            return None;
         };
+        let node = &self.nodes[node_id];
+
+        let link = |other: NodeId, link: ForwardLink| InspectedLink {
+            ty: if link.side() {
+                LinkType::Side
+            } else {
+                LinkType::Default
+            },
+            weight: link.ss(),
+            pos: self.blocks[other.index()].first().map(|&(pos, _)| pos),
+            power: self.nodes[other].output_power,
+        };
+        let outputs = self
+            .forward_links
+            .get(&node.fwd_link_range)
+            .iter()
+            .map(|&fwd| link(fwd.node(), fwd))
+            .collect();
+        // Only forward links are stored, so the inputs have to be searched for
+        let mut inputs = Vec::new();
+        for (idx, source) in self.nodes.inner().iter().enumerate() {
+            let source_id = self.nodes.get(idx);
+            for &fwd in self.forward_links.get(&source.fwd_link_range) {
+                if fwd.node() == node_id {
+                    inputs.push(link(source_id, fwd));
+                }
+            }
+        }
 
-        debug!("Node {:?}: {:#?}", node_id, self.nodes[*node_id]);
+        Some(NodeInspection {
+            id: node_id.index(),
+            ty: format!("{:?}", node.ty),
+            state: NodeState {
+                powered: node.powered,
+                repeater_locked: node.locked,
+                output_strength: node.output_power,
+            },
+            is_io: node.is_io,
+            pending_ticks: self.scheduler.pending_ticks(node_id),
+            inputs,
+            outputs,
+        })
     }
 
     fn reset<W: World>(&mut self, world: &mut W, io_only: bool) {
+        // The scheduler can only hand back ticks that are still in the future
+        self.finish_stepped_tick(Self::tick_node);
         self.scheduler.reset(world, &self.blocks);
 
         let nodes = std::mem::take(&mut self.nodes);
@@ -206,6 +305,7 @@ impl JITBackend for DirectBackend {
         self.pos_map.clear();
         self.noteblock_info.clear();
         self.events.clear();
+        self.probes.clear();
     }
 
     fn on_use_block(&mut self, pos: BlockPos) {
@@ -222,6 +322,10 @@ impl JITBackend for DirectBackend {
             NodeType::Lever => {
                 self.set_node(node_id, !node.powered, bool_to_ss(!node.powered));
             }
+            NodeType::DaylightDetector => {
+                let power = daylight_detector::next_power(node.output_power);
+                self.set_node(node_id, power > 0, power);
+            }
             _ => warn!("Tried to use a {:?} redpiler node", node.ty),
         }
     }
@@ -237,7 +341,74 @@ impl JITBackend for DirectBackend {
         }
     }
 
+    fn set_daylight_detector(&mut self, pos: BlockPos, power: u8) {
+        let node_id = self.pos_map[&pos];
+        let node = &self.nodes[node_id];
+        match node.ty {
+            NodeType::DaylightDetector => {
+                self.set_node(node_id, power > 0, power);
+            }
+            _ => warn!("Tried to set daylight detector power for a {:?}", node.ty),
+        }
+    }
+
+    fn hit_target(&mut self, pos: BlockPos, power: u8, ticks: u32) -> bool {
+        let node_id = self.pos_map[&pos];
+        let node = &self.nodes[node_id];
+        match node.ty {
+            NodeType::Target => {
+                if node.pending_tick {
+                    return false;
+                }
+                let node = &mut self.nodes[node_id];
+                schedule_tick(
+                    &mut self.scheduler,
+                    node_id,
+                    node,
+                    ticks as usize,
+                    TickPriority::Normal,
+                );
+                self.set_node(node_id, power > 0, power);
+                true
+            }
+            _ => {
+                warn!("Tried to hit a {:?} as a target", node.ty);
+                false
+            }
+        }
+    }
+
+    fn trigger_tripwire_hook(&mut self, pos: BlockPos, ticks: u32) -> bool {
+        let node_id = self.pos_map[&pos];
+        let node = &self.nodes[node_id];
+        match node.ty {
+            NodeType::TripwireHook => {
+                if node.pending_tick {
+                    return false;
+                }
+                let node = &mut self.nodes[node_id];
+                schedule_tick(
+                    &mut self.scheduler,
+                    node_id,
+                    node,
+                    ticks as usize,
+                    TickPriority::Normal,
+                );
+                self.set_node(node_id, true, 15);
+                true
+            }
+            _ => {
+                warn!("Tried to trigger a {:?} as a tripwire hook", node.ty);
+                false
+            }
+        }
+    }
+
     fn tick(&mut self) {
+        if self.stepped_priorities != 0 {
+            self.finish_stepped_tick(Self::tick_node);
+            return;
+        }
         let mut queues = self.scheduler.queues_this_tick();
 
         queues.drain_each(|node_id| {
@@ -245,6 +416,7 @@ impl JITBackend for DirectBackend {
         });
 
         self.scheduler.end_tick(queues);
+        self.record_probes();
     }
 
     fn flush<W: World>(&mut self, world: &mut W, io_only: bool) {
@@ -276,6 +448,12 @@ impl JITBackend for DirectBackend {
                 if let Block::Repeater(repeater) = block {
                     repeater.locked = node.locked;
                 }
+                if let Block::Target { power } | Block::DaylightDetector { power, .. } = block {
+                    *power = node.output_power;
+                }
+                if let Some((lit, _)) = block.get_copper_bulb_state() {
+                    *lit = node.output_power > 0;
+                }
                 world.set_block(*pos, *block);
             }
         }
@@ -288,12 +466,186 @@ impl JITBackend for DirectBackend {
         options: &CompilerOptions,
         monitor: Arc<TaskMonitor>,
     ) {
+        self.ticks = 0;
+        self.stepped_priorities = 0;
+        self.probes.clear();
         compile::compile(self, graph, ticks, options, monitor);
     }
 
     fn has_pending_ticks(&self) -> bool {
         self.scheduler.has_pending_ticks()
     }
+
+    fn snapshot(&self) -> BackendSnapshot {
+        BackendSnapshot {
+            partitions: vec![self.snapshot_state()],
+        }
+    }
+
+    fn restore(&mut self, snapshot: &BackendSnapshot) -> bool {
+        match snapshot.partitions.as_slice() {
+            [state] => self.restore_state(state),
+            _ => false,
+        }
+    }
+
+    fn step_priority(&mut self) -> TickPriority {
+        self.step_priority_with(Self::tick_node)
+    }
+
+    fn node_state(&self, pos: BlockPos) -> Option<NodeState> {
+        let node = &self.nodes[*self.pos_map.get(&pos)?];
+        Some(NodeState {
+            powered: node.powered,
+            repeater_locked: node.locked,
+            output_strength: node.output_power,
+        })
+    }
+
+    fn add_probe(&mut self, probe: Probe) -> bool {
+        let Some(&node_id) = self.pos_map.get(&probe.pos) else {
+            return false;
+        };
+        let power = probe_power(&self.nodes[node_id]);
+        self.probes
+            .push((node_id, Signal::new(probe, self.ticks, power)));
+        true
+    }
+
+    fn clear_probes(&mut self) {
+        self.probes.clear();
+    }
+
+    fn trace(&self) -> Trace {
+        Trace {
+            end: self.ticks,
+            signals: self
+                .probes
+                .iter()
+                .map(|(_, signal)| signal.clone())
+                .collect(),
+        }
+    }
+}
+
+impl DirectBackend {
+    /// Runs the next priority level of the current tick, starting a new tick if there is none in
+    /// progress. `tick_node` is used to tick each node.
+    pub(super) fn step_priority_with(
+        &mut self,
+        mut tick_node: impl FnMut(&mut DirectBackend, NodeId),
+    ) -> TickPriority {
+        if self.stepped_priorities == 0 {
+            self.scheduler.start_tick();
+        }
+        let priority = self.stepped_priorities;
+        let queue = self.scheduler.take_queue(priority);
+        for node_id in queue.iter().copied() {
+            tick_node(self, node_id);
+        }
+        self.scheduler.return_queue(priority, queue);
+
+        self.stepped_priorities = (priority + 1) % TickScheduler::NUM_PRIORITIES;
+        if self.stepped_priorities == 0 {
+            self.record_probes();
+        }
+        TickScheduler::priorities()[priority]
+    }
+
+    /// Runs the remaining priorities of a tick that was stepped into.
+    pub(super) fn finish_stepped_tick(
+        &mut self,
+        mut tick_node: impl FnMut(&mut DirectBackend, NodeId),
+    ) {
+        while self.stepped_priorities != 0 {
+            self.step_priority_with(&mut tick_node);
+        }
+    }
+
+    /// Advances the tick count and records the output strength of every probe.
+    pub(super) fn record_probes(&mut self) {
+        self.ticks += 1;
+        for (node_id, signal) in &mut self.probes {
+            signal.record(self.ticks, probe_power(&self.nodes[*node_id]));
+        }
+    }
+
+    pub(super) fn snapshot_state(&self) -> DirectSnapshot {
+        let nodes = self
+            .nodes
+            .inner()
+            .iter()
+            .map(|node| NodeSnapshot {
+                default_inputs: node.default_inputs.ss_counts,
+                side_inputs: node.side_inputs.ss_counts,
+                powered: node.powered,
+                locked: node.locked,
+                output_power: node.output_power,
+                pending_tick: node.pending_tick,
+            })
+            .collect();
+        DirectSnapshot {
+            nodes,
+            scheduler: self.scheduler.clone(),
+            stepped_priorities: self.stepped_priorities,
+        }
+    }
+
+    /// Snapshots may have been loaded from disk, so everything the unchecked indexing relies on
+    /// is validated before restoring one.
+    pub(super) fn fits_snapshot(&self, state: &DirectSnapshot) -> bool {
+        let len = self.nodes.inner().len();
+        len == state.nodes.len()
+            && state.nodes.iter().all(|node| {
+                node.output_power <= 15
+                    && counts_add_up(&node.default_inputs)
+                    && counts_add_up(&node.side_inputs)
+            })
+            && state.stepped_priorities < TickScheduler::NUM_PRIORITIES
+            && state.scheduler.pos < TickScheduler::NUM_QUEUES
+            && state.scheduler.queues_deque.iter().all(|queues| {
+                queues
+                    .0
+                    .iter()
+                    .flatten()
+                    .all(|node_id| node_id.index() < len)
+            })
+    }
+
+    pub(super) fn restore_state(&mut self, state: &DirectSnapshot) -> bool {
+        if !self.fits_snapshot(state) {
+            return false;
+        }
+        for (node, saved) in self.nodes.inner_mut().iter_mut().zip(state.nodes.iter()) {
+            node.default_inputs.ss_counts = saved.default_inputs;
+            node.side_inputs.ss_counts = saved.side_inputs;
+            node.powered = saved.powered;
+            node.locked = saved.locked;
+            node.output_power = saved.output_power;
+            node.pending_tick = saved.pending_tick;
+            node.observed = observed_state(node);
+            // Every block may differ from the world, so all of them need to be flushed
+            node.changed = true;
+        }
+        self.scheduler = state.scheduler.clone();
+        self.stepped_priorities = state.stepped_priorities;
+        true
+    }
+}
+
+/// Whether the signal strength buckets add up to 255 like after compiling, which `get_bool_input`
+/// relies on.
+fn counts_add_up(ss_counts: &[u8; 16]) -> bool {
+    ss_counts.iter().map(|&count| count as u32).sum::<u32>() == 255
+}
+
+/// The strength shown for a probed node. Nodes without an output strength, such as lamps, show
+/// as fully powered when they are on.
+fn probe_power(node: &Node) -> u8 {
+    match node.output_power {
+        0 if node.powered => 15,
+        power => power,
+    }
 }
 
 /// Set node for use in `update`. None of the nodes here have usable output power,
@@ -347,6 +699,14 @@ fn get_all_input(node: &Node) -> (u8, u8) {
     (input_power, side_input_power)
 }
 
+/// The state of the block an observer is watching, as far as the observer's inputs tell: the power
+/// level on its default inputs, or whether its side input is powered. Comparators are linked on
+/// the side, since their output strength is not part of their block state.
+pub(super) fn observed_state(node: &Node) -> u8 {
+    let (input_power, side_input_power) = get_all_input(node);
+    input_power.max(u8::from(side_input_power > 0))
+}
+
 // This function is optimized for input values from 0 to 15 and does not work correctly outside that
 // range
 fn calculate_comparator_output(mode: ComparatorMode, input_strength: u8, power_on_sides: u8) -> u8 {
@@ -386,6 +746,11 @@ impl fmt::Display for DirectBackend {
                 NodeType::Wire => "Wire".to_string(),
                 NodeType::Constant => format!("Constant({})", node.output_power),
                 NodeType::NoteBlock { .. } => "NoteBlock".to_string(),
+                NodeType::Observer => "Observer".to_string(),
+                NodeType::Target => "Target".to_string(),
+                NodeType::TripwireHook => "TripwireHook".to_string(),
+                NodeType::DaylightDetector => format!("DaylightDetector({})", node.output_power),
+                NodeType::CopperBulb => "CopperBulb".to_string(),
             };
             let pos = if !self.blocks[id].is_empty() {
                 let mut string = String::new();
@@ -1,8 +1,9 @@
 use mchprs_blocks::blocks::ComparatorMode;
+use serde::{Deserialize, Serialize};
 use std::num::NonZeroU8;
 use std::ops::{Index, IndexMut};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct NodeId(u32);
 
 impl NodeId {
@@ -162,6 +163,11 @@ pub enum NodeType {
     NoteBlock {
         noteblock_id: u16,
     },
+    Observer,
+    Target,
+    TripwireHook,
+    DaylightDetector,
+    CopperBulb,
 }
 
 #[repr(align(16))]
@@ -202,6 +208,8 @@ pub struct Node {
     pub powered: bool,
     /// Only for repeaters
     pub locked: bool,
+    /// Only for observers: the state of the watched block when the observer was last updated
+    pub observed: u8,
     pub output_power: u8,
     pub changed: bool,
     pub pending_tick: bool,
@@ -57,11 +57,34 @@ impl DirectBackend {
                     self.set_node(node_id, false, 0);
                 }
             }
-            NodeType::Button => {
+            NodeType::Button | NodeType::TripwireHook => {
                 if node.powered {
                     self.set_node(node_id, false, 0);
                 }
             }
+            NodeType::Target => {
+                if node.output_power != 0 {
+                    self.set_node(node_id, false, 0);
+                }
+            }
+            NodeType::CopperBulb => {
+                let should_be_powered = get_bool_input(node);
+                if node.powered != should_be_powered {
+                    // The bulb only toggles when it becomes powered
+                    let lit = (node.output_power > 0) != should_be_powered;
+                    self.set_node(node_id, should_be_powered, bool_to_ss(lit));
+                }
+            }
+            NodeType::Observer => {
+                if node.powered {
+                    self.set_node(node_id, false, 0);
+                } else {
+                    // Observers watching this one need to be scheduled before the unpower tick
+                    self.set_node(node_id, true, 15);
+                    let node = &mut self.nodes[node_id];
+                    schedule_tick(&mut self.scheduler, node_id, node, 1, TickPriority::Normal);
+                }
+            }
             _ => {} //unreachable!("Node {:?} should not be ticked!", node.ty),
         }
     }
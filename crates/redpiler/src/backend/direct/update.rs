@@ -102,6 +102,25 @@ pub(super) fn update_node(
                 }
             }
         }
+        NodeType::CopperBulb => {
+            if node.pending_tick {
+                return;
+            }
+            let should_be_powered = get_bool_input(node);
+            if node.powered != should_be_powered {
+                schedule_tick(scheduler, node_id, node, 1, TickPriority::Normal);
+            }
+        }
+        NodeType::Observer => {
+            let observed = observed_state(node);
+            if observed == node.observed {
+                return;
+            }
+            node.observed = observed;
+            if !node.pending_tick {
+                schedule_tick(scheduler, node_id, node, 1, TickPriority::Normal);
+            }
+        }
         _ => {} // unreachable!("Node {:?} should not be updated!", node.ty),
     }
 }
@@ -1,13 +1,17 @@
+pub mod cranelift;
 pub mod direct;
+pub mod parallel;
 
 use std::sync::Arc;
 
-use super::compile_graph::CompileGraph;
+use super::compile_graph::{CompileGraph, LinkType, NodeState};
 use super::task_monitor::TaskMonitor;
+use super::trace::{Probe, Trace};
 use super::CompilerOptions;
 use enum_dispatch::enum_dispatch;
 use mchprs_blocks::BlockPos;
-use mchprs_world::{TickEntry, World};
+use mchprs_world::{TickEntry, TickPriority, World};
+use serde::{Deserialize, Serialize};
 
 #[enum_dispatch]
 pub trait JITBackend {
@@ -19,6 +23,9 @@ pub trait JITBackend {
         monitor: Arc<TaskMonitor>,
     );
     fn tick(&mut self);
+    /// Runs the ticks of a single priority level and returns that priority. Once the lowest
+    /// priority has run, the tick is complete. A partially stepped tick is finished by `tick`.
+    fn step_priority(&mut self) -> TickPriority;
 
     fn tickn(&mut self, ticks: u64) {
         for _ in 0..ticks {
@@ -28,16 +35,89 @@ pub trait JITBackend {
 
     fn on_use_block(&mut self, pos: BlockPos);
     fn set_pressure_plate(&mut self, pos: BlockPos, powered: bool);
+    fn set_daylight_detector(&mut self, pos: BlockPos, power: u8);
+    /// Simulates a projectile hitting a target block, powering it with `power` for `ticks` ticks.
+    /// Returns false if the hit was ignored because the target is still powered.
+    fn hit_target(&mut self, pos: BlockPos, power: u8, ticks: u32) -> bool;
+    /// Powers a tripwire hook for `ticks` ticks, as if the string it is attached to was triggered.
+    /// Returns false if the hook is still powered.
+    fn trigger_tripwire_hook(&mut self, pos: BlockPos, ticks: u32) -> bool;
     fn flush<W: World>(&mut self, world: &mut W, io_only: bool);
     fn reset<W: World>(&mut self, world: &mut W, io_only: bool);
     fn has_pending_ticks(&self) -> bool;
-    /// Inspect block for debugging
-    fn inspect(&mut self, pos: BlockPos);
+    /// Captures the state of every node along with the pending ticks.
+    fn snapshot(&self) -> BackendSnapshot;
+    /// Rewinds the simulation to a snapshot taken since the backend was last compiled. Returns
+    /// false if the snapshot does not fit the compiled graph.
+    fn restore(&mut self, snapshot: &BackendSnapshot) -> bool;
+    /// Compiles `graph` and restores a snapshot taken from the same graph, possibly by a backend
+    /// in another process. Returns false if the snapshot does not fit the graph.
+    fn resume(
+        &mut self,
+        graph: CompileGraph,
+        options: &CompilerOptions,
+        monitor: Arc<TaskMonitor>,
+        snapshot: &BackendSnapshot,
+    ) -> bool {
+        self.compile(graph, Vec::new(), options, monitor);
+        self.restore(snapshot)
+    }
+    /// Starts recording the output strength of the node at the probe's position every tick.
+    /// Returns false if there is no node there.
+    fn add_probe(&mut self, probe: Probe) -> bool;
+    /// Removes all probes along with their recorded signals.
+    fn clear_probes(&mut self);
+    /// Returns the signals recorded by the probes.
+    fn trace(&self) -> Trace;
+    /// Returns the current state of the node at `pos`.
+    fn node_state(&self, pos: BlockPos) -> Option<NodeState>;
+    /// Describes the node at `pos` along with its links, for debugging.
+    fn inspect(&self, pos: BlockPos) -> Option<NodeInspection>;
 }
 
-use direct::DirectBackend;
+use cranelift::CraneliftBackend;
+use direct::{DirectBackend, DirectSnapshot};
+use parallel::ParallelBackend;
 
+// Only one backend exists at a time, so the size difference between variants does not matter
+#[allow(clippy::large_enum_variant)]
 #[enum_dispatch(JITBackend)]
 pub enum BackendDispatcher {
     DirectBackend,
+    CraneliftBackend,
+    ParallelBackend,
+}
+
+/// A description of a single node, returned by [`JITBackend::inspect`].
+#[derive(Debug, Clone)]
+pub struct NodeInspection {
+    /// The index of the node in the backend
+    pub id: usize,
+    /// The node type along with its properties, such as the delay of a repeater
+    pub ty: String,
+    pub state: NodeState,
+    pub is_io: bool,
+    /// How many ticks from now each tick scheduled for the node runs, with its priority
+    pub pending_ticks: Vec<(usize, TickPriority)>,
+    pub inputs: Vec<InspectedLink>,
+    pub outputs: Vec<InspectedLink>,
+}
+
+/// A link from or to an inspected node.
+#[derive(Debug, Clone)]
+pub struct InspectedLink {
+    pub ty: LinkType,
+    /// The signal strength lost along the link
+    pub weight: u8,
+    /// The position of the node on the other end, if it has a block
+    pub pos: Option<BlockPos>,
+    /// The output strength of the node on the other end
+    pub power: u8,
+}
+
+/// The simulation state of a backend, captured by [`JITBackend::snapshot`].
+#[derive(Serialize, Deserialize)]
+pub struct BackendSnapshot {
+    /// The state of each [`DirectBackend`] simulating part of the graph.
+    partitions: Vec<DirectSnapshot>,
 }
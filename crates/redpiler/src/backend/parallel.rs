@@ -0,0 +1,403 @@
+//! The parallel backend splits the graph into independent parts and runs each part on its own
+//! [`DirectBackend`].
+//!
+//! Nodes in different weakly connected components of the graph can never affect each other, so
+//! ticking the components on separate threads gives the same results as ticking them together.
+//! The components are grouped into at most one partition per available thread, balancing the
+//! number of nodes in each partition.
+//!
+//! Spawning the threads costs more than running a single tick of most circuits, so only batches of
+//! ticks run in parallel. Single ticks, like when stepping through a circuit, run the partitions
+//! one after another.
+
+use super::direct::DirectBackend;
+use super::{BackendSnapshot, JITBackend, NodeInspection};
+use crate::compile_graph::{CompileGraph, NodeIdx, NodeState};
+use crate::task_monitor::TaskMonitor;
+use crate::trace::{Probe, Trace};
+use crate::CompilerOptions;
+use mchprs_blocks::BlockPos;
+use mchprs_world::{TickEntry, TickPriority, World};
+use rustc_hash::FxHashMap;
+use std::sync::Arc;
+use std::thread;
+use tracing::{debug, trace};
+
+#[derive(Default)]
+pub struct ParallelBackend {
+    partitions: Vec<DirectBackend>,
+    /// Maps each block position to the index of the partition simulating it.
+    pos_map: FxHashMap<BlockPos, usize>,
+}
+
+impl ParallelBackend {
+    fn partition(&mut self, pos: BlockPos) -> Option<&mut DirectBackend> {
+        let Some(&idx) = self.pos_map.get(&pos) else {
+            debug!("could not find partition for pos {}", pos);
+            return None;
+        };
+        Some(&mut self.partitions[idx])
+    }
+
+    /// Compiles the graph split into at most `max_partitions` partitions. The split only depends
+    /// on the graph and `max_partitions`.
+    fn compile_partitions(
+        &mut self,
+        graph: CompileGraph,
+        ticks: Vec<TickEntry>,
+        options: &CompilerOptions,
+        monitor: Arc<TaskMonitor>,
+        max_partitions: usize,
+    ) {
+        self.partitions.clear();
+        self.pos_map.clear();
+
+        let graphs = split_graph(graph, max_partitions);
+        trace!("Split graph into {} partitions", graphs.len());
+
+        // Each partition would overwrite the same dot file
+        let partition_options = CompilerOptions {
+            export_dot_graph: false,
+            ..options.clone()
+        };
+        for (idx, graph) in graphs.into_iter().enumerate() {
+            for node in graph.all_node_weights() {
+                for &(pos, _) in &node.block {
+                    self.pos_map.insert(pos, idx);
+                }
+            }
+            let mut partition = DirectBackend::default();
+            partition.compile(graph, ticks.clone(), &partition_options, monitor.clone());
+            if options.export_dot_graph {
+                std::fs::write(
+                    format!("backend_graph_{}.dot", idx),
+                    format!("{}", partition),
+                )
+                .unwrap();
+            }
+            self.partitions.push(partition);
+        }
+    }
+
+    /// Runs `f` on every partition, using a separate thread for all but the first.
+    fn for_each_partition_parallel<F>(&mut self, f: F)
+    where
+        F: Fn(&mut DirectBackend) + Sync,
+    {
+        let Some((first, rest)) = self.partitions.split_first_mut() else {
+            return;
+        };
+        let f = &f;
+        thread::scope(|s| {
+            for partition in rest {
+                s.spawn(move || f(partition));
+            }
+            f(first);
+        });
+    }
+}
+
+/// Finds the weakly connected component of every node, returning the components as lists of nodes.
+fn find_components(graph: &CompileGraph) -> Vec<Vec<NodeIdx>> {
+    let mut parents: Vec<usize> = (0..graph.node_bound()).collect();
+
+    fn find(parents: &mut [usize], mut idx: usize) -> usize {
+        while parents[idx] != idx {
+            parents[idx] = parents[parents[idx]];
+            idx = parents[idx];
+        }
+        idx
+    }
+
+    for edge in graph.all_edges() {
+        let a = find(&mut parents, edge.source().index());
+        let b = find(&mut parents, edge.target().index());
+        parents[a] = b;
+    }
+
+    let mut components: FxHashMap<usize, Vec<NodeIdx>> = FxHashMap::default();
+    for idx in graph.node_indices() {
+        let root = find(&mut parents, idx.index());
+        components.entry(root).or_default().push(idx);
+    }
+    let mut components: Vec<_> = components.into_values().collect();
+    // Largest first, and in a deterministic order for equally sized components
+    components.sort_by_key(|nodes| (std::cmp::Reverse(nodes.len()), nodes[0].index()));
+    components
+}
+
+/// Splits `graph` into at most `max_partitions` graphs that do not share any links.
+fn split_graph(mut graph: CompileGraph, max_partitions: usize) -> Vec<CompileGraph> {
+    let components = find_components(&graph);
+    let num_partitions = components.len().clamp(1, max_partitions.max(1));
+
+    // Give each component to the partition with the fewest nodes so far
+    let mut partition_sizes = vec![0; num_partitions];
+    let mut node_partition = vec![0; graph.node_bound()];
+    for nodes in &components {
+        let (partition, _) = partition_sizes
+            .iter()
+            .enumerate()
+            .min_by_key(|&(idx, size)| (*size, idx))
+            .unwrap();
+        partition_sizes[partition] += nodes.len();
+        for node in nodes {
+            node_partition[node.index()] = partition;
+        }
+    }
+
+    let edges: Vec<_> = graph
+        .all_edges()
+        .map(|edge| (edge.source(), edge.target(), *edge.weight()))
+        .collect();
+    let mut partitions: Vec<CompileGraph> = (0..num_partitions)
+        .map(|_| CompileGraph::default())
+        .collect();
+    let mut node_map = FxHashMap::default();
+    let node_indices: Vec<_> = graph.node_indices().collect();
+    for idx in node_indices {
+        let node = graph.remove_node(idx).unwrap();
+        let partition = node_partition[idx.index()];
+        node_map.insert(idx, partitions[partition].add_node(node));
+    }
+    for (source, target, weight) in edges {
+        let partition = node_partition[source.index()];
+        partitions[partition].add_edge(node_map[&source], node_map[&target], weight);
+    }
+    partitions
+}
+
+impl JITBackend for ParallelBackend {
+    fn compile(
+        &mut self,
+        graph: CompileGraph,
+        ticks: Vec<TickEntry>,
+        options: &CompilerOptions,
+        monitor: Arc<TaskMonitor>,
+    ) {
+        let max_partitions = thread::available_parallelism().map_or(1, |n| n.get());
+        self.compile_partitions(graph, ticks, options, monitor, max_partitions);
+    }
+
+    fn tick(&mut self) {
+        for partition in &mut self.partitions {
+            partition.tick();
+        }
+    }
+
+    fn tickn(&mut self, ticks: u64) {
+        if ticks <= 1 {
+            for _ in 0..ticks {
+                self.tick();
+            }
+            return;
+        }
+        // Partitions never interact, so each one can run all of the ticks on its own
+        self.for_each_partition_parallel(|partition| partition.tickn(ticks));
+    }
+
+    fn step_priority(&mut self) -> TickPriority {
+        // Every partition is at the same point in the tick. Without any partitions there is
+        // nothing to step through, so every step completes a tick.
+        let mut priority = TickPriority::Normal;
+        for partition in &mut self.partitions {
+            priority = partition.step_priority();
+        }
+        priority
+    }
+
+    fn on_use_block(&mut self, pos: BlockPos) {
+        if let Some(partition) = self.partition(pos) {
+            partition.on_use_block(pos);
+        }
+    }
+
+    fn set_pressure_plate(&mut self, pos: BlockPos, powered: bool) {
+        if let Some(partition) = self.partition(pos) {
+            partition.set_pressure_plate(pos, powered);
+        }
+    }
+
+    fn set_daylight_detector(&mut self, pos: BlockPos, power: u8) {
+        if let Some(partition) = self.partition(pos) {
+            partition.set_daylight_detector(pos, power);
+        }
+    }
+
+    fn hit_target(&mut self, pos: BlockPos, power: u8, ticks: u32) -> bool {
+        self.partition(pos)
+            .is_some_and(|partition| partition.hit_target(pos, power, ticks))
+    }
+
+    fn trigger_tripwire_hook(&mut self, pos: BlockPos, ticks: u32) -> bool {
+        self.partition(pos)
+            .is_some_and(|partition| partition.trigger_tripwire_hook(pos, ticks))
+    }
+
+    fn flush<W: World>(&mut self, world: &mut W, io_only: bool) {
+        for partition in &mut self.partitions {
+            partition.flush(world, io_only);
+        }
+    }
+
+    fn reset<W: World>(&mut self, world: &mut W, io_only: bool) {
+        for partition in &mut self.partitions {
+            partition.reset(world, io_only);
+        }
+        self.partitions.clear();
+        self.pos_map.clear();
+    }
+
+    fn has_pending_ticks(&self) -> bool {
+        self.partitions
+            .iter()
+            .any(|partition| partition.has_pending_ticks())
+    }
+
+    fn node_state(&self, pos: BlockPos) -> Option<NodeState> {
+        let &idx = self.pos_map.get(&pos)?;
+        self.partitions[idx].node_state(pos)
+    }
+
+    fn inspect(&self, pos: BlockPos) -> Option<NodeInspection> {
+        let &idx = self.pos_map.get(&pos)?;
+        self.partitions[idx].inspect(pos)
+    }
+
+    fn snapshot(&self) -> BackendSnapshot {
+        BackendSnapshot {
+            partitions: self
+                .partitions
+                .iter()
+                .map(|partition| partition.snapshot_state())
+                .collect(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: &BackendSnapshot) -> bool {
+        if snapshot.partitions.len() != self.partitions.len() {
+            return false;
+        }
+        let fits = self
+            .partitions
+            .iter()
+            .zip(&snapshot.partitions)
+            .all(|(partition, state)| partition.fits_snapshot(state));
+        if !fits {
+            return false;
+        }
+        for (partition, state) in self.partitions.iter_mut().zip(&snapshot.partitions) {
+            partition.restore_state(state);
+        }
+        true
+    }
+
+    fn resume(
+        &mut self,
+        graph: CompileGraph,
+        options: &CompilerOptions,
+        monitor: Arc<TaskMonitor>,
+        snapshot: &BackendSnapshot,
+    ) -> bool {
+        // The snapshot may come from a machine with a different number of threads. Splitting the
+        // graph into as many partitions as the snapshot has gives the same partitions again.
+        let max_partitions = snapshot.partitions.len();
+        self.compile_partitions(graph, Vec::new(), options, monitor, max_partitions);
+        self.restore(snapshot)
+    }
+
+    fn add_probe(&mut self, probe: Probe) -> bool {
+        let pos = probe.pos;
+        self.partition(pos)
+            .is_some_and(|partition| partition.add_probe(probe))
+    }
+
+    fn clear_probes(&mut self) {
+        for partition in &mut self.partitions {
+            partition.clear_probes();
+        }
+    }
+
+    fn trace(&self) -> Trace {
+        // Every partition has run the same number of ticks
+        let mut trace = Trace::default();
+        for partition in &self.partitions {
+            let partition_trace = partition.trace();
+            trace.end = partition_trace.end;
+            trace.signals.extend(partition_trace.signals);
+        }
+        trace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile_graph::{CompileLink, CompileNode, NodeState, NodeType};
+
+    fn add_node(graph: &mut CompileGraph, x: i32) -> NodeIdx {
+        graph.add_node(CompileNode {
+            ty: NodeType::Torch,
+            block: smallvec::smallvec![(BlockPos::new(x, 0, 0), 0)],
+            name: None,
+            state: NodeState::simple(false),
+            is_input: false,
+            is_output: false,
+            annotations: Default::default(),
+        })
+    }
+
+    #[test]
+    fn split_keeps_components_together() {
+        let mut graph = CompileGraph::default();
+        let a = add_node(&mut graph, 0);
+        let b = add_node(&mut graph, 1);
+        let c = add_node(&mut graph, 2);
+        let d = add_node(&mut graph, 3);
+        let e = add_node(&mut graph, 4);
+        graph.add_edge(a, b, CompileLink::default(0));
+        graph.add_edge(c, b, CompileLink::default(0));
+        graph.add_edge(d, e, CompileLink::side(1));
+
+        let partitions = split_graph(graph, 4);
+        assert_eq!(partitions.len(), 2);
+        let sizes: Vec<_> = partitions.iter().map(|g| g.node_count()).collect();
+        let edges: Vec<_> = partitions.iter().map(|g| g.edge_count()).collect();
+        assert_eq!(sizes, [3, 2]);
+        assert_eq!(edges, [2, 1]);
+
+        let mut graph = CompileGraph::default();
+        for x in 0..5 {
+            add_node(&mut graph, x);
+        }
+        let partitions = split_graph(graph, 2);
+        let sizes: Vec<_> = partitions.iter().map(|g| g.node_count()).collect();
+        assert_eq!(sizes, [3, 2]);
+    }
+
+    #[test]
+    fn resume_with_different_thread_count() {
+        let mut graph = CompileGraph::default();
+        for x in 0..4 {
+            add_node(&mut graph, x);
+        }
+        let lever = BlockPos::new(0, 0, 0);
+        graph[NodeIdx::new(0)].ty = NodeType::Lever;
+        let options = CompilerOptions::default();
+        let mut backend = ParallelBackend::default();
+        backend.compile_partitions(graph.clone(), Vec::new(), &options, Default::default(), 4);
+        backend.on_use_block(lever);
+        let snapshot = backend.snapshot();
+        assert_eq!(snapshot.partitions.len(), 4);
+
+        // Compiling on a machine with fewer threads splits the graph differently
+        let mut backend = ParallelBackend::default();
+        backend.compile_partitions(graph.clone(), Vec::new(), &options, Default::default(), 2);
+        assert!(!backend.restore(&snapshot));
+
+        let mut backend = ParallelBackend::default();
+        assert!(backend.resume(graph, &options, Default::default(), &snapshot));
+        assert_eq!(backend.partitions.len(), 4);
+        assert!(backend.node_state(lever).unwrap().powered);
+    }
+}
@@ -0,0 +1,164 @@
+//! Conditional breakpoints that pause the simulation when a node changes.
+
+use crate::compile_graph::NodeState;
+use mchprs_blocks::BlockPos;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl Comparison {
+    fn as_str(self) -> &'static str {
+        match self {
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::LessEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterEqual => ">=",
+        }
+    }
+
+    fn compare(self, lhs: u8, rhs: u8) -> bool {
+        match self {
+            Comparison::Equal => lhs == rhs,
+            Comparison::NotEqual => lhs != rhs,
+            Comparison::Less => lhs < rhs,
+            Comparison::LessEqual => lhs <= rhs,
+            Comparison::Greater => lhs > rhs,
+            Comparison::GreaterEqual => lhs >= rhs,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    Powered,
+    Unpowered,
+    /// Compares the output signal strength with a value.
+    Strength(Comparison, u8),
+}
+
+impl Condition {
+    pub fn is_met(self, state: &NodeState) -> bool {
+        match self {
+            Condition::Powered => state.powered,
+            Condition::Unpowered => !state.powered,
+            Condition::Strength(cmp, value) => cmp.compare(state.output_strength, value),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseConditionError;
+
+impl fmt::Display for ParseConditionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected powered, unpowered or a comparison like >=8")
+    }
+}
+
+impl FromStr for Condition {
+    type Err = ParseConditionError;
+
+    fn from_str(s: &str) -> Result<Condition, ParseConditionError> {
+        match s {
+            "powered" => return Ok(Condition::Powered),
+            "unpowered" => return Ok(Condition::Unpowered),
+            _ => {}
+        }
+        // Two character operators have to be tried first
+        let comparisons = [
+            Comparison::Equal,
+            Comparison::NotEqual,
+            Comparison::LessEqual,
+            Comparison::GreaterEqual,
+            Comparison::Less,
+            Comparison::Greater,
+        ];
+        for cmp in comparisons {
+            if let Some(value) = s.strip_prefix(cmp.as_str()) {
+                return match value.parse() {
+                    Ok(value) if value <= 15 => Ok(Condition::Strength(cmp, value)),
+                    _ => Err(ParseConditionError),
+                };
+            }
+        }
+        Err(ParseConditionError)
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Powered => write!(f, "powered"),
+            Condition::Unpowered => write!(f, "unpowered"),
+            Condition::Strength(cmp, value) => write!(f, "ss {} {}", cmp.as_str(), value),
+        }
+    }
+}
+
+/// Pauses the simulation when the condition becomes met for the node at `pos`.
+#[derive(Debug, Clone)]
+pub struct Breakpoint {
+    pub id: u32,
+    pub pos: BlockPos,
+    pub condition: Condition,
+    /// Whether the condition was met the last time it was checked. The breakpoint only triggers
+    /// when this changes to true.
+    pub(crate) was_met: bool,
+}
+
+impl Breakpoint {
+    /// Checks the condition against the current state. Returns true if the breakpoint triggered.
+    pub(crate) fn check(&mut self, state: &NodeState) -> bool {
+        let met = self.condition.is_met(state);
+        let triggered = met && !self.was_met;
+        self.was_met = met;
+        triggered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_conditions() {
+        assert_eq!("powered".parse(), Ok(Condition::Powered));
+        assert_eq!(
+            ">=8".parse(),
+            Ok(Condition::Strength(Comparison::GreaterEqual, 8))
+        );
+        assert_eq!("<3".parse(), Ok(Condition::Strength(Comparison::Less, 3)));
+        assert_eq!(
+            "!=0".parse(),
+            Ok(Condition::Strength(Comparison::NotEqual, 0))
+        );
+        assert_eq!(">=16".parse::<Condition>(), Err(ParseConditionError));
+        assert_eq!("on".parse::<Condition>(), Err(ParseConditionError));
+    }
+
+    #[test]
+    fn triggers_on_change() {
+        let mut breakpoint = Breakpoint {
+            id: 0,
+            pos: BlockPos::new(0, 0, 0),
+            condition: Condition::Strength(Comparison::GreaterEqual, 8),
+            was_met: false,
+        };
+        assert!(!breakpoint.check(&NodeState::ss(7)));
+        assert!(breakpoint.check(&NodeState::ss(8)));
+        assert!(!breakpoint.check(&NodeState::ss(9)));
+        assert!(!breakpoint.check(&NodeState::ss(0)));
+        assert!(breakpoint.check(&NodeState::ss(15)));
+    }
+}
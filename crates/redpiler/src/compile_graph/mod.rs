@@ -1,5 +1,6 @@
 use mchprs_blocks::blocks::{ComparatorMode, Instrument};
 use mchprs_blocks::BlockPos;
+use serde::{Deserialize, Serialize};
 // use petgraph::stable_graph::{NodeIndex, StableGraph};
 use smallvec::SmallVec;
 use stable_graph::{NodeIndex, StableGraph};
@@ -9,7 +10,7 @@ mod stable_graph;
 pub use stable_graph::{Direction, EdgeRef};
 pub type NodeIdx = NodeIndex<u32>;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum NodeType {
     Repeater {
         delay: u8,
@@ -32,25 +33,35 @@ pub enum NodeType {
         instrument: Instrument,
         note: u8,
     },
+    Observer,
+    Target,
+    TripwireHook,
+    DaylightDetector,
+    CopperBulb,
 }
 
 impl NodeType {
     pub fn is_normally_input(&self) -> bool {
         matches!(
             self,
-            NodeType::Button | NodeType::Lever | NodeType::PressurePlate
+            NodeType::Button
+                | NodeType::Lever
+                | NodeType::PressurePlate
+                | NodeType::Target
+                | NodeType::TripwireHook
+                | NodeType::DaylightDetector
         )
     }
 
     pub fn is_normally_output(&self) -> bool {
         matches!(
             self,
-            NodeType::Trapdoor | NodeType::Lamp | NodeType::NoteBlock { .. }
+            NodeType::Trapdoor | NodeType::Lamp | NodeType::NoteBlock { .. } | NodeType::CopperBulb
         )
     }
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct NodeState {
     pub powered: bool,
     pub repeater_locked: bool,
@@ -81,6 +92,15 @@ impl NodeState {
         }
     }
 
+    /// Copper bulbs store whether they are lit as their output strength.
+    pub fn copper_bulb(lit: bool, powered: bool) -> NodeState {
+        NodeState {
+            powered,
+            output_strength: if lit { 15 } else { 0 },
+            ..Default::default()
+        }
+    }
+
     pub fn comparator(powered: bool, ss: u8) -> NodeState {
         NodeState {
             powered,
@@ -90,10 +110,10 @@ impl NodeState {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Annotations {}
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompileNode {
     pub ty: NodeType,
     pub block: SmallVec<[(BlockPos, u32); 1]>,
@@ -111,13 +131,13 @@ impl CompileNode {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LinkType {
     Default,
     Side,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CompileLink {
     pub ty: LinkType,
     pub ss: u8,
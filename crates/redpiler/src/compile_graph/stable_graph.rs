@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use std::{iter, slice};
 
@@ -43,7 +44,7 @@ impl IdxT for u32 {
     }
 }
 
-#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct NodeIndex<Idx>(Idx);
 
 impl<Idx: IdxT> NodeIndex<Idx> {
@@ -56,7 +57,7 @@ impl<Idx: IdxT> NodeIndex<Idx> {
     }
 }
 
-#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct EdgeIndex<Idx: IdxT>(Idx);
 
 impl<Idx: IdxT> EdgeIndex<Idx> {
@@ -69,6 +70,7 @@ impl<Idx: IdxT> EdgeIndex<Idx> {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 struct RawNode<Node, Idx: IdxT> {
     value: Option<Node>,
 
@@ -85,6 +87,7 @@ impl<Node, Idx: IdxT> Default for RawNode<Node, Idx> {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 struct EdgeDefinition<Edge, Idx: IdxT> {
     value: Edge,
     endpoints: [NodeIndex<Idx>; 2],
@@ -95,6 +98,7 @@ const DIR_OUTGOING: usize = 1;
 const DIR_PREV: usize = 0;
 const DIR_NEXT: usize = 1;
 
+#[derive(Clone, Serialize, Deserialize)]
 struct RawEdge<Edge, Idx: IdxT> {
     def: Option<EdgeDefinition<Edge, Idx>>,
 
@@ -111,6 +115,7 @@ impl<Edge, Idx: IdxT> Default for RawEdge<Edge, Idx> {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct StableGraph<Node, Edge, Idx: IdxT = usize> {
     nodes: Vec<RawNode<Node, Idx>>,
     edges: Vec<RawEdge<Edge, Idx>>,
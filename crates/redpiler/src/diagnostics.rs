@@ -0,0 +1,111 @@
+//! Warnings and errors found while compiling, reported back to the player.
+
+use mchprs_blocks::BlockPos;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The circuit compiles, but may not behave the same as in vanilla.
+    Warning,
+    /// The circuit can't be compiled.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// The block the diagnostic is about, if any.
+    pub pos: Option<BlockPos>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning: ")?,
+            Severity::Error => write!(f, "error: ")?,
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(pos) = self.pos {
+            write!(f, " at {}", pos)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    fn push(&mut self, severity: Severity, message: impl Into<String>, pos: Option<BlockPos>) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            message: message.into(),
+            pos,
+        });
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>, pos: Option<BlockPos>) {
+        self.push(Severity::Warning, message, pos);
+    }
+
+    pub fn error(&mut self, message: impl Into<String>, pos: Option<BlockPos>) {
+        self.push(Severity::Error, message, pos);
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter()
+    }
+
+    pub fn extend(&mut self, other: Diagnostics) {
+        self.diagnostics.extend(other.diagnostics);
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.diagnostics.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_and_display() {
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.warn("Something odd", Some(BlockPos::new(1, 2, 3)));
+        assert!(!diagnostics.has_errors());
+        diagnostics.error("Something wrong", None);
+        assert!(diagnostics.has_errors());
+
+        let messages: Vec<String> = diagnostics.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "warning: Something odd at (1, 2, 3)",
+                "error: Something wrong"
+            ]
+        );
+    }
+}
@@ -1,20 +1,32 @@
 pub mod backend;
+pub mod breakpoint;
 pub mod compile_graph;
+pub mod diagnostics;
 pub mod passes;
 pub mod ril;
 pub mod string_replacer;
 pub mod task_monitor;
+pub mod trace;
 
-use backend::{BackendDispatcher, JITBackend};
+use backend::{BackendDispatcher, BackendSnapshot, JITBackend, NodeInspection};
+use breakpoint::{Breakpoint, Condition};
+use diagnostics::Diagnostics;
 use mchprs_blocks::blocks::Block;
 use mchprs_blocks::BlockPos;
-use mchprs_world::{for_each_block_mut_optimized, TickEntry, World};
+use mchprs_world::{
+    for_each_block_mut_optimized, for_each_block_optimized, TickEntry, TickPriority, World,
+};
+use rustc_hash::FxHasher;
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 use std::time::Instant;
-use tracing::{debug, error, trace, warn};
+use trace::{Probe, Trace};
+use tracing::{debug, error, trace};
 
 pub use task_monitor::TaskMonitor;
 
+use crate::compile_graph::{LinkType, NodeState};
 use crate::{compile_graph::CompileGraph, passes::PassRegistry};
 
 fn block_powered_mut(block: &mut Block) -> Option<&mut bool> {
@@ -28,11 +40,78 @@ fn block_powered_mut(block: &mut Block) -> Option<&mut bool> {
         Block::RedstoneLamp { lit } => lit,
         Block::IronTrapdoor { powered, .. } => powered,
         Block::NoteBlock { powered, .. } => powered,
+        Block::Observer { powered, .. } => powered,
+        Block::TripwireHook { powered, .. } => powered,
+        _ if block.clone().get_copper_bulb_state().is_some() => {
+            return block.get_copper_bulb_state().map(|(_, powered)| powered);
+        }
         _ => return block.get_pressure_plate_powered(),
     })
 }
 
-#[derive(Default, PartialEq, Eq, Debug, Clone)]
+/// Hashes the graph together with the blocks its nodes are at, so that a saved circuit is only
+/// resumed into the world it was saved from. The circuit is flushed before it is saved, so the
+/// blocks match its state.
+fn circuit_fingerprint<W: World>(world: &W, graph: &CompileGraph) -> u64 {
+    let mut hasher = FxHasher::default();
+    for idx in graph.node_indices() {
+        let node = &graph[idx];
+        idx.index().hash(&mut hasher);
+        node.ty.hash(&mut hasher);
+        for &(pos, _) in &node.block {
+            pos.hash(&mut hasher);
+            world.get_block_raw(pos).hash(&mut hasher);
+        }
+    }
+    for edge in graph.all_edges() {
+        edge.source().index().hash(&mut hasher);
+        edge.target().index().hash(&mut hasher);
+        (edge.weight().ty == LinkType::Side).hash(&mut hasher);
+        edge.weight().ss.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Returns true if redpiler is unable to simulate `block`.
+fn is_unsupported_block(block: Block) -> bool {
+    matches!(
+        block,
+        Block::Piston { .. }
+            | Block::StickyPiston { .. }
+            | Block::PistonHead { .. }
+            | Block::MovingPiston { .. }
+    )
+}
+
+fn check_supported<W: World>(world: &W, pos: BlockPos, diagnostics: &mut Diagnostics) {
+    let block = world.get_block(pos);
+    if is_unsupported_block(block) {
+        let name = block.get_name().trim_start_matches("minecraft:");
+        diagnostics.error(format!("Redpiler does not support {}", name), Some(pos));
+    }
+}
+
+/// The compile was aborted because of the errors in `diagnostics`, which may also contain
+/// warnings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileError {
+    pub diagnostics: Diagnostics,
+}
+
+impl std::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors = self
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == diagnostics::Severity::Error)
+            .count();
+        write!(f, "Redpiler compile failed with {} error(s)", errors)
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+#[derive(Default, PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub struct CompilerOptions {
     /// Enable optimization passes which may significantly increase compile times.
     pub optimize: bool,
@@ -54,21 +133,52 @@ pub struct CompilerOptions {
     pub backend_variant: BackendVariant,
     /// A comma seperated list of passes to run. This can only be used by the rilc driver.
     pub passes: Option<String>,
+    /// Keep the compiled graph so the running circuit can be saved and resumed later
+    pub persist: bool,
+    /// Keep the compiled graph so that block edits only recompile the blocks around them
+    pub incremental: bool,
+    /// Only compile the player's WorldEdit selection. The rest of the plot keeps being simulated
+    /// without redpiler, so the circuit may not be connected to anything outside the selection.
+    pub selection: bool,
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum BackendVariant {
     #[default]
     Direct,
+    /// Ticks independent parts of the graph on separate threads.
+    Parallel,
+    /// Compiles each node to native code using cranelift.
+    Cranelift,
+}
+
+impl BackendVariant {
+    fn from_name(name: &str) -> Option<BackendVariant> {
+        Some(match name {
+            "direct" => BackendVariant::Direct,
+            "parallel" => BackendVariant::Parallel,
+            "cranelift" => BackendVariant::Cranelift,
+            _ => return None,
+        })
+    }
 }
 
 impl CompilerOptions {
-    fn parse_option(&mut self, option: &str) {
+    fn parse_option(&mut self, option: &str, diagnostics: &mut Diagnostics) {
         if option.starts_with("--") {
             if let Some(passes_str) = option.strip_prefix("--passes=") {
                 self.passes = Some(passes_str.to_owned());
                 return;
             }
+            if let Some(backend_str) = option.strip_prefix("--backend=") {
+                match BackendVariant::from_name(backend_str) {
+                    Some(variant) => self.backend_variant = variant,
+                    None => {
+                        diagnostics.error(format!("Unrecognized backend: {}", backend_str), None)
+                    }
+                }
+                return;
+            }
 
             match option {
                 "--optimize" => self.optimize = true,
@@ -79,8 +189,10 @@ impl CompilerOptions {
                 "--wire-dot-out" => self.wire_dot_out = true,
                 "--print-after-all" => self.print_after_all = true,
                 "--print-before-backend" => self.print_before_backend = true,
-                // FIXME: use actual error handling
-                _ => warn!("Unrecognized option: {}", option),
+                "--persist" => self.persist = true,
+                "--incremental" => self.incremental = true,
+                "--selection" => self.selection = true,
+                _ => diagnostics.error(format!("Unrecognized option: {}", option), None),
             }
         } else if let Some(str) = option.strip_prefix('-') {
             for c in str.chars() {
@@ -91,31 +203,62 @@ impl CompilerOptions {
                     "i" => self.io_only = true,
                     "u" => self.update = true,
                     "d" => self.wire_dot_out = true,
-                    // FIXME: use actual error handling
-                    _ => warn!("Unrecognized option: -{}", c),
+                    _ => diagnostics.error(format!("Unrecognized option: -{}", c), None),
                 }
             }
         } else {
-            // FIXME: use actual error handling
-            warn!("Unrecognized option: {}", option);
+            diagnostics.error(format!("Unrecognized option: {}", option), None);
         }
     }
 
-    pub fn parse(str: &str) -> CompilerOptions {
+    /// Parses the options given to the compile command. Unrecognized options are reported as
+    /// errors in `diagnostics`.
+    pub fn parse(str: &str, diagnostics: &mut Diagnostics) -> CompilerOptions {
         let mut co: CompilerOptions = Default::default();
         let options = str.split_whitespace();
         for option in options {
-            co.parse_option(option);
+            co.parse_option(option, diagnostics);
         }
         co
     }
 }
 
+/// A compiled circuit along with its simulation state. It can be saved with the world and resumed
+/// later without running the compile passes again.
+#[derive(Serialize, Deserialize)]
+pub struct SavedCircuit {
+    options: CompilerOptions,
+    /// The bounds the circuit was compiled from.
+    bounds: (BlockPos, BlockPos),
+    /// The [`circuit_fingerprint`] of the graph and the world when the circuit was saved.
+    fingerprint: u64,
+    graph: CompileGraph,
+    snapshot: BackendSnapshot,
+}
+
+impl SavedCircuit {
+    pub fn options(&self) -> &CompilerOptions {
+        &self.options
+    }
+}
+
 #[derive(Default)]
 pub struct Compiler {
     is_active: bool,
     backend: Option<BackendDispatcher>,
     options: CompilerOptions,
+    /// The graph the backend was compiled from, only kept when persisting or compiling
+    /// incrementally.
+    graph: Option<CompileGraph>,
+    /// The graph and options kept by [`Compiler::reset_for_recompile`].
+    previous: Option<(CompileGraph, CompilerOptions)>,
+    /// Probes are kept across compiles and added to the backend again if their node still exists.
+    probes: Vec<Probe>,
+    /// Like probes, breakpoints are kept across compiles.
+    breakpoints: Vec<Breakpoint>,
+    next_breakpoint_id: u32,
+    /// The ids of the breakpoints that triggered since they were last taken.
+    triggered_breakpoints: Vec<u32>,
 }
 
 impl Compiler {
@@ -143,39 +286,107 @@ impl Compiler {
         options: CompilerOptions,
         ticks: Vec<TickEntry>,
         monitor: Arc<TaskMonitor>,
-    ) {
+    ) -> Result<Diagnostics, CompileError> {
         debug!("Starting compile");
         let start = Instant::now();
 
+        self.previous = None;
+
+        let mut diagnostics = Diagnostics::default();
+        for_each_block_optimized(world, bounds.0, bounds.1, |pos| {
+            check_supported(world, pos, &mut diagnostics);
+        });
+        if diagnostics.has_errors() {
+            return Err(CompileError { diagnostics });
+        }
+        if options.incremental && options.optimize {
+            diagnostics.warn("--incremental has no effect with --optimize", None);
+        }
+
         let input = CompilerInput { world, bounds };
         let registry = PassRegistry::default();
         let pass_pipeline = passes::build_pass_pipeline::<W>(&registry, &options);
-        let graph =
-            pass_pipeline.run_passes(&options, &input, CompileGraph::default(), monitor.clone());
+        let graph = pass_pipeline.run_passes(
+            &options,
+            &input,
+            CompileGraph::default(),
+            monitor.clone(),
+            &mut diagnostics,
+        );
 
+        if diagnostics.has_errors() {
+            return Err(CompileError { diagnostics });
+        }
         if monitor.cancelled() {
-            return;
+            return Ok(diagnostics);
         }
 
-        let replace_backend = match self.backend {
-            Some(BackendDispatcher::DirectBackend(_)) => {
-                options.backend_variant != BackendVariant::Direct
-            }
-            None => true,
+        self.compile_backend(graph, ticks, options, monitor);
+        debug!("Compile completed in {:?}", start.elapsed());
+        Ok(diagnostics)
+    }
+
+    /// Compiles the circuit kept by [`Compiler::reset_for_recompile`] again after the blocks at
+    /// `changed` were edited. Only the nodes around the edits are identified and searched again,
+    /// the rest of the graph is kept with its state read back from the world. Falls back to a
+    /// full compile if the graph can't be patched.
+    pub fn recompile<W: World>(
+        &mut self,
+        world: &W,
+        bounds: (BlockPos, BlockPos),
+        changed: &[BlockPos],
+        ticks: Vec<TickEntry>,
+        monitor: Arc<TaskMonitor>,
+    ) -> Result<Diagnostics, CompileError> {
+        let Some((graph, options)) = self.previous.take() else {
+            return self.compile(world, bounds, Default::default(), ticks, monitor);
         };
-        if replace_backend {
-            debug!("Switching backend to {:?}", options.backend_variant);
-            let backend = match options.backend_variant {
-                BackendVariant::Direct => BackendDispatcher::DirectBackend(Default::default()),
-            };
-            self.use_backend(backend);
+        debug!("Starting recompile of {} changed blocks", changed.len());
+        let start = Instant::now();
+
+        let mut diagnostics = Diagnostics::default();
+        for &pos in changed {
+            check_supported(world, pos, &mut diagnostics);
+        }
+        if diagnostics.has_errors() {
+            return Err(CompileError { diagnostics });
+        }
+
+        let input = CompilerInput { world, bounds };
+        monitor.set_max_progress(1);
+        monitor.set_message("Patching graph".to_string());
+        let patched =
+            passes::incremental::patch_graph(graph, &options, &input, changed, &mut diagnostics);
+        let Some(graph) = patched else {
+            debug!("Unable to patch graph, falling back to full compile");
+
+            return self.compile(world, bounds, options, ticks, monitor);
+        };
+        if diagnostics.has_errors() {
+            return Err(CompileError { diagnostics });
         }
 
+        self.compile_backend(graph, ticks, options, monitor);
+        debug!("Recompile completed in {:?}", start.elapsed());
+        Ok(diagnostics)
+    }
+
+    fn compile_backend(
+        &mut self,
+        graph: CompileGraph,
+        ticks: Vec<TickEntry>,
+        options: CompilerOptions,
+        monitor: Arc<TaskMonitor>,
+    ) {
+        self.select_backend(options.backend_variant);
+
         if let Some(backend) = &mut self.backend {
             trace!("Compiling backend");
             monitor.set_message("Compiling backend".to_string());
             let start = Instant::now();
 
+            let keep_graph = options.persist || (options.incremental && !options.optimize);
+            self.graph = keep_graph.then(|| graph.clone());
             backend.compile(graph, ticks, &options, monitor.clone());
 
             monitor.inc_progress();
@@ -186,10 +397,180 @@ impl Compiler {
 
         self.options = options;
         self.is_active = true;
-        debug!("Compile completed in {:?}", start.elapsed());
+        self.attach_probes_and_breakpoints();
+    }
+
+    /// Captures the running circuit so it can be resumed later. Returns `None` if redpiler is not
+    /// running or the circuit was not compiled with `--persist`. The circuit must have been
+    /// compiled from `bounds` and flushed to `world`.
+    pub fn save<W: World>(
+        &mut self,
+        world: &W,
+        bounds: (BlockPos, BlockPos),
+    ) -> Option<SavedCircuit> {
+        if !self.is_active || !self.options.persist {
+            return None;
+        }
+        let graph = self.graph.clone()?;
+        let snapshot = self.backend().snapshot();
+        Some(SavedCircuit {
+            options: self.options.clone(),
+            bounds,
+            fingerprint: circuit_fingerprint(world, &graph),
+            graph,
+            snapshot,
+        })
+    }
+
+    /// Resumes a circuit captured by [`Compiler::save`]. Returns false if the circuit was saved
+    /// from other bounds, the world has changed since then, or the saved state does not fit the
+    /// graph.
+    pub fn resume<W: World>(
+        &mut self,
+        saved: SavedCircuit,
+        world: &W,
+        bounds: (BlockPos, BlockPos),
+        monitor: Arc<TaskMonitor>,
+    ) -> bool {
+        let SavedCircuit {
+            options,
+            bounds: saved_bounds,
+            fingerprint,
+            graph,
+            snapshot,
+        } = saved;
+        if saved_bounds != bounds {
+            debug!("Saved circuit was compiled from other bounds");
+            return false;
+        }
+        if fingerprint != circuit_fingerprint(world, &graph) {
+            debug!("World has changed since the circuit was saved");
+            return false;
+        }
+        self.select_backend(options.backend_variant);
+        let Some(backend) = &mut self.backend else {
+            return false;
+        };
+
+        if !backend.resume(graph.clone(), &options, monitor, &snapshot) {
+            // The backend holds a half compiled circuit
+            self.backend = None;
+            return false;
+        }
+
+        self.graph = Some(graph);
+        self.previous = None;
+        self.options = options;
+        self.is_active = true;
+        self.attach_probes_and_breakpoints();
+        true
+    }
+
+    fn attach_probes_and_breakpoints(&mut self) {
+        let Some(backend) = &mut self.backend else {
+            return;
+        };
+        self.breakpoints
+            .retain_mut(|breakpoint| match backend.node_state(breakpoint.pos) {
+                Some(state) => {
+                    breakpoint.was_met = breakpoint.condition.is_met(&state);
+                    true
+                }
+                None => {
+                    debug!(
+                        "Removing breakpoint {} at {}",
+                        breakpoint.id, breakpoint.pos
+                    );
+                    false
+                }
+            });
+        self.probes.retain(|probe| {
+            let attached = backend.add_probe(probe.clone());
+            if !attached {
+                debug!("Removing probe {} at {}", probe.name, probe.pos);
+            }
+            attached
+        });
+    }
+
+    /// Starts recording the output strength of the node at `pos`. Returns false if there is no
+    /// node at `pos`.
+    pub fn add_probe(&mut self, name: String, pos: BlockPos) -> bool {
+        let probe = Probe { name, pos };
+        if !self.backend().add_probe(probe.clone()) {
+            return false;
+        }
+        self.probes.push(probe);
+        true
+    }
+
+    pub fn probes(&self) -> &[Probe] {
+        &self.probes
+    }
+
+    pub fn clear_probes(&mut self) {
+        self.probes.clear();
+        if let Some(backend) = &mut self.backend {
+            backend.clear_probes();
+        }
+    }
+
+    /// Discards the recorded signals and starts recording again from the current tick.
+    pub fn restart_trace(&mut self) {
+        self.backend().clear_probes();
+        self.attach_probes_and_breakpoints();
+    }
+
+    /// Returns the signals recorded since the last compile, in the order the probes were added.
+    pub fn trace(&mut self) -> Trace {
+        let mut trace = self.backend().trace();
+        trace
+            .signals
+            .sort_by_key(|signal| self.probes.iter().position(|probe| *probe == signal.probe));
+        trace
+    }
+
+    fn select_backend(&mut self, variant: BackendVariant) {
+        let replace_backend = match self.backend {
+            Some(BackendDispatcher::DirectBackend(_)) => variant != BackendVariant::Direct,
+            Some(BackendDispatcher::ParallelBackend(_)) => variant != BackendVariant::Parallel,
+            Some(BackendDispatcher::CraneliftBackend(_)) => variant != BackendVariant::Cranelift,
+            None => true,
+        };
+        if replace_backend {
+            debug!("Switching backend to {:?}", variant);
+            let backend = match variant {
+                BackendVariant::Direct => BackendDispatcher::DirectBackend(Default::default()),
+                BackendVariant::Parallel => BackendDispatcher::ParallelBackend(Default::default()),
+                BackendVariant::Cranelift => {
+                    BackendDispatcher::CraneliftBackend(Default::default())
+                }
+            };
+            self.use_backend(backend);
+        }
+    }
+
+    /// Resets like [`Compiler::reset`], but keeps the graph of a circuit compiled with
+    /// `--incremental` so that it can be patched by [`Compiler::recompile`] once the world has
+    /// been edited. Returns true if the graph was kept.
+    pub fn reset_for_recompile<W: World>(
+        &mut self,
+        world: &mut W,
+        bounds: (BlockPos, BlockPos),
+    ) -> bool {
+        let options = self.options.clone();
+        let graph = self
+            .graph
+            .take()
+            .filter(|_| self.is_active && options.incremental && !options.optimize);
+        self.reset(world, bounds);
+        self.previous = graph.map(|graph| (graph, options));
+        self.previous.is_some()
     }
 
     pub fn reset<W: World>(&mut self, world: &mut W, bounds: (BlockPos, BlockPos)) {
+        self.graph = None;
+        self.previous = None;
         if self.is_active {
             self.is_active = false;
             if let Some(backend) = &mut self.backend {
@@ -221,10 +602,90 @@ impl Compiler {
 
     pub fn tick(&mut self) {
         self.backend().tick();
+        self.check_breakpoints();
     }
 
+    /// Runs `ticks` ticks, stopping early if a breakpoint triggers.
     pub fn tickn(&mut self, ticks: u64) {
-        self.backend().tickn(ticks);
+        if self.breakpoints.is_empty() {
+            self.backend().tickn(ticks);
+            return;
+        }
+        for _ in 0..ticks {
+            self.backend().tick();
+            if self.check_breakpoints() {
+                break;
+            }
+        }
+    }
+
+    /// Runs the ticks of the next priority level. The tick is complete once
+    /// [`TickPriority::Normal`] has run.
+    pub fn step_priority(&mut self) -> TickPriority {
+        let priority = self.backend().step_priority();
+        self.check_breakpoints();
+        priority
+    }
+
+    /// Returns true if any breakpoint triggered.
+    fn check_breakpoints(&mut self) -> bool {
+        let Some(backend) = &self.backend else {
+            return false;
+        };
+        let mut triggered = false;
+        for breakpoint in &mut self.breakpoints {
+            let Some(state) = backend.node_state(breakpoint.pos) else {
+                continue;
+            };
+            if breakpoint.check(&state) {
+                self.triggered_breakpoints.push(breakpoint.id);
+                triggered = true;
+            }
+        }
+        triggered
+    }
+
+    /// Adds a breakpoint on the node at `pos` and returns its id, or `None` if there is no node
+    /// at `pos`.
+    pub fn add_breakpoint(&mut self, pos: BlockPos, condition: Condition) -> Option<u32> {
+        let state = self.backend().node_state(pos)?;
+        let id = self.next_breakpoint_id;
+        self.next_breakpoint_id += 1;
+        self.breakpoints.push(Breakpoint {
+            id,
+            pos,
+            condition,
+            was_met: condition.is_met(&state),
+        });
+        Some(id)
+    }
+
+    pub fn remove_breakpoint(&mut self, id: u32) -> bool {
+        let len = self.breakpoints.len();
+        self.breakpoints.retain(|breakpoint| breakpoint.id != id);
+        self.breakpoints.len() != len
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Returns the breakpoints that triggered since this was last called.
+    pub fn take_triggered_breakpoints(&mut self) -> Vec<Breakpoint> {
+        let ids = std::mem::take(&mut self.triggered_breakpoints);
+        self.breakpoints
+            .iter()
+            .filter(|breakpoint| ids.contains(&breakpoint.id))
+            .cloned()
+            .collect()
+    }
+
+    pub fn node_state(&self, pos: BlockPos) -> Option<NodeState> {
+        self.backend.as_ref()?.node_state(pos)
     }
 
     pub fn on_use_block(&mut self, pos: BlockPos) {
@@ -235,16 +696,37 @@ impl Compiler {
         self.backend().set_pressure_plate(pos, powered);
     }
 
+    pub fn set_daylight_detector(&mut self, pos: BlockPos, power: u8) {
+        self.backend().set_daylight_detector(pos, power);
+    }
+
+    pub fn hit_target(&mut self, pos: BlockPos, power: u8, ticks: u32) -> bool {
+        self.backend().hit_target(pos, power, ticks)
+    }
+
+    pub fn trigger_tripwire_hook(&mut self, pos: BlockPos, ticks: u32) -> bool {
+        self.backend().trigger_tripwire_hook(pos, ticks)
+    }
+
     pub fn flush<W: World>(&mut self, world: &mut W) {
         let io_only = self.options.io_only;
         self.backend().flush(world, io_only);
     }
 
-    pub fn inspect(&mut self, pos: BlockPos) {
-        if let Some(backend) = &mut self.backend {
-            backend.inspect(pos);
-        } else {
-            debug!("cannot inspect when backend is not running");
+    pub fn snapshot(&mut self) -> BackendSnapshot {
+        self.backend().snapshot()
+    }
+
+    /// Restores a snapshot taken since the last compile. Returns false if it does not fit the
+    /// compiled graph.
+    pub fn restore(&mut self, snapshot: &BackendSnapshot) -> bool {
+        self.backend().restore(snapshot)
+    }
+
+    pub fn inspect(&self, pos: BlockPos) -> Option<NodeInspection> {
+        match &self.backend {
+            Some(backend) if self.is_active => backend.inspect(pos),
+            _ => None,
         }
     }
 
@@ -264,7 +746,7 @@ mod tests {
 
     #[test]
     fn parse_options() {
-        let input = "-io -u --export";
+        let input = "-io -u --export --backend=parallel";
         let expected_options = CompilerOptions {
             io_only: true,
             optimize: true,
@@ -274,11 +756,41 @@ mod tests {
             wire_dot_out: false,
             print_after_all: false,
             print_before_backend: false,
-            backend_variant: BackendVariant::default(),
+            backend_variant: BackendVariant::Parallel,
             passes: None,
+            persist: false,
+            incremental: false,
+            selection: false,
         };
-        let options = CompilerOptions::parse(input);
+        let mut diagnostics = Diagnostics::default();
+        let options = CompilerOptions::parse(input, &mut diagnostics);
 
         assert_eq!(options, expected_options);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn parse_persist() {
+        let options = CompilerOptions::parse("-o --persist", &mut Diagnostics::default());
+        assert!(options.optimize);
+        assert!(options.persist);
+    }
+
+    #[test]
+    fn parse_unrecognized_options() {
+        let mut diagnostics = Diagnostics::default();
+        let options = CompilerOptions::parse("-ox --backend=foo --fast bar", &mut diagnostics);
+        assert!(options.optimize);
+        assert!(diagnostics.has_errors());
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Unrecognized option: -x",
+                "Unrecognized backend: foo",
+                "Unrecognized option: --fast",
+                "Unrecognized option: bar",
+            ]
+        );
     }
 }
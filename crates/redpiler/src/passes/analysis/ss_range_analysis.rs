@@ -13,6 +13,7 @@
 //! comparator subtract by constant -> comparator loop
 
 use crate::compile_graph::{CompileGraph, Direction, LinkType, NodeIdx, NodeState, NodeType};
+use crate::diagnostics::Diagnostics;
 use crate::passes::{AnalysisInfo, AnalysisInfos, AnalysisUsage, Pass};
 use crate::{CompilerInput, CompilerOptions};
 use itertools::Itertools;
@@ -100,6 +101,7 @@ impl<W: World> Pass<W> for SSRangeAnalysis {
         _: &CompilerOptions,
         _: &CompilerInput<'_, W>,
         analysis_infos: &mut AnalysisInfos,
+        _: &mut Diagnostics,
     ) {
         let mut range_info = SSRangeInfo::default();
         range_info.reserve(graph);
@@ -310,6 +312,13 @@ impl SSRangeAnalysis {
                 }
             }
             NodeType::Wire => default_range,
+            // Bulbs that never become powered keep their current state
+            NodeType::CopperBulb if default_range.high == 0 => {
+                SSRange::constant(state.output_strength)
+            }
+            NodeType::CopperBulb => SSRange::FULL,
+            // Observers only care about changes to their input, not its level
+            NodeType::Observer => SSRange::FULL,
             _ => unreachable!("evaluate node ty: {:?}", ty),
         }
     }
@@ -324,8 +333,14 @@ impl SSRangeAnalysis {
             | NodeType::Wire
             | NodeType::NoteBlock { .. } => SSRange::constant(0),
             NodeType::Torch => SSRange::constant(15),
-            NodeType::Constant => SSRange::constant(state.output_strength),
-            NodeType::Button | NodeType::Lever | NodeType::PressurePlate => SSRange::FULL,
+            NodeType::Constant | NodeType::CopperBulb => SSRange::constant(state.output_strength),
+            NodeType::Button
+            | NodeType::Lever
+            | NodeType::PressurePlate
+            | NodeType::Observer
+            | NodeType::Target
+            | NodeType::TripwireHook
+            | NodeType::DaylightDetector => SSRange::FULL,
         }
     }
 }
@@ -0,0 +1,95 @@
+//! # [`CheckBounds`]
+//!
+//! This pass makes sure that the circuit inside [`CompilerInput::bounds`] is not connected to any
+//! redstone outside of them. It only runs when compiling a selection with `selection` set in
+//! [`CompilerOptions`], since the blocks outside the selection keep being simulated without
+//! redpiler and the two simulations can't exchange signals.
+//!
+//! Any component outside the bounds that is close enough to a component inside to be linked with
+//! it is reported as an error. This is stricter than necessary, but it never misses a link.
+
+use super::identify_nodes;
+use crate::compile_graph::CompileGraph;
+use crate::diagnostics::Diagnostics;
+use crate::passes::{AnalysisInfos, Pass};
+use crate::{CompilerInput, CompilerOptions};
+use mchprs_blocks::BlockPos;
+use mchprs_world::World;
+
+/// The furthest two linked components can be apart, which is a comparator reading a container
+/// through a block.
+const LINK_RANGE: i32 = 2;
+
+pub struct CheckBounds;
+
+impl<W: World> Pass<W> for CheckBounds {
+    fn run_pass(
+        &self,
+        _: &mut CompileGraph,
+        _: &CompilerOptions,
+        input: &CompilerInput<'_, W>,
+        _: &mut AnalysisInfos,
+        diagnostics: &mut Diagnostics,
+    ) {
+        check_bounds(input.world, input.bounds, diagnostics);
+    }
+
+    fn status_message(&self) -> &'static str {
+        "Checking selection bounds"
+    }
+
+    fn driver_key(&self) -> &'static str {
+        "check-bounds"
+    }
+}
+
+/// Reports an error for every component just outside the bounds that could be linked with a
+/// component inside of them.
+pub(crate) fn check_bounds<W: World>(
+    world: &W,
+    bounds: (BlockPos, BlockPos),
+    diagnostics: &mut Diagnostics,
+) {
+    let min = bounds.0.min(bounds.1);
+    let max = bounds.0.max(bounds.1);
+    let inside = |pos: BlockPos| {
+        (min.x..=max.x).contains(&pos.x)
+            && (min.y..=max.y).contains(&pos.y)
+            && (min.z..=max.z).contains(&pos.z)
+    };
+
+    let mut check = |pos: BlockPos| {
+        if !identify_nodes::is_component(world, pos) {
+            return;
+        }
+        let linked = (-LINK_RANGE..=LINK_RANGE).any(|x| {
+            (-LINK_RANGE..=LINK_RANGE).any(|y| {
+                (-LINK_RANGE..=LINK_RANGE).any(|z| {
+                    let other = BlockPos::new(pos.x + x, pos.y + y, pos.z + z);
+                    inside(other) && identify_nodes::is_component(world, other)
+                })
+            })
+        });
+        if linked {
+            diagnostics.error(
+                "Redstone outside of the selection is too close to the circuit inside",
+                Some(pos),
+            );
+        }
+    };
+
+    // Only visit the shell of blocks around the bounds
+    for x in min.x - LINK_RANGE..=max.x + LINK_RANGE {
+        for y in min.y - LINK_RANGE..=max.y + LINK_RANGE {
+            if (min.x..=max.x).contains(&x) && (min.y..=max.y).contains(&y) {
+                for z in (min.z - LINK_RANGE..min.z).chain(max.z + 1..=max.z + LINK_RANGE) {
+                    check(BlockPos::new(x, y, z));
+                }
+            } else {
+                for z in min.z - LINK_RANGE..=max.z + LINK_RANGE {
+                    check(BlockPos::new(x, y, z));
+                }
+            }
+        }
+    }
+}
@@ -8,6 +8,7 @@
 //! There are no requirements for this pass.
 
 use crate::compile_graph::{Annotations, CompileGraph, CompileNode, NodeIdx, NodeState, NodeType};
+use crate::diagnostics::Diagnostics;
 use crate::passes::{AnalysisInfos, Pass};
 use crate::{CompilerInput, CompilerOptions};
 use itertools::Itertools;
@@ -19,7 +20,6 @@ use mchprs_world::{for_each_block_optimized, World};
 use rustc_hash::{FxHashMap, FxHashSet};
 use serde_json::Value;
 use smallvec::smallvec;
-use tracing::warn;
 
 pub struct IdentifyNodes;
 
@@ -30,6 +30,7 @@ impl<W: World> Pass<W> for IdentifyNodes {
         options: &CompilerOptions,
         input: &CompilerInput<'_, W>,
         _: &mut AnalysisInfos,
+        diagnostics: &mut Diagnostics,
     ) {
         let ignore_wires = options.optimize;
         let plot = input.world;
@@ -51,8 +52,10 @@ impl<W: World> Pass<W> for IdentifyNodes {
             );
         });
 
+        check_far_inputs(graph, graph.node_indices(), plot, diagnostics);
+
         for pos in second_pass {
-            apply_annotations(graph, options, &first_pass, plot, pos);
+            apply_annotations(graph, options, &first_pass, plot, pos, diagnostics);
         }
     }
 
@@ -65,6 +68,66 @@ impl<W: World> Pass<W> for IdentifyNodes {
     }
 }
 
+/// Identifies the nodes on the given blocks only, which is used to patch a graph after some blocks
+/// were edited. Returns the nodes that were added.
+pub(crate) fn identify_blocks<W: World>(
+    graph: &mut CompileGraph,
+    options: &CompilerOptions,
+    world: &W,
+    positions: impl IntoIterator<Item = BlockPos>,
+    diagnostics: &mut Diagnostics,
+) -> Vec<NodeIdx> {
+    let mut first_pass = FxHashMap::default();
+    let mut second_pass = FxHashSet::default();
+    for pos in positions {
+        for_pos(
+            graph,
+            &mut first_pass,
+            &mut second_pass,
+            options.optimize,
+            options.wire_dot_out,
+            world,
+            pos,
+        );
+    }
+
+    let added: Vec<NodeIdx> = first_pass.values().copied().collect();
+    check_far_inputs(graph, added.iter().copied(), world, diagnostics);
+
+    if !second_pass.is_empty() {
+        // Annotations may also target nodes that were kept
+        for idx in graph.node_indices() {
+            first_pass.insert(graph[idx].block[0].0, idx);
+        }
+        for pos in second_pass {
+            apply_annotations(graph, options, &first_pass, world, pos, diagnostics);
+        }
+    }
+    added
+}
+
+/// Updates the state of a node from its block in the world. Returns false if the block is no
+/// longer the same kind of node.
+pub(crate) fn refresh_node<W: World>(node: &mut CompileNode, world: &W) -> bool {
+    let [(pos, id)] = node.block.as_mut_slice() else {
+        return false;
+    };
+    let raw = world.get_block_raw(*pos);
+    match identify_block(Block::from_id(raw), *pos, world) {
+        Some((ty, state)) if ty == node.ty => {
+            node.state = state;
+            *id = raw;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if the block at `pos` would become a node.
+pub(crate) fn is_component<W: World>(world: &W, pos: BlockPos) -> bool {
+    identify_block(world.get_block(pos), pos, world).is_some()
+}
+
 fn for_pos<W: World>(
     graph: &mut CompileGraph,
     first_pass: &mut FxHashMap<BlockPos, NodeIdx>,
@@ -115,6 +178,9 @@ fn identify_block<W: World>(
     if let Some(powered) = block.clone().get_pressure_plate_powered() {
         return Some((NodeType::PressurePlate, NodeState::simple(*powered)));
     }
+    if let Some((lit, powered)) = block.clone().get_copper_bulb_state() {
+        return Some((NodeType::CopperBulb, NodeState::copper_bulb(*lit, *powered)));
+    }
     let (ty, state) = match block {
         Block::Repeater(repeater) => (
             NodeType::Repeater {
@@ -153,6 +219,10 @@ fn identify_block<W: World>(
         Block::Lever { powered, .. } => (NodeType::Lever, NodeState::simple(powered)),
         Block::IronTrapdoor { powered, .. } => (NodeType::Trapdoor, NodeState::simple(powered)),
         Block::RedstoneBlock => (NodeType::Constant, NodeState::ss(15)),
+        Block::Observer { powered, .. } => (NodeType::Observer, NodeState::simple(powered)),
+        Block::Target { power } => (NodeType::Target, NodeState::ss(power)),
+        Block::DaylightDetector { power, .. } => (NodeType::DaylightDetector, NodeState::ss(power)),
+        Block::TripwireHook { powered, .. } => (NodeType::TripwireHook, NodeState::simple(powered)),
         Block::NoteBlock {
             instrument: _,
             note,
@@ -173,12 +243,47 @@ fn identify_block<W: World>(
     Some((ty, state))
 }
 
+/// Containers and copper bulbs that comparators read through a block become a constant far input,
+/// which is only correct for copper bulbs as long as they are never toggled.
+fn check_far_inputs<W: World>(
+    graph: &CompileGraph,
+    nodes: impl IntoIterator<Item = NodeIdx>,
+    world: &W,
+    diagnostics: &mut Diagnostics,
+) {
+    for idx in nodes {
+        let node = &graph[idx];
+        let NodeType::Comparator {
+            far_input: Some(_), ..
+        } = node.ty
+        else {
+            continue;
+        };
+        let pos = node.block[0].0;
+        let Block::Comparator(comparator) = world.get_block(pos) else {
+            continue;
+        };
+        let face = comparator.facing.block_face();
+        if world
+            .get_block(pos.offset(face).offset(face))
+            .get_copper_bulb_state()
+            .is_some()
+        {
+            diagnostics.warn(
+                "Comparator reading a copper bulb through a block will not be updated by redpiler",
+                Some(pos),
+            );
+        }
+    }
+}
+
 fn apply_annotations<W: World>(
     graph: &mut CompileGraph,
     options: &CompilerOptions,
     first_pass: &FxHashMap<BlockPos, NodeIdx>,
     world: &W,
     pos: BlockPos,
+    diagnostics: &mut Diagnostics,
 ) {
     let block = world.get_block(pos);
     let annotations = parse_sign_annotations(world.get_block_entity(pos));
@@ -192,7 +297,7 @@ fn apply_annotations<W: World>(
                 let behind = pos.offset(facing.opposite().block_face());
                 vec![behind]
             } else {
-                warn!("Found sign with annotations, but bad rotation at {}", pos);
+                diagnostics.warn("Found sign with annotations, but bad rotation", Some(pos));
                 return;
             }
         }
@@ -212,11 +317,11 @@ fn apply_annotations<W: World>(
         for annotation in annotations {
             let result = annotation.apply(graph, node_idx, options);
             if let Err(msg) = result {
-                warn!("{} at {}", msg, pos);
+                diagnostics.warn(msg, Some(pos));
             }
         }
     } else {
-        warn!("Could not find component for annotation at {}", pos);
+        diagnostics.warn("Could not find component for annotation", Some(pos));
     }
 }
 
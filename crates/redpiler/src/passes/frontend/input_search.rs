@@ -4,6 +4,7 @@
 //! This pass is *mandatory*. Without it, there would be no links between nodes.
 
 use crate::compile_graph::{CompileGraph, CompileLink, LinkType, NodeIdx};
+use crate::diagnostics::Diagnostics;
 use crate::passes::{AnalysisInfos, Pass};
 use crate::{CompilerInput, CompilerOptions};
 use mchprs_blocks::blocks::{Block, LeverFace};
@@ -21,8 +22,9 @@ impl<W: World> Pass<W> for InputSearch {
         _: &CompilerOptions,
         input: &CompilerInput<'_, W>,
         _: &mut AnalysisInfos,
+        diagnostics: &mut Diagnostics,
     ) {
-        let mut state = InputSearchState::new(input.world, graph);
+        let mut state = InputSearchState::new(input.world, graph, diagnostics);
         state.search();
     }
 
@@ -68,15 +70,36 @@ impl<'world, W: World> BlockLookupCache<'world, W> {
     }
 }
 
+/// Searches the inputs of the given nodes only, which is used to patch a graph after some blocks
+/// were edited. The nodes must not have any inputs yet.
+pub(crate) fn search_inputs<W: World>(
+    world: &W,
+    graph: &mut CompileGraph,
+    nodes: impl IntoIterator<Item = NodeIdx>,
+    diagnostics: &mut Diagnostics,
+) {
+    let mut state = InputSearchState::new(world, graph, diagnostics);
+    for idx in nodes {
+        if let Some(block) = state.graph[idx].block.first().copied() {
+            state.search_node(idx, block);
+        }
+    }
+}
+
 struct InputSearchState<'a, W: World> {
     world: &'a W,
     graph: &'a mut CompileGraph,
     pos_map: FxHashMap<BlockPos, NodeIdx>,
     block_lookup_cache: BlockLookupCache<'a, W>,
+    diagnostics: &'a mut Diagnostics,
 }
 
 impl<'a, W: World> InputSearchState<'a, W> {
-    fn new(world: &'a W, graph: &'a mut CompileGraph) -> InputSearchState<'a, W> {
+    fn new(
+        world: &'a W,
+        graph: &'a mut CompileGraph,
+        diagnostics: &'a mut Diagnostics,
+    ) -> InputSearchState<'a, W> {
         let mut pos_map = FxHashMap::default();
         for id in graph.node_indices() {
             for (pos, _) in &graph[id].block {
@@ -89,6 +112,7 @@ impl<'a, W: World> InputSearchState<'a, W> {
             graph,
             pos_map,
             block_lookup_cache: BlockLookupCache::new(world),
+            diagnostics,
         }
     }
 
@@ -105,6 +129,14 @@ impl<'a, W: World> InputSearchState<'a, W> {
         search_wire: bool,
     ) {
         if block.is_solid() {
+            if provides_weak_power(block, side) {
+                self.graph.add_edge(
+                    self.pos_map[&pos],
+                    start_node,
+                    CompileLink::new(link_ty, distance),
+                );
+            }
+
             for side in &BlockFace::values() {
                 let pos = pos.offset(*side);
                 let block = self.block_lookup_cache.get_block(pos);
@@ -275,6 +307,56 @@ impl<'a, W: World> InputSearchState<'a, W> {
         }
     }
 
+    /// Observers are linked to whatever decides the state of the block they are watching, so
+    /// that they get updated whenever that state changes.
+    fn search_observed(&mut self, id: NodeIdx, observed_pos: BlockPos) {
+        let observed_block = self.block_lookup_cache.get_block(observed_pos);
+        match observed_block {
+            // Wires might not be in the graph, so we link the wire's inputs instead
+            Block::RedstoneWire { .. } => {
+                self.search_wire(id, observed_pos, LinkType::Default, 0);
+            }
+            // Only whether a comparator is powered is part of its state, which is what the side
+            // input of an observer stands for
+            Block::Comparator(_) => {
+                if let Some(&observed_idx) = self.pos_map.get(&observed_pos) {
+                    self.graph.add_edge(observed_idx, id, CompileLink::side(0));
+                }
+            }
+            Block::RedstoneLamp { .. } | Block::IronTrapdoor { .. } | Block::NoteBlock { .. } => {
+                self.diagnostics.warn(
+                    format!(
+                        "Observer watching {} will not be triggered by redpiler",
+                        observed_block.get_name()
+                    ),
+                    Some(observed_pos),
+                );
+            }
+            _ => {
+                if let Some(&observed_idx) = self.pos_map.get(&observed_pos) {
+                    self.graph
+                        .add_edge(observed_idx, id, CompileLink::default(0));
+                }
+            }
+        }
+    }
+
+    fn search_all_sides(&mut self, id: NodeIdx, pos: BlockPos) {
+        for face in &BlockFace::values() {
+            let neighbor_pos = pos.offset(*face);
+            let neighbor_block = self.block_lookup_cache.get_block(neighbor_pos);
+            self.get_redstone_links(
+                neighbor_block,
+                *face,
+                neighbor_pos,
+                LinkType::Default,
+                0,
+                id,
+                true,
+            );
+        }
+    }
+
     fn search_node(&mut self, id: NodeIdx, (pos, block_id): (BlockPos, u32)) {
         match Block::from_id(block_id) {
             Block::RedstoneTorch { .. } => {
@@ -328,20 +410,14 @@ impl<'a, W: World> InputSearchState<'a, W> {
             Block::RedstoneWire { .. } => {
                 self.search_wire(id, pos, LinkType::Default, 0);
             }
+            Block::Observer { facing, .. } => {
+                self.search_observed(id, pos.offset(facing.block_face()));
+            }
             Block::RedstoneLamp { .. } | Block::IronTrapdoor { .. } | Block::NoteBlock { .. } => {
-                for face in &BlockFace::values() {
-                    let neighbor_pos = pos.offset(*face);
-                    let neighbor_block = self.block_lookup_cache.get_block(neighbor_pos);
-                    self.get_redstone_links(
-                        neighbor_block,
-                        *face,
-                        neighbor_pos,
-                        LinkType::Default,
-                        0,
-                        id,
-                        true,
-                    );
-                }
+                self.search_all_sides(id, pos);
+            }
+            block if block.clone().get_copper_bulb_state().is_some() => {
+                self.search_all_sides(id, pos);
             }
             _ => {}
         }
@@ -375,10 +451,14 @@ fn provides_weak_power(block: Block, side: BlockFace) -> bool {
         Block::RedstoneTorch { .. } => side != BlockFace::Top,
         Block::RedstoneWallTorch { facing, .. } => facing.block_face() != side,
         Block::RedstoneBlock => true,
+        Block::Target { .. } => true,
+        Block::DaylightDetector { .. } => true,
         Block::Lever { .. } => true,
         Block::StoneButton { .. } => true,
+        Block::TripwireHook { .. } => true,
         Block::Repeater(repeater) => repeater.facing.block_face() == side,
         Block::Comparator(comparator) => comparator.facing.block_face() == side,
+        Block::Observer { facing, .. } => facing.block_face() == side,
         _ => false,
     }
 }
@@ -402,8 +482,10 @@ fn provides_strong_power(block: Block, side: BlockFace) -> bool {
             BlockFace::Bottom => face == LeverFace::Ceiling,
             _ => face == LeverFace::Wall && facing == side.unwrap_direction(),
         },
+        Block::TripwireHook { facing, .. } => facing.block_face() == side,
         Block::Repeater(repeater) => repeater.facing.block_face() == side,
         Block::Comparator(comparator) => comparator.facing.block_face() == side,
+        Block::Observer { facing, .. } => facing.block_face() == side,
         _ => false,
     }
 }
@@ -1,2 +1,3 @@
+pub mod check_bounds;
 pub mod identify_nodes;
 pub mod input_search;
@@ -0,0 +1,149 @@
+//! Patches the graph of a previous compile after some blocks were edited, so that only the nodes
+//! around the edits have to be identified and searched again.
+//!
+//! This only works on graphs that were compiled without `optimize`, where every node is a single
+//! block and no node has been coalesced with another.
+
+use super::frontend::{check_bounds, identify_nodes, input_search};
+use crate::compile_graph::{CompileGraph, Direction, NodeIdx};
+use crate::diagnostics::Diagnostics;
+use crate::{CompilerInput, CompilerOptions};
+use mchprs_blocks::blocks::Block;
+use mchprs_blocks::BlockPos;
+use mchprs_world::World;
+use rustc_hash::FxHashSet;
+
+/// How far away from a node the input search looks for blocks, not counting wire networks.
+const SEARCH_RANGE: i32 = 2;
+/// Patching is not worth it past this many blocks to search again.
+const MAX_DIRTY_BLOCKS: usize = 1 << 16;
+
+/// Returns the patched graph, or `None` if the edits are too large or the graph can't be patched
+/// safely.
+pub fn patch_graph<W: World>(
+    mut graph: CompileGraph,
+    options: &CompilerOptions,
+    input: &CompilerInput<'_, W>,
+    changed: &[BlockPos],
+    diagnostics: &mut Diagnostics,
+) -> Option<CompileGraph> {
+    // The export pass would have to run again
+    if options.optimize || options.export {
+        return None;
+    }
+    let world = input.world;
+    let dirty = dirty_blocks(input, changed)?;
+
+    // Nodes on dirty blocks are removed and identified again. The nodes they were linking to
+    // need to search their inputs again as well.
+    let removed: Vec<NodeIdx> = graph
+        .node_indices()
+        .filter(|&idx| dirty.contains(&graph[idx].block[0].0))
+        .collect();
+    let mut research = FxHashSet::default();
+    for &idx in &removed {
+        research.extend(graph.neighbors(idx, Direction::Outgoing));
+    }
+    for idx in removed {
+        graph.remove_node(idx);
+    }
+    research.retain(|&idx| graph.contains_node(idx));
+    for &idx in &research {
+        let inputs: Vec<_> = graph
+            .edges(idx, Direction::Incoming)
+            .map(|edge| edge.id())
+            .collect();
+        for edge in inputs {
+            graph.remove_edge(edge);
+        }
+    }
+
+    // The rest of the nodes only changed state since the graph was compiled
+    let kept: Vec<NodeIdx> = graph.node_indices().collect();
+    for idx in kept {
+        if !identify_nodes::refresh_node(&mut graph[idx], world) {
+            return None;
+        }
+    }
+
+    let added = identify_nodes::identify_blocks(
+        &mut graph,
+        options,
+        world,
+        dirty.iter().copied(),
+        diagnostics,
+    );
+    research.extend(added);
+    input_search::search_inputs(world, &mut graph, research, diagnostics);
+    if options.selection {
+        check_bounds::check_bounds(world, input.bounds, diagnostics);
+    }
+    // Same as the `ClampWeights` pass
+    graph.retain_edges(|g, edge| g[edge].ss < 15);
+    Some(graph)
+}
+
+/// Finds every block inside the bounds whose node may be identified differently or have different
+/// inputs because of the changed blocks.
+///
+/// A node's input search only looks at blocks within `SEARCH_RANGE`, except that it follows wire
+/// networks and looks at the blocks around each wire. So all wires connected to wires near the
+/// changes are dirty too, along with the blocks around them.
+fn dirty_blocks<W: World>(
+    input: &CompilerInput<'_, W>,
+    changed: &[BlockPos],
+) -> Option<FxHashSet<BlockPos>> {
+    let world = input.world;
+    let (first, second) = input.bounds;
+    let min = BlockPos::new(
+        first.x.min(second.x),
+        first.y.min(second.y),
+        first.z.min(second.z),
+    );
+    let max = BlockPos::new(
+        first.x.max(second.x),
+        first.y.max(second.y),
+        first.z.max(second.z),
+    );
+    let in_bounds = |pos: BlockPos| {
+        (min.x..=max.x).contains(&pos.x)
+            && (min.y..=max.y).contains(&pos.y)
+            && (min.z..=max.z).contains(&pos.z)
+    };
+    let is_wire = |pos: BlockPos| matches!(world.get_block(pos), Block::RedstoneWire { .. });
+
+    let mut dirty = FxHashSet::default();
+    for &pos in changed {
+        dirty.extend(cube(pos, SEARCH_RANGE).filter(|&pos| in_bounds(pos)));
+    }
+
+    // Wires can also connect diagonally, so every wire in the surrounding cube is considered to
+    // be connected.
+    let mut network: FxHashSet<BlockPos> =
+        dirty.iter().copied().filter(|&pos| is_wire(pos)).collect();
+    let mut stack: Vec<BlockPos> = network.iter().copied().collect();
+    while let Some(wire) = stack.pop() {
+        for pos in cube(wire, 1) {
+            if in_bounds(pos) && !network.contains(&pos) && is_wire(pos) {
+                network.insert(pos);
+                stack.push(pos);
+            }
+        }
+        if network.len() > MAX_DIRTY_BLOCKS {
+            return None;
+        }
+    }
+    for wire in network {
+        dirty.extend(cube(wire, SEARCH_RANGE).filter(|&pos| in_bounds(pos)));
+    }
+
+    (dirty.len() <= MAX_DIRTY_BLOCKS).then_some(dirty)
+}
+
+fn cube(center: BlockPos, radius: i32) -> impl Iterator<Item = BlockPos> {
+    (-radius..=radius).flat_map(move |x| {
+        (-radius..=radius).flat_map(move |y| {
+            (-radius..=radius).map(move |z| BlockPos::new(center.x + x, center.y + y, center.z + z))
+        })
+    })
+}
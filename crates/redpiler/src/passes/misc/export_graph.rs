@@ -1,6 +1,7 @@
 use crate::compile_graph::{
     CompileGraph, Direction, LinkType as CLinkType, NodeIdx, NodeType as CNodeType,
 };
+use crate::diagnostics::Diagnostics;
 use crate::passes::{AnalysisInfos, AnalysisUsage, Pass};
 use crate::{CompilerInput, CompilerOptions};
 use itertools::Itertools;
@@ -66,6 +67,11 @@ fn convert_node(
             CNodeType::Wire => NodeType::Wire,
             CNodeType::Constant => NodeType::Constant,
             CNodeType::NoteBlock { .. } => NodeType::NoteBlock,
+            CNodeType::Observer => NodeType::Observer,
+            CNodeType::Target => NodeType::Target,
+            CNodeType::TripwireHook => NodeType::TripwireHook,
+            CNodeType::DaylightDetector => NodeType::DaylightDetector,
+            CNodeType::CopperBulb => NodeType::CopperBulb,
         },
         block: node
             .block
@@ -103,6 +109,7 @@ impl<W: World> Pass<W> for ExportGraph {
         _: &CompilerOptions,
         _: &CompilerInput<'_, W>,
         _: &mut AnalysisInfos,
+        _: &mut Diagnostics,
     ) {
         let mut nodes_map =
             FxHashMap::with_capacity_and_hasher(graph.node_count(), Default::default());
@@ -1,11 +1,13 @@
 mod analysis;
 mod frontend;
+pub(crate) mod incremental;
 mod misc;
 mod opt;
 
 use mchprs_world::World;
 use rustc_hash::{FxHashMap, FxHashSet};
 
+use crate::diagnostics::Diagnostics;
 use crate::ril::DumpGraph;
 
 use super::compile_graph::CompileGraph;
@@ -28,6 +30,9 @@ pub fn build_pass_pipeline<'p, W: World>(
 ) -> PassPipeline<'p, W> {
     let mut builder = PassPipelineBuilder::new(registry);
 
+    if options.selection {
+        builder.add_pass::<check_bounds::CheckBounds>();
+    }
     builder.add_pass::<identify_nodes::IdentifyNodes>();
     builder.add_pass::<input_search::InputSearch>();
     builder.add_pass::<clamp_weights::ClampWeights>();
@@ -60,6 +65,7 @@ impl<W: World> Default for PassRegistry<W> {
         let mut registry = Self::new();
 
         // Frontend passes
+        registry.register_pass(check_bounds::CheckBounds);
         registry.register_pass(identify_nodes::IdentifyNodes);
         registry.register_pass(input_search::InputSearch);
 
@@ -194,12 +200,15 @@ pub struct PassPipeline<'p, W: World> {
 }
 
 impl<'p, W: World> PassPipeline<'p, W> {
+    /// Runs every pass on the graph. Warnings and errors found by the passes are added to
+    /// `diagnostics`, and the remaining passes are skipped once a pass reports an error.
     pub fn run_passes(
         &self,
         options: &CompilerOptions,
         input: &CompilerInput<'_, W>,
         mut graph: CompileGraph,
         monitor: Arc<TaskMonitor>,
+        diagnostics: &mut Diagnostics,
     ) -> CompileGraph {
         // Add one for the backend compile step
         monitor.set_max_progress(self.passes.len() + 1);
@@ -215,7 +224,7 @@ impl<'p, W: World> PassPipeline<'p, W> {
             monitor.set_message(pass.status_message().to_string());
             let start = Instant::now();
 
-            pass.run_pass(&mut graph, options, input, &mut analysis_infos);
+            pass.run_pass(&mut graph, options, input, &mut analysis_infos, diagnostics);
 
             trace!("Completed pass in {:?}", start.elapsed());
             trace!("node_count: {}", graph.node_count());
@@ -226,6 +235,10 @@ impl<'p, W: World> PassPipeline<'p, W> {
                 debug!("Printing circuit after pass: {}", pass.debug_name());
                 graph.dump();
             }
+
+            if diagnostics.has_errors() {
+                return graph;
+            }
         }
 
         if options.print_before_backend {
@@ -271,6 +284,7 @@ pub trait Pass<W: World>: 'static {
         options: &CompilerOptions,
         input: &CompilerInput<'_, W>,
         analysis_infos: &mut AnalysisInfos,
+        diagnostics: &mut Diagnostics,
     );
 
     /// This name should only be use for debugging purposes,
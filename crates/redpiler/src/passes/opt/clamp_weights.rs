@@ -1,4 +1,5 @@
 use crate::compile_graph::CompileGraph;
+use crate::diagnostics::Diagnostics;
 use crate::passes::{AnalysisInfos, Pass};
 use crate::{CompilerInput, CompilerOptions};
 use mchprs_world::World;
@@ -12,6 +13,7 @@ impl<W: World> Pass<W> for ClampWeights {
         _: &CompilerOptions,
         _: &CompilerInput<'_, W>,
         _: &mut AnalysisInfos,
+        _: &mut Diagnostics,
     ) {
         graph.retain_edges(|g, edge| g[edge].ss < 15);
     }
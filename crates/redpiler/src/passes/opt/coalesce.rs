@@ -1,4 +1,5 @@
 use crate::compile_graph::{CompileGraph, Direction, LinkType, NodeIdx, NodeType};
+use crate::diagnostics::Diagnostics;
 use crate::passes::{AnalysisInfos, Pass};
 use crate::{CompilerInput, CompilerOptions};
 use itertools::Itertools;
@@ -14,6 +15,7 @@ impl<W: World> Pass<W> for Coalesce {
         _: &CompilerOptions,
         _: &CompilerInput<'_, W>,
         _: &mut AnalysisInfos,
+        _: &mut Diagnostics,
     ) {
         loop {
             let num_coalesced = run_iteration(graph);
@@ -42,9 +44,11 @@ fn run_iteration(graph: &mut CompileGraph) -> usize {
         }
 
         let node = &graph[idx];
-        // Comparators depend on the link weight as well as the type,
+        // Comparators and observers depend on the link weight as well as the type,
         // we could implement that later if it's beneficial enough.
-        if matches!(node.ty, NodeType::Comparator { .. }) || !node.is_removable() {
+        if matches!(node.ty, NodeType::Comparator { .. } | NodeType::Observer)
+            || !node.is_removable()
+        {
             continue;
         }
 
@@ -1,6 +1,7 @@
 use std::collections::hash_map::Entry;
 
 use crate::compile_graph::{CompileGraph, CompileNode, Direction, NodeIdx, NodeState, NodeType};
+use crate::diagnostics::Diagnostics;
 use crate::passes::{AnalysisInfos, Pass};
 use crate::{CompilerInput, CompilerOptions};
 use mchprs_world::World;
@@ -16,6 +17,7 @@ impl<W: World> Pass<W> for ConstantCoalesce {
         _: &CompilerOptions,
         _: &CompilerInput<'_, W>,
         _: &mut AnalysisInfos,
+        _: &mut Diagnostics,
     ) {
         let mut vertex_sets = UnionFind::new(graph.node_bound());
         for edge in graph.all_edges() {
@@ -1,4 +1,5 @@
 use crate::compile_graph::{CompileGraph, Direction, LinkType, NodeIdx, NodeState, NodeType};
+use crate::diagnostics::Diagnostics;
 use crate::passes::{AnalysisInfos, Pass};
 use crate::{CompilerInput, CompilerOptions};
 use mchprs_blocks::blocks::ComparatorMode;
@@ -14,6 +15,7 @@ impl<W: World> Pass<W> for ConstantFold {
         _: &CompilerOptions,
         _: &CompilerInput<'_, W>,
         _: &mut AnalysisInfos,
+        _: &mut Diagnostics,
     ) {
         let num_folded = fold(graph);
         trace!("Fold iteration: {} nodes", num_folded);
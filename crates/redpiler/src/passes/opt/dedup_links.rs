@@ -6,6 +6,7 @@
 //! weight 15 is removed.
 
 use crate::compile_graph::{CompileGraph, Direction, NodeIdx};
+use crate::diagnostics::Diagnostics;
 use crate::passes::{AnalysisInfos, Pass};
 use crate::{CompilerInput, CompilerOptions};
 use mchprs_world::World;
@@ -19,6 +20,7 @@ impl<W: World> Pass<W> for DedupLinks {
         _: &CompilerOptions,
         _: &CompilerInput<'_, W>,
         _: &mut AnalysisInfos,
+        _: &mut Diagnostics,
     ) {
         for i in 0..graph.node_bound() {
             let idx = NodeIdx::new(i);
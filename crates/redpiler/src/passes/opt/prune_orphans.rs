@@ -4,6 +4,7 @@
 //! redstone component by using Depth-First-Search.
 
 use crate::compile_graph::{CompileGraph, Direction};
+use crate::diagnostics::Diagnostics;
 use crate::passes::{AnalysisInfos, Pass};
 use crate::{CompilerInput, CompilerOptions};
 use itertools::Itertools;
@@ -18,6 +19,7 @@ impl<W: World> Pass<W> for PruneOrphans {
         _: &CompilerOptions,
         _: &CompilerInput<'_, W>,
         _: &mut AnalysisInfos,
+        _: &mut Diagnostics,
     ) {
         // We start searching from output nodes
         let mut worklist = graph
@@ -4,6 +4,7 @@
 //! output ss of a node is never higher than the weight of the link.
 
 use crate::compile_graph::{CompileGraph, Direction, NodeIdx};
+use crate::diagnostics::Diagnostics;
 use crate::passes::analysis::ss_range_analysis::{SSRangeAnalysis, SSRangeInfo};
 use crate::passes::{AnalysisInfos, AnalysisUsage, Pass};
 use crate::{CompilerInput, CompilerOptions};
@@ -18,6 +19,7 @@ impl<W: World> Pass<W> for UnreachableOutput {
         _: &CompilerOptions,
         _: &CompilerInput<'_, W>,
         analysis_infos: &mut AnalysisInfos,
+        _: &mut Diagnostics,
     ) {
         let range_info: &SSRangeInfo = analysis_infos.get_analysis().unwrap();
 
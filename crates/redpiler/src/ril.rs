@@ -2,6 +2,7 @@ use crate::compile_graph::{
     CompileGraph, CompileLink, CompileNode, Direction, EdgeRef, LinkType, NodeIdx, NodeState,
     NodeType,
 };
+use crate::diagnostics::Diagnostics;
 use crate::string_replacer::StringReplacer;
 use crate::CompilerOptions;
 use indexmap::IndexMap;
@@ -165,6 +166,7 @@ fn dump_node(f: &mut impl fmt::Write, ctx: &FmtContext<'_>) -> fmt::Result {
         ),
         NodeType::Button => write!(f, "button {}", node.state.powered),
         NodeType::Lever => write!(f, "lever {}", node.state.powered),
+        NodeType::TripwireHook => write!(f, "tripwire_hook {}", node.state.powered),
         NodeType::PressurePlate => write!(f, "pressure_plate {}", node.state.powered),
         NodeType::Trapdoor => write!(
             f,
@@ -179,6 +181,17 @@ fn dump_node(f: &mut impl fmt::Write, ctx: &FmtContext<'_>) -> fmt::Result {
             inputs.default_inputs()
         ),
         NodeType::Constant => write!(f, "constant {}", node.state.output_strength),
+        NodeType::Target => write!(f, "target {}", node.state.output_strength),
+        NodeType::DaylightDetector => {
+            write!(f, "daylight_detector {}", node.state.output_strength)
+        }
+        NodeType::CopperBulb => write!(
+            f,
+            "copper_bulb {}, {}, {}",
+            node.state.output_strength > 0,
+            node.state.powered,
+            inputs.default_inputs()
+        ),
         NodeType::NoteBlock { instrument, note } => {
             write!(
                 f,
@@ -188,6 +201,13 @@ fn dump_node(f: &mut impl fmt::Write, ctx: &FmtContext<'_>) -> fmt::Result {
                 inputs.default_inputs()
             )
         }
+        NodeType::Observer => write!(
+            f,
+            "observer {}, {}, {}",
+            node.state.powered,
+            inputs.default_inputs(),
+            inputs.side_inputs(),
+        ),
     }?;
 
     if !node.block.is_empty() {
@@ -246,6 +266,11 @@ enum ComponentType {
     Wire,
     Constant,
     NoteBlock,
+    Observer,
+    Target,
+    TripwireHook,
+    DaylightDetector,
+    CopperBulb,
 }
 
 impl ComponentType {
@@ -259,6 +284,10 @@ impl ComponentType {
             ComponentType::Trapdoor => NodeType::Trapdoor,
             ComponentType::Wire => NodeType::Wire,
             ComponentType::Constant => NodeType::Constant,
+            ComponentType::Observer => NodeType::Observer,
+            ComponentType::Target => NodeType::Target,
+            ComponentType::TripwireHook => NodeType::TripwireHook,
+            ComponentType::DaylightDetector => NodeType::DaylightDetector,
             _ => panic!("not a simple type"),
         }
     }
@@ -424,6 +453,15 @@ impl RILParserError {
 
 type RILParserResult<T> = Result<T, RILParserError>;
 
+fn parse_compiler_options(token: &Token, args: &str) -> RILParserResult<CompilerOptions> {
+    let mut diagnostics = Diagnostics::default();
+    let options = CompilerOptions::parse(args, &mut diagnostics);
+    match diagnostics.into_iter().next() {
+        Some(diagnostic) => Err(RILParserError::new(token.pos, diagnostic.message)),
+        None => Ok(options),
+    }
+}
+
 struct Lexer<'a> {
     src_iter: Peekable<CharIndices<'a>>,
     tokens: Vec<Token>,
@@ -562,6 +600,11 @@ impl<'a> Lexer<'a> {
             "wire" => TokenType::ComponentType(ComponentType::Wire),
             "constant" => TokenType::ComponentType(ComponentType::Constant),
             "note_block" => TokenType::ComponentType(ComponentType::NoteBlock),
+            "observer" => TokenType::ComponentType(ComponentType::Observer),
+            "target" => TokenType::ComponentType(ComponentType::Target),
+            "tripwire_hook" => TokenType::ComponentType(ComponentType::TripwireHook),
+            "daylight_detector" => TokenType::ComponentType(ComponentType::DaylightDetector),
+            "copper_bulb" => TokenType::ComponentType(ComponentType::CopperBulb),
             // Note Block Instruments
             "harp" => TokenType::Instrument(Instrument::Harp),
             "basedrum" => TokenType::Instrument(Instrument::Basedrum),
@@ -860,6 +903,19 @@ impl Parser {
                     },
                 }
             }
+            ComponentType::Observer => {
+                let (_, powered) = self.expect_bool()?;
+                self.expect_token(&[TokenType::Comma])?;
+                let mut inputs = self.parse_input_list(LinkType::Default)?;
+                self.expect_token(&[TokenType::Comma])?;
+                inputs.append(&mut self.parse_input_list(LinkType::Side)?);
+                ast::Component {
+                    name,
+                    inputs,
+                    node_state: NodeState::simple(powered),
+                    node_ty: NodeType::Observer,
+                }
+            }
             ComponentType::Torch | ComponentType::Lamp | ComponentType::Trapdoor => {
                 let (_, powered) = self.expect_bool()?;
                 self.expect_token(&[TokenType::Comma])?;
@@ -901,7 +957,23 @@ impl Parser {
                     },
                 }
             }
-            ComponentType::Button | ComponentType::Lever | ComponentType::PressurePlate => {
+            ComponentType::CopperBulb => {
+                let (_, lit) = self.expect_bool()?;
+                self.expect_token(&[TokenType::Comma])?;
+                let (_, powered) = self.expect_bool()?;
+                self.expect_token(&[TokenType::Comma])?;
+                let inputs = self.parse_input_list(LinkType::Default)?;
+                ast::Component {
+                    name,
+                    inputs,
+                    node_state: NodeState::copper_bulb(lit, powered),
+                    node_ty: NodeType::CopperBulb,
+                }
+            }
+            ComponentType::Button
+            | ComponentType::Lever
+            | ComponentType::PressurePlate
+            | ComponentType::TripwireHook => {
                 let (_, powered) = self.expect_bool()?;
                 ast::Component {
                     name,
@@ -921,7 +993,7 @@ impl Parser {
                     node_ty: component_ty.simple_node_type(),
                 }
             }
-            ComponentType::Constant => {
+            ComponentType::Constant | ComponentType::Target | ComponentType::DaylightDetector => {
                 let (_, ss) = self.expect_int()?;
                 ast::Component {
                     name,
@@ -980,9 +1052,9 @@ impl Parser {
         let test_args = match parens_or_comma.ty {
             TokenType::RightParens => None,
             TokenType::Comma => {
-                let (_, args) = self.expect_string()?;
+                let (token, args) = self.expect_string()?;
                 self.expect_token(&[TokenType::RightParens])?;
-                Some(CompilerOptions::parse(&args))
+                Some(parse_compiler_options(&token, &args)?)
             }
             _ => unreachable!(),
         };
@@ -1010,8 +1082,8 @@ impl Parser {
     }
 
     fn parse_test_args(&mut self) -> RILParserResult<()> {
-        let (_, args) = self.expect_string()?;
-        let args = CompilerOptions::parse(&args);
+        let (token, args) = self.expect_string()?;
+        let args = parse_compiler_options(&token, &args)?;
         self.module.test_args = Some(ast::TestArgs { args });
         Ok(())
     }
@@ -0,0 +1,184 @@
+//! Waveform recording of probed nodes and export to the Value Change Dump (VCD) format.
+
+use mchprs_blocks::BlockPos;
+use std::io::{self, Write};
+
+/// A node whose output strength is recorded every tick.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Probe {
+    pub name: String,
+    pub pos: BlockPos,
+}
+
+/// The recorded output strength of a single probe.
+#[derive(Debug, Clone)]
+pub struct Signal {
+    pub probe: Probe,
+    /// The tick and new output strength of every change, starting with the strength at the time
+    /// the probe was added.
+    pub changes: Vec<(u64, u8)>,
+}
+
+impl Signal {
+    pub fn new(probe: Probe, tick: u64, power: u8) -> Signal {
+        Signal {
+            probe,
+            changes: vec![(tick, power)],
+        }
+    }
+
+    pub fn record(&mut self, tick: u64, power: u8) {
+        if self.changes.last().is_none_or(|&(_, last)| last != power) {
+            self.changes.push((tick, power));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    /// The number of ticks simulated since the backend was compiled.
+    pub end: u64,
+    pub signals: Vec<Signal>,
+}
+
+impl Trace {
+    /// Writes the trace as a VCD file. Each signal is a 4 bit vector and each time step is one
+    /// redstone tick.
+    pub fn write_vcd(&self, w: &mut impl Write) -> io::Result<()> {
+        writeln!(w, "$version MCHPRS redpiler $end")?;
+        writeln!(w, "$timescale 100 ms $end")?;
+        writeln!(w, "$scope module redpiler $end")?;
+        for (i, signal) in self.signals.iter().enumerate() {
+            // VCD identifiers can't contain whitespace
+            let name = signal.probe.name.replace(char::is_whitespace, "_");
+            writeln!(w, "$var wire 4 {} {} $end", vcd_id(i), name)?;
+        }
+        writeln!(w, "$upscope $end")?;
+        writeln!(w, "$enddefinitions $end")?;
+
+        let start = self
+            .signals
+            .iter()
+            .filter_map(|signal| signal.changes.first())
+            .map(|&(tick, _)| tick)
+            .min()
+            .unwrap_or(0);
+        writeln!(w, "#{}", start)?;
+        writeln!(w, "$dumpvars")?;
+        let mut next = vec![0; self.signals.len()];
+        for (i, signal) in self.signals.iter().enumerate() {
+            match signal.changes.first() {
+                Some(&(tick, power)) if tick == start => {
+                    writeln!(w, "b{:04b} {}", power, vcd_id(i))?;
+                    next[i] = 1;
+                }
+                // The probe was added later on
+                _ => writeln!(w, "bxxxx {}", vcd_id(i))?,
+            }
+        }
+        writeln!(w, "$end")?;
+
+        // Write the changes of every signal in order of time
+        let next_tick = |next: &[usize]| {
+            self.signals
+                .iter()
+                .zip(next)
+                .filter_map(|(signal, &idx)| signal.changes.get(idx))
+                .map(|&(tick, _)| tick)
+                .min()
+        };
+        let mut time = start;
+        while let Some(tick) = next_tick(&next) {
+            time = tick;
+            writeln!(w, "#{}", tick)?;
+            for (i, signal) in self.signals.iter().enumerate() {
+                if let Some(&(change_tick, power)) = signal.changes.get(next[i])
+                    && change_tick == tick
+                {
+                    writeln!(w, "b{:04b} {}", power, vcd_id(i))?;
+                    next[i] += 1;
+                }
+            }
+        }
+        // Mark the end of the recording so the last values are shown up to it
+        if self.end > time {
+            writeln!(w, "#{}", self.end)?;
+        }
+        Ok(())
+    }
+}
+
+/// Creates a short identifier for a variable from the printable ASCII characters.
+fn vcd_id(mut idx: usize) -> String {
+    const FIRST: u8 = b'!';
+    const NUM_CHARS: usize = (b'~' - FIRST + 1) as usize;
+    let mut id = String::new();
+    loop {
+        id.push((FIRST + (idx % NUM_CHARS) as u8) as char);
+        idx /= NUM_CHARS;
+        if idx == 0 {
+            break id;
+        }
+        idx -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(name: &str) -> Probe {
+        Probe {
+            name: name.to_string(),
+            pos: BlockPos::new(0, 0, 0),
+        }
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        let ids: Vec<String> = (0..10000).map(vcd_id).collect();
+        let mut deduped = ids.clone();
+        deduped.sort();
+        deduped.dedup();
+        assert_eq!(ids.len(), deduped.len());
+        assert_eq!(ids[0], "!");
+        assert_eq!(ids[94], "!!");
+    }
+
+    #[test]
+    fn write_vcd() {
+        let mut clock = Signal::new(probe("clock"), 0, 0);
+        clock.record(1, 0);
+        clock.record(2, 15);
+        clock.record(3, 0);
+        let late = Signal::new(probe("late out"), 2, 7);
+        let trace = Trace {
+            end: 4,
+            signals: vec![clock, late],
+        };
+
+        let mut out = Vec::new();
+        trace.write_vcd(&mut out).unwrap();
+        let expected = "\
+$version MCHPRS redpiler $end
+$timescale 100 ms $end
+$scope module redpiler $end
+$var wire 4 ! clock $end
+$var wire 4 \" late_out $end
+$upscope $end
+$enddefinitions $end
+#0
+$dumpvars
+b0000 !
+bxxxx \"
+$end
+#2
+b1111 !
+b0111 \"
+#3
+b0000 !
+#4
+";
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+}
@@ -43,6 +43,11 @@ pub enum NodeType {
     Wire,
     Constant,
     NoteBlock,
+    Observer,
+    Target,
+    TripwireHook,
+    DaylightDetector,
+    CopperBulb,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
@@ -26,11 +26,15 @@ fn get_power_on_sides(comp: Comparator, world: &impl World, pos: BlockPos) -> u8
 }
 
 pub fn has_override(block: Block) -> bool {
+    if block.clone().get_copper_bulb_state().is_some() {
+        return true;
+    }
     matches!(
         block,
         Block::Barrel { .. }
             | Block::Furnace { .. }
             | Block::Hopper { .. }
+            | Block::Crafter { .. }
             | Block::Cauldron
             | Block::Composter { .. }
             | Block::Cake { .. }
@@ -39,8 +43,14 @@ pub fn has_override(block: Block) -> bool {
 }
 
 pub fn get_override(block: Block, world: &impl World, pos: BlockPos) -> u8 {
+    if let Some((lit, _)) = block.clone().get_copper_bulb_state() {
+        return super::bool_to_ss(*lit);
+    }
     match block {
-        Block::Barrel { .. } | Block::Furnace { .. } | Block::Hopper { .. } => {
+        Block::Barrel { .. }
+        | Block::Furnace { .. }
+        | Block::Hopper { .. }
+        | Block::Crafter { .. } => {
             match world.get_block_entity(pos) {
                 Some(BlockEntity::Container {
                     comparator_override,
@@ -180,10 +190,10 @@ pub fn tick(mut comp: Comparator, world: &mut impl World, pos: BlockPos) {
         let powered = comp.powered;
         if powered && !should_be_powered {
             comp.powered = false;
-            world.set_block(pos, Block::Comparator(comp));
+            super::set_block(world, pos, Block::Comparator(comp));
         } else if !powered && should_be_powered {
             comp.powered = true;
-            world.set_block(pos, Block::Comparator(comp));
+            super::set_block(world, pos, Block::Comparator(comp));
         }
         on_state_change(comp, world, pos);
     }
@@ -0,0 +1,34 @@
+//! Copper bulbs act as T flip-flops: every time they become powered they toggle between lit and
+//! unlit. They do not output a redstone signal themselves, but comparators read 15 from a lit bulb.
+
+use mchprs_blocks::blocks::Block;
+use mchprs_blocks::BlockPos;
+use mchprs_world::{TickPriority, World};
+
+/// How long it takes for a copper bulb to react to a change in its input.
+pub const DELAY: u32 = 1;
+
+pub fn update(block: Block, world: &mut impl World, pos: BlockPos) {
+    let Some((_, &mut powered)) = block.clone().get_copper_bulb_state() else {
+        return;
+    };
+    if powered != super::redstone_lamp_should_be_lit(world, pos) && !world.pending_tick_at(pos) {
+        world.schedule_tick(pos, DELAY, TickPriority::Normal);
+    }
+}
+
+pub fn tick(mut block: Block, world: &mut impl World, pos: BlockPos) {
+    let should_be_powered = super::redstone_lamp_should_be_lit(world, pos);
+    let Some((lit, powered)) = block.get_copper_bulb_state() else {
+        return;
+    };
+    if *powered == should_be_powered {
+        return;
+    }
+    if should_be_powered {
+        *lit = !*lit;
+    }
+    *powered = should_be_powered;
+    super::set_block(world, pos, block);
+    super::update_surrounding_blocks(world, pos);
+}
@@ -0,0 +1,30 @@
+use mchprs_blocks::blocks::Block;
+use mchprs_blocks::BlockPos;
+use mchprs_world::World;
+
+/// Sets the output strength of the daylight detector at `pos`, and optionally whether it is
+/// inverted. Daylight detectors ignore the time of day, so this is the only way their output
+/// changes. Returns false if there is no daylight detector at `pos`.
+pub fn set_power(world: &mut impl World, pos: BlockPos, power: u8, inverted: Option<bool>) -> bool {
+    let Block::DaylightDetector {
+        inverted: old_inverted,
+        power: old_power,
+    } = world.get_block(pos)
+    else {
+        return false;
+    };
+    let block = Block::DaylightDetector {
+        inverted: inverted.unwrap_or(old_inverted),
+        power,
+    };
+    super::set_block(world, pos, block);
+    if power != old_power {
+        super::update_surrounding_blocks(world, pos);
+    }
+    true
+}
+
+/// Returns the output strength a daylight detector will have after being right-clicked.
+pub fn next_power(power: u8) -> u8 {
+    (power + 1) % 16
+}
@@ -3,8 +3,14 @@
 //! scenerio (i.e. regular buiding)
 
 pub mod comparator;
+pub mod copper_bulb;
+pub mod daylight_detector;
 pub mod noteblock;
+pub mod observer;
+pub mod piston;
 pub mod repeater;
+pub mod target;
+pub mod tripwire;
 pub mod wire;
 
 use mchprs_blocks::block_entities::BlockEntity;
@@ -19,6 +25,16 @@ pub fn bool_to_ss(b: bool) -> u8 {
     }
 }
 
+/// Sets a block in the world and notifies any observers watching it.
+/// Returns true if a block was changed.
+fn set_block(world: &mut impl World, pos: BlockPos, block: Block) -> bool {
+    let changed = world.set_block(pos, block);
+    if changed {
+        observer::notify_observers(world, pos);
+    }
+    changed
+}
+
 fn get_weak_power(
     block: Block,
     world: &impl World,
@@ -38,9 +54,16 @@ fn get_weak_power(
         Block::RedstoneTorch { lit: true } if side != BlockFace::Top => 15,
         Block::RedstoneWallTorch { lit: true, facing } if facing.block_face() != side => 15,
         Block::RedstoneBlock => 15,
+        Block::Target { power } => power,
+        Block::DaylightDetector { power, .. } => power,
         Block::Lever { powered, .. } if powered => 15,
         Block::StoneButton { powered, .. } if powered => 15,
+        Block::TripwireHook { powered, .. } if powered => 15,
         Block::Repeater(repeater) if repeater.facing.block_face() == side && repeater.powered => 15,
+        Block::Observer {
+            facing,
+            powered: true,
+        } if facing.block_face() == side => 15,
         Block::Comparator(comparator) if comparator.facing.block_face() == side => {
             if let Some(BlockEntity::Comparator { output_strength }) = world.get_block_entity(pos) {
                 *output_strength
@@ -110,9 +133,13 @@ fn get_strong_power(
                 _ => face == LeverFace::Wall && facing == side.unwrap_direction(),
             } && powered,
         ),
+        Block::TripwireHook {
+            facing, powered, ..
+        } => bool_to_ss(facing.block_face() == side && powered),
         Block::RedstoneWire { .. } => get_weak_power(block, world, pos, side, dust_power),
         Block::Repeater(_) => get_weak_power(block, world, pos, side, dust_power),
         Block::Comparator(_) => get_weak_power(block, world, pos, side, dust_power),
+        Block::Observer { .. } => get_weak_power(block, world, pos, side, dust_power),
         _ => 0,
     }
 }
@@ -139,7 +166,8 @@ pub fn get_redstone_power(
     facing: BlockFace,
 ) -> u8 {
     if block.is_solid() {
-        get_max_strong_power(world, pos, true)
+        // Some solid blocks, like target blocks, are a source of power themselves
+        get_max_strong_power(world, pos, true).max(get_weak_power(block, world, pos, facing, true))
     } else {
         get_weak_power(block, world, pos, facing, true)
     }
@@ -153,6 +181,7 @@ fn get_redstone_power_no_dust(
 ) -> u8 {
     if block.is_solid() {
         get_max_strong_power(world, pos, false)
+            .max(get_weak_power(block, world, pos, facing, false))
     } else {
         get_weak_power(block, world, pos, facing, false)
     }
@@ -227,7 +256,7 @@ pub fn update(block: Block, world: &mut impl World, pos: BlockPos) {
             if lit && !should_be_lit {
                 world.schedule_tick(pos, 2, TickPriority::Normal);
             } else if !lit && should_be_lit {
-                world.set_block(pos, Block::RedstoneLamp { lit: true });
+                set_block(world, pos, Block::RedstoneLamp { lit: true });
             }
         }
         Block::IronTrapdoor {
@@ -246,7 +275,7 @@ pub fn update(block: Block, world: &mut impl World, pos: BlockPos) {
                     open: should_be_powered,
                     waterlogged,
                 };
-                world.set_block(pos, new_block);
+                set_block(world, pos, new_block);
             }
         }
         Block::NoteBlock {
@@ -272,9 +301,18 @@ pub fn update(block: Block, world: &mut impl World, pos: BlockPos) {
                 if should_be_powered && noteblock::is_noteblock_unblocked(world, pos) {
                     noteblock::play_note(world, pos, instrument, note);
                 }
-                world.set_block(pos, new_block);
+                set_block(world, pos, new_block);
             }
         }
+        Block::Piston { extended, facing } => {
+            piston::update(false, extended, facing, world, pos);
+        }
+        Block::StickyPiston { extended, facing } => {
+            piston::update(true, extended, facing, world, pos);
+        }
+        block if block.clone().get_copper_bulb_state().is_some() => {
+            copper_bulb::update(block, world, pos);
+        }
         _ => {}
     }
 }
@@ -290,36 +328,55 @@ pub fn tick(block: Block, world: &mut impl World, pos: BlockPos) {
         Block::RedstoneTorch { lit } => {
             let should_be_off = torch_should_be_off(world, pos);
             if lit && should_be_off {
-                world.set_block(pos, Block::RedstoneTorch { lit: false });
+                set_block(world, pos, Block::RedstoneTorch { lit: false });
                 update_surrounding_blocks(world, pos);
             } else if !lit && !should_be_off {
-                world.set_block(pos, Block::RedstoneTorch { lit: true });
+                set_block(world, pos, Block::RedstoneTorch { lit: true });
                 update_surrounding_blocks(world, pos);
             }
         }
         Block::RedstoneWallTorch { lit, facing } => {
             let should_be_off = wall_torch_should_be_off(world, pos, facing);
             if lit && should_be_off {
-                world.set_block(pos, Block::RedstoneWallTorch { lit: false, facing });
+                set_block(world, pos, Block::RedstoneWallTorch { lit: false, facing });
                 update_surrounding_blocks(world, pos);
             } else if !lit && !should_be_off {
-                world.set_block(pos, Block::RedstoneWallTorch { lit: true, facing });
+                set_block(world, pos, Block::RedstoneWallTorch { lit: true, facing });
                 update_surrounding_blocks(world, pos);
             }
         }
         Block::RedstoneLamp { lit } => {
             let should_be_lit = redstone_lamp_should_be_lit(world, pos);
             if lit && !should_be_lit {
-                world.set_block(pos, Block::RedstoneLamp { lit: false });
+                set_block(world, pos, Block::RedstoneLamp { lit: false });
             }
         }
+        Block::Observer { facing, powered } => {
+            observer::tick(facing, powered, world, pos);
+        }
+        Block::Target { power } => {
+            target::tick(power, world, pos);
+        }
+        Block::MovingPiston { .. } => {
+            piston::finish_moving(world, pos, false);
+        }
+        Block::TripwireHook { .. } => {
+            tripwire::update_hook(world, pos, false);
+        }
+        Block::Tripwire { .. } => {
+            tripwire::tick(block, world, pos);
+        }
+        block if block.clone().get_copper_bulb_state().is_some() => {
+            copper_bulb::tick(block, world, pos);
+        }
         Block::StoneButton {
             powered,
             face,
             facing,
         } => {
             if powered {
-                world.set_block(
+                set_block(
+                    world,
                     pos,
                     Block::StoneButton {
                         face,
@@ -388,14 +445,14 @@ pub fn on_use(block: Block, world: &mut impl World, pos: BlockPos) -> bool {
             if repeater.delay > 4 {
                 repeater.delay -= 4;
             }
-            world.set_block(pos, Block::Repeater(repeater));
+            set_block(world, pos, Block::Repeater(repeater));
             true
         }
         Block::Comparator(comparator) => {
             let mut comparator = comparator;
             comparator.mode = comparator.mode.toggle();
             comparator::tick(comparator, world, pos);
-            world.set_block(pos, Block::Comparator(comparator));
+            set_block(world, pos, Block::Comparator(comparator));
             true
         }
         Block::Lever {
@@ -403,7 +460,8 @@ pub fn on_use(block: Block, world: &mut impl World, pos: BlockPos) -> bool {
             facing,
             powered,
         } => {
-            world.set_block(
+            set_block(
+                world,
                 pos,
                 Block::Lever {
                     powered: !powered,
@@ -431,7 +489,8 @@ pub fn on_use(block: Block, world: &mut impl World, pos: BlockPos) -> bool {
             facing,
         } => {
             if !powered {
-                world.set_block(
+                set_block(
+                    world,
                     pos,
                     Block::StoneButton {
                         powered: true,
@@ -465,18 +524,23 @@ pub fn on_use(block: Block, world: &mut impl World, pos: BlockPos) -> bool {
                 new_wire.power = wire.power;
                 new_wire = wire::get_regulated_sides(new_wire, world, pos);
                 if wire != new_wire {
-                    world.set_block(pos, Block::RedstoneWire(new_wire));
+                    set_block(world, pos, Block::RedstoneWire(new_wire));
                     update_wire_neighbors(world, pos);
                     return true;
                 }
             }
             false
         }
+        Block::DaylightDetector { power, .. } => {
+            daylight_detector::set_power(world, pos, daylight_detector::next_power(power), None);
+            true
+        }
         Block::NoteBlock { note, powered, .. } => {
             let note = (note + 1) % 25;
             let instrument = noteblock::get_noteblock_instrument(world, pos);
 
-            world.set_block(
+            set_block(
+                world,
                 pos,
                 Block::NoteBlock {
                     instrument,
@@ -0,0 +1,52 @@
+use mchprs_blocks::blocks::Block;
+use mchprs_blocks::{BlockFace, BlockFacing, BlockPos};
+use mchprs_world::{TickPriority, World};
+
+/// Schedules a pulse for every observer that is watching `pos`. This should be
+/// called whenever the block at `pos` changes state.
+pub fn notify_observers(world: &mut impl World, pos: BlockPos) {
+    for face in &BlockFace::values() {
+        let observer_pos = pos.offset(*face);
+        if let Block::Observer { facing, .. } = world.get_block(observer_pos)
+            && facing.opposite().block_face() == *face
+            && !world.pending_tick_at(observer_pos)
+        {
+            world.schedule_tick(observer_pos, 1, TickPriority::Normal);
+        }
+    }
+}
+
+fn on_state_change(facing: BlockFacing, world: &mut impl World, pos: BlockPos) {
+    let front_pos = pos.offset(facing.opposite().block_face());
+    let front_block = world.get_block(front_pos);
+    super::update(front_block, world, front_pos);
+    for direction in &BlockFace::values() {
+        let neighbor_pos = front_pos.offset(*direction);
+        let block = world.get_block(neighbor_pos);
+        super::update(block, world, neighbor_pos);
+    }
+}
+
+pub fn tick(facing: BlockFacing, powered: bool, world: &mut impl World, pos: BlockPos) {
+    if powered {
+        super::set_block(
+            world,
+            pos,
+            Block::Observer {
+                facing,
+                powered: false,
+            },
+        );
+    } else {
+        super::set_block(
+            world,
+            pos,
+            Block::Observer {
+                facing,
+                powered: true,
+            },
+        );
+        world.schedule_tick(pos, 1, TickPriority::Normal);
+    }
+    on_state_change(facing, world, pos);
+}
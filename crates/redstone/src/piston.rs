@@ -0,0 +1,373 @@
+//! Pistons start moving as soon as they are updated. The blocks they move are
+//! replaced with moving pistons, which turn back into the moved blocks one
+//! redstone tick later.
+
+use mchprs_blocks::block_entities::BlockEntity;
+use mchprs_blocks::blocks::{Block, PistonType};
+use mchprs_blocks::{BlockFace, BlockFacing, BlockPos};
+use mchprs_world::{TickPriority, World};
+
+/// The maximum number of blocks a piston can push or pull.
+pub const PUSH_LIMIT: usize = 12;
+
+#[derive(PartialEq, Eq)]
+enum PushReaction {
+    Normal,
+    Destroy,
+    Block,
+}
+
+fn push_reaction(block: Block) -> PushReaction {
+    if block.clone().get_pressure_plate_powered().is_some() {
+        return PushReaction::Destroy;
+    }
+    match block {
+        Block::RedstoneWire(_)
+        | Block::RedstoneTorch { .. }
+        | Block::RedstoneWallTorch { .. }
+        | Block::Repeater(_)
+        | Block::Comparator(_)
+        | Block::Lever { .. }
+        | Block::StoneButton { .. }
+        | Block::TripwireHook { .. }
+        | Block::Tripwire { .. }
+        | Block::SeaPickle { .. }
+        | Block::Cake { .. }
+        | Block::Pumpkin => PushReaction::Destroy,
+        // Daylight detectors have a block entity in vanilla
+        Block::PistonHead { .. }
+        | Block::MovingPiston { .. }
+        | Block::EndPortalFrame { .. }
+        | Block::DaylightDetector { .. } => PushReaction::Block,
+        _ => PushReaction::Normal,
+    }
+}
+
+fn piston_block(sticky: bool, extended: bool, facing: BlockFacing) -> Block {
+    if sticky {
+        Block::StickyPiston { extended, facing }
+    } else {
+        Block::Piston { extended, facing }
+    }
+}
+
+fn piston_type(sticky: bool) -> PistonType {
+    if sticky {
+        PistonType::Sticky
+    } else {
+        PistonType::Normal
+    }
+}
+
+fn in_world(world: &impl World, pos: BlockPos) -> bool {
+    let (chunk_x, chunk_z) = (pos.x >> 4, pos.z >> 4);
+    pos.y >= 0
+        && world.get_chunk(chunk_x, chunk_z).is_some_and(|chunk| {
+            chunk.x == chunk_x && chunk.z == chunk_z && (pos.y as usize) < chunk.sections.len() * 16
+        })
+}
+
+fn is_pushable(
+    world: &impl World,
+    block: Block,
+    pos: BlockPos,
+    move_dir: BlockFace,
+    allow_destroy: bool,
+) -> bool {
+    if !in_world(world, pos) {
+        return false;
+    }
+    if block == Block::Air {
+        return true;
+    }
+    if !in_world(world, pos.offset(move_dir)) {
+        return false;
+    }
+    match block {
+        Block::Piston { extended, .. } | Block::StickyPiston { extended, .. } => !extended,
+        _ => match push_reaction(block) {
+            PushReaction::Normal => !block.has_block_entity(),
+            PushReaction::Destroy => allow_destroy,
+            PushReaction::Block => false,
+        },
+    }
+}
+
+/// The blocks that will be affected by a piston moving.
+#[derive(Default)]
+struct PistonStructure {
+    /// Blocks that will be moved, ordered from closest to furthest from the piston
+    to_push: Vec<BlockPos>,
+    /// Blocks that will be broken
+    to_destroy: Vec<BlockPos>,
+}
+
+/// Finds the blocks that will be moved by the piston at `pos`. Returns `None` if the piston is
+/// unable to move them.
+fn resolve_structure(
+    world: &impl World,
+    pos: BlockPos,
+    facing: BlockFacing,
+    extending: bool,
+) -> Option<PistonStructure> {
+    let dir = facing.block_face();
+    let (start, move_dir) = if extending {
+        (pos.offset(dir), dir)
+    } else {
+        (pos.offset(dir).offset(dir), facing.opposite().block_face())
+    };
+
+    let mut structure = PistonStructure::default();
+    let start_block = world.get_block(start);
+    if !is_pushable(world, start_block, start, move_dir, false) {
+        if extending && push_reaction(start_block) == PushReaction::Destroy {
+            structure.to_destroy.push(start);
+            return Some(structure);
+        }
+        return None;
+    }
+    if start_block == Block::Air {
+        return Some(structure);
+    }
+
+    structure.to_push.push(start);
+    let mut next = start;
+    loop {
+        next = next.offset(move_dir);
+        let block = world.get_block(next);
+        if block == Block::Air {
+            break;
+        }
+        if next == pos || !is_pushable(world, block, next, move_dir, true) {
+            return None;
+        }
+        if push_reaction(block) == PushReaction::Destroy {
+            structure.to_destroy.push(next);
+            break;
+        }
+        if structure.to_push.len() >= PUSH_LIMIT {
+            return None;
+        }
+        structure.to_push.push(next);
+    }
+    Some(structure)
+}
+
+/// Returns true if the piston at `pos` is receiving power from any side other than its front.
+/// Like in vanilla, pistons can also be powered by anything that would power the block above
+/// them (quasi-connectivity).
+pub fn should_extend(world: &impl World, pos: BlockPos, facing: BlockFacing) -> bool {
+    for face in &BlockFace::values() {
+        if *face == facing.block_face() {
+            continue;
+        }
+        let neighbor_pos = pos.offset(*face);
+        let neighbor = world.get_block(neighbor_pos);
+        if super::get_redstone_power(neighbor, world, neighbor_pos, *face) > 0 {
+            return true;
+        }
+    }
+
+    let up_pos = pos.offset(BlockFace::Top);
+    for face in &BlockFace::values() {
+        if *face == BlockFace::Bottom {
+            continue;
+        }
+        let neighbor_pos = up_pos.offset(*face);
+        let neighbor = world.get_block(neighbor_pos);
+        if super::get_redstone_power(neighbor, world, neighbor_pos, *face) > 0 {
+            return true;
+        }
+    }
+    false
+}
+
+pub fn update(
+    sticky: bool,
+    extended: bool,
+    facing: BlockFacing,
+    world: &mut impl World,
+    pos: BlockPos,
+) {
+    let should_extend = should_extend(world, pos, facing);
+    if should_extend && !extended {
+        extend(sticky, facing, world, pos);
+    } else if !should_extend && extended {
+        retract(sticky, facing, world, pos);
+    }
+}
+
+fn start_moving(
+    world: &mut impl World,
+    pos: BlockPos,
+    moving: Block,
+    moved: Block,
+    facing: BlockFacing,
+    extending: bool,
+    source: bool,
+) {
+    super::set_block(world, pos, moving);
+    world.set_block_entity(
+        pos,
+        BlockEntity::MovingPiston {
+            block_state: moved.get_id(),
+            facing,
+            extending,
+            source,
+        },
+    );
+    world.schedule_tick(pos, 1, TickPriority::Normal);
+}
+
+/// Moves the blocks in front of a piston. Returns the positions of all blocks that were changed,
+/// or `None` if the blocks could not be moved.
+fn move_blocks(
+    sticky: bool,
+    facing: BlockFacing,
+    extending: bool,
+    world: &mut impl World,
+    pos: BlockPos,
+) -> Option<Vec<BlockPos>> {
+    let head_pos = pos.offset(facing.block_face());
+    let structure = resolve_structure(world, pos, facing, extending)?;
+    let move_dir = if extending {
+        facing.block_face()
+    } else {
+        facing.opposite().block_face()
+    };
+
+    let mut changed = Vec::new();
+    for &destroy_pos in structure.to_destroy.iter().rev() {
+        world.delete_block_entity(destroy_pos);
+        super::set_block(world, destroy_pos, Block::Air);
+        changed.push(destroy_pos);
+    }
+
+    let mut vacated = structure.to_push.clone();
+    for &push_pos in structure.to_push.iter().rev() {
+        let block = world.get_block(push_pos);
+        let dest = push_pos.offset(move_dir);
+        vacated.retain(|&pos| pos != dest);
+        let moving = Block::MovingPiston {
+            facing,
+            ty: PistonType::Normal,
+        };
+        start_moving(world, dest, moving, block, facing, extending, false);
+        changed.push(dest);
+    }
+
+    if extending {
+        let ty = piston_type(sticky);
+        let head = Block::PistonHead {
+            facing,
+            short: false,
+            ty,
+        };
+        vacated.retain(|&pos| pos != head_pos);
+        let moving = Block::MovingPiston { facing, ty };
+        start_moving(world, head_pos, moving, head, facing, true, true);
+        changed.push(head_pos);
+    }
+
+    for &vacated_pos in &vacated {
+        super::set_block(world, vacated_pos, Block::Air);
+        changed.push(vacated_pos);
+    }
+    Some(changed)
+}
+
+fn extend(sticky: bool, facing: BlockFacing, world: &mut impl World, pos: BlockPos) {
+    let Some(changed) = move_blocks(sticky, facing, true, world, pos) else {
+        return;
+    };
+    super::set_block(world, pos, piston_block(sticky, true, facing));
+
+    super::update_surrounding_blocks(world, pos);
+    for changed_pos in changed {
+        super::update_surrounding_blocks(world, changed_pos);
+    }
+}
+
+fn retract(sticky: bool, facing: BlockFacing, world: &mut impl World, pos: BlockPos) {
+    let dir = facing.block_face();
+    let head_pos = pos.offset(dir);
+    // The base has to start moving first so that updates from finishing the head below don't
+    // cause the piston to retract again
+    let moving = Block::MovingPiston {
+        facing,
+        ty: piston_type(sticky),
+    };
+    let retracted = piston_block(sticky, false, facing);
+    start_moving(world, pos, moving, retracted, facing, false, true);
+
+    // If the head hasn't finished extending yet, it disappears right away
+    if let Block::MovingPiston { .. } = world.get_block(head_pos) {
+        finish_moving(world, head_pos, true);
+    }
+
+    let mut changed = vec![head_pos];
+    if let Block::PistonHead { .. } = world.get_block(head_pos) {
+        super::set_block(world, head_pos, Block::Air);
+    }
+
+    if sticky {
+        let pull_pos = head_pos.offset(dir);
+        let pull_block = world.get_block(pull_pos);
+        let still_extending = matches!(
+            world.get_block_entity(pull_pos),
+            Some(BlockEntity::MovingPiston {
+                facing: moving_facing,
+                extending: true,
+                ..
+            }) if *moving_facing == facing
+        );
+        if let Block::MovingPiston { .. } = pull_block
+            && still_extending
+        {
+            // A block that is still being pushed by this piston gets left behind
+            finish_moving(world, pull_pos, true);
+        } else if push_reaction(pull_block) == PushReaction::Normal
+            && let Some(moved) = move_blocks(sticky, facing, false, world, pos)
+        {
+            changed.extend(moved);
+        }
+    }
+
+    super::update_surrounding_blocks(world, pos);
+    for changed_pos in changed {
+        super::update_surrounding_blocks(world, changed_pos);
+    }
+}
+
+/// Replaces the moving piston at `pos` with the block it is moving. If `interrupted` is true, the
+/// move was cut short and moving piston heads are removed instead of placed.
+pub fn finish_moving(world: &mut impl World, pos: BlockPos, interrupted: bool) {
+    let Some(&BlockEntity::MovingPiston {
+        block_state,
+        source,
+        ..
+    }) = world.get_block_entity(pos)
+    else {
+        return;
+    };
+    world.delete_block_entity(pos);
+
+    let mut block = match Block::from_id(block_state) {
+        Block::PistonHead { .. } if source && interrupted => Block::Air,
+        block => block,
+    };
+    // Observers always produce a pulse after being moved
+    if let Block::Observer { facing, .. } = block {
+        block = Block::Observer {
+            facing,
+            powered: false,
+        };
+        if !world.pending_tick_at(pos) {
+            world.schedule_tick(pos, 1, TickPriority::Normal);
+        }
+    }
+
+    super::set_block(world, pos, block);
+    super::update(block, world, pos);
+    super::update_surrounding_blocks(world, pos);
+}
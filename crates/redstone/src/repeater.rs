@@ -62,10 +62,10 @@ pub fn on_neighbor_updated(mut rep: Repeater, world: &mut impl World, pos: Block
     let should_be_locked = should_be_locked(rep.facing, world, pos);
     if !rep.locked && should_be_locked {
         rep.locked = true;
-        world.set_block(pos, Block::Repeater(rep));
+        super::set_block(world, pos, Block::Repeater(rep));
     } else if rep.locked && !should_be_locked {
         rep.locked = false;
-        world.set_block(pos, Block::Repeater(rep));
+        super::set_block(world, pos, Block::Repeater(rep));
     }
 
     if !rep.locked && !world.pending_tick_at(pos) {
@@ -84,14 +84,14 @@ pub fn tick(mut rep: Repeater, world: &mut impl World, pos: BlockPos) {
     let should_be_powered = should_be_powered(rep, world, pos);
     if rep.powered && !should_be_powered {
         rep.powered = false;
-        world.set_block(pos, Block::Repeater(rep));
+        super::set_block(world, pos, Block::Repeater(rep));
         on_state_change(rep, world, pos);
     } else if !rep.powered {
         if !should_be_powered {
             world.schedule_tick(pos, rep.delay as u32, TickPriority::Higher);
         }
         rep.powered = true;
-        world.set_block(pos, Block::Repeater(rep));
+        super::set_block(world, pos, Block::Repeater(rep));
         on_state_change(rep, world, pos);
     }
 }
@@ -0,0 +1,45 @@
+use mchprs_blocks::blocks::Block;
+use mchprs_blocks::{BlockFace, BlockPos};
+use mchprs_world::{TickPriority, World};
+
+/// How long a target block stays powered after being hit by an arrow or trident.
+pub const ARROW_HIT_TICKS: u32 = 10;
+/// How long a target block stays powered after being hit by any other projectile.
+pub const PROJECTILE_HIT_TICKS: u32 = 4;
+
+/// Calculates the output strength of a target block hit on `face` at `cursor`, which is the
+/// position of the hit relative to the block. Hits closer to the center of the face produce a
+/// stronger signal.
+pub fn get_hit_strength(face: BlockFace, cursor: (f32, f32, f32)) -> u8 {
+    let (x, y, z) = cursor;
+    let dx = (x.fract() - 0.5).abs();
+    let dy = (y.fract() - 0.5).abs();
+    let dz = (z.fract() - 0.5).abs();
+    let dist = match face {
+        BlockFace::Top | BlockFace::Bottom => dx.max(dz),
+        BlockFace::North | BlockFace::South => dx.max(dy),
+        BlockFace::East | BlockFace::West => dy.max(dz),
+    };
+    let strength = (15.0 * ((0.5 - dist) / 0.5).clamp(0.0, 1.0)).ceil() as u8;
+    strength.max(1)
+}
+
+/// Simulates a projectile hitting the target block at `pos`. The target will output `power` for
+/// `ticks` redstone ticks. Returns false if the target is still powered from a previous hit, in
+/// which case the hit is ignored.
+pub fn hit(world: &mut impl World, pos: BlockPos, power: u8, ticks: u32) -> bool {
+    if world.pending_tick_at(pos) {
+        return false;
+    }
+    super::set_block(world, pos, Block::Target { power });
+    world.schedule_tick(pos, ticks, TickPriority::Normal);
+    super::update_surrounding_blocks(world, pos);
+    true
+}
+
+pub fn tick(power: u8, world: &mut impl World, pos: BlockPos) {
+    if power != 0 {
+        super::set_block(world, pos, Block::Target { power: 0 });
+        super::update_surrounding_blocks(world, pos);
+    }
+}
@@ -0,0 +1,276 @@
+//! Tripwire hooks facing each other are connected by a line of tripwire string. When the string is
+//! triggered, both hooks power the blocks they are attached to.
+
+use mchprs_blocks::blocks::Block;
+use mchprs_blocks::{BlockDirection, BlockPos};
+use mchprs_world::{TickPriority, World};
+
+/// The furthest distance a hook will search for another hook to connect to.
+pub const MAX_LENGTH: i32 = 42;
+/// How long string stays triggered. This is how often vanilla checks for entities standing
+/// in the string.
+pub const ENTITY_CHECK_TICKS: u32 = 5;
+
+fn connects_to(block: Block, direction: BlockDirection) -> bool {
+    match block {
+        Block::TripwireHook { facing, .. } => facing == direction.opposite(),
+        Block::Tripwire { .. } => true,
+        _ => false,
+    }
+}
+
+/// Recalculates which sides of the string at `pos` are connected to neighboring string or hooks.
+pub fn update_connections(block: Block, world: &impl World, pos: BlockPos) -> Block {
+    let Block::Tripwire {
+        attached,
+        disarmed,
+        powered,
+        ..
+    } = block
+    else {
+        return block;
+    };
+    let connects = |direction: BlockDirection| {
+        connects_to(
+            world.get_block(pos.offset(direction.block_face())),
+            direction,
+        )
+    };
+    Block::Tripwire {
+        attached,
+        disarmed,
+        powered,
+        north: connects(BlockDirection::North),
+        east: connects(BlockDirection::East),
+        south: connects(BlockDirection::South),
+        west: connects(BlockDirection::West),
+    }
+}
+
+pub fn get_state_for_placement(world: &impl World, pos: BlockPos) -> Block {
+    let block = Block::Tripwire {
+        attached: false,
+        disarmed: false,
+        north: false,
+        east: false,
+        south: false,
+        west: false,
+        powered: false,
+    };
+    update_connections(block, world, pos)
+}
+
+fn update_hook_neighbors(world: &mut impl World, pos: BlockPos, facing: BlockDirection) {
+    super::update_surrounding_blocks(world, pos);
+    super::update_surrounding_blocks(world, pos.offset(facing.opposite().block_face()));
+}
+
+/// Searches for the hook that the hook at `pos` is connected to. Returns the distance to the other
+/// hook if there is one, whether every block in between is string, and whether any of the string
+/// has been triggered.
+fn search_line(world: &impl World, pos: BlockPos, facing: BlockDirection) -> (i32, bool, bool) {
+    let mut other_distance = 0;
+    let mut complete = true;
+    let mut triggered = false;
+    for distance in 1..MAX_LENGTH {
+        match world.get_block(facing.block_facing().offset_pos(pos, distance)) {
+            Block::TripwireHook {
+                facing: other_facing,
+                ..
+            } => {
+                if other_facing == facing.opposite() {
+                    other_distance = distance;
+                }
+                break;
+            }
+            Block::Tripwire {
+                disarmed, powered, ..
+            } => triggered |= powered && !disarmed,
+            _ => complete = false,
+        }
+    }
+    (other_distance, complete, triggered)
+}
+
+/// Recalculates the state of the hook at `pos` and the hook it is connected to, along with all the
+/// string in between. If `removing` is true, the hook at `pos` is about to be removed and the line
+/// is detached.
+pub fn update_hook(world: &mut impl World, pos: BlockPos, removing: bool) {
+    let Block::TripwireHook {
+        attached: was_attached,
+        facing,
+        ..
+    } = world.get_block(pos)
+    else {
+        return;
+    };
+
+    let (other_distance, complete, triggered) = search_line(world, pos, facing);
+    let attached = !removing && complete && other_distance > 1;
+    let powered = attached && triggered;
+
+    if other_distance > 0 {
+        let other_pos = facing.block_facing().offset_pos(pos, other_distance);
+        let other_facing = facing.opposite();
+        let other = Block::TripwireHook {
+            attached,
+            facing: other_facing,
+            powered,
+        };
+        if super::set_block(world, other_pos, other) {
+            update_hook_neighbors(world, other_pos, other_facing);
+        }
+    }
+
+    if !removing {
+        let hook = Block::TripwireHook {
+            attached,
+            facing,
+            powered,
+        };
+        if super::set_block(world, pos, hook) {
+            update_hook_neighbors(world, pos, facing);
+        }
+    }
+
+    if attached != was_attached {
+        for distance in 1..other_distance {
+            let string_pos = facing.block_facing().offset_pos(pos, distance);
+            if let Block::Tripwire {
+                disarmed,
+                north,
+                east,
+                south,
+                west,
+                powered,
+                ..
+            } = world.get_block(string_pos)
+            {
+                let string = Block::Tripwire {
+                    attached,
+                    disarmed,
+                    north,
+                    east,
+                    south,
+                    west,
+                    powered,
+                };
+                super::set_block(world, string_pos, string);
+            }
+        }
+    }
+}
+
+/// Updates the hooks at either end of the line of string that `pos` is part of.
+pub fn update_source(world: &mut impl World, pos: BlockPos) {
+    for direction in [BlockDirection::South, BlockDirection::West] {
+        for distance in 1..MAX_LENGTH {
+            let other_pos = direction.block_facing().offset_pos(pos, distance);
+            match world.get_block(other_pos) {
+                Block::TripwireHook { facing, .. } => {
+                    if facing == direction.opposite() {
+                        update_hook(world, other_pos, false);
+                    }
+                    break;
+                }
+                Block::Tripwire { .. } => {}
+                _ => break,
+            }
+        }
+    }
+}
+
+/// Returns the positions of the hooks that would be powered by triggering the string at `pos`.
+pub fn find_line_hooks(world: &impl World, pos: BlockPos) -> Option<[BlockPos; 2]> {
+    let Block::Tripwire {
+        attached: true,
+        disarmed: false,
+        ..
+    } = world.get_block(pos)
+    else {
+        return None;
+    };
+    for direction in [BlockDirection::South, BlockDirection::West] {
+        let mut hooks = [pos; 2];
+        for (hook, direction) in hooks.iter_mut().zip([direction, direction.opposite()]) {
+            for distance in 1..MAX_LENGTH {
+                let other_pos = direction.block_facing().offset_pos(pos, distance);
+                match world.get_block(other_pos) {
+                    Block::TripwireHook {
+                        facing,
+                        attached: true,
+                        ..
+                    } if facing == direction.opposite() => {
+                        *hook = other_pos;
+                        break;
+                    }
+                    Block::Tripwire { .. } => {}
+                    _ => break,
+                }
+            }
+        }
+        if !hooks.contains(&pos) {
+            return Some(hooks);
+        }
+    }
+    None
+}
+
+/// Simulates something passing through the string at `pos`, triggering it for `ticks` redstone
+/// ticks. Returns false if the string is still triggered, in which case nothing happens.
+pub fn trigger(world: &mut impl World, pos: BlockPos, ticks: u32) -> bool {
+    let block = world.get_block(pos);
+    let Block::Tripwire {
+        attached,
+        disarmed,
+        north,
+        east,
+        south,
+        west,
+        powered: false,
+    } = block
+    else {
+        return false;
+    };
+    if world.pending_tick_at(pos) {
+        return false;
+    }
+    let block = Block::Tripwire {
+        attached,
+        disarmed,
+        north,
+        east,
+        south,
+        west,
+        powered: true,
+    };
+    super::set_block(world, pos, block);
+    world.schedule_tick(pos, ticks, TickPriority::Normal);
+    update_source(world, pos);
+    true
+}
+
+pub fn tick(block: Block, world: &mut impl World, pos: BlockPos) {
+    if let Block::Tripwire {
+        attached,
+        disarmed,
+        north,
+        east,
+        south,
+        west,
+        powered: true,
+    } = block
+    {
+        let block = Block::Tripwire {
+            attached,
+            disarmed,
+            north,
+            east,
+            south,
+            west,
+            powered: false,
+        };
+        super::set_block(world, pos, block);
+        update_source(world, pos);
+    }
+}
@@ -1,3 +1,4 @@
+use mchprs_redpiler::diagnostics::Diagnostics;
 use mchprs_redpiler::passes::{build_pass_pipeline, PassRegistry};
 use mchprs_redpiler::ril::ast::Global;
 use mchprs_redpiler::ril::dump_graph;
@@ -48,7 +49,15 @@ pub fn compile(input_path: &Path, output_path: &Option<PathBuf>, options: &Compi
         };
 
         let monitor = Arc::new(TaskMonitor::default());
-        let result_graph = pass_pipeline.run_passes(options, &input, graph, monitor);
+        let mut diagnostics = Diagnostics::default();
+        let result_graph =
+            pass_pipeline.run_passes(options, &input, graph, monitor, &mut diagnostics);
+        for diagnostic in diagnostics.iter() {
+            eprintln!("{}", diagnostic);
+        }
+        if diagnostics.has_errors() {
+            process::exit(1);
+        }
         result.push('\n');
         dump_graph(&mut result, &result_graph, name).unwrap();
         result.push('\n');
@@ -166,6 +166,9 @@ fn main() {
                 print_before_backend: false,
                 backend_variant: Default::default(),
                 passes,
+                persist: false,
+                incremental: false,
+                selection: false,
             };
             compile::compile(&input_path, &output_path, &options);
         }
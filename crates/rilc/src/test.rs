@@ -1,5 +1,6 @@
 use anstream::println;
 use mchprs_redpiler::{
+    diagnostics::Diagnostics,
     passes::{build_pass_pipeline, PassRegistry},
     ril::{self, RILModule, RILTest},
     string_replacer::StringReplacer,
@@ -64,7 +65,12 @@ fn run_test(
         None => build_pass_pipeline(&registry, &test.options),
     };
     let monitor = Arc::new(TaskMonitor::default());
-    let result_graph = pass_pipeline.run_passes(&test.options, &input, test.graph, monitor);
+    let mut diagnostics = Diagnostics::default();
+    let result_graph =
+        pass_pipeline.run_passes(&test.options, &input, test.graph, monitor, &mut diagnostics);
+    for diagnostic in diagnostics.iter() {
+        println!("{}", diagnostic);
+    }
     let test_path = match test_root {
         Some(test_root) => test_path.strip_prefix(test_root).unwrap(),
         None => test_path,
@@ -0,0 +1,85 @@
+use crate::plot_data::{PlotLoadError, PlotSaveError};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use mchprs_blocks::block_entities::BlockEntity;
+use mchprs_blocks::BlockPos;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Version History:
+/// 0: Initial history file
+pub const VERSION: u32 = 0;
+
+static HISTORY_MAGIC: &[u8; 8] = b"\x86MCHPRSH";
+
+/// A clipboard with its blocks stored like a chunk section.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClipboardData {
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub offset_z: i32,
+    pub size_x: u32,
+    pub size_y: u32,
+    pub size_z: u32,
+    pub bits_per_entry: u8,
+    pub data: Vec<u64>,
+    pub palette: Vec<u32>,
+    pub block_entities: FxHashMap<BlockPos, BlockEntity>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryEntryData {
+    pub id: u32,
+    pub author: u128,
+    pub author_name: String,
+    /// The command or tool that made the edit.
+    pub description: String,
+    /// When the edit was made, in seconds since the unix epoch.
+    pub time: u64,
+    /// If the edit is undone, the order it was undone in.
+    pub undone: Option<u32>,
+    pub pos: BlockPos,
+    /// The blocks to paste at `pos` to undo the edit, or to redo it if it is undone.
+    pub clipboards: Vec<ClipboardData>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HistoryData {
+    pub next_id: u32,
+    pub next_undo: u32,
+    pub entries: Vec<HistoryEntryData>,
+}
+
+impl HistoryData {
+    pub fn load_from_file(path: impl AsRef<Path>) -> Result<HistoryData, PlotLoadError> {
+        let mut file = File::open(&path)?;
+
+        let mut magic = [0; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != HISTORY_MAGIC {
+            return Err(PlotLoadError::InvalidHeader);
+        }
+
+        let version = file.read_u32::<LittleEndian>()?;
+        if version > VERSION {
+            return Err(PlotLoadError::TooNew(version));
+        }
+
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        Ok(bincode::deserialize(&buf)?)
+    }
+
+    pub fn save_to_file(&self, path: impl AsRef<Path>) -> Result<(), PlotSaveError> {
+        let mut file = File::create(path)?;
+
+        file.write_all(HISTORY_MAGIC)?;
+        file.write_u32::<LittleEndian>(VERSION)?;
+        let data = bincode::serialize(self)?;
+        file.write_all(&data)?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
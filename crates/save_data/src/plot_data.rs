@@ -18,7 +18,8 @@ use thiserror::Error;
 /// 0: Initial plot data file with header (MC 1.18.2)
 /// 1: Add world send rate
 /// 2: Update to MC 1.20.4
-pub const VERSION: u32 = 2;
+/// 3: Add saved redpiler state
+pub const VERSION: u32 = 3;
 
 #[derive(Error, Debug)]
 pub enum PlotLoadError {
@@ -162,6 +163,8 @@ pub struct PlotData {
     pub world_send_rate: WorldSendRate,
     pub chunk_data: Vec<ChunkData>,
     pub pending_ticks: Vec<TickEntry>,
+    /// The serialized redpiler circuit, if it was running with `--persist` when the plot was saved
+    pub redpiler_state: Option<Vec<u8>>,
 }
 
 impl PlotData {
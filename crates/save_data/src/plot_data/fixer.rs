@@ -7,9 +7,12 @@
 //! seperate download. As our save format changes in the future, the fixer
 //! module may become quite big.
 
-use super::{PlotData, PlotLoadError};
+use super::{ChunkData, PlotData, PlotLoadError, Tps, WorldSendRate};
 use crate::plot_data::VERSION;
-use std::fs;
+use mchprs_world::TickEntry;
+use serde::Deserialize;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 use tracing::debug;
 
@@ -35,12 +38,38 @@ fn make_backup(path: impl AsRef<Path>) -> Result<(), PlotLoadError> {
     Ok(())
 }
 
+/// Plot data before the redpiler state was added
+#[derive(Deserialize)]
+struct PlotDataV2 {
+    tps: Tps,
+    world_send_rate: WorldSendRate,
+    chunk_data: Vec<ChunkData>,
+    pending_ticks: Vec<TickEntry>,
+}
+
+fn convert_v2(path: impl AsRef<Path>) -> Result<PlotData, PlotLoadError> {
+    let mut file = File::open(path)?;
+    // Skip the magic and version
+    file.seek(SeekFrom::Start(12))?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    let old: PlotDataV2 = bincode::deserialize(&buf)?;
+    Ok(PlotData {
+        tps: old.tps,
+        world_send_rate: old.world_send_rate,
+        chunk_data: old.chunk_data,
+        pending_ticks: old.pending_ticks,
+        redpiler_state: None,
+    })
+}
+
 pub fn try_fix(path: impl AsRef<Path>, info: FixInfo) -> Result<Option<PlotData>, PlotLoadError> {
     debug!("Trying to fix plot with {:?}", info);
     let result: Option<PlotData> = match info {
         FixInfo::OldVersion {
             version: version @ 0..=1,
         } => return Err(PlotLoadError::ConversionUnavailable(version)),
+        FixInfo::OldVersion { version: 2 } => Some(convert_v2(&path)?),
         _ => None,
     };
 
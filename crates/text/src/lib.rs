@@ -87,7 +87,7 @@ pub enum TextColor {
 #[serde(rename_all = "snake_case")]
 enum ClickEventType {
     OpenUrl,
-    // RunCommand,
+    RunCommand,
     // SuggestCommand,
 }
 
@@ -97,6 +97,23 @@ pub struct ClickEvent {
     value: String,
 }
 
+impl ClickEvent {
+    pub fn open_url(url: String) -> Self {
+        Self {
+            action: ClickEventType::OpenUrl,
+            value: url,
+        }
+    }
+
+    /// Makes the player run `command` when clicked. The command must start with a `/`.
+    pub fn run_command(command: String) -> Self {
+        Self {
+            action: ClickEventType::RunCommand,
+            value: command,
+        }
+    }
+}
+
 /// This is only used for `TextComponent` serialize
 #[allow(clippy::trivially_copy_pass_by_ref)]
 fn is_false(field: &bool) -> bool {
@@ -131,6 +148,16 @@ impl TextComponentBuilder {
         self
     }
 
+    pub fn underlined(mut self, val: bool) -> Self {
+        self.component.underlined = val;
+        self
+    }
+
+    pub fn click_event(mut self, event: ClickEvent) -> Self {
+        self.component.click_event = Some(event);
+        self
+    }
+
     pub fn finish(self) -> TextComponent {
         self.component
     }
@@ -152,6 +152,33 @@ impl PalettedBitBuffer {
         }
     }
 
+    /// Like [`PalettedBitBuffer::load`], but checks that the data fits the number of entries and
+    /// that every entry is in the palette, so that data read from disk can't cause a panic later.
+    pub fn try_load(
+        entries: usize,
+        bits_per_entry: u8,
+        longs: Vec<u64>,
+        palette: Vec<u32>,
+        direct_threshold: u64,
+    ) -> Option<PalettedBitBuffer> {
+        if !matches!(bits_per_entry, 4..=8 | 15) {
+            return None;
+        }
+        let entries_per_long = 64 / bits_per_entry as usize;
+        if longs.len() != entries.div_ceil(entries_per_long) {
+            return None;
+        }
+        let buffer =
+            PalettedBitBuffer::load(entries, bits_per_entry, longs, palette, direct_threshold);
+        if buffer.use_palette
+            && (buffer.palette.len() > buffer.max_entries as usize
+                || (0..entries).any(|i| buffer.data.get_entry(i) as usize >= buffer.palette.len()))
+        {
+            return None;
+        }
+        Some(buffer)
+    }
+
     fn resize_buffer(&mut self) {
         assert!(
             self.use_palette,
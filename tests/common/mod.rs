@@ -5,25 +5,27 @@ use mchprs_redstone::wire::make_cross;
 use mchprs_world::testing::TestWorld;
 use mchprs_world::World;
 
-struct RedpilerInstance {
+pub struct RedpilerInstance {
     options: CompilerOptions,
-    compiler: Compiler,
+    pub compiler: Compiler,
+}
+
+pub fn world_bounds(world: &TestWorld) -> (BlockPos, BlockPos) {
+    let max_x = world.x_size * 16 - 1;
+    let max_y = world.y_size * 16 - 1;
+    let max_z = world.z_size * 16 - 1;
+    (BlockPos::new(0, 0, 0), BlockPos::new(max_x, max_y, max_z))
 }
 
 impl RedpilerInstance {
-    fn new(world: &TestWorld, variant: BackendVariant) -> RedpilerInstance {
-        let options = CompilerOptions {
-            backend_variant: variant,
-            ..Default::default()
-        };
+    fn new(world: &TestWorld, options: CompilerOptions) -> RedpilerInstance {
         let mut compiler = Compiler::default();
-        let max_x = world.x_size * 16 - 1;
-        let max_y = world.y_size * 16 - 1;
-        let max_z = world.z_size * 16 - 1;
-        let bounds = (BlockPos::new(0, 0, 0), BlockPos::new(max_x, max_y, max_z));
+        let bounds = world_bounds(world);
         let monitor = Default::default();
         let ticks = world.to_be_ticked.clone();
-        compiler.compile(world, bounds, options.clone(), ticks, monitor);
+        compiler
+            .compile(world, bounds, options.clone(), ticks, monitor)
+            .unwrap();
         RedpilerInstance { options, compiler }
     }
 }
@@ -34,22 +36,40 @@ pub enum TestBackend {
     Redpiler(BackendVariant),
 }
 
+/// Helpers that only some test binaries use are added to this in those binaries, so that the other
+/// ones don't warn about dead code.
 pub struct BackendRunner {
-    world: TestWorld,
-    redpiler: Option<RedpilerInstance>,
+    pub world: TestWorld,
+    pub redpiler: Option<RedpilerInstance>,
 }
 
 impl BackendRunner {
     pub fn new(world: TestWorld, backend: TestBackend) -> BackendRunner {
+        BackendRunner::with_options(world, backend, Default::default())
+    }
+
+    /// Creates a runner that compiles with `options` if it's using redpiler. The backend variant in
+    /// `options` is replaced with the one from `backend`.
+    pub fn with_options(
+        world: TestWorld,
+        backend: TestBackend,
+        options: CompilerOptions,
+    ) -> BackendRunner {
         match backend {
             TestBackend::Redstone => BackendRunner {
                 world,
                 redpiler: None,
             },
-            TestBackend::Redpiler(variant) => BackendRunner {
-                redpiler: Some(RedpilerInstance::new(&world, variant)),
-                world,
-            },
+            TestBackend::Redpiler(variant) => {
+                let options = CompilerOptions {
+                    backend_variant: variant,
+                    ..options
+                };
+                BackendRunner {
+                    redpiler: Some(RedpilerInstance::new(&world, options)),
+                    world,
+                }
+            }
         }
     }
 
@@ -110,6 +130,10 @@ fn is_block_powered(block: Block) -> Option<bool> {
     if let Some(powered) = block.clone().get_pressure_plate_powered() {
         return Some(*powered);
     }
+    // Copper bulbs are checked by whether they are lit, not whether they are receiving power
+    if let Some((lit, _)) = block.clone().get_copper_bulb_state() {
+        return Some(*lit);
+    }
     Some(match block {
         Block::Comparator(comparator) => comparator.powered,
         Block::RedstoneTorch { lit } => lit,
@@ -120,6 +144,10 @@ fn is_block_powered(block: Block) -> Option<bool> {
         Block::RedstoneLamp { lit } => lit,
         Block::IronTrapdoor { powered, .. } => powered,
         Block::NoteBlock { powered, .. } => powered,
+        Block::Observer { powered, .. } => powered,
+        Block::Target { power } => power > 0,
+        Block::TripwireHook { powered, .. } => powered,
+        Block::DaylightDetector { power, .. } => power > 0,
         _ => return None,
     })
 }
@@ -131,6 +159,10 @@ macro_rules! test_all_backends {
             fn [< $name _redstone >]() { $name(TestBackend::Redstone) }
             #[test]
             fn [< $name _rp_direct >]() { $name(TestBackend::Redpiler(::mchprs_redpiler::BackendVariant::Direct)) }
+            #[test]
+            fn [< $name _rp_parallel >]() { $name(TestBackend::Redpiler(::mchprs_redpiler::BackendVariant::Parallel)) }
+            #[test]
+            fn [< $name _rp_cranelift >]() { $name(TestBackend::Redpiler(::mchprs_redpiler::BackendVariant::Cranelift)) }
         }
     };
 }
@@ -1,10 +1,53 @@
 mod common;
 use common::*;
 
-use mchprs_blocks::blocks::Block;
-use mchprs_blocks::BlockDirection;
+use mchprs_blocks::block_entities::{BlockEntity, ContainerType, InventoryEntry};
+use mchprs_blocks::blocks::{Block, ComparatorMode, CrafterOrientation, LeverFace};
+use mchprs_blocks::items::Item;
+use mchprs_blocks::{BlockDirection, BlockFacing, BlockPos};
+use mchprs_redpiler::diagnostics::{Diagnostic, Severity};
+use mchprs_redpiler::{CompileError, Compiler, CompilerOptions};
+use mchprs_redstone::target::{ARROW_HIT_TICKS, PROJECTILE_HIT_TICKS};
+use mchprs_redstone::tripwire::{self, ENTITY_CHECK_TICKS};
 use mchprs_world::{testing::TestWorld, TickPriority, World};
 
+impl BackendRunner {
+    fn hit_target(&mut self, pos: BlockPos, power: u8, ticks: u32) {
+        if let Some(redpiler) = &mut self.redpiler {
+            redpiler.compiler.hit_target(pos, power, ticks);
+            redpiler.compiler.flush(&mut self.world);
+            return;
+        }
+        mchprs_redstone::target::hit(&mut self.world, pos, power, ticks);
+    }
+
+    fn set_daylight_detector(&mut self, pos: BlockPos, power: u8) {
+        if let Some(redpiler) = &mut self.redpiler {
+            redpiler.compiler.set_daylight_detector(pos, power);
+            redpiler.compiler.flush(&mut self.world);
+            return;
+        }
+        mchprs_redstone::daylight_detector::set_power(&mut self.world, pos, power, None);
+    }
+
+    fn trigger_tripwire(&mut self, pos: BlockPos, ticks: u32) {
+        if let Some(redpiler) = &mut self.redpiler {
+            if let Some(hooks) = mchprs_redstone::tripwire::find_line_hooks(&self.world, pos) {
+                for hook in hooks {
+                    redpiler.compiler.trigger_tripwire_hook(hook, ticks);
+                }
+            }
+            redpiler.compiler.flush(&mut self.world);
+            return;
+        }
+        mchprs_redstone::tripwire::trigger(&mut self.world, pos, ticks);
+    }
+
+    fn get_block(&self, pos: BlockPos) -> Block {
+        self.world.get_block(pos)
+    }
+}
+
 test_all_backends!(lever_on_off);
 fn lever_on_off(backend: TestBackend) {
     let lever_pos = pos(0, 1, 0);
@@ -199,3 +242,677 @@ fn ground_torch_does_not_power_block_below(backend: TestBackend) {
     runner.tick();
     runner.check_block_powered(lamp_pos, false);
 }
+
+test_all_backends!(observer_pulse);
+fn observer_pulse(backend: TestBackend) {
+    let lever_pos = pos(0, 1, 0);
+    let observer_pos = pos(1, 1, 0);
+    let trapdoor_pos = pos(2, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    make_lever(&mut world, lever_pos);
+    world.set_block(
+        observer_pos,
+        Block::Observer {
+            facing: BlockFacing::West,
+            powered: false,
+        },
+    );
+    world.set_block(trapdoor_pos, trapdoor());
+
+    let mut runner = BackendRunner::new(world, backend);
+    runner.check_block_powered(trapdoor_pos, false);
+
+    // Turning the lever on and off should both produce a 1 tick pulse
+    for _ in 0..2 {
+        runner.use_block(lever_pos);
+        runner.check_powered_for(trapdoor_pos, false, 1);
+        runner.check_block_powered(observer_pos, true);
+        runner.check_powered_for(trapdoor_pos, true, 1);
+        runner.check_powered_for(trapdoor_pos, false, 10);
+    }
+}
+
+test_all_backends!(observer_watches_wire);
+fn observer_watches_wire(backend: TestBackend) {
+    let lever_pos = pos(0, 1, 0);
+    let trapdoor_pos = pos(1, 1, 2);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    make_lever(&mut world, lever_pos);
+    make_wire(&mut world, pos(1, 1, 0));
+    world.set_block(
+        pos(1, 1, 1),
+        Block::Observer {
+            facing: BlockFacing::North,
+            powered: false,
+        },
+    );
+    world.set_block(trapdoor_pos, trapdoor());
+
+    let mut runner = BackendRunner::new(world, backend);
+    runner.use_block(lever_pos);
+    runner.check_powered_for(trapdoor_pos, false, 1);
+    runner.check_powered_for(trapdoor_pos, true, 1);
+    runner.check_powered_for(trapdoor_pos, false, 10);
+}
+
+test_all_backends!(observer_ignores_wire_inputs);
+fn observer_ignores_wire_inputs(backend: TestBackend) {
+    let first_lever_pos = pos(0, 1, 0);
+    let second_lever_pos = pos(2, 1, 0);
+    let trapdoor_pos = pos(1, 1, 2);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    make_lever(&mut world, first_lever_pos);
+    make_lever(&mut world, second_lever_pos);
+    make_wire(&mut world, pos(1, 1, 0));
+    world.set_block(
+        pos(1, 1, 1),
+        Block::Observer {
+            facing: BlockFacing::North,
+            powered: false,
+        },
+    );
+    world.set_block(trapdoor_pos, trapdoor());
+
+    let mut runner = BackendRunner::new(world, backend);
+    runner.use_block(first_lever_pos);
+    runner.check_powered_for(trapdoor_pos, false, 1);
+    runner.check_powered_for(trapdoor_pos, true, 1);
+    runner.check_powered_for(trapdoor_pos, false, 10);
+
+    // The wire stays at 15 while either lever is on
+    runner.use_block(second_lever_pos);
+    runner.check_powered_for(trapdoor_pos, false, 10);
+    runner.use_block(first_lever_pos);
+    runner.check_powered_for(trapdoor_pos, false, 10);
+
+    runner.use_block(second_lever_pos);
+    runner.check_powered_for(trapdoor_pos, false, 1);
+    runner.check_powered_for(trapdoor_pos, true, 1);
+    runner.check_powered_for(trapdoor_pos, false, 10);
+}
+
+test_all_backends!(observer_watches_comparator);
+fn observer_watches_comparator(backend: TestBackend) {
+    let input_lever_pos = pos(0, 1, 0);
+    let side_lever_pos = pos(1, 1, 3);
+    let trapdoor_pos = pos(3, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    make_lever(&mut world, input_lever_pos);
+    make_comparator(
+        &mut world,
+        pos(1, 1, 0),
+        ComparatorMode::Subtract,
+        BlockDirection::West,
+    );
+    // The side input is 14 when the lever is on, leaving the comparator at 1
+    make_wire(&mut world, pos(1, 1, 1));
+    make_wire(&mut world, pos(1, 1, 2));
+    make_lever(&mut world, side_lever_pos);
+    world.set_block(
+        pos(2, 1, 0),
+        Block::Observer {
+            facing: BlockFacing::West,
+            powered: false,
+        },
+    );
+    world.set_block(trapdoor_pos, trapdoor());
+
+    let mut runner = BackendRunner::new(world, backend);
+    runner.use_block(input_lever_pos);
+    runner.check_powered_for(trapdoor_pos, false, 2);
+    runner.check_powered_for(trapdoor_pos, true, 1);
+    runner.check_powered_for(trapdoor_pos, false, 10);
+
+    // Changing the output strength from 15 to 1 and back doesn't change the comparator's state
+    runner.use_block(side_lever_pos);
+    runner.check_powered_for(trapdoor_pos, false, 10);
+    runner.use_block(side_lever_pos);
+    runner.check_powered_for(trapdoor_pos, false, 10);
+
+    runner.use_block(input_lever_pos);
+    runner.check_powered_for(trapdoor_pos, false, 2);
+    runner.check_powered_for(trapdoor_pos, true, 1);
+    runner.check_powered_for(trapdoor_pos, false, 10);
+}
+
+test_all_backends!(observer_chain);
+fn observer_chain(backend: TestBackend) {
+    let lever_pos = pos(0, 1, 0);
+    let trapdoor_pos = pos(3, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    make_lever(&mut world, lever_pos);
+    for x in 1..=2 {
+        world.set_block(
+            pos(x, 1, 0),
+            Block::Observer {
+                facing: BlockFacing::West,
+                powered: false,
+            },
+        );
+    }
+    world.set_block(trapdoor_pos, trapdoor());
+
+    let mut runner = BackendRunner::new(world, backend);
+    runner.use_block(lever_pos);
+    runner.check_powered_for(trapdoor_pos, false, 2);
+    runner.check_powered_for(trapdoor_pos, true, 1);
+    runner.check_powered_for(trapdoor_pos, false, 10);
+}
+
+test_all_backends!(target_hit);
+fn target_hit(backend: TestBackend) {
+    let target_pos = pos(0, 1, 0);
+    let trapdoor_pos = pos(1, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    world.set_block(target_pos, Block::Target { power: 0 });
+    world.set_block(trapdoor_pos, trapdoor());
+
+    let mut runner = BackendRunner::new(world, backend);
+    runner.check_block_powered(trapdoor_pos, false);
+
+    runner.hit_target(target_pos, 15, ARROW_HIT_TICKS);
+    runner.check_powered_for(trapdoor_pos, true, 5);
+    // Hitting a target that is still powered should not extend the pulse
+    runner.hit_target(target_pos, 15, ARROW_HIT_TICKS);
+    runner.check_powered_for(trapdoor_pos, true, 5);
+    runner.check_powered_for(trapdoor_pos, false, 10);
+
+    runner.hit_target(target_pos, 1, PROJECTILE_HIT_TICKS);
+    runner.check_powered_for(trapdoor_pos, true, 4);
+    runner.check_powered_for(trapdoor_pos, false, 10);
+}
+
+test_all_backends!(target_signal_strength);
+fn target_signal_strength(backend: TestBackend) {
+    let target_pos = pos(0, 1, 0);
+    let trapdoor_pos = pos(2, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    world.set_block(target_pos, Block::Target { power: 0 });
+    make_wire(&mut world, pos(1, 1, 0));
+    world.set_block(trapdoor_pos, trapdoor());
+
+    let mut runner = BackendRunner::new(world, backend);
+    // A signal strength of 1 dies out in the wire
+    runner.hit_target(target_pos, 1, PROJECTILE_HIT_TICKS);
+    runner.check_powered_for(target_pos, true, 4);
+    runner.check_block_powered(trapdoor_pos, false);
+    runner.check_powered_for(target_pos, false, 2);
+
+    runner.hit_target(target_pos, 2, PROJECTILE_HIT_TICKS);
+    runner.check_powered_for(trapdoor_pos, true, 4);
+    runner.check_powered_for(trapdoor_pos, false, 2);
+}
+
+test_all_backends!(daylight_detector_power);
+fn daylight_detector_power(backend: TestBackend) {
+    let detector_pos = pos(0, 1, 0);
+    let trapdoor_pos = pos(2, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    world.set_block(
+        detector_pos,
+        Block::DaylightDetector {
+            inverted: false,
+            power: 0,
+        },
+    );
+    make_wire(&mut world, pos(1, 1, 0));
+    world.set_block(trapdoor_pos, trapdoor());
+
+    let mut runner = BackendRunner::new(world, backend);
+    // Right-clicking raises the output strength by one
+    runner.use_block(detector_pos);
+    runner.check_block_powered(detector_pos, true);
+    runner.check_block_powered(trapdoor_pos, true);
+
+    runner.set_daylight_detector(detector_pos, 0);
+    runner.check_block_powered(trapdoor_pos, false);
+    runner.set_daylight_detector(detector_pos, 15);
+    runner.check_block_powered(trapdoor_pos, true);
+    // The output strength wraps around after 15
+    runner.use_block(detector_pos);
+    runner.check_block_powered(detector_pos, false);
+    runner.check_block_powered(trapdoor_pos, false);
+}
+
+fn tripwire_hook(facing: BlockDirection, attached: bool) -> Block {
+    Block::TripwireHook {
+        attached,
+        facing,
+        powered: false,
+    }
+}
+
+fn tripwire_string(attached: bool) -> Block {
+    Block::Tripwire {
+        attached,
+        disarmed: false,
+        north: false,
+        east: true,
+        south: false,
+        west: true,
+        powered: false,
+    }
+}
+
+/// Builds a line of tripwire with hooks at x = 1 and x = 5, attached to sandstone at x = 0 and
+/// x = 6.
+fn make_tripwire_line(world: &mut TestWorld, attached: bool) {
+    world.set_block(pos(0, 1, 0), Block::Sandstone {});
+    world.set_block(pos(1, 1, 0), tripwire_hook(BlockDirection::East, attached));
+    for x in 2..=4 {
+        world.set_block(pos(x, 1, 0), tripwire_string(attached));
+    }
+    world.set_block(pos(5, 1, 0), tripwire_hook(BlockDirection::West, attached));
+    world.set_block(pos(6, 1, 0), Block::Sandstone {});
+}
+
+test_all_backends!(tripwire_trigger);
+fn tripwire_trigger(backend: TestBackend) {
+    let trapdoor_pos = pos(0, 2, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    make_tripwire_line(&mut world, true);
+    world.set_block(trapdoor_pos, trapdoor());
+
+    let mut runner = BackendRunner::new(world, backend);
+    runner.check_block_powered(trapdoor_pos, false);
+
+    runner.trigger_tripwire(pos(3, 1, 0), ENTITY_CHECK_TICKS);
+    runner.check_block_powered(pos(1, 1, 0), true);
+    runner.check_block_powered(pos(5, 1, 0), true);
+    // The hooks strongly power the blocks they are attached to
+    runner.check_powered_for(trapdoor_pos, true, 5);
+    runner.check_block_powered(trapdoor_pos, false);
+    runner.check_block_powered(pos(1, 1, 0), false);
+    runner.check_block_powered(pos(5, 1, 0), false);
+}
+
+#[test]
+fn tripwire_attaches_hooks() {
+    let mut world = TestWorld::new(1, 1, 1);
+    make_tripwire_line(&mut world, false);
+
+    tripwire::update_hook(&mut world, pos(1, 1, 0), false);
+    assert_eq!(
+        world.get_block(pos(1, 1, 0)),
+        tripwire_hook(BlockDirection::East, true)
+    );
+    assert_eq!(
+        world.get_block(pos(5, 1, 0)),
+        tripwire_hook(BlockDirection::West, true)
+    );
+    assert_eq!(world.get_block(pos(3, 1, 0)), tripwire_string(true));
+
+    // Removing a piece of string detaches both hooks
+    world.set_block(pos(3, 1, 0), Block::Air);
+    tripwire::update_source(&mut world, pos(3, 1, 0));
+    assert_eq!(
+        world.get_block(pos(1, 1, 0)),
+        tripwire_hook(BlockDirection::East, false)
+    );
+    assert_eq!(
+        world.get_block(pos(5, 1, 0)),
+        tripwire_hook(BlockDirection::West, false)
+    );
+
+    // A detached line can't be triggered
+    let mut runner = BackendRunner::new(world, TestBackend::Redstone);
+    runner.trigger_tripwire(pos(2, 1, 0), ENTITY_CHECK_TICKS);
+    runner.check_block_powered(pos(1, 1, 0), false);
+}
+
+fn piston(sticky: bool, extended: bool) -> Block {
+    let facing = BlockFacing::East;
+    if sticky {
+        Block::StickyPiston { extended, facing }
+    } else {
+        Block::Piston { extended, facing }
+    }
+}
+
+#[test]
+fn piston_pushes_block() {
+    let lever_pos = pos(0, 1, 0);
+    let piston_pos = pos(1, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    make_lever(&mut world, lever_pos);
+    world.set_block(piston_pos, piston(false, false));
+    world.set_block(pos(2, 1, 0), Block::Stone);
+
+    let mut runner = BackendRunner::new(world, TestBackend::Redstone);
+    runner.use_block(lever_pos);
+    assert_eq!(runner.get_block(piston_pos), piston(false, true));
+    // Moved blocks are placed one tick after the piston starts moving
+    assert!(matches!(
+        runner.get_block(pos(3, 1, 0)),
+        Block::MovingPiston { .. }
+    ));
+    runner.tick();
+    assert!(matches!(
+        runner.get_block(pos(2, 1, 0)),
+        Block::PistonHead { .. }
+    ));
+    assert_eq!(runner.get_block(pos(3, 1, 0)), Block::Stone);
+
+    runner.use_block(lever_pos);
+    assert!(matches!(
+        runner.get_block(piston_pos),
+        Block::MovingPiston { .. }
+    ));
+    runner.tick();
+    assert_eq!(runner.get_block(piston_pos), piston(false, false));
+    assert_eq!(runner.get_block(pos(2, 1, 0)), Block::Air);
+    assert_eq!(runner.get_block(pos(3, 1, 0)), Block::Stone);
+}
+
+#[test]
+fn sticky_piston_pulls_block() {
+    let lever_pos = pos(0, 1, 0);
+    let piston_pos = pos(1, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    make_lever(&mut world, lever_pos);
+    world.set_block(piston_pos, piston(true, false));
+    world.set_block(pos(2, 1, 0), Block::Stone);
+
+    let mut runner = BackendRunner::new(world, TestBackend::Redstone);
+    runner.use_block(lever_pos);
+    runner.tick();
+    assert_eq!(runner.get_block(pos(3, 1, 0)), Block::Stone);
+
+    runner.use_block(lever_pos);
+    runner.tick();
+    assert_eq!(runner.get_block(piston_pos), piston(true, false));
+    assert_eq!(runner.get_block(pos(2, 1, 0)), Block::Stone);
+    assert_eq!(runner.get_block(pos(3, 1, 0)), Block::Air);
+}
+
+#[test]
+fn sticky_piston_drops_block_on_short_pulse() {
+    let lever_pos = pos(0, 1, 0);
+    let piston_pos = pos(1, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    make_lever(&mut world, lever_pos);
+    world.set_block(piston_pos, piston(true, false));
+    world.set_block(pos(2, 1, 0), Block::Stone);
+
+    let mut runner = BackendRunner::new(world, TestBackend::Redstone);
+    runner.use_block(lever_pos);
+    runner.use_block(lever_pos);
+    runner.tick();
+    assert_eq!(runner.get_block(piston_pos), piston(true, false));
+    assert_eq!(runner.get_block(pos(2, 1, 0)), Block::Air);
+    assert_eq!(runner.get_block(pos(3, 1, 0)), Block::Stone);
+}
+
+#[test]
+fn piston_push_limit() {
+    for (num_blocks, should_extend) in [(12, true), (13, false)] {
+        let lever_pos = pos(0, 1, 0);
+        let piston_pos = pos(1, 1, 0);
+
+        let mut world = TestWorld::new(1, 1, 1);
+        make_lever(&mut world, lever_pos);
+        world.set_block(piston_pos, piston(false, false));
+        for x in 0..num_blocks {
+            world.set_block(pos(2 + x, 1, 0), Block::Stone);
+        }
+
+        let mut runner = BackendRunner::new(world, TestBackend::Redstone);
+        runner.use_block(lever_pos);
+        runner.tick();
+        assert_eq!(runner.get_block(piston_pos), piston(false, should_extend));
+    }
+}
+
+#[test]
+fn piston_quasi_connectivity() {
+    let lever_pos = pos(1, 3, 0);
+    let piston_pos = pos(1, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    world.set_block(piston_pos, piston(false, false));
+    // Glass does not conduct power, so the piston can only be powered through the lever
+    // powering the space above it
+    world.set_block(pos(1, 2, 0), Block::Glass);
+    world.set_block(
+        lever_pos,
+        Block::Lever {
+            face: LeverFace::Floor,
+            facing: BlockDirection::West,
+            powered: false,
+        },
+    );
+
+    let mut runner = BackendRunner::new(world, TestBackend::Redstone);
+    runner.use_block(lever_pos);
+    assert_eq!(runner.get_block(piston_pos), piston(false, true));
+    runner.use_block(lever_pos);
+    runner.tick();
+    assert_eq!(runner.get_block(piston_pos), piston(false, false));
+}
+
+#[test]
+fn redpiler_rejects_pistons() {
+    let piston_pos = pos(1, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    world.set_block(piston_pos, piston(false, false));
+
+    let mut compiler = Compiler::default();
+    let bounds = (pos(0, 0, 0), pos(15, 15, 15));
+    let result = compiler.compile(
+        &world,
+        bounds,
+        Default::default(),
+        Vec::new(),
+        Default::default(),
+    );
+    let Err(CompileError { diagnostics }) = result else {
+        panic!("compile should fail");
+    };
+    let diagnostics: Vec<Diagnostic> = diagnostics.into_iter().collect();
+    assert_eq!(
+        diagnostics,
+        [Diagnostic {
+            severity: Severity::Error,
+            message: "Redpiler does not support piston".to_string(),
+            pos: Some(piston_pos),
+        }]
+    );
+    assert!(!compiler.is_active());
+}
+
+#[test]
+fn redpiler_selection_bounds() {
+    let mut world = TestWorld::new(1, 1, 1);
+    make_lever(&mut world, pos(1, 1, 0));
+    for x in 2..=5 {
+        make_wire(&mut world, pos(x, 1, 0));
+    }
+    // A separate circuit far enough away from the selection
+    make_lever(&mut world, pos(12, 1, 0));
+    make_wire(&mut world, pos(13, 1, 0));
+
+    let options = CompilerOptions {
+        selection: true,
+        ..Default::default()
+    };
+    let mut compiler = Compiler::default();
+    let result = compiler.compile(
+        &world,
+        (pos(0, 0, 0), pos(3, 15, 15)),
+        options.clone(),
+        Vec::new(),
+        Default::default(),
+    );
+    let Err(CompileError { diagnostics }) = result else {
+        panic!("compile should fail");
+    };
+    let positions: Vec<_> = diagnostics.iter().map(|d| d.pos).collect();
+    assert_eq!(positions, [Some(pos(4, 1, 0)), Some(pos(5, 1, 0))]);
+    assert!(!compiler.is_active());
+
+    let result = compiler.compile(
+        &world,
+        (pos(0, 0, 0), pos(8, 15, 15)),
+        options,
+        Vec::new(),
+        Default::default(),
+    );
+    assert_eq!(result, Ok(Default::default()));
+    assert!(compiler.node_state(pos(5, 1, 0)).is_some());
+    assert!(compiler.node_state(pos(12, 1, 0)).is_none());
+}
+
+#[test]
+fn redpiler_resume_checks_world() {
+    let lever_pos = pos(0, 1, 0);
+    let wire_pos = pos(1, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    make_lever(&mut world, lever_pos);
+    make_wire(&mut world, wire_pos);
+    world.set_block(pos(2, 1, 0), trapdoor());
+    let bounds = world_bounds(&world);
+
+    let options = CompilerOptions {
+        persist: true,
+        ..Default::default()
+    };
+    let mut compiler = Compiler::default();
+    let result = compiler.compile(&world, bounds, options, Vec::new(), Default::default());
+    assert!(result.is_ok());
+    compiler.on_use_block(lever_pos);
+    compiler.flush(&mut world);
+
+    let saved = compiler.save(&world, bounds).unwrap();
+    let mut resumed = Compiler::default();
+    assert!(resumed.resume(saved, &world, bounds, Default::default()));
+    assert!(resumed.node_state(lever_pos).unwrap().powered);
+
+    // A circuit saved from a single plot doesn't fit a merged one
+    let saved = compiler.save(&world, bounds).unwrap();
+    let merged_bounds = (bounds.0, bounds.1 + pos(16, 0, 0));
+    let mut resumed = Compiler::default();
+    assert!(!resumed.resume(saved, &world, merged_bounds, Default::default()));
+    assert!(!resumed.is_active());
+
+    // Neither does a world that was edited after saving
+    let saved = compiler.save(&world, bounds).unwrap();
+    world.set_block(wire_pos, Block::Air);
+    let mut resumed = Compiler::default();
+    assert!(!resumed.resume(saved, &world, bounds, Default::default()));
+    assert!(!resumed.is_active());
+}
+
+test_all_backends!(copper_bulb_toggle);
+fn copper_bulb_toggle(backend: TestBackend) {
+    let lever_pos = pos(0, 1, 0);
+    let bulb_pos = pos(1, 1, 0);
+    let trapdoor_pos = pos(3, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    make_lever(&mut world, lever_pos);
+    world.set_block(
+        bulb_pos,
+        Block::CopperBulb {
+            lit: false,
+            powered: false,
+        },
+    );
+    make_comparator(
+        &mut world,
+        pos(2, 1, 0),
+        ComparatorMode::Compare,
+        BlockDirection::West,
+    );
+    world.set_block(trapdoor_pos, trapdoor());
+
+    let mut runner = BackendRunner::new(world, backend);
+    // The bulb lights up one tick after being powered, and comparators read it as 15
+    runner.use_block(lever_pos);
+    runner.check_powered_for(bulb_pos, false, 1);
+    runner.check_block_powered(bulb_pos, true);
+    runner.check_powered_for(trapdoor_pos, false, 1);
+    runner.check_block_powered(trapdoor_pos, true);
+
+    // Losing power does not change the bulb
+    runner.use_block(lever_pos);
+    runner.check_powered_for(bulb_pos, true, 4);
+    runner.check_block_powered(trapdoor_pos, true);
+
+    // Being powered again turns it off
+    runner.use_block(lever_pos);
+    runner.check_powered_for(bulb_pos, true, 1);
+    runner.check_block_powered(bulb_pos, false);
+    runner.check_powered_for(trapdoor_pos, true, 1);
+    runner.check_block_powered(trapdoor_pos, false);
+}
+
+test_all_backends!(crafter_comparator_output);
+fn crafter_comparator_output(backend: TestBackend) {
+    let crafter_pos = pos(0, 1, 0);
+    let lever_pos = pos(1, 1, 2);
+    let wire_pos = pos(2, 1, 0);
+    let trapdoor_pos = pos(4, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+    world.set_block(
+        crafter_pos,
+        Block::Crafter {
+            crafting: false,
+            orientation: CrafterOrientation::NorthUp,
+            triggered: false,
+        },
+    );
+    let inventory = (0..3)
+        .map(|slot| InventoryEntry {
+            id: Item::Redstone {}.get_id(),
+            slot,
+            count: 1,
+            nbt: None,
+        })
+        .collect();
+    world.set_block_entity(
+        crafter_pos,
+        BlockEntity::Container {
+            comparator_override: 3,
+            inventory,
+            ty: ContainerType::Crafter,
+        },
+    );
+    make_comparator(
+        &mut world,
+        pos(1, 1, 0),
+        ComparatorMode::Subtract,
+        BlockDirection::West,
+    );
+    // A side input to hold the comparator off until the lever is flipped
+    make_wire(&mut world, pos(1, 1, 1));
+    make_lever(&mut world, lever_pos);
+    make_wire(&mut world, wire_pos);
+    make_wire(&mut world, pos(3, 1, 0));
+    world.set_block(trapdoor_pos, trapdoor());
+
+    let mut runner = BackendRunner::new(world, backend);
+    runner.use_block(lever_pos);
+    runner.use_block(lever_pos);
+    runner.tick();
+    // Crafters output the number of occupied slots
+    runner.check_block_powered(trapdoor_pos, true);
+    let Block::RedstoneWire(wire) = runner.get_block(wire_pos) else {
+        panic!("expected wire at {wire_pos}");
+    };
+    assert_eq!(wire.power, 3);
+}
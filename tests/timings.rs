@@ -2,8 +2,62 @@ mod common;
 use common::*;
 
 use mchprs_blocks::blocks::{Block, ComparatorMode};
-use mchprs_blocks::BlockDirection;
+use mchprs_blocks::{BlockDirection, BlockPos};
+use mchprs_redpiler::backend::BackendSnapshot;
+use mchprs_redpiler::{Compiler, CompilerOptions};
 use mchprs_world::testing::TestWorld;
+use mchprs_world::TickPriority;
+
+enum RunnerSnapshot {
+    Redstone(TestWorld),
+    Redpiler(BackendSnapshot),
+}
+
+impl BackendRunner {
+    fn snapshot(&mut self) -> RunnerSnapshot {
+        match &mut self.redpiler {
+            Some(redpiler) => RunnerSnapshot::Redpiler(redpiler.compiler.snapshot()),
+            None => RunnerSnapshot::Redstone(self.world.clone()),
+        }
+    }
+
+    fn restore(&mut self, snapshot: &RunnerSnapshot) {
+        match (&mut self.redpiler, snapshot) {
+            (Some(redpiler), RunnerSnapshot::Redpiler(snapshot)) => {
+                assert!(redpiler.compiler.restore(snapshot));
+                redpiler.compiler.flush(&mut self.world);
+            }
+            (None, RunnerSnapshot::Redstone(world)) => self.world = world.clone(),
+            _ => panic!("snapshot was taken with a different backend"),
+        }
+    }
+
+    /// Changes the blocks at `changed` with `edit` like a player would with auto redpiler: redpiler
+    /// is reset, then recompiled around the changed blocks.
+    fn edit(&mut self, changed: &[BlockPos], edit: impl FnOnce(&mut TestWorld)) {
+        let Some(redpiler) = &mut self.redpiler else {
+            edit(&mut self.world);
+            return;
+        };
+        let bounds = world_bounds(&self.world);
+        assert!(redpiler
+            .compiler
+            .reset_for_recompile(&mut self.world, bounds));
+        edit(&mut self.world);
+        let ticks = std::mem::take(&mut self.world.to_be_ticked);
+        redpiler
+            .compiler
+            .recompile(&self.world, bounds, changed, ticks, Default::default())
+            .unwrap();
+    }
+
+    /// Returns the compiler if the runner is using redpiler.
+    fn compiler(&mut self) -> Option<&mut Compiler> {
+        self.redpiler
+            .as_mut()
+            .map(|redpiler| &mut redpiler.compiler)
+    }
+}
 
 test_all_backends!(repeater_t_flip_flop);
 fn repeater_t_flip_flop(backend: TestBackend) {
@@ -107,3 +161,241 @@ fn pulse_gen_1t(backend: TestBackend) {
     runner.check_powered_for(output_pos, true, 1);
     runner.check_powered_for(output_pos, false, 10);
 }
+
+test_all_backends!(independent_circuits);
+fn independent_circuits(backend: TestBackend) {
+    // Two circuits that do not touch each other, which the parallel backend ticks separately
+    let fast_lever = pos(0, 1, 0);
+    let fast_repeater = pos(1, 1, 0);
+    let slow_lever = pos(0, 1, 4);
+    let slow_repeater = pos(1, 1, 4);
+
+    let mut world = TestWorld::new(1, 1, 1);
+
+    make_lever(&mut world, fast_lever);
+    make_repeater(&mut world, fast_repeater, 1, BlockDirection::West);
+    make_wire(&mut world, pos(2, 1, 0));
+    make_lever(&mut world, slow_lever);
+    make_repeater(&mut world, slow_repeater, 3, BlockDirection::West);
+    make_wire(&mut world, pos(2, 1, 4));
+
+    let mut runner = BackendRunner::new(world, backend);
+
+    runner.use_block(fast_lever);
+    runner.use_block(slow_lever);
+    for ticks in 0..6 {
+        runner.check_block_powered(fast_repeater, ticks >= 1);
+        runner.check_block_powered(slow_repeater, ticks >= 3);
+        runner.tick();
+    }
+
+    runner.use_block(slow_lever);
+    for ticks in 0..6 {
+        runner.check_block_powered(fast_repeater, true);
+        runner.check_block_powered(slow_repeater, ticks < 3);
+        runner.tick();
+    }
+}
+
+test_all_backends!(snapshot_restore);
+fn snapshot_restore(backend: TestBackend) {
+    let lever = pos(0, 1, 0);
+    let repeater = pos(1, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+
+    make_lever(&mut world, lever);
+    make_repeater(&mut world, repeater, 3, BlockDirection::West);
+    make_wire(&mut world, pos(2, 1, 0));
+
+    let mut runner = BackendRunner::new(world, backend);
+
+    // Take the snapshot while the repeater has a pending tick
+    runner.use_block(lever);
+    runner.tick();
+    let snapshot = runner.snapshot();
+
+    runner.check_powered_for(repeater, false, 2);
+    runner.check_block_powered(repeater, true);
+    runner.use_block(lever);
+    for _ in 0..5 {
+        runner.tick();
+    }
+    runner.check_block_powered(lever, false);
+    runner.check_block_powered(repeater, false);
+
+    runner.restore(&snapshot);
+    runner.check_block_powered(lever, true);
+    runner.check_powered_for(repeater, false, 2);
+    runner.check_block_powered(repeater, true);
+}
+
+test_all_backends!(probe_trace);
+fn probe_trace(backend: TestBackend) {
+    let lever = pos(0, 1, 0);
+    let repeater = pos(1, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+
+    make_lever(&mut world, lever);
+    make_repeater(&mut world, repeater, 3, BlockDirection::West);
+    make_wire(&mut world, pos(2, 1, 0));
+
+    let mut runner = BackendRunner::new(world, backend);
+    // Probes only exist in redpiler
+    let Some(compiler) = runner.compiler() else {
+        return;
+    };
+    assert!(compiler.add_probe("lever".to_string(), lever));
+    assert!(compiler.add_probe("repeater".to_string(), repeater));
+    assert!(!compiler.add_probe("air".to_string(), pos(5, 1, 5)));
+
+    runner.use_block(lever);
+    for _ in 0..5 {
+        runner.tick();
+    }
+
+    let trace = runner.compiler().unwrap().trace();
+    assert_eq!(trace.end, 5);
+    assert_eq!(trace.signals.len(), 2);
+    assert_eq!(trace.signals[0].probe.name, "lever");
+    assert_eq!(trace.signals[0].changes, vec![(0, 0), (1, 15)]);
+    assert_eq!(trace.signals[1].changes, vec![(0, 0), (3, 15)]);
+}
+
+test_all_backends!(breakpoint_and_step);
+fn breakpoint_and_step(backend: TestBackend) {
+    let lever = pos(0, 1, 0);
+    let repeater = pos(1, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+
+    make_lever(&mut world, lever);
+    make_repeater(&mut world, repeater, 3, BlockDirection::West);
+    make_wire(&mut world, pos(2, 1, 0));
+
+    let mut runner = BackendRunner::new(world, backend);
+    // Breakpoints only exist in redpiler
+    let Some(compiler) = runner.compiler() else {
+        return;
+    };
+    let condition = ">=8".parse().unwrap();
+    let id = compiler.add_breakpoint(repeater, condition).unwrap();
+
+    runner.use_block(lever);
+    let compiler = runner.compiler().unwrap();
+    compiler.tickn(10);
+    let triggered = compiler.take_triggered_breakpoints();
+    assert_eq!(triggered.len(), 1);
+    assert_eq!(triggered[0].id, id);
+    assert_eq!(compiler.node_state(repeater).unwrap().output_strength, 15);
+    // The breakpoint stops the simulation on the tick the repeater turned on
+    assert!(!compiler.has_pending_ticks());
+
+    // Turning the lever off schedules the repeater, which turns off 3 ticks later. Stepping
+    // through the priorities of a tick is the same as running the tick.
+    runner.use_block(lever);
+    let compiler = runner.compiler().unwrap();
+    compiler.tick();
+    compiler.tick();
+    assert_eq!(compiler.step_priority(), TickPriority::Highest);
+    assert!(compiler.node_state(repeater).unwrap().powered);
+    // A repeater turning off ticks with the higher priority
+    assert_eq!(compiler.step_priority(), TickPriority::Higher);
+    assert!(!compiler.node_state(repeater).unwrap().powered);
+    compiler.tick();
+    assert!(!compiler.has_pending_ticks());
+    assert!(compiler.take_triggered_breakpoints().is_empty());
+
+    // A stepped tick counts as a single tick
+    runner.use_block(lever);
+    let compiler = runner.compiler().unwrap();
+    compiler.tick();
+    compiler.tick();
+    for _ in 0..3 {
+        compiler.step_priority();
+    }
+    assert_eq!(compiler.step_priority(), TickPriority::Normal);
+    assert_eq!(compiler.take_triggered_breakpoints().len(), 1);
+}
+
+test_all_backends!(inspect_node);
+fn inspect_node(backend: TestBackend) {
+    let lever = pos(0, 1, 0);
+    let repeater = pos(1, 1, 0);
+    let wire = pos(2, 1, 0);
+
+    let mut world = TestWorld::new(1, 1, 1);
+
+    make_lever(&mut world, lever);
+    make_repeater(&mut world, repeater, 3, BlockDirection::West);
+    make_wire(&mut world, wire);
+
+    let mut runner = BackendRunner::new(world, backend);
+    runner.use_block(lever);
+    // Inspection is only available in redpiler
+    let Some(compiler) = runner.compiler() else {
+        return;
+    };
+
+    let inspection = compiler.inspect(repeater).unwrap();
+    assert!(inspection.ty.starts_with("Repeater"));
+    assert!(!inspection.state.powered);
+    assert_eq!(inspection.inputs.len(), 1);
+    assert_eq!(inspection.inputs[0].pos, Some(lever));
+    assert_eq!(inspection.inputs[0].power, 15);
+    assert_eq!(inspection.outputs.len(), 1);
+    assert_eq!(inspection.outputs[0].pos, Some(wire));
+    assert_eq!(inspection.pending_ticks.len(), 1);
+    assert_eq!(inspection.pending_ticks[0].0, 3);
+
+    compiler.tick();
+    let inspection = compiler.inspect(repeater).unwrap();
+    assert_eq!(inspection.pending_ticks[0].0, 2);
+    assert!(compiler.inspect(pos(5, 1, 5)).is_none());
+}
+
+test_all_backends!(recompile_after_edit);
+fn recompile_after_edit(backend: TestBackend) {
+    let lever = pos(0, 1, 0);
+    let gap = pos(5, 1, 0);
+    let lamp = pos(10, 1, 0);
+    let other_lever = pos(0, 1, 4);
+    let other_repeater = pos(1, 1, 4);
+
+    let mut world = TestWorld::new(1, 1, 1);
+
+    // A line of wire from the lever to the lamp, with a gap in the middle
+    make_lever(&mut world, lever);
+    for x in 1..10 {
+        if x != gap.x {
+            make_wire(&mut world, pos(x, 1, 0));
+        }
+    }
+    place_on_block(&mut world, lamp, Block::RedstoneLamp { lit: false });
+    make_lever(&mut world, other_lever);
+    make_repeater(&mut world, other_repeater, 1, BlockDirection::West);
+
+    // Only incremental compiles can be patched after an edit
+    let options = CompilerOptions {
+        incremental: true,
+        ..Default::default()
+    };
+    let mut runner = BackendRunner::with_options(world, backend, options);
+    runner.use_block(other_lever);
+    runner.check_powered_for(other_repeater, false, 1);
+    runner.check_block_powered(other_repeater, true);
+    runner.use_block(lever);
+    runner.check_powered_for(lamp, false, 2);
+
+    runner.edit(&[gap, gap - pos(0, 1, 0)], |world| make_wire(world, gap));
+    // The part of the circuit that wasn't edited keeps its state
+    runner.check_block_powered(other_repeater, true);
+
+    // The lamp is out of range of the edit, but linked to the lever through the wire
+    runner.use_block(lever);
+    runner.tick();
+    runner.use_block(lever);
+    runner.tick();
+    runner.check_block_powered(lamp, true);
+}